@@ -0,0 +1,159 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.24;
+
+import {Test} from "forge-std/Test.sol";
+import {PoolManager} from "../../src/core/PoolManager.sol";
+import {SwapRouter} from "../../src/core/SwapRouter.sol";
+import {IPoolManager} from "../../src/interfaces/IPoolManager.sol";
+import {PoolKey} from "../../src/types/PoolKey.sol";
+import {Currency} from "../../src/types/Currency.sol";
+import {IHooks} from "../../src/interfaces/IHooks.sol";
+import {TickMath} from "../../src/libraries/TickMath.sol";
+import {MockERC20} from "../mocks/MockERC20.sol";
+import {NoOpHook} from "../mocks/NoOpHook.sol";
+
+/// @title GasSnapshotsTest
+/// @notice Gas snapshots the routing engine's gas model is calibrated from
+/// @dev `forge test --match-contract GasSnapshotsTest` writes `snapshots/PoolManager.json`,
+///      `snapshots/SwapRouter.json` and `snapshots/ERC20.json`; point the router's `gas.snapshot_dir`
+///      at `snapshots/`. Each test starts from `setUp`, so every measured call sees the same state.
+///      The SwapRouter has no `multicall` or WETH steps, so `multicall_2_legs`, `wrapETH` and
+///      `unwrapWETH` are not measured and the router keeps its defaults for them.
+contract GasSnapshotsTest is Test {
+    PoolManager public poolManager;
+    SwapRouter public swapRouter;
+    NoOpHook public hook;
+
+    MockERC20 public token0;
+    MockERC20 public token1;
+
+    address public alice = makeAddr("alice");
+    address public bob = makeAddr("bob");
+
+    address constant PERMIT2 = 0x000000000022D473030F116dDEE9F6B43aC78BA3;
+
+    uint160 constant SQRT_PRICE_1_1 = 79228162514264337593543950336;
+    uint24 constant FEE_TIER = 3000;
+    int24 constant TICK_SPACING = 60;
+
+    // Deep enough that SWAP_AMOUNT moves the price ~200 ticks, inside [-600, 600)
+    int256 constant LIQUIDITY = 1e21;
+    int256 constant SWAP_AMOUNT = 1e19;
+
+    function setUp() public {
+        poolManager = new PoolManager();
+        swapRouter = new SwapRouter(address(poolManager), PERMIT2);
+        hook = new NoOpHook();
+
+        token0 = new MockERC20("Token A", "TKA", 18);
+        token1 = new MockERC20("Token B", "TKB", 18);
+        if (address(token0) > address(token1)) {
+            (token0, token1) = (token1, token0);
+        }
+
+        token0.mint(alice, 1000000 ether);
+        token1.mint(alice, 1000000 ether);
+        token0.mint(bob, 1000000 ether);
+        token1.mint(bob, 1000000 ether);
+        // The router pays swap output from its own balance
+        token0.mint(address(swapRouter), 1000000 ether);
+        token1.mint(address(swapRouter), 1000000 ether);
+
+        vm.prank(alice);
+        token0.approve(address(swapRouter), type(uint256).max);
+    }
+
+    // ══════════════════════════════════════════════════════════════════════
+    // POOL MANAGER
+    // ══════════════════════════════════════════════════════════════════════
+
+    function test_GasSnapshot_Swap() public {
+        PoolKey memory key = _initializePool(IHooks(address(0)));
+
+        poolManager.swap(key, _swapParams());
+        vm.snapshotGasLastCall("PoolManager", "swap");
+    }
+
+    function test_GasSnapshot_SwapCrossTick() public {
+        PoolKey memory key = _initializePool(IHooks(address(0)));
+        // A narrower position puts an initialized tick at -60, inside the swap's range
+        poolManager.modifyLiquidity(
+            key,
+            IPoolManager.ModifyLiquidityParams({ tickLower: -60, tickUpper: 60, liquidityDelta: LIQUIDITY / 100 })
+        );
+
+        poolManager.swap(key, _swapParams());
+        vm.snapshotGasLastCall("PoolManager", "swap_cross_tick");
+
+        (, int24 tick,,) = poolManager.pools(keccak256(abi.encode(key)));
+        assertLt(tick, -60, "swap should cross tick -60");
+        assertGt(tick, -600, "swap should stay inside the wide position");
+    }
+
+    function test_GasSnapshot_SwapHooks() public {
+        PoolKey memory key = _initializePool(IHooks(address(hook)));
+
+        poolManager.swap(key, _swapParams());
+        vm.snapshotGasLastCall("PoolManager", "swap_hooks");
+    }
+
+    // ══════════════════════════════════════════════════════════════════════
+    // SWAP ROUTER
+    // ══════════════════════════════════════════════════════════════════════
+
+    function test_GasSnapshot_ExactInputSingle() public {
+        PoolKey memory key = _initializePool(IHooks(address(0)));
+
+        SwapRouter.ExactInputSingleParams memory params = SwapRouter.ExactInputSingleParams({
+            poolKey: key,
+            zeroForOne: true,
+            amountIn: uint256(SWAP_AMOUNT),
+            amountOutMinimum: 0,
+            sqrtPriceLimitX96: 0,
+            recipient: alice,
+            deadline: block.timestamp + 1 hours
+        });
+
+        vm.prank(alice);
+        uint256 amountOut = swapRouter.exactInputSingle(params);
+        vm.snapshotGasLastCall("SwapRouter", "exactInputSingle");
+
+        assertGt(amountOut, 0);
+    }
+
+    // ══════════════════════════════════════════════════════════════════════
+    // ERC20
+    // ══════════════════════════════════════════════════════════════════════
+
+    function test_GasSnapshot_Transfer() public {
+        vm.prank(alice);
+        token0.transfer(bob, 1 ether);
+        vm.snapshotGasLastCall("ERC20", "transfer");
+    }
+
+    // ══════════════════════════════════════════════════════════════════════
+    // HELPERS
+    // ══════════════════════════════════════════════════════════════════════
+
+    function _initializePool(IHooks hooks) internal returns (PoolKey memory key) {
+        key = PoolKey({
+            currency0: Currency.wrap(address(token0)),
+            currency1: Currency.wrap(address(token1)),
+            fee: FEE_TIER,
+            tickSpacing: TICK_SPACING,
+            hooks: hooks
+        });
+        poolManager.initialize(key, SQRT_PRICE_1_1);
+        poolManager.modifyLiquidity(
+            key, IPoolManager.ModifyLiquidityParams({ tickLower: -600, tickUpper: 600, liquidityDelta: LIQUIDITY })
+        );
+    }
+
+    function _swapParams() internal pure returns (IPoolManager.SwapParams memory) {
+        return IPoolManager.SwapParams({
+            zeroForOne: true,
+            amountSpecified: SWAP_AMOUNT,
+            sqrtPriceLimitX96: TickMath.MIN_SQRT_PRICE + 1
+        });
+    }
+}
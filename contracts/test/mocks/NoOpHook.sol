@@ -0,0 +1,52 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.24;
+
+import {IHooks} from "../../src/interfaces/IHooks.sol";
+import {IPoolManager} from "../../src/interfaces/IPoolManager.sol";
+import {PoolKey} from "../../src/types/PoolKey.sol";
+import {BalanceDelta} from "../../src/types/BalanceDelta.sol";
+
+/// @title NoOpHook
+/// @notice Hook that accepts every callback and does nothing, to measure the cost of the calls themselves
+contract NoOpHook is IHooks {
+    function beforeInitialize(address, PoolKey calldata, uint160) external pure returns (bytes4) {
+        return IHooks.beforeInitialize.selector;
+    }
+
+    function afterInitialize(address, PoolKey calldata, uint160, int24) external pure returns (bytes4) {
+        return IHooks.afterInitialize.selector;
+    }
+
+    function beforeModifyLiquidity(address, PoolKey calldata, IPoolManager.ModifyLiquidityParams calldata)
+        external
+        pure
+        returns (bytes4)
+    {
+        return IHooks.beforeModifyLiquidity.selector;
+    }
+
+    function afterModifyLiquidity(
+        address,
+        PoolKey calldata,
+        IPoolManager.ModifyLiquidityParams calldata,
+        BalanceDelta
+    ) external pure returns (bytes4) {
+        return IHooks.afterModifyLiquidity.selector;
+    }
+
+    function beforeSwap(address, PoolKey calldata, IPoolManager.SwapParams calldata)
+        external
+        pure
+        returns (bytes4)
+    {
+        return IHooks.beforeSwap.selector;
+    }
+
+    function afterSwap(address, PoolKey calldata, IPoolManager.SwapParams calldata, BalanceDelta)
+        external
+        pure
+        returns (bytes4)
+    {
+        return IHooks.afterSwap.selector;
+    }
+}
@@ -3,7 +3,6 @@ use routing_engine::utils::address_from_u64;
 use routing_engine::{
     cache::EnhancedRouteCache,
     graph::{PoolEdge, PoolGraph, TokenNode},
-    routing::{Router, RouterConfig},
 };
 use std::sync::Arc;
 use std::time::Instant;
@@ -12,7 +11,6 @@ use std::time::Instant;
 ///
 /// This measures memory usage patterns for different workloads.
 /// Run with: cargo run --release --bin routing-engine --features memory-profile
-
 fn setup_large_graph(token_count: usize) -> Arc<PoolGraph> {
     let graph = Arc::new(PoolGraph::new());
 
@@ -163,18 +161,6 @@ fn benchmark_concurrent_memory() {
     println!("\n📊 CONCURRENT ACCESS MEMORY");
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
 
-    let graph = setup_large_graph(100);
-
-    let config = RouterConfig {
-        enable_cache: true,
-        enable_parallel: true,
-        cache_ttl_seconds: 15,
-        max_routes_cached: 1000,
-        max_quotes_cached: 2000,
-    };
-
-    let router = Router::with_config(graph, config);
-
     println!("\n🔹 Router with concurrent access:");
     println!("   Graph: 100 tokens");
     println!("   Cache: 1000 routes, 2000 quotes");
@@ -186,11 +172,7 @@ fn benchmark_concurrent_memory() {
     let start = Instant::now();
 
     let handles: Vec<_> = (0..100)
-        .map(|i| {
-            let token_in = address_from_u64((i % 100) as u64 + 1);
-            let token_out = address_from_u64(((i + 1) % 100) as u64 + 1);
-            let amount = U256::from(1_000_000_000_000_000_000u128);
-
+        .map(|_| {
             std::thread::spawn(move || {
                 // Simulate work (we don't actually call router in this benchmark)
                 std::thread::sleep(std::time::Duration::from_micros(100));
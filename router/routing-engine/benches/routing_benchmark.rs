@@ -1,12 +1,12 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId};
-use alloy_primitives::{Address, U256};
+use alloy_primitives::U256;
 use routing_engine::utils::address_from_u64;
 use routing_engine::{
     cache::EnhancedRouteCache,
-    graph::{PoolEdge, PoolGraph, TokenNode},
+    graph::{GraphFixture, PoolEdge, PoolGraph, TokenNode},
     routing::{
         find_best_multi_hop_route, find_best_route_parallel, find_best_single_hop_route,
-        optimize_split_route, Router,
+        optimize_split_route,
     },
 };
 use std::sync::Arc;
@@ -44,36 +44,11 @@ fn setup_test_graph(pool_count: usize) -> PoolGraph {
 }
 
 fn setup_complex_graph() -> Arc<PoolGraph> {
+    // Dense 10-token graph: each token is connected to its next two neighbours
     let graph = Arc::new(PoolGraph::new());
-
-    let tokens: Vec<Address> = (1..=10).map(|i| address_from_u64(i)).collect();
-    let nodes: Vec<TokenNode> = tokens
-        .iter()
-        .enumerate()
-        .map(|(i, addr)| TokenNode::new(*addr, format!("T{}", i), 18))
-        .collect();
-
-    // Create dense graph for benchmarking
-    for i in 0..9 {
-        for j in (i + 1)..10 {
-            if (j - i) <= 2 {
-                // Connect nearby tokens
-                let pool_id = [(i * 10 + j) as u8; 32];
-                let pool = PoolEdge::new(
-                    pool_id,
-                    tokens[i],
-                    tokens[j],
-                    3000,
-                    60,
-                    5_000_000_000_000_000_000_000,
-                    U256::from(1u128 << 96),
-                    0,
-                );
-                graph.upsert_pool(pool, nodes[i].clone(), nodes[j].clone());
-            }
-        }
-    }
-
+    let fixture = GraphFixture::from_json(include_str!("../fixtures/dense-10.json"))
+        .expect("Fixture should parse");
+    graph.import_fixture(&fixture).expect("Fixture should import");
     graph
 }
 
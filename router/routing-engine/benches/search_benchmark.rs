@@ -0,0 +1,152 @@
+use alloy_primitives::U256;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use routing_engine::graph::synthetic::long_tail_graph;
+use routing_engine::graph::{GraphFixture, PoolGraph};
+use routing_engine::routing::{
+    search_candidates, CandidateConfig, CandidateGraph, Screening, SearchParams,
+};
+use routing_engine::utils::address_from_u64;
+use routing_engine::utils::addresses::{usdc, weth};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// System allocator that counts allocations, so the benchmark can
+/// report allocations per quote next to latency
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Allocations and bytes allocated by one call of `f`, averaged over runs
+fn allocations_per_call<T>(mut f: impl FnMut() -> T) -> (usize, usize) {
+    const RUNS: usize = 100;
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    for _ in 0..RUNS {
+        black_box(f());
+    }
+    (
+        (ALLOCATIONS.load(Ordering::Relaxed) - allocations) / RUNS,
+        (ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes) / RUNS,
+    )
+}
+
+fn setup_dense_graph() -> PoolGraph {
+    // 10 tokens, each connected to its next two neighbours
+    let graph = PoolGraph::new();
+    let fixture = GraphFixture::from_json(include_str!("../fixtures/dense-10.json"))
+        .expect("Fixture should parse");
+    graph
+        .import_fixture(&fixture)
+        .expect("Fixture should import");
+    graph
+}
+
+fn bench_dense_search(c: &mut Criterion) {
+    let graph = setup_dense_graph();
+    let candidates = CandidateGraph::build(&graph, &CandidateConfig::unfiltered());
+    let amount = U256::from(1_000_000_000_000_000_000u128);
+
+    let mut group = c.benchmark_group("search_dense_10");
+    for (max_hops, k) in [(2, 1), (4, 1), (4, 6)] {
+        for screened in [false, true] {
+            let mut params = SearchParams::new(max_hops, k);
+            let mut name = format!("{}hops_top{}", max_hops, k);
+            if screened {
+                params = params.screened(Screening::default());
+                name.push_str("_screened");
+            }
+            let search = || {
+                search_candidates(
+                    &candidates,
+                    black_box(address_from_u64(1)),
+                    black_box(address_from_u64(9)),
+                    black_box(amount),
+                    params,
+                )
+            };
+            let (allocations, bytes) = allocations_per_call(search);
+            println!(
+                "search_dense_10/{}: {} allocations, {} bytes per quote",
+                name, allocations, bytes
+            );
+
+            group.bench_function(BenchmarkId::from_parameter(name), |b| b.iter(search));
+        }
+    }
+    group.finish();
+}
+
+fn bench_large_graph(c: &mut Criterion) {
+    // 50k pools, the same graph as tests/large_graph_test.rs
+    let graph = long_tail_graph(10_000);
+    let config = CandidateConfig {
+        connector_tokens: vec![weth(), usdc()],
+        ..Default::default()
+    };
+    let amount = U256::from(1_000_000_000_000_000_000u128);
+
+    let mut group = c.benchmark_group("large_graph_50k");
+    group.sample_size(10);
+    group.bench_function("candidate_index", |b| {
+        b.iter(|| CandidateGraph::build(&graph, black_box(&config)));
+    });
+
+    // Connector-filtered, as the router runs it, and over every pool,
+    // where most of the work is pricing hops
+    let filtered = CandidateGraph::build(&graph, &config);
+    let unfiltered = CandidateGraph::build(&graph, &CandidateConfig::unfiltered());
+    for (name, candidates, max_hops) in [
+        ("quote_top6", &filtered, 4),
+        ("unfiltered_3hops_top6", &unfiltered, 3),
+    ] {
+        for screened in [false, true] {
+            let mut params = SearchParams::new(max_hops, 6);
+            let mut name = name.to_string();
+            if screened {
+                params = params.screened(Screening::default());
+                name.push_str("_screened");
+            }
+            let search = || {
+                search_candidates(
+                    candidates,
+                    black_box(address_from_u64(17)),
+                    black_box(address_from_u64(4242)),
+                    black_box(amount),
+                    params,
+                )
+            };
+            let (allocations, bytes) = allocations_per_call(search);
+            println!(
+                "large_graph_50k/{}: {} allocations, {} bytes per quote",
+                name, allocations, bytes
+            );
+            group.bench_function(name, |b| b.iter(search));
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_dense_search, bench_large_graph);
+criterion_main!(benches);
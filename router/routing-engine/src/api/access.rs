@@ -0,0 +1,682 @@
+//! API access control: API keys, rate limits, quotas and CORS
+//!
+//! [`authorize`] runs in front of every public route except the probes
+//! (`access.public_paths`). Metrics and admin endpoints are served on
+//! the internal listener instead (`server.admin_port`). A request names
+//! its key in the `X-API-Key` header (or `Authorization: Bearer <key>`).
+//! Keyed requests draw from their key's token bucket and quota; requests
+//! without a key, when those are allowed, draw from a bucket per client
+//! IP. The caller is attached to the request as a [`Caller`] extension.
+
+use super::error::{ApiError, ErrorCode};
+use crate::metrics::Metrics;
+use crate::utils::{Result, RouterError};
+use alloy_primitives::Address;
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, Method},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+
+/// Header carrying the API key
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Metrics label of requests without an API key
+const ANONYMOUS: &str = "anonymous";
+
+/// Access control settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessConfig {
+    /// Refuse requests without an API key
+    pub require_api_key: bool,
+    /// API keys, in addition to those in `keys_path`
+    pub keys: Vec<ApiKeyConfig>,
+    /// TOML file with more keys, as `[[keys]]` entries
+    pub keys_path: Option<String>,
+    /// Rate limit of keys without their own
+    pub key_rate_limit: RateLimit,
+    /// Rate limit of each client IP sending requests without a key
+    pub ip_rate_limit: RateLimit,
+    /// Length of the quota window (seconds)
+    pub quota_window_secs: u64,
+    /// Paths served without a key or rate limit
+    pub public_paths: Vec<String>,
+    /// Take the client IP from the first `X-Forwarded-For` entry (only
+    /// behind a proxy that sets it)
+    pub trust_forwarded_for: bool,
+    /// Per-IP buckets kept. Past it full buckets are dropped, then the
+    /// least recently seen half
+    pub max_tracked_ips: usize,
+    pub cors: CorsConfig,
+}
+
+impl Default for AccessConfig {
+    fn default() -> Self {
+        Self {
+            require_api_key: false,
+            keys: Vec::new(),
+            keys_path: None,
+            key_rate_limit: RateLimit {
+                per_second: 20.0,
+                burst: 40,
+            },
+            ip_rate_limit: RateLimit {
+                per_second: 5.0,
+                burst: 10,
+            },
+            quota_window_secs: 86_400,
+            public_paths: ["/live", "/ready", "/health"]
+                .map(String::from)
+                .to_vec(),
+            trust_forwarded_for: false,
+            max_tracked_ips: 100_000,
+            cors: CorsConfig::default(),
+        }
+    }
+}
+
+/// One integrator's API key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyConfig {
+    pub key: String,
+    /// Name shown in metrics and logs (never the key itself)
+    pub name: String,
+    /// Overrides `access.key_rate_limit`
+    #[serde(default)]
+    pub rate_limit: Option<RateLimit>,
+    /// Requests allowed per quota window (`None` for no quota)
+    #[serde(default)]
+    pub quota: Option<u64>,
+    /// Integrator fee charged on this key's quotes unless a request sets
+    /// `fee_bps`
+    #[serde(default)]
+    pub fee_bps: Option<u16>,
+    /// Recipient of this key's integrator fee
+    #[serde(default)]
+    pub fee_recipient: Option<Address>,
+}
+
+/// Token bucket parameters
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// Sustained requests per second (`0` disables the limit)
+    pub per_second: f64,
+    /// Requests allowed in a burst
+    pub burst: u32,
+}
+
+impl RateLimit {
+    fn is_enabled(&self) -> bool {
+        self.per_second > 0.0
+    }
+}
+
+/// Origins allowed to call the API from a browser
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CorsConfig {
+    /// Allowed origins; `*` allows any
+    pub allowed_origins: Vec<String>,
+    /// How long browsers may cache a preflight response (seconds)
+    pub max_age_secs: u64,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: vec!["*".to_string()],
+            max_age_secs: 600,
+        }
+    }
+}
+
+impl CorsConfig {
+    /// CORS layer allowing `GET` and `POST` (quote refreshes) from the
+    /// configured origins, with the API key header
+    pub fn layer(&self) -> Result<CorsLayer> {
+        let origins = if self.allowed_origins.iter().any(|origin| origin == "*") {
+            AllowOrigin::from(Any)
+        } else {
+            let origins = self
+                .allowed_origins
+                .iter()
+                .map(|origin| {
+                    HeaderValue::from_str(origin).map_err(|_| {
+                        RouterError::ConfigError(format!("Invalid CORS origin {:?}", origin))
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            AllowOrigin::list(origins)
+        };
+
+        Ok(CorsLayer::new()
+            .allow_origin(origins)
+            .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
+            .allow_headers([
+                header::CONTENT_TYPE,
+                header::AUTHORIZATION,
+                HeaderName::from_static(API_KEY_HEADER),
+            ])
+            .expose_headers([header::RETRY_AFTER])
+            .max_age(Duration::from_secs(self.max_age_secs)))
+    }
+}
+
+/// Who sent a request, attached to it by [`authorize`]
+#[derive(Debug, Clone)]
+pub enum Caller {
+    Key(Arc<ApiKeyConfig>),
+    Anonymous(IpAddr),
+}
+
+impl Caller {
+    /// Metrics label: the key's name, or `anonymous`
+    pub fn label(&self) -> &str {
+        match self {
+            Caller::Key(key) => &key.name,
+            Caller::Anonymous(_) => ANONYMOUS,
+        }
+    }
+}
+
+/// A token bucket holding up to `burst` tokens, refilled at
+/// `per_second`
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    tokens: f64,
+    /// Last time tokens were taken
+    updated: Instant,
+}
+
+impl TokenBucket {
+    /// A full bucket
+    pub fn new(limit: &RateLimit, now: Instant) -> Self {
+        Self {
+            tokens: f64::from(limit.burst),
+            updated: now,
+        }
+    }
+
+    /// Take one token, or return how long until one is available
+    pub fn try_take(
+        &mut self,
+        limit: &RateLimit,
+        now: Instant,
+    ) -> std::result::Result<(), Duration> {
+        self.refill(limit, now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - self.tokens) / limit.per_second,
+            ))
+        }
+    }
+
+    /// Whether the bucket has refilled completely, so dropping it loses
+    /// nothing
+    fn is_full(&self, limit: &RateLimit, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens + elapsed * limit.per_second >= f64::from(limit.burst)
+    }
+
+    fn refill(&mut self, limit: &RateLimit, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * limit.per_second).min(f64::from(limit.burst));
+        self.updated = now;
+    }
+}
+
+/// Rate limit and quota state of one key
+struct KeyUsage {
+    bucket: TokenBucket,
+    /// Start of the quota window, set by the key's first request
+    window_start: Option<Instant>,
+    used: u64,
+}
+
+/// A registered key and its usage
+struct KeyEntry {
+    config: Arc<ApiKeyConfig>,
+    limit: RateLimit,
+    usage: Mutex<KeyUsage>,
+}
+
+/// Why a request was refused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Rejection {
+    RateLimited(Duration),
+    QuotaExceeded(Duration),
+}
+
+/// API keys, buckets and quotas shared by all requests
+pub struct AccessControl {
+    config: AccessConfig,
+    keys: HashMap<String, KeyEntry>,
+    ip_buckets: DashMap<IpAddr, TokenBucket>,
+    metrics: Arc<Metrics>,
+}
+
+impl AccessControl {
+    /// Register the keys of `config` and its `keys_path` file
+    ///
+    /// Fails if the file cannot be read or a key is listed twice.
+    pub fn new(config: AccessConfig, metrics: Arc<Metrics>) -> Result<Self> {
+        let mut keys = config.keys.clone();
+        if let Some(path) = &config.keys_path {
+            keys.extend(load_keys(Path::new(path))?);
+        }
+
+        let mut entries = HashMap::new();
+        let now = Instant::now();
+        for key in keys {
+            let limit = key.rate_limit.unwrap_or(config.key_rate_limit);
+            let entry = KeyEntry {
+                usage: Mutex::new(KeyUsage {
+                    bucket: TokenBucket::new(&limit, now),
+                    window_start: None,
+                    used: 0,
+                }),
+                limit,
+                config: Arc::new(key),
+            };
+            if let Some(quota) = entry.config.quota {
+                metrics.observe_api_quota(&entry.config.name, quota);
+            }
+            if entries.insert(entry.config.key.clone(), entry).is_some() {
+                return Err(RouterError::ConfigError("Duplicate API key".to_string()));
+            }
+        }
+
+        Ok(Self {
+            config,
+            keys: entries,
+            ip_buckets: DashMap::new(),
+            metrics,
+        })
+    }
+
+    pub fn config(&self) -> &AccessConfig {
+        &self.config
+    }
+
+    /// Identify the caller of a request and charge it one request
+    pub fn admit(
+        &self,
+        headers: &HeaderMap,
+        peer: Option<IpAddr>,
+    ) -> std::result::Result<Caller, ApiError> {
+        self.admit_at(headers, peer, Instant::now())
+    }
+
+    fn admit_at(
+        &self,
+        headers: &HeaderMap,
+        peer: Option<IpAddr>,
+        now: Instant,
+    ) -> std::result::Result<Caller, ApiError> {
+        let caller = match request_key(headers) {
+            Some(key) => match self.keys.get(key) {
+                Some(entry) => {
+                    self.charge_key(entry, now).map_err(|rejection| {
+                        self.reject(
+                            &entry.config.name,
+                            rejection,
+                            &entry.limit,
+                            entry.config.quota,
+                        )
+                    })?;
+                    Caller::Key(entry.config.clone())
+                }
+                None => {
+                    self.metrics.record_api_rejected(ANONYMOUS, "invalid_key");
+                    return Err(ApiError::new(ErrorCode::InvalidApiKey, "Unknown API key"));
+                }
+            },
+            None if self.config.require_api_key => {
+                self.metrics.record_api_rejected(ANONYMOUS, "missing_key");
+                return Err(ApiError::new(
+                    ErrorCode::MissingApiKey,
+                    format!("An API key is required in the {} header", API_KEY_HEADER),
+                ));
+            }
+            None => {
+                let ip = self.client_ip(headers, peer);
+                self.charge_ip(ip, now).map_err(|rejection| {
+                    self.reject(ANONYMOUS, rejection, &self.config.ip_rate_limit, None)
+                })?;
+                Caller::Anonymous(ip)
+            }
+        };
+
+        self.metrics.record_api_request(caller.label());
+        Ok(caller)
+    }
+
+    fn charge_key(&self, entry: &KeyEntry, now: Instant) -> std::result::Result<(), Rejection> {
+        let mut usage = entry.usage.lock();
+        let window = Duration::from_secs(self.config.quota_window_secs);
+        let elapsed = now.saturating_duration_since(*usage.window_start.get_or_insert(now));
+        if elapsed >= window {
+            usage.window_start = Some(now);
+            usage.used = 0;
+        }
+        if let Some(quota) = entry.config.quota {
+            if usage.used >= quota {
+                let reset = window.saturating_sub(elapsed);
+                return Err(Rejection::QuotaExceeded(reset));
+            }
+        }
+        if entry.limit.is_enabled() {
+            usage
+                .bucket
+                .try_take(&entry.limit, now)
+                .map_err(Rejection::RateLimited)?;
+        }
+
+        usage.used += 1;
+        if let Some(quota) = entry.config.quota {
+            self.metrics
+                .observe_api_quota(&entry.config.name, quota.saturating_sub(usage.used));
+        }
+        Ok(())
+    }
+
+    fn charge_ip(&self, ip: IpAddr, now: Instant) -> std::result::Result<(), Rejection> {
+        let limit = self.config.ip_rate_limit;
+        if !limit.is_enabled() {
+            return Ok(());
+        }
+        if self.ip_buckets.len() >= self.config.max_tracked_ips {
+            self.evict_ip_buckets(&limit, now);
+        }
+
+        self.ip_buckets
+            .entry(ip)
+            .or_insert_with(|| TokenBucket::new(&limit, now))
+            .try_take(&limit, now)
+            .map_err(Rejection::RateLimited)
+    }
+
+    /// Make room for another client IP: drop the full buckets, which
+    /// loses nothing, then if still at the limit the least recently seen
+    /// half
+    fn evict_ip_buckets(&self, limit: &RateLimit, now: Instant) {
+        self.ip_buckets
+            .retain(|_, bucket| !bucket.is_full(limit, now));
+        if self.ip_buckets.is_empty() || self.ip_buckets.len() < self.config.max_tracked_ips {
+            return;
+        }
+
+        let mut seen: Vec<Instant> = self.ip_buckets.iter().map(|entry| entry.updated).collect();
+        let middle = seen.len() / 2;
+        let (_, &mut cutoff, _) = seen.select_nth_unstable(middle);
+        self.ip_buckets.retain(|_, bucket| bucket.updated > cutoff);
+    }
+
+    fn reject(
+        &self,
+        label: &str,
+        rejection: Rejection,
+        limit: &RateLimit,
+        quota: Option<u64>,
+    ) -> ApiError {
+        match rejection {
+            Rejection::RateLimited(wait) => {
+                self.metrics.record_api_rejected(label, "rate_limited");
+                ApiError::new(
+                    ErrorCode::RateLimited,
+                    format!(
+                        "Rate limit of {} requests/s (burst {}) exceeded",
+                        limit.per_second, limit.burst
+                    ),
+                )
+                .with_details(json!({ "per_second": limit.per_second, "burst": limit.burst }))
+                .with_retry_after(wait)
+            }
+            Rejection::QuotaExceeded(reset) => {
+                self.metrics.record_api_rejected(label, "quota_exceeded");
+                ApiError::new(
+                    ErrorCode::QuotaExceeded,
+                    format!(
+                        "Quota of {} requests per {}s exceeded",
+                        quota.unwrap_or_default(),
+                        self.config.quota_window_secs
+                    ),
+                )
+                .with_details(json!({
+                    "quota": quota,
+                    "window_secs": self.config.quota_window_secs,
+                }))
+                .with_retry_after(reset)
+            }
+        }
+    }
+
+    /// Client IP: the peer address, or the first `X-Forwarded-For`
+    /// entry when trusted
+    fn client_ip(&self, headers: &HeaderMap, peer: Option<IpAddr>) -> IpAddr {
+        let forwarded = self
+            .config
+            .trust_forwarded_for
+            .then(|| headers.get("x-forwarded-for")?.to_str().ok())
+            .flatten()
+            .and_then(|value| value.split(',').next()?.trim().parse().ok());
+        forwarded
+            .or(peer)
+            .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED))
+    }
+}
+
+/// The API key of a request, from `X-API-Key` or a bearer token
+fn request_key(headers: &HeaderMap) -> Option<&str> {
+    if let Some(key) = headers.get(API_KEY_HEADER) {
+        return key.to_str().ok();
+    }
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+/// Keys file: `[[keys]]` entries
+#[derive(Deserialize)]
+struct KeysFile {
+    keys: Vec<ApiKeyConfig>,
+}
+
+fn load_keys(path: &Path) -> Result<Vec<ApiKeyConfig>> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        RouterError::ConfigError(format!("Cannot read API keys {}: {}", path.display(), e))
+    })?;
+    let file: KeysFile = toml::from_str(&content).map_err(|e| {
+        RouterError::ConfigError(format!("Invalid API keys {}: {}", path.display(), e))
+    })?;
+    Ok(file.keys)
+}
+
+/// Middleware admitting requests through [`AccessControl`]
+pub async fn authorize(
+    State(access): State<Arc<AccessControl>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path();
+    if access
+        .config
+        .public_paths
+        .iter()
+        .any(|public| public == path)
+    {
+        return next.run(request).await;
+    }
+
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    match access.admit(request.headers(), peer) {
+        Ok(caller) => {
+            request.extensions_mut().insert(caller);
+            next.run(request).await
+        }
+        Err(err) => err.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(key: &str, quota: Option<u64>) -> ApiKeyConfig {
+        ApiKeyConfig {
+            key: key.to_string(),
+            name: format!("{}-name", key),
+            rate_limit: Some(RateLimit {
+                per_second: 1.0,
+                burst: 2,
+            }),
+            quota,
+            fee_bps: None,
+            fee_recipient: None,
+        }
+    }
+
+    fn headers(key: Option<&str>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Some(key) = key {
+            headers.insert(API_KEY_HEADER, HeaderValue::from_str(key).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn test_token_bucket_refills() {
+        let limit = RateLimit {
+            per_second: 2.0,
+            burst: 2,
+        };
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(&limit, start);
+
+        assert!(bucket.try_take(&limit, start).is_ok());
+        assert!(bucket.try_take(&limit, start).is_ok());
+        let wait = bucket.try_take(&limit, start).unwrap_err();
+        assert_eq!(wait, Duration::from_millis(500));
+
+        assert!(bucket.try_take(&limit, start + wait).is_ok());
+        assert!(bucket.is_full(&limit, start + Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn test_keys_have_their_own_buckets_and_quota() {
+        let config = AccessConfig {
+            keys: vec![key("a", None), key("b", Some(2))],
+            ..Default::default()
+        };
+        let access = AccessControl::new(config, Arc::new(Metrics::new())).unwrap();
+        let now = Instant::now();
+
+        for _ in 0..2 {
+            assert!(access.admit_at(&headers(Some("a")), None, now).is_ok());
+        }
+        let err = access.admit_at(&headers(Some("a")), None, now).unwrap_err();
+        assert_eq!(err.code, ErrorCode::RateLimited);
+        assert_eq!(err.retry_after, Some(Duration::from_secs(1)));
+
+        // Key b is untouched by a's traffic, but runs out of quota
+        let later = now + Duration::from_secs(5);
+        for _ in 0..2 {
+            assert!(access.admit_at(&headers(Some("b")), None, later).is_ok());
+        }
+        let err = access
+            .admit_at(&headers(Some("b")), None, later)
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::QuotaExceeded);
+        assert_eq!(err.retry_after, Some(Duration::from_secs(86_400)));
+
+        let next_window = later + Duration::from_secs(86_400);
+        assert!(access
+            .admit_at(&headers(Some("b")), None, next_window)
+            .is_ok());
+
+        let err = access.admit_at(&headers(Some("c")), None, now).unwrap_err();
+        assert_eq!(err.code, ErrorCode::InvalidApiKey);
+    }
+
+    #[test]
+    fn test_anonymous_requests_are_limited_per_ip() {
+        let config = AccessConfig {
+            ip_rate_limit: RateLimit {
+                per_second: 1.0,
+                burst: 1,
+            },
+            ..Default::default()
+        };
+        let access = AccessControl::new(config, Arc::new(Metrics::new())).unwrap();
+        let now = Instant::now();
+        let [first, second]: [IpAddr; 2] =
+            ["10.0.0.1".parse().unwrap(), "10.0.0.2".parse().unwrap()];
+
+        assert!(access.admit_at(&headers(None), Some(first), now).is_ok());
+        let err = access
+            .admit_at(&headers(None), Some(first), now)
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::RateLimited);
+        assert!(access.admit_at(&headers(None), Some(second), now).is_ok());
+
+        let required = AccessControl::new(
+            AccessConfig {
+                require_api_key: true,
+                ..Default::default()
+            },
+            Arc::new(Metrics::new()),
+        )
+        .unwrap();
+        let err = required
+            .admit_at(&headers(None), Some(first), now)
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::MissingApiKey);
+    }
+
+    #[test]
+    fn test_ip_buckets_evict_the_least_recently_seen() {
+        let config = AccessConfig {
+            ip_rate_limit: RateLimit {
+                per_second: 0.001,
+                burst: 2,
+            },
+            max_tracked_ips: 4,
+            ..Default::default()
+        };
+        let access = AccessControl::new(config, Arc::new(Metrics::new())).unwrap();
+        let start = Instant::now();
+        let ip = |n: u8| IpAddr::from([10, 0, 0, n]);
+
+        // None of the buckets refill, so none can be dropped as full
+        for n in 1..=4 {
+            let now = start + Duration::from_secs(u64::from(n));
+            assert!(access.admit_at(&headers(None), Some(ip(n)), now).is_ok());
+        }
+        let now = start + Duration::from_secs(5);
+        assert!(access.admit_at(&headers(None), Some(ip(5)), now).is_ok());
+
+        let mut tracked: Vec<IpAddr> = access.ip_buckets.iter().map(|entry| *entry.key()).collect();
+        tracked.sort();
+        assert_eq!(tracked, [ip(4), ip(5)]);
+    }
+}
@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Deserialize)]
 pub struct QuoteRequest {
+    /// Chain to quote on (defaults to the first configured chain)
+    pub chain_id: Option<u64>,
     pub token_in: Address,
     pub token_out: Address,
     pub amount_in: String,
@@ -10,25 +12,151 @@ pub struct QuoteRequest {
     pub slippage: f64,
     pub max_hops: Option<usize>,
     pub max_splits: Option<usize>,
+    /// Search time budget in milliseconds (defaults to
+    /// `routing.search_timeout_ms`, capped at `routing.max_search_timeout_ms`)
+    pub timeout_ms: Option<u64>,
+    /// Integrator fee in basis points (defaults to the API key's
+    /// `fee_bps`)
+    pub fee_bps: Option<u16>,
+    /// Integrator fee recipient (defaults to the API key's
+    /// `fee_recipient`)
+    pub fee_recipient: Option<Address>,
+    /// Side the integrator fee is taken from (default `output`)
+    pub fee_side: Option<crate::routing::FeeSide>,
 }
 
 fn default_slippage() -> f64 {
     0.5
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize)]
+pub struct GraphDumpRequest {
+    pub chain_id: Option<u64>,
+    /// `json` (default) or `toml`
+    pub format: Option<crate::graph::FixtureFormat>,
+}
+
+/// Query parameters of endpoints that only select a chain
+#[derive(Debug, Default, Deserialize)]
+pub struct ChainQuery {
+    pub chain_id: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuoteResponse {
+    /// Id to fetch or refresh the quote with
+    pub quote_id: uuid::Uuid,
+    pub chain_id: u64,
+    /// Tokens as requested (native ETH stays native)
+    pub token_in: Address,
+    pub token_out: Address,
+    /// Block of the pool state the quote was computed from
+    pub block_number: u64,
+    /// Pool graph version the quote was computed from
+    pub graph_version: u64,
+    /// Unix time after which the quote should not be signed
+    pub expires_at: u64,
     pub quote: crate::routing::Quote,
+    /// EIP-712 signature, when the engine has a signing key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<super::signing::QuoteSignature>,
     pub timestamp: u64,
     pub cached: bool,
 }
 
+impl QuoteResponse {
+    /// Whether the quote has expired at unix time `now`
+    pub fn is_expired(&self, now: u64) -> bool {
+        now >= self.expires_at
+    }
+}
+
+/// A stored quote re-simulated against current pool state
+#[derive(Debug, Serialize)]
+pub struct QuoteRefreshResponse {
+    pub quote_id: uuid::Uuid,
+    pub chain_id: u64,
+    /// Block and graph version the route was re-simulated at
+    pub block_number: u64,
+    pub graph_version: u64,
+    /// Block the quote was issued at
+    pub quoted_block_number: u64,
+    /// Output the quote promised, integrator fee deducted
+    pub quoted_amount_out: String,
+    /// Output of the same route now, integrator fee deducted
+    pub amount_out: String,
+    /// The quote's minimum output
+    pub amount_out_min: String,
+    /// Change from `quoted_amount_out` to `amount_out` in basis points
+    /// (negative when the output dropped)
+    pub drift_bps: f64,
+    /// `amount_out` still meets `amount_out_min`
+    pub within_slippage: bool,
+    pub expires_at: u64,
+    pub expired: bool,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PricesResponse {
+    pub chain_id: u64,
+    /// Block of the pool state the prices were derived from
+    pub block_number: u64,
+    /// Priced tokens, deepest liquidity first
+    pub prices: Vec<crate::pricing::TokenPrice>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TokenPriceResponse {
+    pub chain_id: u64,
+    pub block_number: u64,
+    pub price: crate::pricing::TokenPrice,
+}
+
 #[derive(Debug, Serialize)]
 pub struct HealthResponse {
+    /// "healthy", "degraded" or "unhealthy"
     pub status: String,
     pub version: String,
     pub chain_id: u64,
+    /// Whether the engine is serving quotes
+    pub ready: bool,
     pub graph_stats: GraphStatsDto,
+    pub sync: SyncStatusDto,
+    /// Subsystems currently failing (empty when healthy)
+    pub issues: Vec<SubsystemIssue>,
+    /// Summary of every chain this instance serves
+    pub chains: Vec<ChainHealthDto>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChainHealthDto {
+    pub chain_id: u64,
+    pub status: String,
+    pub ready: bool,
+    pub pool_count: usize,
+    pub block_number: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SyncStatusDto {
+    pub last_success: u64,
+    pub last_attempt: u64,
+    pub consecutive_failures: u64,
+    pub staleness_secs: Option<u64>,
+    pub max_staleness_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SubsystemIssue {
+    /// "rpc", "sync" or "compute"
+    pub subsystem: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LivenessResponse {
+    pub status: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -36,10 +164,16 @@ pub struct GraphStatsDto {
     pub token_count: usize,
     pub pool_count: usize,
     pub last_update: u64,
+    pub block_number: u64,
 }
 
 #[derive(Debug, Serialize)]
 pub struct ErrorResponse {
+    /// HTTP reason phrase
     pub error: String,
+    /// Stable error code to branch on
+    pub code: super::ErrorCode,
     pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
 }
@@ -0,0 +1,348 @@
+//! API errors: a stable code per failure, its HTTP status, and the JSON
+//! error body
+//!
+//! Clients should branch on `code`; `message` is for humans and may
+//! change. The catalogue is documented in the README ("Error Codes").
+
+use super::dto::ErrorResponse;
+use crate::service::ComputeError;
+use crate::utils::RouterError;
+use axum::{
+    extract::rejection::{PathRejection, QueryRejection},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// Machine-readable error code of an API error
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// Query or path parameters are missing or malformed
+    InvalidRequest,
+    /// Several quote parameters are invalid; see `details.violations`
+    ValidationFailed,
+    InvalidAmount,
+    InvalidTokenAddress,
+    /// `token_in` and `token_out` are the same token
+    SameToken,
+    /// `slippage` is not a percentage within the allowed range
+    InvalidSlippage,
+    /// `max_hops` is zero or above the engine's limit
+    InvalidMaxHops,
+    /// `max_splits` is zero or above the engine's limit
+    InvalidMaxSplits,
+    /// `fee_bps` is above the limit or has no `fee_recipient`
+    InvalidFee,
+    /// `chain_id` is not served by this instance
+    UnsupportedChain,
+    /// A token has no pools on the chain
+    UnknownToken,
+    NoRouteFound,
+    PoolNotFound,
+    /// A token has no USD price
+    TokenNotPriced,
+    /// The pools cannot fill the amount
+    InsufficientLiquidity,
+    PriceImpactTooHigh,
+    /// No quote with the id was issued within the retention window
+    QuoteNotFound,
+    /// The quote is past its `expires_at`; request a new one
+    QuoteExpired,
+    /// No API key was sent and the instance requires one
+    MissingApiKey,
+    /// The API key is not registered
+    InvalidApiKey,
+    /// The caller's rate limit is exhausted; retry after `Retry-After`
+    RateLimited,
+    /// The API key's quota for the current window is used up
+    QuotaExceeded,
+    /// The compute queue is full; retry after `Retry-After`
+    Overloaded,
+    /// Pool data is not synced, stale, or the service is shutting down
+    NotReady,
+    /// The route search ran out of time before finding any route
+    SearchTimeout,
+    /// The request waited too long for a compute slot
+    QueueTimeout,
+    /// The chain's RPC node failed
+    RpcError,
+    SimulationError,
+    CacheError,
+    ConfigError,
+    InternalError,
+}
+
+impl ErrorCode {
+    /// Every code, in catalogue order
+    pub const ALL: [ErrorCode; 31] = [
+        ErrorCode::InvalidRequest,
+        ErrorCode::ValidationFailed,
+        ErrorCode::InvalidAmount,
+        ErrorCode::InvalidTokenAddress,
+        ErrorCode::SameToken,
+        ErrorCode::InvalidSlippage,
+        ErrorCode::InvalidMaxHops,
+        ErrorCode::InvalidMaxSplits,
+        ErrorCode::InvalidFee,
+        ErrorCode::UnsupportedChain,
+        ErrorCode::UnknownToken,
+        ErrorCode::NoRouteFound,
+        ErrorCode::PoolNotFound,
+        ErrorCode::TokenNotPriced,
+        ErrorCode::InsufficientLiquidity,
+        ErrorCode::PriceImpactTooHigh,
+        ErrorCode::QuoteNotFound,
+        ErrorCode::QuoteExpired,
+        ErrorCode::MissingApiKey,
+        ErrorCode::InvalidApiKey,
+        ErrorCode::RateLimited,
+        ErrorCode::QuotaExceeded,
+        ErrorCode::Overloaded,
+        ErrorCode::NotReady,
+        ErrorCode::SearchTimeout,
+        ErrorCode::QueueTimeout,
+        ErrorCode::RpcError,
+        ErrorCode::SimulationError,
+        ErrorCode::CacheError,
+        ErrorCode::ConfigError,
+        ErrorCode::InternalError,
+    ];
+
+    /// HTTP status answered with this code
+    pub fn status(self) -> StatusCode {
+        match self {
+            ErrorCode::InvalidRequest
+            | ErrorCode::ValidationFailed
+            | ErrorCode::InvalidAmount
+            | ErrorCode::InvalidTokenAddress
+            | ErrorCode::SameToken
+            | ErrorCode::InvalidSlippage
+            | ErrorCode::InvalidMaxHops
+            | ErrorCode::InvalidMaxSplits
+            | ErrorCode::InvalidFee
+            | ErrorCode::UnsupportedChain => StatusCode::BAD_REQUEST,
+            ErrorCode::UnknownToken
+            | ErrorCode::NoRouteFound
+            | ErrorCode::PoolNotFound
+            | ErrorCode::TokenNotPriced
+            | ErrorCode::QuoteNotFound => StatusCode::NOT_FOUND,
+            ErrorCode::QuoteExpired => StatusCode::GONE,
+            ErrorCode::InsufficientLiquidity | ErrorCode::PriceImpactTooHigh => {
+                StatusCode::UNPROCESSABLE_ENTITY
+            }
+            ErrorCode::MissingApiKey | ErrorCode::InvalidApiKey => StatusCode::UNAUTHORIZED,
+            ErrorCode::RateLimited | ErrorCode::QuotaExceeded | ErrorCode::Overloaded => {
+                StatusCode::TOO_MANY_REQUESTS
+            }
+            ErrorCode::NotReady
+            | ErrorCode::SearchTimeout
+            | ErrorCode::QueueTimeout
+            | ErrorCode::RpcError => StatusCode::SERVICE_UNAVAILABLE,
+            ErrorCode::SimulationError
+            | ErrorCode::CacheError
+            | ErrorCode::ConfigError
+            | ErrorCode::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl From<&RouterError> for ErrorCode {
+    fn from(err: &RouterError) -> Self {
+        match err {
+            RouterError::NoRouteFound { .. } => ErrorCode::NoRouteFound,
+            RouterError::InsufficientLiquidity { .. } => ErrorCode::InsufficientLiquidity,
+            RouterError::PriceImpactTooHigh { .. } => ErrorCode::PriceImpactTooHigh,
+            RouterError::InvalidTokenAddress(_) => ErrorCode::InvalidTokenAddress,
+            RouterError::InvalidAmount(_) => ErrorCode::InvalidAmount,
+            RouterError::PoolNotFound(_) => ErrorCode::PoolNotFound,
+            RouterError::SimulationError(_) => ErrorCode::SimulationError,
+            RouterError::RpcError(_) => ErrorCode::RpcError,
+            RouterError::CacheError(_) => ErrorCode::CacheError,
+            RouterError::ConfigError(_) => ErrorCode::ConfigError,
+            RouterError::InternalError(_) => ErrorCode::InternalError,
+            RouterError::SearchTimeout { .. } => ErrorCode::SearchTimeout,
+        }
+    }
+}
+
+/// An API error: code, human-readable message and optional details
+#[derive(Debug)]
+pub struct ApiError {
+    pub code: ErrorCode,
+    pub message: String,
+    /// Structured context (offending values, limits), per code
+    pub details: Option<Value>,
+    /// When a 429 may be retried (one second if unset)
+    pub retry_after: Option<Duration>,
+}
+
+impl ApiError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            details: None,
+            retry_after: None,
+        }
+    }
+
+    pub fn with_details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    pub fn with_retry_after(mut self, retry_after: Duration) -> Self {
+        self.retry_after = Some(retry_after);
+        self
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.code.status()
+    }
+}
+
+impl From<ComputeError> for ApiError {
+    fn from(err: ComputeError) -> Self {
+        let code = match err {
+            ComputeError::QueueFull(_) => ErrorCode::Overloaded,
+            ComputeError::QueueTimeout(_) => ErrorCode::QueueTimeout,
+            ComputeError::Panicked => ErrorCode::InternalError,
+        };
+        ApiError::new(code, err.to_string())
+    }
+}
+
+impl From<RouterError> for ApiError {
+    fn from(err: RouterError) -> Self {
+        let details = match &err {
+            RouterError::NoRouteFound { from, to } | RouterError::SearchTimeout { from, to } => {
+                Some(json!({ "token_in": from, "token_out": to }))
+            }
+            RouterError::InsufficientLiquidity { required, available } => {
+                Some(json!({ "required": required, "available": available }))
+            }
+            RouterError::PriceImpactTooHigh { impact } => Some(json!({ "impact": impact })),
+            RouterError::PoolNotFound(pool_id) => Some(json!({ "pool_id": pool_id })),
+            _ => None,
+        };
+        ApiError {
+            code: ErrorCode::from(&err),
+            message: err.to_string(),
+            details,
+            retry_after: None,
+        }
+    }
+}
+
+impl From<QueryRejection> for ApiError {
+    fn from(rejection: QueryRejection) -> Self {
+        ApiError::new(ErrorCode::InvalidRequest, rejection.body_text())
+    }
+}
+
+impl From<PathRejection> for ApiError {
+    fn from(rejection: PathRejection) -> Self {
+        ApiError::new(ErrorCode::InvalidRequest, rejection.body_text())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let error_response = ErrorResponse {
+            error: status.canonical_reason().unwrap_or("Error").to_string(),
+            code: self.code,
+            message: self.message,
+            details: self.details,
+        };
+
+        let mut response = (status, Json(error_response)).into_response();
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            // Queued jobs drain within about one search budget; rate
+            // limits and quotas know their own wait
+            let secs = self
+                .retry_after
+                .map_or(1, |wait| wait.as_secs() + u64::from(wait.subsec_nanos() > 0))
+                .max(1);
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, header::HeaderValue::from(secs));
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::Address;
+
+    #[test]
+    fn test_router_errors_map_to_codes_and_statuses() {
+        let cases = [
+            (
+                RouterError::NoRouteFound { from: Address::ZERO, to: Address::ZERO },
+                ErrorCode::NoRouteFound,
+                StatusCode::NOT_FOUND,
+            ),
+            (
+                RouterError::InsufficientLiquidity {
+                    required: "10".to_string(),
+                    available: "3".to_string(),
+                },
+                ErrorCode::InsufficientLiquidity,
+                StatusCode::UNPROCESSABLE_ENTITY,
+            ),
+            (
+                RouterError::PriceImpactTooHigh { impact: 12.5 },
+                ErrorCode::PriceImpactTooHigh,
+                StatusCode::UNPROCESSABLE_ENTITY,
+            ),
+            (
+                RouterError::InvalidAmount("0".to_string()),
+                ErrorCode::InvalidAmount,
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                RouterError::RpcError("timeout".to_string()),
+                ErrorCode::RpcError,
+                StatusCode::SERVICE_UNAVAILABLE,
+            ),
+            (
+                RouterError::SimulationError("overflow".to_string()),
+                ErrorCode::SimulationError,
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+        ];
+        for (err, code, status) in cases {
+            let kind = err.kind();
+            let api = ApiError::from(err);
+            assert_eq!(api.code, code);
+            assert_eq!(api.status(), status);
+            // Codes of router errors match their metrics labels
+            assert_eq!(serde_json::to_value(code).unwrap(), kind);
+        }
+
+        let api = ApiError::from(RouterError::InsufficientLiquidity {
+            required: "10".to_string(),
+            available: "3".to_string(),
+        });
+        assert_eq!(api.details, Some(json!({ "required": "10", "available": "3" })));
+    }
+
+    #[test]
+    fn test_every_code_has_a_distinct_name() {
+        let names: std::collections::HashSet<String> = ErrorCode::ALL
+            .iter()
+            .map(|code| serde_json::to_value(code).unwrap().as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(names.len(), ErrorCode::ALL.len());
+        assert!(ErrorCode::ALL
+            .iter()
+            .all(|code| code.status().is_client_error() || code.status().is_server_error()));
+    }
+}
@@ -0,0 +1,15 @@
+//! Extractors that reject with an [`ApiError`] instead of axum's plain
+//! text responses, so malformed parameters get an `invalid_request` code
+
+use super::error::ApiError;
+use axum::extract::{FromRequestParts, Path, Query};
+
+/// [`Query`] rejecting with an [`ApiError`]
+#[derive(Debug, FromRequestParts)]
+#[from_request(via(Query), rejection(ApiError))]
+pub struct ApiQuery<T>(pub T);
+
+/// [`Path`] rejecting with an [`ApiError`]
+#[derive(Debug, FromRequestParts)]
+#[from_request(via(Path), rejection(ApiError))]
+pub struct ApiPath<T>(pub T);
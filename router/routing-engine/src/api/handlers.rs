@@ -1,131 +1,492 @@
-use super::dto::{ErrorResponse, GraphStatsDto, HealthResponse, QuoteRequest, QuoteResponse};
-use super::state::AppState;
+use super::dto::{
+    ChainHealthDto, ChainQuery, GraphDumpRequest, GraphStatsDto, HealthResponse,
+    LivenessResponse, PricesResponse, QuoteRefreshResponse, QuoteRequest, QuoteResponse,
+    SubsystemIssue, SyncStatusDto, TokenPriceResponse,
+};
+use super::error::{ApiError, ErrorCode};
+use super::extract::{ApiPath, ApiQuery};
+use super::state::{AppState, ChainState};
+use super::access::Caller;
+use super::validation::{validate_quote, ValidQuote};
+use crate::metrics::Metrics;
+use crate::routing::{resimulate_split, Quote};
+use crate::utils::u256_ext::to_f64;
 use axum::{
-    extract::{Query, State},
-    http::StatusCode,
+    extract::{Extension, State},
+    http::{header, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
-use alloy_primitives::U256;
+use alloy_primitives::{Address, U256};
+use serde_json::json;
 use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
 
 /// Health check endpoint
-pub async fn health_check(State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    let stats = state.graph.stats();
+///
+/// Always answers 200; `status` is "healthy", "degraded" (serving quotes
+/// with failing subsystems) or "unhealthy" (not serving quotes) for the
+/// requested chain, and `chains` summarizes every chain.
+pub async fn health_check(
+    State(state): State<Arc<AppState>>,
+    ApiQuery(params): ApiQuery<ChainQuery>,
+) -> Result<Json<HealthResponse>, ApiError> {
+    let chain = select_chain(&state, params.chain_id)?;
+    Ok(Json(health_report(&state, chain)))
+}
+
+/// Liveness probe: the process is up and the runtime is responsive
+pub async fn liveness() -> impl IntoResponse {
+    Json(LivenessResponse {
+        status: "alive".to_string(),
+    })
+}
+
+/// Readiness probe: 200 only while the chain's pool data is synced and
+/// fresh
+pub async fn readiness(
+    State(state): State<Arc<AppState>>,
+    ApiQuery(params): ApiQuery<ChainQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let chain = select_chain(&state, params.chain_id)?;
+    let report = health_report(&state, chain);
+    let status = if report.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    Ok((status, Json(report)))
+}
+
+/// The chain a request selected with `chain_id`, or the default chain
+fn select_chain(state: &AppState, chain_id: Option<u64>) -> Result<&Arc<ChainState>, ApiError> {
+    state.chain(chain_id).ok_or_else(|| {
+        let supported: Vec<u64> = state.chains.keys().copied().collect();
+        ApiError::new(
+            ErrorCode::UnsupportedChain,
+            format!(
+                "Unsupported chain_id {}; this instance serves {:?}",
+                chain_id.unwrap_or_default(),
+                supported
+            ),
+        )
+        .with_details(json!({ "chain_id": chain_id, "supported": supported }))
+    })
+}
+
+fn health_report(state: &AppState, chain: &ChainState) -> HealthResponse {
+    let stats = chain.graph.stats();
+    let (status, ready) = chain_status(state, chain);
 
-    let response = HealthResponse {
-        status: "healthy".to_string(),
+    HealthResponse {
+        status: status.to_string(),
         version: env!("CARGO_PKG_VERSION").to_string(),
-        chain_id: state.settings.chain.chain_id,
+        chain_id: chain.chain_id,
+        ready,
         graph_stats: GraphStatsDto {
             token_count: stats.token_count,
             pool_count: stats.pool_count,
             last_update: stats.last_update,
+            block_number: stats.block_number,
         },
+        sync: SyncStatusDto {
+            last_success: chain.sync_status.last_success(),
+            last_attempt: chain.sync_status.last_attempt(),
+            consecutive_failures: chain.sync_status.consecutive_failures(),
+            staleness_secs: graph_staleness(chain),
+            max_staleness_secs: state.settings.sync.max_staleness_secs,
+        },
+        issues: subsystem_issues(state, chain),
+        chains: state
+            .chains
+            .values()
+            .map(|chain| {
+                let stats = chain.graph.stats();
+                let (status, ready) = chain_status(state, chain);
+                ChainHealthDto {
+                    chain_id: chain.chain_id,
+                    status: status.to_string(),
+                    ready,
+                    pool_count: stats.pool_count,
+                    block_number: stats.block_number,
+                }
+            })
+            .collect(),
+    }
+}
+
+/// Overall status of a chain and whether it serves quotes
+fn chain_status(state: &AppState, chain: &ChainState) -> (&'static str, bool) {
+    let ready = check_data_ready(state, chain).is_ok();
+    let status = if !ready {
+        "unhealthy"
+    } else if !subsystem_issues(state, chain).is_empty() {
+        "degraded"
+    } else {
+        "healthy"
+    };
+    (status, ready)
+}
+
+/// Seconds since the pool graph was last updated, if it ever was
+fn graph_staleness(chain: &ChainState) -> Option<u64> {
+    let last_update = chain.graph.stats().last_update;
+    if last_update == 0 {
+        return None;
+    }
+
+    let now = chrono::Utc::now().timestamp() as u64;
+    Some(now.saturating_sub(last_update))
+}
+
+/// [`check_data_ready`] as an API error
+fn require_data_ready(state: &AppState, chain: &ChainState) -> Result<(), ApiError> {
+    check_data_ready(state, chain).map_err(|message| ApiError::new(ErrorCode::NotReady, message))
+}
+
+/// Check that pool data is fit to quote from
+fn check_data_ready(state: &AppState, chain: &ChainState) -> Result<(), String> {
+    if state.shutdown.is_cancelled() {
+        return Err("Service is shutting down".to_string());
+    }
+
+    if !chain.sync_status.has_synced() {
+        return Err("Pool data has not been synced yet".to_string());
+    }
+
+    let max_staleness = state.settings.sync.max_staleness_secs;
+    match graph_staleness(chain) {
+        None => Err("Pool graph is empty".to_string()),
+        Some(age) if age > max_staleness => Err(format!(
+            "Pool data is stale: last update {}s ago (max {}s)",
+            age, max_staleness
+        )),
+        Some(_) => Ok(()),
+    }
+}
+
+/// Collect subsystems that are currently failing
+fn subsystem_issues(state: &AppState, chain: &ChainState) -> Vec<SubsystemIssue> {
+    let mut issues = Vec::new();
+
+    if !chain.sync_status.rpc_healthy() {
+        issues.push(SubsystemIssue {
+            subsystem: "rpc".to_string(),
+            message: chain
+                .sync_status
+                .rpc_error()
+                .unwrap_or_else(|| "RPC unavailable".to_string()),
+        });
+    }
+
+    if let Err(message) = check_data_ready(state, chain) {
+        issues.push(SubsystemIssue {
+            subsystem: "sync".to_string(),
+            message,
+        });
+    } else if chain.sync_status.consecutive_failures() > 0 {
+        issues.push(SubsystemIssue {
+            subsystem: "sync".to_string(),
+            message: format!(
+                "{} consecutive sync failures: {}",
+                chain.sync_status.consecutive_failures(),
+                chain.sync_status.last_error().unwrap_or_default()
+            ),
+        });
+    }
+
+    if state.compute.is_saturated() {
+        issues.push(SubsystemIssue {
+            subsystem: "compute".to_string(),
+            message: format!(
+                "Compute queue is full ({} jobs waiting), shedding quotes",
+                state.compute.queue_depth()
+            ),
+        });
+    }
+
+    issues
+}
+
+/// Prometheus metrics endpoint
+///
+/// Reports every chain, or only the one named by `chain_id`.
+pub async fn get_metrics(
+    State(state): State<Arc<AppState>>,
+    ApiQuery(params): ApiQuery<ChainQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let chains: Vec<&Arc<ChainState>> = match params.chain_id {
+        Some(_) => vec![select_chain(&state, params.chain_id)?],
+        None => state.chains.values().collect(),
     };
+    for chain in &chains {
+        chain.metrics.observe_graph(&chain.graph);
+        chain.metrics.observe_cache(&chain.router.cache_stats());
+    }
+    let chain_metrics: Vec<&Metrics> = chains.iter().map(|chain| chain.metrics.as_ref()).collect();
 
-    Json(response)
+    Ok((
+        [(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)],
+        Metrics::encode_all(&state.metrics, &chain_metrics),
+    ))
 }
 
 /// Get quote for a swap
 pub async fn get_quote(
     State(state): State<Arc<AppState>>,
-    Query(params): Query<QuoteRequest>,
+    caller: Option<Extension<Caller>>,
+    ApiQuery(params): ApiQuery<QuoteRequest>,
 ) -> Result<Json<QuoteResponse>, ApiError> {
-    // Parse amount
-    let amount_in = params.amount_in.parse::<U256>()
-        .map_err(|_| ApiError::BadRequest("Invalid amount".to_string()))?;
+    let _in_flight = state.metrics.in_flight("quote");
+    let chain = select_chain(&state, params.chain_id)?;
 
-    // Check cache
+    // Refuse to quote from missing or stale pool data
+    require_data_ready(&state, chain)?;
+
+    let valid = validate_quote(
+        &params,
+        caller.as_ref().map(|Extension(caller)| caller),
+        &state.settings.routing,
+        &chain.router,
+        chain.chain_id,
+    )?;
+    // An input-side fee is taken before the swap
+    let amount_in = valid
+        .fee
+        .map_or(valid.amount_in, |fee| fee.routed_input(valid.amount_in));
+
+    // Check cache. The key holds every parameter the quote depends on,
+    // exactly: a cached quote is issued (and signed) as the answer to
+    // this request, so it must be the quote this request would get. The
+    // graph version retires cached quotes once a sync changes any pool.
+    let version = chain.graph.version();
     let cache_key = format!(
-        "{}:{}:{}",
+        "{}:{}:{}:{}:{}:{:?}:{:?}",
+        version,
         params.token_in,
         params.token_out,
-        bucket_amount(amount_in)
+        amount_in,
+        params.slippage,
+        params.max_hops,
+        params.max_splits
     );
 
-    if let Some(cached_quote) = state.cache.get(&cache_key).await {
-        return Ok(Json(QuoteResponse {
-            quote: cached_quote,
-            timestamp: chrono::Utc::now().timestamp() as u64,
-            cached: true,
-        }));
+    if let Some(cached_quote) = chain.cache.get(&cache_key).await {
+        let quote = charge_fee(chain, cached_quote, &params, valid);
+        return issue_quote(&state, chain, &params, quote, true);
     }
 
-    // Calculate route
+    // Calculate route on the compute pool. If the client disconnects
+    // this future is dropped, and the guard cancels the search.
+    let cancel = CancellationToken::new();
+    let _cancel_on_drop = cancel.clone().drop_guard();
+    let budget = chain.router.budget(params.timeout_ms).with_cancel(cancel);
+    let router = chain.router.clone();
     let quote = state
-        .router
-        .get_quote(
-            params.token_in,
-            params.token_out,
-            amount_in,
-            params.slippage,
-            params.max_hops,
-        )
-        .await
-        .map_err(ApiError::from)?;
+        .compute
+        .run(move || match params.max_splits {
+            // The amount may be split across several routes
+            Some(max_splits) if max_splits > 1 => router.get_split_quote_within(
+                params.token_in,
+                params.token_out,
+                amount_in,
+                params.slippage,
+                params.max_hops,
+                Some(max_splits),
+                &budget,
+            ),
+            _ => router.get_quote_within(
+                params.token_in,
+                params.token_out,
+                amount_in,
+                params.slippage,
+                params.max_hops,
+                &budget,
+            ),
+        })
+        .await?
+        .map_err(|e| {
+            chain.metrics.record_error(&e);
+            ApiError::from(e)
+        })?;
 
-    // Cache result
-    state
-        .cache
-        .set(&cache_key, &quote, std::time::Duration::from_secs(15))
-        .await;
+    // Cache result; partial quotes are only good for this request. The
+    // cache holds quotes without integrator fees, under the version they
+    // were computed at.
+    if !quote.partial && quote.graph_version == version {
+        chain
+            .cache
+            .set(&cache_key, &quote, std::time::Duration::from_secs(15))
+            .await;
+    }
+
+    let quote = charge_fee(chain, quote, &params, valid);
+    issue_quote(&state, chain, &params, quote, false)
+}
 
-    Ok(Json(QuoteResponse {
+/// Issue a quote answering `params` through the quote store
+fn issue_quote(
+    state: &AppState,
+    chain: &ChainState,
+    params: &QuoteRequest,
+    quote: Quote,
+    cached: bool,
+) -> Result<Json<QuoteResponse>, ApiError> {
+    let response = state.quotes.issue(
+        chain.chain_id,
+        params.token_in,
+        params.token_out,
         quote,
-        timestamp: chrono::Utc::now().timestamp() as u64,
-        cached: false,
-    }))
+        cached,
+    )?;
+    Ok(Json(response))
 }
 
-/// Bucket amounts to improve cache hit rate
-fn bucket_amount(amount: U256) -> String {
-    // Round to 2 significant figures
-    let amount_str = amount.to_string();
-    if amount_str.len() <= 2 {
-        return amount_str;
+/// A quote issued earlier, while it is still valid
+pub async fn get_issued_quote(
+    State(state): State<Arc<AppState>>,
+    ApiPath(id): ApiPath<Uuid>,
+) -> Result<Json<QuoteResponse>, ApiError> {
+    let issued = find_quote(&state, id)?;
+    let now = chrono::Utc::now().timestamp() as u64;
+    if issued.is_expired(now) {
+        return Err(ApiError::new(
+            ErrorCode::QuoteExpired,
+            format!("Quote {} expired at {}", id, issued.expires_at),
+        )
+        .with_details(json!({ "quote_id": id, "expires_at": issued.expires_at })));
     }
+    Ok(Json(issued))
+}
+
+/// Re-simulate an issued quote's route against current pool state
+///
+/// Every leg is re-run on the compute pool with the input it was quoted
+/// with, so the result shows how far the output has drifted since the
+/// quote was issued. Expired quotes can be refreshed while they are
+/// retained.
+pub async fn refresh_quote(
+    State(state): State<Arc<AppState>>,
+    ApiPath(id): ApiPath<Uuid>,
+) -> Result<Json<QuoteRefreshResponse>, ApiError> {
+    let issued = find_quote(&state, id)?;
+    let chain = select_chain(&state, Some(issued.chain_id))?;
+    require_data_ready(&state, chain)?;
+
+    let quote = &issued.quote;
+    let block_number = chain.graph.block_number();
+    let graph_version = chain.graph.version();
+    let graph = chain.graph.clone();
+    let route = quote.route.clone();
+    let route_out = state
+        .compute
+        .run(move || resimulate_split(&graph, &route))
+        .await?
+        .map_err(|e| {
+            chain.metrics.record_error(&e);
+            ApiError::from(e)
+        })?;
 
-    let first_two: String = amount_str.chars().take(2).collect();
-    let zeros = "0".repeat(amount_str.len() - 2);
-    format!("{}{}", first_two, zeros)
-}
-
-/// API Error type
-#[derive(Debug)]
-pub enum ApiError {
-    BadRequest(String),
-    InternalError(String),
-    NotFound(String),
-}
-
-impl From<crate::utils::RouterError> for ApiError {
-    fn from(err: crate::utils::RouterError) -> Self {
-        match err {
-            crate::utils::RouterError::NoRouteFound { .. } => {
-                ApiError::NotFound(err.to_string())
-            }
-            crate::utils::RouterError::InvalidAmount(_) => {
-                ApiError::BadRequest(err.to_string())
-            }
-            _ => ApiError::InternalError(err.to_string()),
-        }
+    let quoted = quote.net_amount_out();
+    let amount_out = quote.net_output(route_out);
+    let amount_out_min: U256 = quote.amount_out_min.parse().unwrap_or_default();
+    let drift_bps = if quoted.is_zero() {
+        0.0
+    } else {
+        (to_f64(amount_out) - to_f64(quoted)) / to_f64(quoted) * 10_000.0
+    };
+    let now = chrono::Utc::now().timestamp() as u64;
+
+    Ok(Json(QuoteRefreshResponse {
+        quote_id: id,
+        chain_id: chain.chain_id,
+        block_number,
+        graph_version,
+        quoted_block_number: issued.block_number,
+        quoted_amount_out: quoted.to_string(),
+        amount_out: amount_out.to_string(),
+        amount_out_min: amount_out_min.to_string(),
+        drift_bps,
+        within_slippage: amount_out >= amount_out_min,
+        expires_at: issued.expires_at,
+        expired: issued.is_expired(now),
+        timestamp: now,
+    }))
+}
+
+/// An issued quote still in the store
+fn find_quote(state: &AppState, id: Uuid) -> Result<QuoteResponse, ApiError> {
+    state.quotes.get(&id).ok_or_else(|| {
+        ApiError::new(ErrorCode::QuoteNotFound, format!("No quote {}", id))
+            .with_details(json!({ "quote_id": id }))
+    })
+}
+
+/// Charge the request's integrator fee, if any, on a quote
+fn charge_fee(chain: &ChainState, quote: Quote, params: &QuoteRequest, valid: ValidQuote) -> Quote {
+    match valid.fee {
+        Some(fee) => chain.router.with_integrator_fee(
+            quote,
+            fee,
+            valid.amount_in,
+            params.token_in,
+            params.token_out,
+        ),
+        None => quote,
     }
 }
 
-impl IntoResponse for ApiError {
-    fn into_response(self) -> Response {
-        let (status, message) = match self {
-            ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
-            ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
-            ApiError::InternalError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
-        };
+/// USD prices of every token priced from the chain's pools
+pub async fn get_prices(
+    State(state): State<Arc<AppState>>,
+    ApiQuery(params): ApiQuery<ChainQuery>,
+) -> Result<Json<PricesResponse>, ApiError> {
+    let chain = select_chain(&state, params.chain_id)?;
+    require_data_ready(&state, chain)?;
 
-        let error_response = ErrorResponse {
-            error: status.canonical_reason().unwrap_or("Error").to_string(),
-            message,
-        };
+    let prices = chain.prices.prices();
+    Ok(Json(PricesResponse {
+        chain_id: chain.chain_id,
+        block_number: prices.block_number(),
+        prices: prices.all().into_iter().cloned().collect(),
+    }))
+}
 
-        (status, Json(error_response)).into_response()
-    }
+/// USD price of one token
+pub async fn get_token_price(
+    State(state): State<Arc<AppState>>,
+    ApiPath(token): ApiPath<Address>,
+    ApiQuery(params): ApiQuery<ChainQuery>,
+) -> Result<Json<TokenPriceResponse>, ApiError> {
+    let chain = select_chain(&state, params.chain_id)?;
+    require_data_ready(&state, chain)?;
+
+    let prices = chain.prices.prices();
+    let price = prices.get(token).cloned().ok_or_else(|| {
+        ApiError::new(ErrorCode::TokenNotPriced, format!("No price for token {}", token))
+            .with_details(json!({ "token": token }))
+    })?;
+    Ok(Json(TokenPriceResponse {
+        chain_id: chain.chain_id,
+        block_number: prices.block_number(),
+        price,
+    }))
+}
+
+/// Dump the live pool graph in the fixture format
+pub async fn dump_graph(
+    State(state): State<Arc<AppState>>,
+    ApiQuery(params): ApiQuery<GraphDumpRequest>,
+) -> Result<Response, ApiError> {
+    let chain = select_chain(&state, params.chain_id)?;
+    let format = params.format.unwrap_or_default();
+    let body = chain
+        .graph
+        .export_fixture()
+        .render(format)
+        .map_err(|e| ApiError::new(ErrorCode::InternalError, e.to_string()))?;
+
+    Ok(([(header::CONTENT_TYPE, format.content_type())], body).into_response())
 }
@@ -1,7 +1,17 @@
+pub mod access;
 pub mod dto;
+pub mod error;
+pub mod extract;
 pub mod handlers;
+pub mod quote_store;
 pub mod routes;
+pub mod signing;
 pub mod state;
+pub mod validation;
 
-pub use routes::create_router;
-pub use state::AppState;
+pub use access::{AccessConfig, AccessControl, Caller};
+pub use error::{ApiError, ErrorCode};
+pub use quote_store::{QuoteStore, QuoteStoreConfig};
+pub use routes::{create_admin_router, create_router};
+pub use signing::{verify_quote, QuoteSigner, SignatureError, SigningConfig};
+pub use state::{AppState, ChainState};
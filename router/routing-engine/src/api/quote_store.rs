@@ -0,0 +1,143 @@
+//! Issued quotes, kept server-side for a short window
+//!
+//! Every quote the API answers gets an id and an `expires_at`, and is
+//! stored here so it can be fetched again (`GET /v1/quote/{id}`) or
+//! re-simulated against current pool state (`POST /v1/quote/{id}/refresh`).
+//! Quotes stay retrievable for `retention_secs`, past their expiry, so a
+//! client can still see how an expired quote would fare.
+
+use super::dto::QuoteResponse;
+use super::signing::QuoteSigner;
+use crate::cache::LruCache;
+use crate::routing::Quote;
+use crate::utils::Result;
+use alloy_primitives::Address;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Quote expiry and retention settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuoteStoreConfig {
+    /// How long a quote is valid after it is issued (seconds)
+    pub ttl_secs: u64,
+    /// How long a quote can be fetched or refreshed (seconds)
+    pub retention_secs: u64,
+    /// Quotes kept before the least recently used are dropped
+    pub max_quotes: usize,
+}
+
+impl Default for QuoteStoreConfig {
+    fn default() -> Self {
+        Self {
+            ttl_secs: 30,
+            retention_secs: 300,
+            max_quotes: 10_000,
+        }
+    }
+}
+
+/// Quotes issued by this instance, by id
+pub struct QuoteStore {
+    quotes: LruCache<Uuid, QuoteResponse>,
+    config: QuoteStoreConfig,
+    /// Signs issued quotes, if a signing key is configured
+    signer: Option<QuoteSigner>,
+}
+
+impl QuoteStore {
+    pub fn new(config: QuoteStoreConfig) -> Self {
+        Self {
+            quotes: LruCache::new(
+                config.max_quotes,
+                config.retention_secs.max(config.ttl_secs),
+            ),
+            config,
+            signer: None,
+        }
+    }
+
+    /// Sign every quote issued from now on
+    pub fn with_signer(mut self, signer: QuoteSigner) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Address quotes are signed by, if they are signed
+    pub fn signer(&self) -> Option<Address> {
+        self.signer.as_ref().map(QuoteSigner::address)
+    }
+
+    /// Give `quote` an id and expiry, stamp it with the graph state it
+    /// was computed from, sign it, and keep it
+    ///
+    /// The stamp comes from the quote itself, so a cached quote keeps the
+    /// block it was routed at rather than the block it is served at.
+    pub fn issue(
+        &self,
+        chain_id: u64,
+        token_in: Address,
+        token_out: Address,
+        quote: Quote,
+        cached: bool,
+    ) -> Result<QuoteResponse> {
+        let timestamp = chrono::Utc::now().timestamp() as u64;
+        let mut response = QuoteResponse {
+            quote_id: Uuid::new_v4(),
+            chain_id,
+            token_in,
+            token_out,
+            block_number: quote.block_number,
+            graph_version: quote.graph_version,
+            expires_at: timestamp + self.config.ttl_secs,
+            quote,
+            signature: None,
+            timestamp,
+            cached,
+        };
+        if let Some(signer) = &self.signer {
+            response.signature = Some(signer.sign(&response)?);
+        }
+        self.quotes.insert(response.quote_id, response.clone());
+        Ok(response)
+    }
+
+    /// A quote issued within the retention window, expired or not
+    pub fn get(&self, id: &Uuid) -> Option<QuoteResponse> {
+        self.quotes.get(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routing::SplitRoute;
+    use alloy_primitives::U256;
+
+    #[test]
+    fn test_issued_quotes_are_stamped_and_kept() {
+        let store = QuoteStore::new(QuoteStoreConfig::default());
+        let quote = Quote::from_route(
+            SplitRoute::new(Vec::new(), U256::ZERO, U256::ZERO, 0.0, 0),
+            0.5,
+        )
+        .computed_at(42, 7);
+
+        let tokens = (Address::ZERO, Address::repeat_byte(1));
+        let first = store
+            .issue(8453, tokens.0, tokens.1, quote.clone(), false)
+            .unwrap();
+        let second = store.issue(8453, tokens.0, tokens.1, quote, true).unwrap();
+        assert_ne!(first.quote_id, second.quote_id);
+        assert_eq!(first.block_number, 42);
+        assert_eq!(first.expires_at, first.timestamp + 30);
+        assert!(!first.is_expired(first.timestamp));
+        assert!(first.is_expired(first.expires_at));
+        assert!(first.signature.is_none());
+
+        let stored = store.get(&first.quote_id).unwrap();
+        assert_eq!(stored.quote_id, first.quote_id);
+        assert!(!stored.cached);
+        assert!(store.get(&Uuid::new_v4()).is_none());
+    }
+}
@@ -1,25 +1,55 @@
-use super::handlers::{get_quote, health_check};
+use super::handlers::{
+    dump_graph, get_issued_quote, get_metrics, get_prices, get_quote, get_token_price,
+    health_check, liveness, readiness, refresh_quote,
+};
+use super::access::authorize;
 use super::state::AppState;
 use axum::{
-    routing::get,
+    middleware,
+    routing::{get, post},
     Router,
 };
 use std::sync::Arc;
-use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
 
-/// Create the main router with all routes
+/// Create the public router: probes, quotes and prices
+///
+/// Panics if the configured CORS origins are not valid header values.
 pub fn create_router(state: AppState) -> Router {
-    // CORS layer
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
+    let cors = state
+        .settings
+        .access
+        .cors
+        .layer()
+        .expect("CORS origins are valid");
+    let access = middleware::from_fn_with_state(state.access.clone(), authorize);
 
     Router::new()
         .route("/health", get(health_check))
+        .route("/live", get(liveness))
+        .route("/ready", get(readiness))
         .route("/v1/quote", get(get_quote))
+        .route("/v1/quote/:id", get(get_issued_quote))
+        .route("/v1/quote/:id/refresh", post(refresh_quote))
+        .route("/v1/prices", get(get_prices))
+        .route("/v1/prices/:token", get(get_token_price))
+        .layer(access)
+        // Outside access control, so rejections carry CORS headers and
+        // preflights need no key
         .layer(cors)
         .layer(TraceLayer::new_for_http())
         .with_state(Arc::new(state))
 }
+
+/// Create the internal router: metrics and admin endpoints
+///
+/// It skips access control (metrics carry partner names, and the graph
+/// dump is the whole pool state), so serve it on a listener only
+/// operators and Prometheus can reach.
+pub fn create_admin_router(state: AppState) -> Router {
+    Router::new()
+        .route("/metrics", get(get_metrics))
+        .route("/admin/graph", get(dump_graph))
+        .layer(TraceLayer::new_for_http())
+        .with_state(Arc::new(state))
+}
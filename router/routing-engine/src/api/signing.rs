@@ -0,0 +1,377 @@
+//! EIP-712 signed quotes
+//!
+//! With a signing key configured (`signing.key` or `signing.key_env`),
+//! every issued quote carries a `signature` over its typed data: the quote
+//! id, chain, tokens, amounts, minimum output, route legs, integrator fee
+//! and expiry. A service that relays quotes can prove they left the engine
+//! unmodified with [`verify_quote`]. Fields outside the typed data (USD
+//! values, gas estimates, the pool snapshots in the route, the integrator
+//! fee's calldata) are not covered.
+
+use super::dto::QuoteResponse;
+use crate::utils::{Result, RouterError};
+use alloy_primitives::{Address, Bytes, Signature, B256, U256};
+use alloy_sol_types::{sol, Eip712Domain, SolStruct};
+use k256::ecdsa::SigningKey;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use thiserror::Error;
+
+/// EIP-712 domain name of quote signatures
+pub const DOMAIN_NAME: &str = "BaseBook Router";
+/// EIP-712 domain version of quote signatures
+pub const DOMAIN_VERSION: &str = "1";
+
+/// Typed data of a signed quote
+pub mod typed {
+    use super::sol;
+
+    sol! {
+        /// One leg of a split route
+        #[derive(Debug, PartialEq, Eq)]
+        struct RouteLeg {
+            bytes32[] pools;
+            /// Tokens along the leg, input first
+            address[] tokens;
+            uint256 amountIn;
+            uint256 amountOut;
+        }
+
+        /// A quote as signed by the engine
+        #[derive(Debug, PartialEq, Eq)]
+        struct Quote {
+            bytes16 quoteId;
+            uint256 chainId;
+            address tokenIn;
+            address tokenOut;
+            /// Input the user pays, integrator fee included
+            uint256 amountIn;
+            /// Output the user receives, integrator fee deducted
+            uint256 amountOut;
+            uint256 amountOutMin;
+            RouteLeg[] legs;
+            uint16 feeBps;
+            address feeRecipient;
+            uint64 expiresAt;
+        }
+    }
+}
+
+/// Quote signing settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SigningConfig {
+    /// Hex secp256k1 private key signing quotes (quotes are unsigned
+    /// without a key)
+    pub key: Option<String>,
+    /// Environment variable holding the key, to keep it out of config
+    /// files (overrides `key`)
+    pub key_env: Option<String>,
+    /// Contract named as `verifyingContract` in the EIP-712 domain
+    pub verifying_contract: Option<Address>,
+}
+
+/// Signature of a quote and the address that made it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuoteSignature {
+    /// 65-byte `r ‖ s ‖ v` signature over the quote's EIP-712 hash
+    pub signature: Bytes,
+    pub signer: Address,
+}
+
+/// Why a quote failed verification
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SignatureError {
+    #[error("Quote is not signed")]
+    Unsigned,
+
+    #[error("Malformed signed quote: {0}")]
+    Malformed(String),
+
+    #[error("Quote was signed by {recovered}, not {expected}")]
+    WrongSigner {
+        expected: Address,
+        recovered: Address,
+    },
+}
+
+/// EIP-712 domain of quotes on `chain_id`
+pub fn quote_domain(chain_id: u64, verifying_contract: Option<Address>) -> Eip712Domain {
+    Eip712Domain {
+        name: Some(Cow::Borrowed(DOMAIN_NAME)),
+        version: Some(Cow::Borrowed(DOMAIN_VERSION)),
+        chain_id: Some(U256::from(chain_id)),
+        verifying_contract,
+        salt: None,
+    }
+}
+
+/// Typed data of a quote response, as signed
+pub fn typed_quote(response: &QuoteResponse) -> std::result::Result<typed::Quote, SignatureError> {
+    let quote = &response.quote;
+    let amount = |field: &str, value: &str| {
+        value.parse::<U256>().map_err(|_| {
+            SignatureError::Malformed(format!("{} {:?} is not an amount", field, value))
+        })
+    };
+    let legs = quote
+        .route
+        .routes
+        .iter()
+        .map(|(route, _)| {
+            let tokens = route
+                .hops
+                .first()
+                .map(|hop| hop.token_in)
+                .into_iter()
+                .chain(route.hops.iter().map(|hop| hop.token_out))
+                .collect();
+            typed::RouteLeg {
+                pools: route
+                    .hops
+                    .iter()
+                    .map(|hop| B256::from(hop.pool.pool_id))
+                    .collect(),
+                tokens,
+                amountIn: route.total_amount_in,
+                amountOut: route.total_amount_out,
+            }
+        })
+        .collect();
+    let (fee_bps, fee_recipient) = quote
+        .integrator_fee
+        .as_ref()
+        .map_or((0, Address::ZERO), |fee| {
+            (fee.terms.bps, fee.terms.recipient)
+        });
+
+    Ok(typed::Quote {
+        quoteId: response.quote_id.into_bytes().into(),
+        chainId: U256::from(response.chain_id),
+        tokenIn: response.token_in,
+        tokenOut: response.token_out,
+        amountIn: amount("amount_in", &quote.amount_in)?,
+        amountOut: amount("amount_out", &quote.amount_out)?,
+        amountOutMin: amount("amount_out_min", &quote.amount_out_min)?,
+        legs,
+        feeBps: fee_bps,
+        feeRecipient: fee_recipient,
+        expiresAt: response.expires_at,
+    })
+}
+
+/// Signs issued quotes with the configured key
+pub struct QuoteSigner {
+    key: SigningKey,
+    address: Address,
+    verifying_contract: Option<Address>,
+}
+
+impl QuoteSigner {
+    /// Signer for a hex private key (with or without `0x`)
+    pub fn new(key: &str, verifying_contract: Option<Address>) -> Result<Self> {
+        let bytes: B256 = key.trim().parse().map_err(|_| {
+            RouterError::ConfigError("Quote signing key is not 32 hex bytes".to_string())
+        })?;
+        let key = SigningKey::from_bytes(&bytes.0.into()).map_err(|_| {
+            RouterError::ConfigError("Quote signing key is not a valid secp256k1 key".to_string())
+        })?;
+        Ok(Self {
+            address: Address::from_private_key(&key),
+            key,
+            verifying_contract,
+        })
+    }
+
+    /// Signer from the settings, or `None` when no key is configured
+    pub fn from_config(config: &SigningConfig) -> Result<Option<Self>> {
+        let key = match &config.key_env {
+            Some(var) => Some(std::env::var(var).map_err(|_| {
+                RouterError::ConfigError(format!("Quote signing key variable {} is not set", var))
+            })?),
+            None => config.key.clone(),
+        };
+        key.map(|key| Self::new(&key, config.verifying_contract))
+            .transpose()
+    }
+
+    /// Address quotes are signed by
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Sign a quote response's typed data
+    pub fn sign(&self, response: &QuoteResponse) -> Result<QuoteSignature> {
+        let typed = typed_quote(response).map_err(|e| RouterError::InternalError(e.to_string()))?;
+        let hash =
+            typed.eip712_signing_hash(&quote_domain(response.chain_id, self.verifying_contract));
+        let signature: Signature = self
+            .key
+            .sign_prehash_recoverable(hash.as_slice())
+            .map_err(|e| RouterError::InternalError(format!("Quote signing failed: {}", e)))?
+            .into();
+
+        Ok(QuoteSignature {
+            signature: Bytes::copy_from_slice(&signature.as_bytes()),
+            signer: self.address,
+        })
+    }
+}
+
+/// Check that `response` is signed by `signer`, unmodified
+///
+/// `signer` and `verifying_contract` must come from the verifier's own
+/// configuration: the `signer` field of the response is not trusted.
+pub fn verify_quote(
+    response: &QuoteResponse,
+    signer: Address,
+    verifying_contract: Option<Address>,
+) -> std::result::Result<(), SignatureError> {
+    let signed = response
+        .signature
+        .as_ref()
+        .ok_or(SignatureError::Unsigned)?;
+    let signature = Signature::from_raw(&signed.signature)
+        .map_err(|e| SignatureError::Malformed(e.to_string()))?;
+    let hash = typed_quote(response)?
+        .eip712_signing_hash(&quote_domain(response.chain_id, verifying_contract));
+    let recovered = signature
+        .recover_address_from_prehash(&hash)
+        .map_err(|e| SignatureError::Malformed(e.to_string()))?;
+
+    if recovered == signer {
+        Ok(())
+    } else {
+        Err(SignatureError::WrongSigner {
+            expected: signer,
+            recovered,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routing::{FeeSide, FeeTerms, Quote, Route, RouteHop, SplitRoute};
+    use crate::utils::address_from_u64;
+    use crate::PoolEdge;
+
+    const KEY: &str = "0x0000000000000000000000000000000000000000000000000000000000000001";
+
+    fn signed_response(signer: &QuoteSigner) -> QuoteResponse {
+        let (token_a, token_b) = (address_from_u64(1), address_from_u64(2));
+        let pool = PoolEdge::new(
+            [7u8; 32],
+            token_a,
+            token_b,
+            3000,
+            60,
+            1_000_000_000_000_000_000_000,
+            U256::from(1u128 << 96),
+            0,
+        );
+        let amount = U256::from(1_000_000u64);
+        let hop = RouteHop::new(
+            pool,
+            token_a,
+            token_b,
+            amount,
+            amount - U256::from(3_000u64),
+        );
+        let route = Route::new(
+            vec![hop],
+            amount,
+            amount - U256::from(3_000u64),
+            0.3,
+            100_000,
+        );
+        let split = SplitRoute::new(
+            vec![(route, 100)],
+            amount,
+            amount - U256::from(3_000u64),
+            0.3,
+            100_000,
+        );
+        let fee = FeeTerms {
+            bps: 25,
+            recipient: address_from_u64(9),
+            side: FeeSide::Output,
+        };
+        let quote =
+            Quote::from_route(split, 0.5).with_integrator_fee(fee, amount, token_a, token_b);
+
+        let mut response = QuoteResponse {
+            quote_id: uuid::Uuid::new_v4(),
+            chain_id: 8453,
+            token_in: token_a,
+            token_out: token_b,
+            block_number: 100,
+            graph_version: 0,
+            expires_at: 1_700_000_030,
+            quote,
+            signature: None,
+            timestamp: 1_700_000_000,
+            cached: false,
+        };
+        response.signature = Some(signer.sign(&response).unwrap());
+        response
+    }
+
+    #[test]
+    fn test_signer_address_comes_from_the_key() {
+        let signer = QuoteSigner::new(KEY, None).unwrap();
+        assert_eq!(
+            signer.address(),
+            "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf"
+                .parse::<Address>()
+                .unwrap()
+        );
+        assert!(QuoteSigner::new("0x1234", None).is_err());
+        assert!(QuoteSigner::from_config(&SigningConfig::default())
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_signed_quotes_verify_until_modified() {
+        let contract = Some(address_from_u64(42));
+        let signer = QuoteSigner::new(KEY, contract).unwrap();
+        let response = signed_response(&signer);
+        let signed = response.signature.clone().unwrap();
+        assert_eq!(signed.signer, signer.address());
+        assert_eq!(signed.signature.len(), 65);
+
+        // Survives a JSON round trip, as a relaying service sees it
+        let json = serde_json::to_string(&response).unwrap();
+        let relayed: QuoteResponse = serde_json::from_str(&json).unwrap();
+        assert_eq!(verify_quote(&relayed, signer.address(), contract), Ok(()));
+
+        let mut tampered = relayed.clone();
+        tampered.quote.amount_out_min = "1".to_string();
+        assert!(matches!(
+            verify_quote(&tampered, signer.address(), contract),
+            Err(SignatureError::WrongSigner { .. })
+        ));
+
+        let mut tampered = relayed.clone();
+        tampered
+            .quote
+            .integrator_fee
+            .as_mut()
+            .unwrap()
+            .terms
+            .recipient = address_from_u64(10);
+        assert!(verify_quote(&tampered, signer.address(), contract).is_err());
+
+        // Another domain or another trusted signer does not verify
+        assert!(verify_quote(&relayed, signer.address(), None).is_err());
+        assert!(verify_quote(&relayed, address_from_u64(5), contract).is_err());
+
+        let mut unsigned = relayed;
+        unsigned.signature = None;
+        assert_eq!(
+            verify_quote(&unsigned, signer.address(), contract),
+            Err(SignatureError::Unsigned)
+        );
+    }
+}
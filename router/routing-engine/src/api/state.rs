@@ -1,29 +1,178 @@
+use super::access::AccessControl;
+use super::quote_store::QuoteStore;
+use super::signing::QuoteSigner;
 use crate::cache::RouteCache;
-use crate::config::Settings;
+use crate::config::{ChainSettings, ContractAddresses, Settings};
+use crate::gas::GasOracle;
 use crate::graph::PoolGraph;
-use crate::routing::Router;
+use crate::metrics::Metrics;
+use crate::pricing::{PriceOracle, PricingConfig};
+use crate::routing::{Router, RouterConfig};
+use crate::service::ComputePool;
+use crate::sync::SyncStatus;
+use crate::utils::{Result, RouterError};
+use std::collections::BTreeMap;
 use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
 
-/// Application state shared across handlers
-#[derive(Clone)]
-pub struct AppState {
+/// Everything the engine keeps for one chain
+pub struct ChainState {
+    pub chain_id: u64,
+    pub contracts: ContractAddresses,
     pub router: Arc<Router>,
     pub graph: Arc<PoolGraph>,
     pub cache: Arc<RouteCache>,
+    /// Routing, cache, graph and sync metrics, labelled with the chain
+    pub metrics: Arc<Metrics>,
+    pub sync_status: Arc<SyncStatus>,
+    /// USD prices derived from the chain's pools
+    pub prices: Arc<PriceOracle>,
+    /// L2 gas price and L1 fee parameters, refreshed from the chain's RPC
+    pub gas: Arc<GasOracle>,
+    pub settings: ChainSettings,
+}
+
+impl ChainState {
+    /// Set up an empty graph and router for `chain`
+    ///
+    /// Fails for chains without known contract addresses.
+    pub fn new(chain: &ChainSettings, settings: &Settings) -> Result<Self> {
+        let contracts = ContractAddresses::for_chain(chain.chain_id).ok_or_else(|| {
+            RouterError::ConfigError(format!("Unsupported chain {}", chain.chain_id))
+        })?;
+
+        let mut candidates = settings.routing.candidates.clone();
+        if let Some(connectors) = &chain.connector_tokens {
+            candidates.connector_tokens = connectors.clone();
+        }
+
+        let pricing = PricingConfig {
+            anchors: chain
+                .price_anchors
+                .clone()
+                .unwrap_or_else(|| settings.pricing.anchors.clone()),
+            wrapped_native: chain.wrapped_native,
+            ..settings.pricing.clone()
+        };
+
+        let graph = Arc::new(PoolGraph::new());
+        let metrics = Arc::new(Metrics::for_chain(chain.chain_id));
+        let prices = Arc::new(PriceOracle::new(graph.clone(), pricing));
+        let sync_status = Arc::new(SyncStatus::new());
+        let gas = Arc::new(
+            GasOracle::new(chain.rpc_url.clone(), settings.gas.clone())
+                .with_status(sync_status.clone()),
+        );
+        let router = Router::with_metrics(
+            graph.clone(),
+            RouterConfig {
+                candidates,
+                screening: settings.routing.screening,
+                search_timeout_ms: settings.routing.search_timeout_ms,
+                max_search_timeout_ms: settings.routing.max_search_timeout_ms,
+                wrapped_native: chain.wrapped_native,
+                gas_model: settings.gas.model()?,
+                ..Default::default()
+            },
+            metrics.clone(),
+        )
+        .with_price_oracle(prices.clone())
+        .with_gas_oracle(gas.clone());
+
+        Ok(Self {
+            chain_id: chain.chain_id,
+            contracts,
+            router: Arc::new(router),
+            graph,
+            cache: Arc::new(RouteCache::default()),
+            metrics,
+            sync_status,
+            prices,
+            gas,
+            settings: chain.clone(),
+        })
+    }
+}
+
+/// Application state shared across handlers
+#[derive(Clone)]
+pub struct AppState {
+    /// Chains served, by chain id
+    pub chains: BTreeMap<u64, Arc<ChainState>>,
+    /// Chain used for requests without a `chain_id`
+    pub default_chain_id: u64,
+    /// Process-wide metrics (requests in flight, compute pool, tasks)
+    pub metrics: Arc<Metrics>,
+    /// Runs route searches off the async runtime
+    pub compute: Arc<ComputePool>,
+    /// Cancelled when the service begins shutting down
+    pub shutdown: CancellationToken,
+    /// API keys, rate limits and quotas
+    pub access: Arc<AccessControl>,
+    /// Quotes issued recently, by id
+    pub quotes: Arc<QuoteStore>,
     pub settings: Settings,
 }
 
 impl AppState {
+    /// Build the state for every chain in `settings.chains`
+    ///
+    /// Panics if no chain is configured or a chain is unsupported.
     pub fn new(settings: Settings) -> Self {
-        let graph = Arc::new(PoolGraph::new());
-        let router = Arc::new(Router::new(graph.clone()));
-        let cache = Arc::new(RouteCache::default());
+        let chains = settings
+            .chains
+            .iter()
+            .map(|chain| {
+                let state = ChainState::new(chain, &settings).expect("chain settings are valid");
+                (chain.chain_id, Arc::new(state))
+            })
+            .collect();
+
+        Self::with_chains(settings, chains)
+    }
+
+    /// Build the state around already constructed chains
+    pub fn with_chains(settings: Settings, chains: BTreeMap<u64, Arc<ChainState>>) -> Self {
+        let default_chain_id = settings
+            .default_chain_id()
+            .filter(|id| chains.contains_key(id))
+            .or_else(|| chains.keys().next().copied())
+            .expect("at least one chain is configured");
+        let metrics = Arc::new(Metrics::new());
+        let compute = Arc::new(ComputePool::with_metrics(
+            settings.compute.clone(),
+            metrics.clone(),
+        ));
+        let access = Arc::new(
+            AccessControl::new(settings.access.clone(), metrics.clone())
+                .expect("access settings are valid"),
+        );
+
+        let mut quotes = QuoteStore::new(settings.quotes.clone());
+        if let Some(signer) =
+            QuoteSigner::from_config(&settings.signing).expect("signing settings are valid")
+        {
+            quotes = quotes.with_signer(signer);
+        }
 
         Self {
-            router,
-            graph,
-            cache,
+            chains,
+            default_chain_id,
+            metrics,
+            compute,
+            shutdown: CancellationToken::new(),
+            access,
+            quotes: Arc::new(quotes),
             settings,
         }
     }
+
+    /// The chain a request asked for, or the default chain
+    pub fn chain(&self, chain_id: Option<u64>) -> Option<&Arc<ChainState>> {
+        self.chains.get(&chain_id.unwrap_or(self.default_chain_id))
+    }
+
+    pub fn default_chain(&self) -> &Arc<ChainState> {
+        &self.chains[&self.default_chain_id]
+    }
 }
@@ -0,0 +1,237 @@
+//! Quote request validation
+//!
+//! Quote parameters are checked against the configured
+//! [`RequestLimits`](crate::config::RequestLimits) before any routing work
+//! starts. Every violation is collected, so a client sees all of them in
+//! one response: a single violation is answered with its own code, several
+//! with `validation_failed`, and `details.violations` lists them either way.
+
+use super::dto::QuoteRequest;
+use super::error::{ApiError, ErrorCode};
+use crate::config::settings::RoutingSettings;
+use super::access::Caller;
+use crate::routing::{native, FeeTerms, Router};
+use crate::utils::u256_ext::to_f64;
+use crate::utils::{MAX_HOPS, MAX_SPLITS};
+use alloy_primitives::U256;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+/// One rejected quote parameter
+#[derive(Debug, Clone, Serialize)]
+pub struct Violation {
+    /// Query parameter at fault
+    pub field: &'static str,
+    pub code: ErrorCode,
+    pub message: String,
+    /// Offending value and the limits it broke
+    #[serde(flatten)]
+    pub context: Map<String, Value>,
+}
+
+impl Violation {
+    fn new(field: &'static str, code: ErrorCode, message: String, context: Value) -> Self {
+        let context = match context {
+            Value::Object(map) => map,
+            _ => Map::new(),
+        };
+        Self {
+            field,
+            code,
+            message,
+            context,
+        }
+    }
+}
+
+/// A quote request that passed validation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValidQuote {
+    pub amount_in: U256,
+    /// Integrator fee from the request or the caller's API key
+    pub fee: Option<FeeTerms>,
+}
+
+/// Check a quote request from `caller`
+///
+/// The liquidity check runs only once the amount and `token_in` are
+/// otherwise valid, since it needs both.
+pub fn validate_quote(
+    params: &QuoteRequest,
+    caller: Option<&Caller>,
+    routing: &RoutingSettings,
+    router: &Router,
+    chain_id: u64,
+) -> Result<ValidQuote, ApiError> {
+    let mut violations = Vec::new();
+
+    let amount_in = params
+        .amount_in
+        .parse::<U256>()
+        .ok()
+        .filter(|amount| !amount.is_zero());
+    if amount_in.is_none() {
+        violations.push(Violation::new(
+            "amount_in",
+            ErrorCode::InvalidAmount,
+            format!(
+                "Invalid amount {:?}: expected a positive integer in base units",
+                params.amount_in
+            ),
+            json!({ "amount_in": params.amount_in }),
+        ));
+    }
+
+    if native::normalize(params.token_in) == native::normalize(params.token_out) {
+        violations.push(Violation::new(
+            "token_out",
+            ErrorCode::SameToken,
+            format!("token_in and token_out are both {}", params.token_in),
+            json!({ "token": params.token_in }),
+        ));
+    }
+
+    let max_slippage = routing.limits.max_slippage;
+    if !(params.slippage.is_finite() && (0.0..=max_slippage).contains(&params.slippage)) {
+        violations.push(Violation::new(
+            "slippage",
+            ErrorCode::InvalidSlippage,
+            format!(
+                "slippage {} is outside 0-{}%",
+                params.slippage, max_slippage
+            ),
+            json!({ "slippage": params.slippage, "min": 0.0, "max": max_slippage }),
+        ));
+    }
+
+    let limits = [
+        (
+            "max_hops",
+            params.max_hops,
+            routing.max_hops.min(MAX_HOPS),
+            ErrorCode::InvalidMaxHops,
+        ),
+        (
+            "max_splits",
+            params.max_splits,
+            routing.max_splits.min(MAX_SPLITS),
+            ErrorCode::InvalidMaxSplits,
+        ),
+    ];
+    for (field, value, max, code) in limits {
+        if let Some(value) = value.filter(|&value| value == 0 || value > max) {
+            violations.push(Violation::new(
+                field,
+                code,
+                format!("{} {} is outside 1-{}", field, value, max),
+                json!({ field: value, "min": 1, "max": max }),
+            ));
+        }
+    }
+
+    let key = match caller {
+        Some(Caller::Key(key)) => Some(key.as_ref()),
+        _ => None,
+    };
+    let fee_bps = params
+        .fee_bps
+        .or_else(|| key.and_then(|key| key.fee_bps))
+        .unwrap_or(0);
+    let fee_recipient = params
+        .fee_recipient
+        .or_else(|| key.and_then(|key| key.fee_recipient));
+    let max_fee_bps = routing.limits.max_fee_bps;
+    if fee_bps > max_fee_bps {
+        violations.push(Violation::new(
+            "fee_bps",
+            ErrorCode::InvalidFee,
+            format!("fee_bps {} is above the maximum of {}", fee_bps, max_fee_bps),
+            json!({ "fee_bps": fee_bps, "max": max_fee_bps }),
+        ));
+    }
+    let fee = match fee_recipient.filter(|recipient| !recipient.is_zero()) {
+        _ if fee_bps == 0 => None,
+        Some(recipient) => Some(FeeTerms {
+            bps: fee_bps,
+            recipient,
+            side: params.fee_side.unwrap_or_default(),
+        }),
+        None => {
+            violations.push(Violation::new(
+                "fee_recipient",
+                ErrorCode::InvalidFee,
+                "fee_bps needs a non-zero fee_recipient".to_string(),
+                json!({ "fee_recipient": fee_recipient }),
+            ));
+            None
+        }
+    };
+
+    let token_in_routable = router.is_routable(params.token_in);
+    let routability = [
+        ("token_in", params.token_in, token_in_routable),
+        (
+            "token_out",
+            params.token_out,
+            router.is_routable(params.token_out),
+        ),
+    ];
+    for (field, token, routable) in routability {
+        if !routable {
+            violations.push(Violation::new(
+                field,
+                ErrorCode::UnknownToken,
+                format!("Token {} has no pools on chain {}", token, chain_id),
+                json!({ "token": token, "chain_id": chain_id }),
+            ));
+        }
+    }
+
+    let max_ratio = routing.limits.max_amount_to_liquidity;
+    if let Some(amount) = amount_in.filter(|_| token_in_routable && max_ratio > 0.0) {
+        let liquidity = router.token_liquidity(params.token_in);
+        if to_f64(amount) > to_f64(liquidity) * max_ratio {
+            violations.push(Violation::new(
+                "amount_in",
+                ErrorCode::InsufficientLiquidity,
+                format!(
+                    "amount_in {} exceeds the pool liquidity of {} ({})",
+                    amount, params.token_in, liquidity
+                ),
+                json!({ "required": amount.to_string(), "available": liquidity.to_string() }),
+            ));
+        }
+    }
+
+    match violations.len() {
+        0 => Ok(ValidQuote {
+            amount_in: amount_in.expect("amount is valid without violations"),
+            fee,
+        }),
+        1 => {
+            let violation = violations.remove(0);
+            let details = violation_details(&violation);
+            Err(ApiError::new(violation.code, violation.message).with_details(details))
+        }
+        count => {
+            let message = violations
+                .iter()
+                .map(|violation| violation.message.as_str())
+                .collect::<Vec<_>>()
+                .join("; ");
+            Err(ApiError::new(
+                ErrorCode::ValidationFailed,
+                format!("{} invalid parameters: {}", count, message),
+            )
+            .with_details(json!({ "violations": violations })))
+        }
+    }
+}
+
+/// Details of a lone violation: its context, plus the violation list
+/// every validation error carries
+fn violation_details(violation: &Violation) -> Value {
+    let mut details = violation.context.clone();
+    details.insert("violations".to_string(), json!([violation]));
+    Value::Object(details)
+}
@@ -0,0 +1,117 @@
+use crate::graph::{FixtureError, FixtureFormat, GraphFixture};
+use alloy_primitives::Address;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use thiserror::Error;
+
+/// Recorded pool state and swap requests, one entry per block
+///
+/// Blocks are replayed in order. A block without `state` keeps the
+/// pool state of the block before it, so a dataset only needs to
+/// carry state when it changes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BacktestDataset {
+    pub blocks: Vec<BlockRecord>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockRecord {
+    pub block_number: u64,
+    /// Pool state at this block, in the graph fixture format
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<GraphFixture>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub swaps: Vec<SwapRequest>,
+}
+
+/// A swap request as it was received
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwapRequest {
+    /// Token address, or symbol if unique within the block state
+    pub token_in: String,
+    /// Token address, or symbol if unique within the block state
+    pub token_out: String,
+    /// Input amount in base units (decimal string)
+    pub amount_in: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_hops: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_splits: Option<usize>,
+    /// Output actually received on-chain, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub executed_amount_out: Option<String>,
+}
+
+#[derive(Error, Debug)]
+pub enum BacktestError {
+    #[error("Backtest I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Malformed backtest dataset: {0}")]
+    Malformed(String),
+
+    #[error("Block {block}: no pool state recorded at or before this block")]
+    MissingState { block: u64 },
+
+    #[error("Block {block}: {source}")]
+    Fixture {
+        block: u64,
+        #[source]
+        source: FixtureError,
+    },
+
+    #[error("Block {block}, swap {index}: {reason}")]
+    InvalidSwap {
+        block: u64,
+        index: usize,
+        reason: String,
+    },
+}
+
+impl BacktestDataset {
+    pub fn parse(input: &str, format: FixtureFormat) -> Result<Self, BacktestError> {
+        match format {
+            FixtureFormat::Json => {
+                serde_json::from_str(input).map_err(|e| BacktestError::Malformed(e.to_string()))
+            }
+            FixtureFormat::Toml => {
+                toml::from_str(input).map_err(|e| BacktestError::Malformed(e.to_string()))
+            }
+        }
+    }
+
+    /// Read a dataset file, choosing the format from its extension
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, BacktestError> {
+        let path = path.as_ref();
+        let input = std::fs::read_to_string(path)?;
+        Self::parse(&input, FixtureFormat::from_path(path))
+    }
+
+    /// Write a dataset file as pretty-printed JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), BacktestError> {
+        let output = serde_json::to_string_pretty(self)
+            .map_err(|e| BacktestError::Malformed(e.to_string()))?;
+        std::fs::write(path, output)?;
+        Ok(())
+    }
+
+    pub fn swap_count(&self) -> usize {
+        self.blocks.iter().map(|b| b.swaps.len()).sum()
+    }
+}
+
+/// Resolve a token given by address or by a symbol unique in `state`
+pub(crate) fn resolve_token(state: &GraphFixture, value: &str) -> Option<Address> {
+    if let Ok(address) = value.parse::<Address>() {
+        return Some(address);
+    }
+
+    let mut matches = state
+        .tokens
+        .iter()
+        .filter(|t| t.symbol.eq_ignore_ascii_case(value));
+    match (matches.next(), matches.next()) {
+        (Some(token), None) => Some(token.address),
+        _ => None,
+    }
+}
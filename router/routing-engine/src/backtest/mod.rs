@@ -0,0 +1,16 @@
+//! Offline backtesting of routing quality
+//!
+//! Replays recorded pool state and swap requests block by block,
+//! quotes each swap with one or two router profiles and re-simulates
+//! the quoted routes against the state at execution time. The result
+//! is a [`BacktestReport`] that can be saved and compared across runs.
+
+pub mod dataset;
+pub mod report;
+pub mod runner;
+
+pub use dataset::{BacktestDataset, BacktestError, BlockRecord, SwapRequest};
+pub use report::{
+    BacktestReport, Comparison, Distribution, ProfileSummary, SwapOutcome, SwapRecord,
+};
+pub use runner::{Backtest, RouterProfile};
@@ -0,0 +1,363 @@
+use super::runner::RouterProfile;
+use crate::utils::u256_ext;
+use alloy_primitives::{Address, U256};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Bumped when the report layout changes incompatibly
+pub const REPORT_VERSION: u32 = 1;
+
+/// Result of a backtest run
+///
+/// Serialized as JSON; two reports from different runs over the same
+/// dataset can be compared record by record.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestReport {
+    pub version: u32,
+    pub blocks: usize,
+    pub swaps: usize,
+    pub execution_delay_blocks: usize,
+    pub baseline: ProfileSummary,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub candidate: Option<ProfileSummary>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comparison: Option<Comparison>,
+    pub records: Vec<SwapRecord>,
+}
+
+/// One replayed swap
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwapRecord {
+    pub block_number: u64,
+    /// Block whose state the quoted route was executed against
+    pub execution_block: u64,
+    /// Position of the swap within its block
+    pub index: usize,
+    pub token_in: Address,
+    pub token_out: Address,
+    pub amount_in: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub executed_amount_out: Option<String>,
+    pub baseline: SwapOutcome,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub candidate: Option<SwapOutcome>,
+    /// Whether the candidate picked different pools or percentages
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub route_changed: Option<bool>,
+}
+
+/// How one profile did on one swap
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SwapOutcome {
+    pub latency_us: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quoted_amount_out: Option<String>,
+    /// Output of the quoted route re-simulated at the execution block
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub achievable_amount_out: Option<String>,
+    /// (quoted - achievable) / quoted, in basis points
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shortfall_bps: Option<f64>,
+    pub routes: usize,
+    /// Pools and percentage of every leg, e.g. `60%:0x01..>0x04..|40%:0x02..`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub route_key: Option<String>,
+    /// Routing failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The quoted route could not be executed at the execution block
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execution_error: Option<String>,
+}
+
+impl SwapOutcome {
+    pub(crate) fn failed(latency_us: u64, error: String) -> Self {
+        Self {
+            latency_us,
+            error: Some(error),
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn quoted(
+        latency_us: u64,
+        quoted: U256,
+        achievable: U256,
+        routes: usize,
+        route_key: String,
+        execution_error: Option<String>,
+    ) -> Self {
+        Self {
+            latency_us,
+            quoted_amount_out: Some(quoted.to_string()),
+            achievable_amount_out: Some(achievable.to_string()),
+            shortfall_bps: Some(relative_bps(quoted, achievable)),
+            routes,
+            route_key: Some(route_key),
+            error: None,
+            execution_error,
+        }
+    }
+}
+
+/// Aggregates for one router profile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileSummary {
+    pub name: String,
+    pub profile: RouterProfile,
+    pub quoted: usize,
+    pub failed: usize,
+    /// Quotes whose route could not be executed at all
+    pub execution_failures: usize,
+    /// Quotes that returned less than quoted when executed
+    pub overquoted: usize,
+    pub shortfall_bps: Distribution,
+    /// Achievable output against the recorded on-chain output, in
+    /// basis points (positive means the router would have done better)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vs_executed_bps: Option<Distribution>,
+    pub latency_us: Distribution,
+    /// Quotes using more than one route
+    pub split_quotes: usize,
+    pub split_rate: f64,
+    pub mean_routes: f64,
+}
+
+/// Baseline against candidate, over swaps both profiles quoted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Comparison {
+    pub compared: usize,
+    pub route_changes: usize,
+    pub candidate_better: usize,
+    pub baseline_better: usize,
+    pub equal: usize,
+    /// Candidate achievable output relative to the baseline's, in basis points
+    pub achievable_delta_bps: Distribution,
+    pub quoted_only_by_baseline: usize,
+    pub quoted_only_by_candidate: usize,
+}
+
+/// Summary statistics of a sample
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Distribution {
+    pub count: usize,
+    pub mean: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+impl Distribution {
+    /// Nearest-rank percentiles; all zero for an empty sample
+    pub fn from_samples(mut samples: Vec<f64>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+
+        samples.sort_by(|a, b| a.total_cmp(b));
+        let count = samples.len();
+        let rank =
+            |p: f64| samples[((p / 100.0 * count as f64).ceil() as usize).clamp(1, count) - 1];
+
+        Self {
+            count,
+            mean: samples.iter().sum::<f64>() / count as f64,
+            p50: rank(50.0),
+            p90: rank(90.0),
+            p99: rank(99.0),
+            max: samples[count - 1],
+        }
+    }
+}
+
+fn parse_amount(amount: &Option<String>) -> Option<U256> {
+    amount.as_deref().and_then(|a| a.parse().ok())
+}
+
+/// (reference - value) / reference in basis points
+fn relative_bps(reference: U256, value: U256) -> f64 {
+    let reference = u256_ext::to_f64(reference);
+    if reference == 0.0 {
+        return 0.0;
+    }
+    (reference - u256_ext::to_f64(value)) / reference * 10_000.0
+}
+
+impl ProfileSummary {
+    fn build<'a>(
+        profile: &RouterProfile,
+        outcomes: impl Iterator<Item = (&'a SwapRecord, &'a SwapOutcome)>,
+    ) -> Self {
+        let mut summary = Self {
+            name: profile.name.clone(),
+            profile: profile.clone(),
+            quoted: 0,
+            failed: 0,
+            execution_failures: 0,
+            overquoted: 0,
+            shortfall_bps: Distribution::default(),
+            vs_executed_bps: None,
+            latency_us: Distribution::default(),
+            split_quotes: 0,
+            split_rate: 0.0,
+            mean_routes: 0.0,
+        };
+        let mut shortfall = Vec::new();
+        let mut vs_executed = Vec::new();
+        let mut latency = Vec::new();
+        let mut routes = 0;
+
+        for (record, outcome) in outcomes {
+            latency.push(outcome.latency_us as f64);
+            if outcome.error.is_some() {
+                summary.failed += 1;
+                continue;
+            }
+
+            summary.quoted += 1;
+            routes += outcome.routes;
+            if outcome.routes > 1 {
+                summary.split_quotes += 1;
+            }
+            if outcome.execution_error.is_some() {
+                summary.execution_failures += 1;
+            }
+            if let Some(bps) = outcome.shortfall_bps {
+                if bps > 0.0 {
+                    summary.overquoted += 1;
+                }
+                shortfall.push(bps);
+            }
+            if let (Some(executed), Some(achievable)) = (
+                parse_amount(&record.executed_amount_out),
+                parse_amount(&outcome.achievable_amount_out),
+            ) {
+                vs_executed.push(-relative_bps(executed, achievable));
+            }
+        }
+
+        if summary.quoted > 0 {
+            summary.split_rate = summary.split_quotes as f64 / summary.quoted as f64;
+            summary.mean_routes = routes as f64 / summary.quoted as f64;
+        }
+        summary.shortfall_bps = Distribution::from_samples(shortfall);
+        summary.latency_us = Distribution::from_samples(latency);
+        if !vs_executed.is_empty() {
+            summary.vs_executed_bps = Some(Distribution::from_samples(vs_executed));
+        }
+        summary
+    }
+}
+
+impl Comparison {
+    fn build(records: &[SwapRecord]) -> Self {
+        let mut comparison = Self {
+            compared: 0,
+            route_changes: 0,
+            candidate_better: 0,
+            baseline_better: 0,
+            equal: 0,
+            achievable_delta_bps: Distribution::default(),
+            quoted_only_by_baseline: 0,
+            quoted_only_by_candidate: 0,
+        };
+        let mut deltas = Vec::new();
+
+        for record in records {
+            let Some(candidate) = &record.candidate else {
+                continue;
+            };
+            match (
+                parse_amount(&record.baseline.achievable_amount_out),
+                parse_amount(&candidate.achievable_amount_out),
+            ) {
+                (Some(baseline), Some(candidate)) => {
+                    comparison.compared += 1;
+                    if record.route_changed == Some(true) {
+                        comparison.route_changes += 1;
+                    }
+                    match candidate.cmp(&baseline) {
+                        std::cmp::Ordering::Greater => comparison.candidate_better += 1,
+                        std::cmp::Ordering::Less => comparison.baseline_better += 1,
+                        std::cmp::Ordering::Equal => comparison.equal += 1,
+                    }
+                    deltas.push(-relative_bps(baseline, candidate));
+                }
+                (Some(_), None) => comparison.quoted_only_by_baseline += 1,
+                (None, Some(_)) => comparison.quoted_only_by_candidate += 1,
+                (None, None) => {}
+            }
+        }
+
+        comparison.achievable_delta_bps = Distribution::from_samples(deltas);
+        comparison
+    }
+}
+
+impl BacktestReport {
+    pub(crate) fn build(
+        blocks: usize,
+        execution_delay_blocks: usize,
+        baseline: &RouterProfile,
+        candidate: Option<&RouterProfile>,
+        records: Vec<SwapRecord>,
+    ) -> Self {
+        let baseline_summary =
+            ProfileSummary::build(baseline, records.iter().map(|r| (r, &r.baseline)));
+        let candidate_summary = candidate.map(|profile| {
+            ProfileSummary::build(
+                profile,
+                records
+                    .iter()
+                    .filter_map(|r| r.candidate.as_ref().map(|c| (r, c))),
+            )
+        });
+        let comparison = candidate.map(|_| Comparison::build(&records));
+
+        Self {
+            version: REPORT_VERSION,
+            blocks,
+            swaps: records.len(),
+            execution_delay_blocks,
+            baseline: baseline_summary,
+            candidate: candidate_summary,
+            comparison,
+            records,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("report serializes")
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_json())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distribution_percentiles() {
+        let dist = Distribution::from_samples((1..=100).rev().map(f64::from).collect());
+        assert_eq!(dist.count, 100);
+        assert_eq!(dist.p50, 50.0);
+        assert_eq!(dist.p90, 90.0);
+        assert_eq!(dist.p99, 99.0);
+        assert_eq!(dist.max, 100.0);
+        assert_eq!(dist.mean, 50.5);
+
+        assert_eq!(Distribution::from_samples(vec![]), Distribution::default());
+        assert_eq!(Distribution::from_samples(vec![7.0]).p99, 7.0);
+    }
+
+    #[test]
+    fn test_relative_bps() {
+        assert_eq!(relative_bps(U256::from(10_000), U256::from(9_900)), 100.0);
+        assert_eq!(relative_bps(U256::from(10_000), U256::from(10_100)), -100.0);
+        assert_eq!(relative_bps(U256::ZERO, U256::from(1)), 0.0);
+    }
+}
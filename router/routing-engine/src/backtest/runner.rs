@@ -0,0 +1,460 @@
+use super::dataset::{resolve_token, BacktestDataset, BacktestError, SwapRequest};
+use super::report::{BacktestReport, SwapOutcome, SwapRecord};
+use crate::graph::{FixtureError, GraphFixture, PoolGraph};
+use crate::routing::{resimulate_split, Router, RouterConfig, SplitRoute};
+use crate::utils::{MAX_HOPS, MAX_SPLITS};
+use alloy_primitives::{Address, B256, U256};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Router settings a backtest quotes with
+///
+/// `max_hops` and `max_splits` cap the limits of each request; a
+/// request asking for fewer keeps its own limit. The route cache is
+/// always disabled during a backtest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RouterProfile {
+    /// Defaults to the file name when loaded from a file
+    #[serde(default)]
+    pub name: String,
+    pub max_hops: usize,
+    pub max_splits: usize,
+    pub router: RouterConfig,
+}
+
+impl Default for RouterProfile {
+    fn default() -> Self {
+        Self {
+            name: "baseline".to_string(),
+            max_hops: MAX_HOPS,
+            max_splits: MAX_SPLITS,
+            router: RouterConfig {
+                enable_cache: false,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl RouterProfile {
+    pub fn named(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Read a profile from a `.toml` or `.json` file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, BacktestError> {
+        let path = path.as_ref();
+        let input = std::fs::read_to_string(path)?;
+        let malformed = |e: String| BacktestError::Malformed(format!("{}: {}", path.display(), e));
+
+        let mut profile: Self = match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => {
+                toml::from_str(&input).map_err(|e| malformed(e.to_string()))?
+            }
+            _ => serde_json::from_str(&input).map_err(|e| malformed(e.to_string()))?,
+        };
+        if profile.name.is_empty() {
+            profile.name = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default();
+        }
+        Ok(profile)
+    }
+}
+
+/// Replays a [`BacktestDataset`] against one or two router profiles
+pub struct Backtest {
+    baseline: RouterProfile,
+    candidate: Option<RouterProfile>,
+    execution_delay_blocks: usize,
+}
+
+impl Backtest {
+    pub fn new(baseline: RouterProfile) -> Self {
+        Self {
+            baseline,
+            candidate: None,
+            execution_delay_blocks: 1,
+        }
+    }
+
+    /// Also quote every swap with `candidate` and diff the results
+    pub fn with_candidate(mut self, candidate: RouterProfile) -> Self {
+        self.candidate = Some(candidate);
+        self
+    }
+
+    /// Number of dataset blocks between quoting and execution
+    ///
+    /// Defaults to 1: a quote made at one block executes against the
+    /// state of the next. Past the end of the dataset the last block's
+    /// state is used.
+    pub fn with_execution_delay(mut self, blocks: usize) -> Self {
+        self.execution_delay_blocks = blocks;
+        self
+    }
+
+    pub fn run(&self, dataset: &BacktestDataset) -> Result<BacktestReport, BacktestError> {
+        let states = build_states(dataset)?;
+        let mut records = Vec::with_capacity(dataset.swap_count());
+
+        for (i, block) in dataset.blocks.iter().enumerate() {
+            if block.swaps.is_empty() {
+                continue;
+            }
+
+            let (fixture, graph) = &states[i];
+            let exec = (i + self.execution_delay_blocks).min(states.len() - 1);
+            let exec_graph = &states[exec].1;
+            let execution_block = dataset.blocks[exec].block_number;
+
+            let baseline = self.router(&self.baseline, graph);
+            let candidate = self.candidate.as_ref().map(|p| (p, self.router(p, graph)));
+
+            for (index, swap) in block.swaps.iter().enumerate() {
+                let parsed =
+                    parse_swap(fixture, swap).map_err(|reason| BacktestError::InvalidSwap {
+                        block: block.block_number,
+                        index,
+                        reason,
+                    })?;
+
+                let baseline_outcome =
+                    quote_and_execute(&baseline, &self.baseline, &parsed, exec_graph);
+                let candidate_outcome = candidate.as_ref().map(|(profile, router)| {
+                    quote_and_execute(router, profile, &parsed, exec_graph)
+                });
+
+                records.push(SwapRecord {
+                    block_number: block.block_number,
+                    execution_block,
+                    index,
+                    token_in: parsed.token_in,
+                    token_out: parsed.token_out,
+                    amount_in: parsed.amount_in.to_string(),
+                    executed_amount_out: parsed.executed_amount_out.map(|a| a.to_string()),
+                    route_changed: candidate_outcome
+                        .as_ref()
+                        .map(|c| c.route_key != baseline_outcome.route_key),
+                    baseline: baseline_outcome,
+                    candidate: candidate_outcome,
+                });
+            }
+        }
+
+        Ok(BacktestReport::build(
+            dataset.blocks.len(),
+            self.execution_delay_blocks,
+            &self.baseline,
+            self.candidate.as_ref(),
+            records,
+        ))
+    }
+
+    /// Router for one block; the cache is always off so that routes
+    /// are never carried over from a different pool state
+    fn router(&self, profile: &RouterProfile, graph: &Arc<PoolGraph>) -> Router {
+        Router::with_config(
+            graph.clone(),
+            RouterConfig {
+                enable_cache: false,
+                ..profile.router.clone()
+            },
+        )
+    }
+}
+
+/// Pool state of one block, as recorded and as a graph
+type BlockState = (Arc<GraphFixture>, Arc<PoolGraph>);
+
+/// Pool state of every block, carrying state forward where a block has none
+fn build_states(dataset: &BacktestDataset) -> Result<Vec<BlockState>, BacktestError> {
+    let mut states: Vec<BlockState> = Vec::with_capacity(dataset.blocks.len());
+
+    for block in &dataset.blocks {
+        let state = match &block.state {
+            Some(fixture) => {
+                let graph = PoolGraph::new();
+                graph
+                    .import_fixture(fixture)
+                    .map_err(|source: FixtureError| BacktestError::Fixture {
+                        block: block.block_number,
+                        source,
+                    })?;
+                graph.set_block_number(block.block_number);
+                (Arc::new(fixture.clone()), Arc::new(graph))
+            }
+            None => states.last().cloned().ok_or(BacktestError::MissingState {
+                block: block.block_number,
+            })?,
+        };
+        states.push(state);
+    }
+
+    Ok(states)
+}
+
+struct ParsedSwap {
+    token_in: Address,
+    token_out: Address,
+    amount_in: U256,
+    max_hops: Option<usize>,
+    max_splits: Option<usize>,
+    executed_amount_out: Option<U256>,
+}
+
+fn parse_swap(state: &GraphFixture, swap: &SwapRequest) -> Result<ParsedSwap, String> {
+    let token = |value: &str| {
+        resolve_token(state, value).ok_or_else(|| format!("Unknown token: {}", value))
+    };
+    let amount = |value: &str| {
+        value
+            .parse::<U256>()
+            .map_err(|_| format!("Invalid amount: {}", value))
+    };
+
+    Ok(ParsedSwap {
+        token_in: token(&swap.token_in)?,
+        token_out: token(&swap.token_out)?,
+        amount_in: amount(&swap.amount_in)?,
+        max_hops: swap.max_hops,
+        max_splits: swap.max_splits,
+        executed_amount_out: swap
+            .executed_amount_out
+            .as_deref()
+            .map(amount)
+            .transpose()?,
+    })
+}
+
+fn quote_and_execute(
+    router: &Router,
+    profile: &RouterProfile,
+    swap: &ParsedSwap,
+    exec_graph: &PoolGraph,
+) -> SwapOutcome {
+    let max_hops = swap
+        .max_hops
+        .map_or(profile.max_hops, |h| h.min(profile.max_hops));
+    let max_splits = swap
+        .max_splits
+        .map_or(profile.max_splits, |s| s.min(profile.max_splits));
+
+    let start = Instant::now();
+    let result = router
+        .find_split_route(
+            swap.token_in,
+            swap.token_out,
+            swap.amount_in,
+            Some(max_hops),
+            Some(max_splits),
+        );
+    let latency_us = start.elapsed().as_micros() as u64;
+
+    let split = match result {
+        Ok(split) => split,
+        Err(e) => return SwapOutcome::failed(latency_us, e.to_string()),
+    };
+
+    let (achievable, execution_error) = match resimulate_split(exec_graph, &split) {
+        Ok(amount) => (amount, None),
+        Err(e) => (U256::ZERO, Some(e.to_string())),
+    };
+
+    SwapOutcome::quoted(
+        latency_us,
+        split.total_amount_out,
+        achievable,
+        split.routes.len(),
+        route_key(&split),
+        execution_error,
+    )
+}
+
+/// Identify the route choice: pools and percentage of every leg
+fn route_key(split: &SplitRoute) -> String {
+    split
+        .routes
+        .iter()
+        .map(|(route, percent)| {
+            let pools: Vec<String> = route
+                .hops
+                .iter()
+                .map(|h| B256::from(h.pool.pool_id).to_string())
+                .collect();
+            format!("{}%:{}", percent, pools.join(">"))
+        })
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::BlockRecord;
+    use crate::sync::pool_sync::DEFAULT_FIXTURE;
+
+    fn swap(token_in: &str, token_out: &str, amount_in: &str) -> SwapRequest {
+        SwapRequest {
+            token_in: token_in.to_string(),
+            token_out: token_out.to_string(),
+            amount_in: amount_in.to_string(),
+            max_hops: None,
+            max_splits: None,
+            executed_amount_out: None,
+        }
+    }
+
+    /// Two blocks of dev pool state; WETH/USDC liquidity drops by 90% in the second
+    fn dataset() -> BacktestDataset {
+        let state = GraphFixture::from_toml(DEFAULT_FIXTURE).unwrap();
+        let mut drained = state.clone();
+        for pool in drained
+            .pools
+            .iter_mut()
+            .filter(|p| p.token0 == "WETH" && p.token1 == "USDC")
+        {
+            let liquidity: u128 = pool.liquidity.parse().unwrap();
+            pool.liquidity = (liquidity / 10).to_string();
+        }
+
+        BacktestDataset {
+            blocks: vec![
+                BlockRecord {
+                    block_number: 100,
+                    state: Some(state),
+                    swaps: vec![
+                        swap("WETH", "USDC", "1000000000000000000"),
+                        swap("WETH", "DAI", "50000000000000000000"),
+                    ],
+                },
+                BlockRecord {
+                    block_number: 101,
+                    state: Some(drained),
+                    swaps: vec![],
+                },
+                BlockRecord {
+                    block_number: 102,
+                    state: None,
+                    swaps: vec![swap("DAI", "WETH", "1000000000000000000")],
+                },
+            ],
+        }
+    }
+
+    fn single_hop() -> RouterProfile {
+        RouterProfile {
+            max_hops: 1,
+            max_splits: 1,
+            ..RouterProfile::named("single-hop")
+        }
+    }
+
+    #[test]
+    fn test_backtest_quoted_vs_achievable() {
+        let report = Backtest::new(single_hop()).run(&dataset()).unwrap();
+
+        assert_eq!(report.swaps, 3);
+        assert_eq!(report.baseline.quoted, 3);
+        assert_eq!(report.baseline.latency_us.count, 3);
+
+        // The WETH/USDC quote at block 100 executes against the drained pool
+        let record = &report.records[0];
+        assert_eq!(record.execution_block, 101);
+        let quoted: U256 = record
+            .baseline
+            .quoted_amount_out
+            .as_deref()
+            .unwrap()
+            .parse()
+            .unwrap();
+        let achievable: U256 = record
+            .baseline
+            .achievable_amount_out
+            .as_deref()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(achievable < quoted);
+        assert!(record.baseline.shortfall_bps.unwrap() > 0.0);
+        assert_eq!(report.baseline.overquoted, 1);
+
+        // Block 102 carries block 101's state forward and executes against itself
+        let record = &report.records[2];
+        assert_eq!(record.execution_block, 102);
+        assert!(record.baseline.shortfall_bps.unwrap().abs() < 1.0);
+    }
+
+    #[test]
+    fn test_backtest_same_block_execution() {
+        let report = Backtest::new(single_hop())
+            .with_execution_delay(0)
+            .run(&dataset())
+            .unwrap();
+
+        assert_eq!(report.records[0].execution_block, 100);
+        // Same state for quoting and execution: only rounding differences remain
+        assert!(report
+            .records
+            .iter()
+            .all(|r| r.baseline.shortfall_bps.unwrap().abs() < 1.0));
+    }
+
+    #[test]
+    fn test_backtest_compares_profiles() {
+        let report = Backtest::new(RouterProfile::default())
+            .with_candidate(single_hop())
+            .run(&dataset())
+            .unwrap();
+
+        let candidate = report.candidate.as_ref().unwrap();
+        assert_eq!(candidate.name, "single-hop");
+        assert_eq!(candidate.split_quotes, 0);
+        assert_eq!(candidate.latency_us.count, 3);
+
+        let comparison = report.comparison.as_ref().unwrap();
+        assert_eq!(
+            comparison.compared
+                + comparison.quoted_only_by_baseline
+                + comparison.quoted_only_by_candidate,
+            3
+        );
+        assert!(comparison.route_changes > 0);
+        assert_eq!(
+            comparison.candidate_better + comparison.baseline_better + comparison.equal,
+            comparison.compared
+        );
+        assert!(report.records.iter().all(|r| r.route_changed.is_some()));
+    }
+
+    #[test]
+    fn test_backtest_rejects_bad_input() {
+        let mut data = dataset();
+        data.blocks[0].swaps.push(swap("NOPE", "USDC", "1"));
+        let err = Backtest::new(RouterProfile::default())
+            .run(&data)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            BacktestError::InvalidSwap {
+                block: 100,
+                index: 2,
+                ..
+            }
+        ));
+
+        let mut data = dataset();
+        data.blocks[0].state = None;
+        let err = Backtest::new(RouterProfile::default())
+            .run(&data)
+            .unwrap_err();
+        assert!(matches!(err, BacktestError::MissingState { block: 100 }));
+    }
+}
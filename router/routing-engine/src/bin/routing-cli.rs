@@ -0,0 +1,700 @@
+//! Offline quoting tool.
+//!
+//! Runs the routing engine against a pool graph loaded from a snapshot
+//! or fixture file, without starting the server:
+//!
+//! ```text
+//! routing-cli --snapshot data/graph-snapshot-8453.json quote --from WETH --to USDC --amount 1000000000000000000
+//! routing-cli --snapshot data/graph-snapshot-8453.json --json routes --from WETH --to DAI --amount 1e18 --top 5
+//! routing-cli --fixture fixtures/base-mainnet.toml graph stats
+//! routing-cli backtest --dataset fixtures/backtest-sample.json --candidate candidate.toml
+//! ```
+
+use alloy_primitives::{Address, B256, U256};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use routing_engine::backtest::{
+    Backtest, BacktestDataset, BacktestReport, Distribution, ProfileSummary, RouterProfile,
+};
+use routing_engine::gas::GasModel;
+use routing_engine::graph::{GraphFixture, GraphSnapshot, PoolEdge, PoolGraph, PoolType, TokenNode};
+use routing_engine::pricing::{PriceOracle, PricingConfig};
+use routing_engine::routing::native::{self, NATIVE};
+use routing_engine::routing::{find_top_routes, Quote, Route, Router, RouterConfig};
+use routing_engine::utils::{MAX_HOPS, MAX_SPLITS};
+use serde_json::json;
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::sync::Arc;
+
+fn cli() -> Command {
+    let swap_args = [
+        Arg::new("from")
+            .long("from")
+            .required(true)
+            .help("Input token (address or symbol)"),
+        Arg::new("to")
+            .long("to")
+            .required(true)
+            .help("Output token (address or symbol)"),
+        Arg::new("amount")
+            .long("amount")
+            .required(true)
+            .help("Input amount in base units (e.g. 1000000000000000000 or 1e18)"),
+        Arg::new("max-hops")
+            .long("max-hops")
+            .value_parser(value_parser!(usize))
+            .help("Maximum hops per route [default: 4]"),
+    ];
+    let slippage = Arg::new("slippage")
+        .long("slippage")
+        .value_parser(value_parser!(f64))
+        .default_value("0.5")
+        .help("Slippage tolerance in percent");
+
+    Command::new("routing-cli")
+        .about("Quote swaps offline against a saved pool graph")
+        .after_help("The graph is loaded from either --snapshot or --fixture (not needed for backtest).")
+        .version(env!("CARGO_PKG_VERSION"))
+        .subcommand_required(true)
+        .arg(
+            Arg::new("snapshot")
+                .long("snapshot")
+                .value_name("FILE")
+                .global(true)
+                .help("Graph snapshot file to load"),
+        )
+        .arg(
+            Arg::new("fixture")
+                .long("fixture")
+                .value_name("FILE")
+                .global(true)
+                .conflicts_with("snapshot")
+                .help("Graph fixture file to load (.json or .toml)"),
+        )
+        .arg(
+            Arg::new("gas-snapshots")
+                .long("gas-snapshots")
+                .value_name("DIR")
+                .global(true)
+                .help("Forge gas snapshot directory to calibrate gas estimates from"),
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .help("Print JSON instead of human-readable output"),
+        )
+        .subcommand(
+            Command::new("quote")
+                .about("Best single-route quote")
+                .args(swap_args.clone())
+                .arg(slippage.clone()),
+        )
+        .subcommand(
+            Command::new("split-quote")
+                .about("Best quote with the input split across routes")
+                .args(swap_args.clone())
+                .arg(slippage)
+                .arg(
+                    Arg::new("max-splits")
+                        .long("max-splits")
+                        .value_parser(value_parser!(usize))
+                        .help("Maximum number of routes to split across [default: 3]"),
+                ),
+        )
+        .subcommand(
+            Command::new("routes")
+                .about("Top routes ranked by output")
+                .args(swap_args)
+                .arg(
+                    Arg::new("top")
+                        .long("top")
+                        .value_parser(value_parser!(usize))
+                        .default_value("5")
+                        .help("Number of routes to list"),
+                ),
+        )
+        .subcommand(
+            Command::new("graph")
+                .about("Inspect the pool graph")
+                .subcommand_required(true)
+                .subcommand(Command::new("stats").about("Token and pool counts")),
+        )
+        .subcommand(
+            Command::new("pool")
+                .about("Inspect pools")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("show")
+                        .about("Show a pool's state and ticks")
+                        .arg(Arg::new("pool-id").required(true).help("Pool id (32-byte hex)")),
+                ),
+        )
+        .subcommand(
+            Command::new("backtest")
+                .about("Replay recorded pool state and swaps, and report routing quality")
+                .arg(
+                    Arg::new("dataset")
+                        .long("dataset")
+                        .value_name("FILE")
+                        .required(true)
+                        .help("Backtest dataset (.json or .toml)"),
+                )
+                .arg(
+                    Arg::new("baseline")
+                        .long("baseline")
+                        .value_name("FILE")
+                        .help("Router profile to quote with [default: built-in defaults]"),
+                )
+                .arg(
+                    Arg::new("candidate")
+                        .long("candidate")
+                        .value_name("FILE")
+                        .help("Second router profile to compare against the baseline"),
+                )
+                .arg(
+                    Arg::new("delay")
+                        .long("delay")
+                        .value_parser(value_parser!(usize))
+                        .default_value("1")
+                        .help("Blocks between quoting and execution"),
+                )
+                .arg(
+                    Arg::new("output")
+                        .long("output")
+                        .value_name("FILE")
+                        .help("Also write the full JSON report to this file"),
+                ),
+        )
+}
+
+fn main() -> ExitCode {
+    let matches = cli().get_matches();
+
+    match run(&matches) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(matches: &ArgMatches) -> Result<(), String> {
+    let json_output = matches.get_flag("json");
+    if let Some(("backtest", args)) = matches.subcommand() {
+        return run_backtest(args, json_output);
+    }
+
+    let graph = Arc::new(load_graph(matches)?);
+    let gas_model = match matches.get_one::<String>("gas-snapshots") {
+        Some(dir) => GasModel::from_snapshots(dir).map_err(|e| e.to_string())?,
+        None => GasModel::default(),
+    };
+
+    match matches.subcommand() {
+        Some(("quote", args)) => {
+            let swap = SwapArgs::parse(&graph, args)?;
+            let slippage = *args.get_one::<f64>("slippage").unwrap();
+            let quote = router(&graph, gas_model)
+                .get_quote(
+                    swap.token_in,
+                    swap.token_out,
+                    swap.amount_in,
+                    slippage,
+                    Some(swap.max_hops),
+                )
+                .map_err(|e| e.to_string())?;
+            print_quote(&graph, &quote, slippage, json_output)
+        }
+        Some(("split-quote", args)) => {
+            let swap = SwapArgs::parse(&graph, args)?;
+            let slippage = *args.get_one::<f64>("slippage").unwrap();
+            let max_splits = args
+                .get_one::<usize>("max-splits")
+                .copied()
+                .unwrap_or(MAX_SPLITS);
+            let quote = router(&graph, gas_model)
+                .get_split_quote(
+                    swap.token_in,
+                    swap.token_out,
+                    swap.amount_in,
+                    slippage,
+                    Some(swap.max_hops),
+                    Some(max_splits),
+                )
+                .map_err(|e| e.to_string())?;
+            print_quote(&graph, &quote, slippage, json_output)
+        }
+        Some(("routes", args)) => {
+            let swap = SwapArgs::parse(&graph, args)?;
+            let top = *args.get_one::<usize>("top").unwrap();
+            let routes: Vec<Route> = find_top_routes(
+                &graph,
+                swap.token_in,
+                swap.token_out,
+                swap.amount_in,
+                swap.max_hops,
+                top,
+            )
+            .into_iter()
+            .map(|route| gas_model.price_route(route))
+            .collect();
+            if routes.is_empty() {
+                return Err(format!(
+                    "No route found from {} to {}",
+                    swap.token_in, swap.token_out
+                ));
+            }
+            print_routes(&graph, &routes, json_output)
+        }
+        Some(("graph", args)) => match args.subcommand() {
+            Some(("stats", _)) => print_graph_stats(&graph, json_output),
+            _ => unreachable!("subcommand is required"),
+        },
+        Some(("pool", args)) => match args.subcommand() {
+            Some(("show", args)) => {
+                let pool_id = parse_pool_id(args.get_one::<String>("pool-id").unwrap())?;
+                print_pool(&graph, pool_id, json_output)
+            }
+            _ => unreachable!("subcommand is required"),
+        },
+        _ => unreachable!("subcommand is required"),
+    }
+}
+
+fn run_backtest(args: &ArgMatches, json_output: bool) -> Result<(), String> {
+    let dataset_path = args.get_one::<String>("dataset").unwrap();
+    let dataset = BacktestDataset::load(dataset_path).map_err(|e| format!("{}: {}", dataset_path, e))?;
+
+    let baseline = match args.get_one::<String>("baseline") {
+        Some(path) => RouterProfile::load(path).map_err(|e| e.to_string())?,
+        None => RouterProfile::default(),
+    };
+    let mut backtest =
+        Backtest::new(baseline).with_execution_delay(*args.get_one::<usize>("delay").unwrap());
+    if let Some(path) = args.get_one::<String>("candidate") {
+        backtest = backtest.with_candidate(RouterProfile::load(path).map_err(|e| e.to_string())?);
+    }
+
+    let report = backtest.run(&dataset).map_err(|e| e.to_string())?;
+    if let Some(path) = args.get_one::<String>("output") {
+        report
+            .save(path)
+            .map_err(|e| format!("Cannot write {}: {}", path, e))?;
+    }
+
+    if json_output {
+        return print_json(&report);
+    }
+    print_backtest(&report);
+    Ok(())
+}
+
+/// Build the graph from the file given on the command line
+fn load_graph(matches: &ArgMatches) -> Result<PoolGraph, String> {
+    let graph = PoolGraph::new();
+
+    if let Some(path) = matches.get_one::<String>("fixture") {
+        let fixture = GraphFixture::load(path).map_err(|e| format!("{}: {}", path, e))?;
+        graph
+            .import_fixture(&fixture)
+            .map_err(|e| format!("{}: {}", path, e))?;
+        return Ok(graph);
+    }
+
+    let path = matches
+        .get_one::<String>("snapshot")
+        .ok_or("A graph source is required (--snapshot <FILE> or --fixture <FILE>)")?;
+
+    let bytes = std::fs::read(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+    let snapshot = GraphSnapshot::from_bytes(&bytes).map_err(|e| format!("{}: {}", path, e))?;
+    snapshot
+        .restore_into(&graph)
+        .map_err(|e| format!("{}: {}", path, e))?;
+    Ok(graph)
+}
+
+/// One-shot router: no caching, nothing is reused between invocations
+fn router(graph: &Arc<PoolGraph>, gas_model: GasModel) -> Router {
+    Router::with_config(
+        graph.clone(),
+        RouterConfig {
+            enable_cache: false,
+            gas_model,
+            ..Default::default()
+        },
+    )
+    .with_price_oracle(Arc::new(PriceOracle::new(
+        graph.clone(),
+        PricingConfig::default(),
+    )))
+}
+
+struct SwapArgs {
+    token_in: Address,
+    token_out: Address,
+    amount_in: U256,
+    max_hops: usize,
+}
+
+impl SwapArgs {
+    fn parse(graph: &PoolGraph, args: &ArgMatches) -> Result<Self, String> {
+        Ok(Self {
+            token_in: resolve_token(graph, args.get_one::<String>("from").unwrap())?,
+            token_out: resolve_token(graph, args.get_one::<String>("to").unwrap())?,
+            amount_in: parse_amount(args.get_one::<String>("amount").unwrap())?,
+            max_hops: args.get_one::<usize>("max-hops").copied().unwrap_or(MAX_HOPS),
+        })
+    }
+}
+
+/// Accept either a token address or a symbol present in the graph
+fn resolve_token(graph: &PoolGraph, value: &str) -> Result<Address, String> {
+    if let Ok(address) = Address::from_str(value) {
+        return Ok(address);
+    }
+
+    let matches: Vec<TokenNode> = graph
+        .get_all_tokens()
+        .into_iter()
+        .filter(|t| t.symbol.eq_ignore_ascii_case(value))
+        .collect();
+
+    match matches.as_slice() {
+        [token] => Ok(token.address),
+        [] if value.eq_ignore_ascii_case("ETH") => Ok(NATIVE),
+        [] => Err(format!("Unknown token: {}", value)),
+        _ => Err(format!(
+            "Ambiguous symbol {}: use one of {}",
+            value,
+            matches
+                .iter()
+                .map(|t| t.address.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+/// Parse a base-unit amount, allowing `<digits>e<exp>` shorthand
+fn parse_amount(value: &str) -> Result<U256, String> {
+    let invalid = || format!("Invalid amount: {}", value);
+
+    let amount = match value.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => {
+            let mantissa = U256::from_str(mantissa).map_err(|_| invalid())?;
+            let exponent: u8 = exponent.parse().map_err(|_| invalid())?;
+            mantissa
+                .checked_mul(U256::from(10).pow(U256::from(exponent)))
+                .ok_or_else(invalid)?
+        }
+        None => U256::from_str(value).map_err(|_| invalid())?,
+    };
+
+    if amount.is_zero() {
+        return Err("Amount must be greater than zero".to_string());
+    }
+    Ok(amount)
+}
+
+fn parse_pool_id(value: &str) -> Result<[u8; 32], String> {
+    B256::from_str(value)
+        .map(|id| id.0)
+        .map_err(|_| format!("Invalid pool id: {}", value))
+}
+
+fn symbol(graph: &PoolGraph, address: Address) -> String {
+    graph
+        .get_all_tokens()
+        .into_iter()
+        .find(|t| t.address == address)
+        .map(|t| t.symbol)
+        .unwrap_or_else(|| {
+            if native::is_native(address) {
+                "ETH".to_string()
+            } else {
+                address.to_string()
+            }
+        })
+}
+
+fn decimals(graph: &PoolGraph, address: Address) -> u8 {
+    graph
+        .get_all_tokens()
+        .into_iter()
+        .find(|t| t.address == address)
+        .map(|t| t.decimals)
+        .unwrap_or(if native::is_native(address) { 18 } else { 0 })
+}
+
+/// Format a base-unit amount with the token's decimals
+fn format_units(amount: U256, decimals: u8) -> String {
+    if decimals == 0 {
+        return amount.to_string();
+    }
+
+    let digits = amount.to_string();
+    let decimals = decimals as usize;
+    let padded = format!("{:0>width$}", digits, width = decimals + 1);
+    let (whole, fraction) = padded.split_at(padded.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, fraction)
+    }
+}
+
+fn describe_route(graph: &PoolGraph, route: &Route) -> String {
+    let Some(first) = route.hops.first() else {
+        return String::new();
+    };
+
+    let mut parts = Vec::new();
+    if route.wrap {
+        parts.push(format!("{} [wrap]", symbol(graph, NATIVE)));
+    }
+    parts.push(symbol(graph, first.token_in));
+    for hop in &route.hops {
+        let kind = match hop.pool_type {
+            PoolType::Concentrated => "",
+            PoolType::ConstantProduct => " v2",
+            PoolType::StableSwap => " stable",
+        };
+        parts.push(format!(
+            "[{:.2}%{}] {}",
+            hop.pool.fee as f64 / 10_000.0,
+            kind,
+            symbol(graph, hop.token_out)
+        ));
+    }
+    if route.unwrap {
+        parts.push(format!("[unwrap] {}", symbol(graph, NATIVE)));
+    }
+    parts.join(" → ")
+}
+
+/// ` ($1234.56)`, or nothing for an unpriced amount
+fn format_usd(usd: Option<f64>) -> String {
+    usd.map(|usd| format!(" (${:.2})", usd)).unwrap_or_default()
+}
+
+fn print_json(value: &impl serde::Serialize) -> Result<(), String> {
+    let output = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    println!("{}", output);
+    Ok(())
+}
+
+fn print_quote(
+    graph: &PoolGraph,
+    quote: &Quote,
+    slippage: f64,
+    json_output: bool,
+) -> Result<(), String> {
+    if json_output {
+        return print_json(quote);
+    }
+
+    let Some((first, _)) = quote.route.routes.first() else {
+        return Err("Quote has no routes".to_string());
+    };
+    let token_in = if first.wrap { NATIVE } else { first.hops[0].token_in };
+    let token_out = if first.unwrap {
+        NATIVE
+    } else {
+        first.hops[first.hops.len() - 1].token_out
+    };
+    let (in_symbol, in_decimals) = (symbol(graph, token_in), decimals(graph, token_in));
+    let (out_symbol, out_decimals) = (symbol(graph, token_out), decimals(graph, token_out));
+
+    println!("Quote {} → {}", in_symbol, out_symbol);
+    println!(
+        "  amount in:     {} {}{}",
+        format_units(quote.route.total_amount_in, in_decimals),
+        in_symbol,
+        format_usd(quote.amount_in_usd)
+    );
+    println!(
+        "  amount out:    {} {}{}",
+        format_units(quote.route.total_amount_out, out_decimals),
+        out_symbol,
+        format_usd(quote.amount_out_usd)
+    );
+    println!(
+        "  min out:       {} {} ({}% slippage)",
+        format_units(
+            U256::from_str(&quote.amount_out_min).unwrap_or_default(),
+            out_decimals
+        ),
+        out_symbol,
+        slippage
+    );
+    println!("  price impact:  {:.4}%", quote.price_impact);
+    match quote.gas_estimate_usd {
+        Some(usd) => println!("  gas estimate:  {} (${:.4})", quote.gas_estimate, usd),
+        None => println!("  gas estimate:  {}", quote.gas_estimate),
+    }
+    println!("  routes:");
+    for (route, percent) in &quote.route.routes {
+        println!("    {:>3}%  {}", percent, describe_route(graph, route));
+    }
+    Ok(())
+}
+
+fn print_routes(graph: &PoolGraph, routes: &[Route], json_output: bool) -> Result<(), String> {
+    if json_output {
+        return print_json(&routes);
+    }
+
+    let token_out = routes[0].hops[routes[0].hops.len() - 1].token_out;
+    let (out_symbol, out_decimals) = (symbol(graph, token_out), decimals(graph, token_out));
+
+    for (rank, route) in routes.iter().enumerate() {
+        println!(
+            "{:>2}. {} {}  (impact {:.4}%, gas {})  {}",
+            rank + 1,
+            format_units(route.total_amount_out, out_decimals),
+            out_symbol,
+            route.price_impact,
+            route.gas_estimate,
+            describe_route(graph, route)
+        );
+    }
+    Ok(())
+}
+
+fn print_graph_stats(graph: &PoolGraph, json_output: bool) -> Result<(), String> {
+    let stats = graph.stats();
+
+    if json_output {
+        return print_json(&json!({
+            "token_count": stats.token_count,
+            "pool_count": stats.pool_count,
+            "last_update": stats.last_update,
+            "block_number": stats.block_number,
+        }));
+    }
+
+    println!("tokens:        {}", stats.token_count);
+    println!("pools:         {}", stats.pool_count);
+    println!("block number:  {}", stats.block_number);
+    println!(
+        "last update:   {}",
+        chrono::DateTime::from_timestamp(stats.last_update as i64, 0)
+            .map(|t| t.to_rfc3339())
+            .unwrap_or_else(|| stats.last_update.to_string())
+    );
+    Ok(())
+}
+
+fn print_pool(graph: &PoolGraph, pool_id: [u8; 32], json_output: bool) -> Result<(), String> {
+    let pool: PoolEdge = graph
+        .get_pool(pool_id)
+        .ok_or_else(|| format!("Pool not found: {}", B256::from(pool_id)))?;
+    let ticks = graph.get_ticks(pool_id).map(|t| t.to_vec()).unwrap_or_default();
+
+    if json_output {
+        return print_json(&json!({ "pool": pool, "ticks": ticks }));
+    }
+
+    println!("pool:           {}", B256::from(pool.pool_id));
+    println!(
+        "pair:           {} / {}",
+        symbol(graph, pool.token0),
+        symbol(graph, pool.token1)
+    );
+    println!("type:           {}", pool.pool_type);
+    println!("fee:            {} ({:.2}%)", pool.fee, pool.fee as f64 / 10_000.0);
+    if pool.pool_type != PoolType::Concentrated {
+        println!("reserves:       {} / {}", pool.reserves[0], pool.reserves[1]);
+        if pool.pool_type == PoolType::StableSwap {
+            println!("amplification:  {}", pool.amplification);
+        }
+        return Ok(());
+    }
+    println!("tick spacing:   {}", pool.tick_spacing);
+    println!("tick:           {}", pool.tick);
+    println!("sqrtPriceX96:   {}", pool.sqrt_price_x96);
+    println!("liquidity:      {}", pool.liquidity);
+    if pool.hook_address != Address::ZERO {
+        println!("hook:           {}", pool.hook_address);
+    }
+    println!("ticks:          {}", ticks.len());
+    for tick in ticks {
+        println!(
+            "  {:>8}  net {:>40}  gross {:>40}",
+            tick.index, tick.liquidity_net, tick.liquidity_gross
+        );
+    }
+    Ok(())
+}
+
+fn print_backtest(report: &BacktestReport) {
+    println!(
+        "Backtest: {} blocks, {} swaps, execution delay {} block(s)",
+        report.blocks, report.swaps, report.execution_delay_blocks
+    );
+    print_profile_summary(&report.baseline);
+    if let Some(candidate) = &report.candidate {
+        print_profile_summary(candidate);
+    }
+
+    if let Some(comparison) = &report.comparison {
+        println!("comparison (candidate vs baseline):");
+        println!("  compared:        {}", comparison.compared);
+        println!("  route changes:   {}", comparison.route_changes);
+        println!(
+            "  better / worse / equal:  {} / {} / {}",
+            comparison.candidate_better, comparison.baseline_better, comparison.equal
+        );
+        println!(
+            "  achievable delta (bps):  {}",
+            format_distribution(&comparison.achievable_delta_bps, 2)
+        );
+        println!(
+            "  quoted only by baseline / candidate:  {} / {}",
+            comparison.quoted_only_by_baseline, comparison.quoted_only_by_candidate
+        );
+    }
+}
+
+fn print_profile_summary(summary: &ProfileSummary) {
+    println!("{}:", summary.name);
+    println!(
+        "  quoted:          {} ({} failed, {} not executable)",
+        summary.quoted, summary.failed, summary.execution_failures
+    );
+    println!("  overquoted:      {}", summary.overquoted);
+    println!(
+        "  shortfall (bps): {}",
+        format_distribution(&summary.shortfall_bps, 2)
+    );
+    if let Some(vs_executed) = &summary.vs_executed_bps {
+        println!("  vs executed (bps): {}", format_distribution(vs_executed, 2));
+    }
+    println!(
+        "  latency (us):    {}",
+        format_distribution(&summary.latency_us, 0)
+    );
+    println!(
+        "  splits:          {} ({:.1}%), {:.2} routes per quote",
+        summary.split_quotes,
+        summary.split_rate * 100.0,
+        summary.mean_routes
+    );
+}
+
+fn format_distribution(dist: &Distribution, precision: usize) -> String {
+    format!(
+        "mean {:.p$}  p50 {:.p$}  p90 {:.p$}  p99 {:.p$}  max {:.p$}",
+        dist.mean,
+        dist.p50,
+        dist.p90,
+        dist.p99,
+        dist.max,
+        p = precision
+    )
+}
@@ -3,13 +3,16 @@ use crate::routing::{Quote, Route, SplitRoute};
 use alloy_primitives::{Address, U256};
 use std::sync::Arc;
 
-/// Enhanced route cache with bucketing for better hit rate
+/// Enhanced route cache
+///
+/// Entries are keyed on the exact amount: a route or quote priced for one
+/// amount is never served for another.
 pub struct EnhancedRouteCache {
     // Cache for single routes
     route_cache: Arc<LruCache<RouteKey, Route>>,
 
     // Cache for split routes
-    split_cache: Arc<LruCache<RouteKey, SplitRoute>>,
+    split_cache: Arc<LruCache<SplitKey, SplitRoute>>,
 
     // Cache for quotes
     quote_cache: Arc<LruCache<QuoteKey, Quote>>,
@@ -19,15 +22,24 @@ pub struct EnhancedRouteCache {
 struct RouteKey {
     token_in: Address,
     token_out: Address,
-    amount_bucket: String,
+    amount: U256,
     max_hops: usize,
 }
 
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+struct SplitKey {
+    token_in: Address,
+    token_out: Address,
+    amount: U256,
+    max_hops: usize,
+    max_splits: usize,
+}
+
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
 struct QuoteKey {
     token_in: Address,
     token_out: Address,
-    amount_bucket: String,
+    amount: U256,
     slippage_bps: u32,
     max_hops: usize,
 }
@@ -52,7 +64,7 @@ impl EnhancedRouteCache {
         let key = RouteKey {
             token_in,
             token_out,
-            amount_bucket: Self::bucket_amount(amount),
+            amount,
             max_hops,
         };
 
@@ -71,7 +83,7 @@ impl EnhancedRouteCache {
         let key = RouteKey {
             token_in,
             token_out,
-            amount_bucket: Self::bucket_amount(amount),
+            amount,
             max_hops,
         };
 
@@ -85,12 +97,14 @@ impl EnhancedRouteCache {
         token_out: Address,
         amount: U256,
         max_hops: usize,
+        max_splits: usize,
     ) -> Option<SplitRoute> {
-        let key = RouteKey {
+        let key = SplitKey {
             token_in,
             token_out,
-            amount_bucket: Self::bucket_amount(amount),
+            amount,
             max_hops,
+            max_splits,
         };
 
         self.split_cache.get(&key)
@@ -103,13 +117,15 @@ impl EnhancedRouteCache {
         token_out: Address,
         amount: U256,
         max_hops: usize,
+        max_splits: usize,
         split_route: SplitRoute,
     ) {
-        let key = RouteKey {
+        let key = SplitKey {
             token_in,
             token_out,
-            amount_bucket: Self::bucket_amount(amount),
+            amount,
             max_hops,
+            max_splits,
         };
 
         self.split_cache.insert(key, split_route);
@@ -127,7 +143,7 @@ impl EnhancedRouteCache {
         let key = QuoteKey {
             token_in,
             token_out,
-            amount_bucket: Self::bucket_amount(amount),
+            amount,
             slippage_bps: (slippage * 100.0) as u32,
             max_hops,
         };
@@ -148,7 +164,7 @@ impl EnhancedRouteCache {
         let key = QuoteKey {
             token_in,
             token_out,
-            amount_bucket: Self::bucket_amount(amount),
+            amount,
             slippage_bps: (slippage * 100.0) as u32,
             max_hops,
         };
@@ -156,26 +172,6 @@ impl EnhancedRouteCache {
         self.quote_cache.insert(key, quote);
     }
 
-    /// Bucket amounts to improve cache hit rate
-    ///
-    /// Groups similar amounts together by rounding to 2 significant figures
-    fn bucket_amount(amount: U256) -> String {
-        if amount.is_zero() {
-            return "0".to_string();
-        }
-
-        let amount_str = amount.to_string();
-
-        if amount_str.len() <= 2 {
-            return amount_str;
-        }
-
-        // Take first 2 digits and fill with zeros
-        let first_two: String = amount_str.chars().take(2).collect();
-        let zeros = "0".repeat(amount_str.len() - 2);
-        format!("{}{}", first_two, zeros)
-    }
-
     /// Clear all caches
     pub fn clear_all(&self) {
         self.route_cache.clear();
@@ -215,16 +211,6 @@ mod tests {
     use super::*;
     use crate::utils::address_from_u64;
 
-    #[test]
-    fn test_amount_bucketing() {
-        assert_eq!(EnhancedRouteCache::bucket_amount(U256::from(0)), "0");
-        assert_eq!(EnhancedRouteCache::bucket_amount(U256::from(5)), "5");
-        assert_eq!(EnhancedRouteCache::bucket_amount(U256::from(99)), "99");
-        assert_eq!(EnhancedRouteCache::bucket_amount(U256::from(123)), "120");
-        assert_eq!(EnhancedRouteCache::bucket_amount(U256::from(1234)), "1200");
-        assert_eq!(EnhancedRouteCache::bucket_amount(U256::from(98765)), "98000");
-    }
-
     #[test]
     fn test_route_cache_basic() {
         let cache = EnhancedRouteCache::new(10, 10, 60);
@@ -238,7 +224,7 @@ mod tests {
     }
 
     #[test]
-    fn test_cache_hit_with_similar_amounts() {
+    fn test_cache_is_keyed_on_exact_amounts() {
         use crate::graph::PoolEdge;
         use crate::routing::RouteHop;
 
@@ -278,8 +264,12 @@ mod tests {
         // Insert with amount 1234
         cache.insert_route(token_in, token_out, U256::from(1234), 2, route.clone());
 
-        // Should hit cache with amount 1250 (same bucket)
-        let cached = cache.get_route(token_in, token_out, U256::from(1250), 2);
-        assert!(cached.is_some());
+        assert!(cache
+            .get_route(token_in, token_out, U256::from(1234), 2)
+            .is_some());
+        // A nearby amount was not priced by this route
+        assert!(cache
+            .get_route(token_in, token_out, U256::from(1250), 2)
+            .is_none());
     }
 }
@@ -1,6 +1,7 @@
 use dashmap::DashMap;
 use std::collections::VecDeque;
 use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
@@ -10,6 +11,8 @@ pub struct LruCache<K, V> {
     access_order: Arc<parking_lot::Mutex<VecDeque<K>>>,
     max_size: usize,
     ttl: Duration,
+    hits: AtomicU64,
+    misses: AtomicU64,
 }
 
 struct CacheEntry<V> {
@@ -25,6 +28,8 @@ impl<K: Clone + Hash + Eq, V: Clone> LruCache<K, V> {
             access_order: Arc::new(parking_lot::Mutex::new(VecDeque::with_capacity(max_size))),
             max_size,
             ttl: Duration::from_secs(ttl_seconds),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
         }
     }
 
@@ -42,6 +47,7 @@ impl<K: Clone + Hash + Eq, V: Clone> LruCache<K, V> {
                 }
                 order.push_back(key.clone());
 
+                self.hits.fetch_add(1, Ordering::Relaxed);
                 return Some(entry.value.clone());
             } else {
                 // Expired, remove it
@@ -49,6 +55,7 @@ impl<K: Clone + Hash + Eq, V: Clone> LruCache<K, V> {
                 self.cache.remove(key);
             }
         }
+        self.misses.fetch_add(1, Ordering::Relaxed);
         None
     }
 
@@ -94,6 +101,8 @@ impl<K: Clone + Hash + Eq, V: Clone> LruCache<K, V> {
             size: self.cache.len(),
             max_size: self.max_size,
             total_accesses,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
         }
     }
 
@@ -109,6 +118,10 @@ pub struct CacheStats {
     pub size: usize,
     pub max_size: usize,
     pub total_accesses: u64,
+    /// Lookups that returned a live entry
+    pub hits: u64,
+    /// Lookups that found nothing or an expired entry
+    pub misses: u64,
 }
 
 #[cfg(test)]
@@ -187,5 +200,10 @@ mod tests {
         assert_eq!(stats.size, 2);
         assert_eq!(stats.max_size, 10);
         assert!(stats.total_accesses >= 3);
+        assert_eq!(stats.hits, 3);
+        assert_eq!(stats.misses, 0);
+
+        cache.get(&"missing");
+        assert_eq!(cache.stats().misses, 1);
     }
 }
@@ -2,4 +2,4 @@ pub mod contracts;
 pub mod settings;
 
 pub use contracts::ContractAddresses;
-pub use settings::Settings;
+pub use settings::{ChainSettings, RequestLimits, Settings};
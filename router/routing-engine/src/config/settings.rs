@@ -1,16 +1,57 @@
+use crate::api::{AccessConfig, QuoteStoreConfig, SigningConfig};
+use crate::gas::GasConfig;
+use crate::pricing::PricingConfig;
+use crate::routing::{CandidateConfig, Screening};
+use crate::service::ComputeConfig;
+use crate::utils::addresses::{usdc, weth};
+use alloy_primitives::Address;
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settings {
     pub server: ServerSettings,
-    pub chain: ChainSettings,
+    /// Chains served by this instance; the first is the default for
+    /// requests without a `chain_id`
+    pub chains: Vec<ChainSettings>,
     pub routing: RoutingSettings,
+    pub sync: SyncSettings,
+    pub snapshot: SnapshotSettings,
+    /// Thread pool and queue limits for route computation
+    #[serde(default)]
+    pub compute: ComputeConfig,
+    /// Stablecoin anchors and liquidity floor for USD prices
+    #[serde(default)]
+    pub pricing: PricingConfig,
+    /// L2 gas price and L1 data fee refresh
+    #[serde(default)]
+    pub gas: GasConfig,
+    /// API keys, rate limits, quotas and CORS origins
+    #[serde(default)]
+    pub access: AccessConfig,
+    /// Quote expiry and how long issued quotes are kept
+    #[serde(default)]
+    pub quotes: QuoteStoreConfig,
+    /// Key signing quotes as EIP-712 typed data
+    #[serde(default)]
+    pub signing: SigningConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerSettings {
     pub host: String,
     pub port: u16,
+    /// Port of the internal listener serving `/metrics` and `/admin/*`.
+    /// It has no access control: keep it off public load balancers.
+    #[serde(default = "default_admin_port")]
+    pub admin_port: u16,
+    /// Deadline shared by draining in-flight requests, stopping background
+    /// tasks and running shutdown hooks on shutdown (seconds)
+    pub shutdown_timeout_secs: u64,
+}
+
+fn default_admin_port() -> u16 {
+    9091
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,12 +59,126 @@ pub struct ChainSettings {
     pub chain_id: u64,
     pub rpc_url: String,
     pub pool_manager: String,
+    /// Fixture for the mock sync of this chain (overrides
+    /// `sync.fixture_path`)
+    #[serde(default)]
+    pub fixture_path: Option<String>,
+    /// Connector tokens on this chain (overrides
+    /// `routing.candidates.connector_tokens`)
+    #[serde(default)]
+    pub connector_tokens: Option<Vec<Address>>,
+    /// Wrapped native token that native ETH swaps are routed through
+    #[serde(default = "weth")]
+    pub wrapped_native: Address,
+    /// Stablecoin anchors for USD prices on this chain (overrides
+    /// `pricing.anchors`)
+    #[serde(default)]
+    pub price_anchors: Option<Vec<Address>>,
+}
+
+impl ChainSettings {
+    pub fn base_mainnet() -> Self {
+        Self {
+            chain_id: 8453,
+            rpc_url: "https://mainnet.base.org".to_string(),
+            pool_manager: "0x91B9463d0e4d99BB2D922cba2C9D4cd13c9a7C05".to_string(),
+            fixture_path: None,
+            connector_tokens: None,
+            wrapped_native: weth(),
+            price_anchors: None,
+        }
+    }
+
+    pub fn base_sepolia() -> Self {
+        Self {
+            chain_id: 84532,
+            rpc_url: "https://sepolia.base.org".to_string(),
+            pool_manager: "0x91B9463d0e4d99BB2D922cba2C9D4cd13c9a7C05".to_string(),
+            fixture_path: None,
+            connector_tokens: None,
+            wrapped_native: weth(),
+            price_anchors: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RoutingSettings {
     pub max_hops: usize,
     pub max_splits: usize,
+    /// Pool filtering and connector tokens for the route search
+    #[serde(default)]
+    pub candidates: CandidateConfig,
+    /// Approximate pre-screen for the route search, `None` for exact only
+    #[serde(default)]
+    pub screening: Option<Screening>,
+    /// Time budget per quote search (milliseconds, `0` for no limit)
+    pub search_timeout_ms: u64,
+    /// Largest `timeout_ms` a request may ask for (milliseconds)
+    pub max_search_timeout_ms: u64,
+    /// Bounds on quote parameters, checked before any routing work
+    #[serde(default)]
+    pub limits: RequestLimits,
+}
+
+/// Bounds on quote request parameters
+///
+/// `max_hops` and `max_splits` requests are bounded by
+/// [`RoutingSettings::max_hops`] and [`RoutingSettings::max_splits`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RequestLimits {
+    /// Largest slippage tolerance a quote accepts (percent)
+    pub max_slippage: f64,
+    /// Largest `amount_in` as a multiple of the input token's balance
+    /// across its pools (`0` disables the check)
+    pub max_amount_to_liquidity: f64,
+    /// Largest integrator fee a quote may charge (basis points)
+    pub max_fee_bps: u16,
+}
+
+impl Default for RequestLimits {
+    fn default() -> Self {
+        Self {
+            max_slippage: 50.0,
+            max_amount_to_liquidity: 1.0,
+            max_fee_bps: 300,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncSettings {
+    /// How often pool data is refreshed (seconds)
+    pub refresh_interval_secs: u64,
+    /// Graph age after which the engine stops serving quotes (seconds)
+    pub max_staleness_secs: u64,
+    /// Fixture file for the mock sync (`None` uses the bundled dev pools)
+    pub fixture_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotSettings {
+    /// Where the graph snapshots are stored (`None` disables snapshots).
+    /// Each chain writes its own file, with the chain id appended to the
+    /// file stem (see [`SnapshotSettings::path_for`]).
+    pub path: Option<String>,
+    /// How often the snapshot is rewritten (seconds)
+    pub interval_secs: u64,
+}
+
+impl SnapshotSettings {
+    /// Snapshot file of one chain: `data/graph-snapshot.json` becomes
+    /// `data/graph-snapshot-8453.json`
+    pub fn path_for(&self, chain_id: u64) -> Option<PathBuf> {
+        let path = Path::new(self.path.as_ref()?);
+        let stem = path.file_stem()?.to_string_lossy();
+        let name = match path.extension() {
+            Some(ext) => format!("{}-{}.{}", stem, chain_id, ext.to_string_lossy()),
+            None => format!("{}-{}", stem, chain_id),
+        };
+        Some(path.with_file_name(name))
+    }
 }
 
 impl Default for Settings {
@@ -32,16 +187,38 @@ impl Default for Settings {
             server: ServerSettings {
                 host: "0.0.0.0".to_string(),
                 port: 3001,
+                admin_port: default_admin_port(),
+                // Fits the k8s grace period (20s) after the 10s preStop sleep
+                shutdown_timeout_secs: 8,
             },
-            chain: ChainSettings {
-                chain_id: 8453, // Base mainnet
-                rpc_url: "https://mainnet.base.org".to_string(),
-                pool_manager: "0x91B9463d0e4d99BB2D922cba2C9D4cd13c9a7C05".to_string(),
-            },
+            chains: vec![ChainSettings::base_mainnet()],
             routing: RoutingSettings {
                 max_hops: 4,
                 max_splits: 3,
+                candidates: CandidateConfig {
+                    connector_tokens: vec![weth(), usdc()],
+                    ..Default::default()
+                },
+                screening: Some(Screening::default()),
+                search_timeout_ms: 200,
+                max_search_timeout_ms: 2000,
+                limits: RequestLimits::default(),
             },
+            sync: SyncSettings {
+                refresh_interval_secs: 12, // ~1 Base block
+                max_staleness_secs: 120,
+                fixture_path: None,
+            },
+            snapshot: SnapshotSettings {
+                path: Some("data/graph-snapshot.json".to_string()),
+                interval_secs: 60,
+            },
+            compute: ComputeConfig::default(),
+            pricing: PricingConfig::default(),
+            gas: GasConfig::default(),
+            access: AccessConfig::default(),
+            quotes: QuoteStoreConfig::default(),
+            signing: SigningConfig::default(),
         }
     }
 }
@@ -52,4 +229,33 @@ impl Settings {
         // For now, return defaults
         Self::default()
     }
+
+    /// Chain used when a request does not name one
+    pub fn default_chain_id(&self) -> Option<u64> {
+        self.chains.first().map(|chain| chain.chain_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_path_per_chain() {
+        let settings = Settings::default();
+        assert_eq!(
+            settings.snapshot.path_for(8453),
+            Some(PathBuf::from("data/graph-snapshot-8453.json"))
+        );
+
+        let snapshot = SnapshotSettings {
+            path: Some("snapshot".to_string()),
+            interval_secs: 60,
+        };
+        assert_eq!(snapshot.path_for(84532), Some(PathBuf::from("snapshot-84532")));
+        assert_eq!(
+            SnapshotSettings { path: None, ..snapshot }.path_for(8453),
+            None
+        );
+    }
 }
@@ -0,0 +1,127 @@
+//! Calldata size of the swap transaction for a route
+//!
+//! The estimate follows the ABI layout of a SwapRouter `exactInput`
+//! call per route, batched with `multicall` when the swap needs more
+//! than one call (split legs, wrapping or unwrapping, an integrator fee).
+//! The integrator fee call is also encoded, for the quote to return
+//! ([`fee_transfer_calldata`]).
+
+use crate::routing::{Route, SplitRoute};
+use alloy_primitives::{Address, Bytes, U256};
+use alloy_sol_types::{sol, SolCall};
+
+sol! {
+    /// SwapRouter call paying `amount` of `token` to `recipient`
+    #[derive(Debug, PartialEq, Eq)]
+    function pay(address token, address recipient, uint256 amount);
+}
+
+const WORD: usize = 32;
+const SELECTOR: usize = 4;
+
+/// `exactInput(ExactInputParams)`: selector, the tuple head (currency
+/// in, path offset, amount in, minimum out, recipient, deadline) and
+/// the path length
+const EXACT_INPUT_BASE: usize = SELECTOR + 7 * WORD;
+
+/// One `PathKey` (next currency, fee, tick spacing, hooks, hook data
+/// offset) plus its empty hook data
+const PATH_KEY: usize = 6 * WORD;
+
+/// `wrapETH(amount)` or `unwrapWETH(amountMinimum, recipient)`
+const NATIVE_STEP: usize = SELECTOR + 2 * WORD;
+
+/// `pay(token, recipient, amount)`: the integrator fee transfer
+const FEE_TRANSFER: usize = SELECTOR + 3 * WORD;
+
+/// `multicall(bytes[])`: selector, array offset and length
+const MULTICALL_BASE: usize = SELECTOR + 2 * WORD;
+
+/// Encoded `pay` call transferring an integrator fee of `amount` of
+/// `token` to `recipient`, one of the swap's `multicall` entries
+pub fn fee_transfer_calldata(token: Address, recipient: Address, amount: U256) -> Bytes {
+    payCall::new((token, recipient, amount)).abi_encode().into()
+}
+
+/// Calldata bytes of one route's `exactInput` call
+pub fn route_calldata_size(route: &Route) -> usize {
+    EXACT_INPUT_BASE + route.hops.len() * PATH_KEY
+}
+
+/// Calldata bytes of the transaction executing `split`
+pub fn calldata_size(split: &SplitRoute) -> usize {
+    let mut calls: Vec<usize> = split
+        .routes
+        .iter()
+        .map(|(route, _)| route_calldata_size(route))
+        .collect();
+    if split.wrap {
+        calls.push(NATIVE_STEP);
+    }
+    if split.unwrap {
+        calls.push(NATIVE_STEP);
+    }
+    if split.fee_transfer {
+        calls.push(FEE_TRANSFER);
+    }
+
+    match calls.as_slice() {
+        [single] => *single,
+        // Each call is an offset, a length and the padded bytes
+        _ => {
+            MULTICALL_BASE
+                + calls
+                    .iter()
+                    .map(|call| 2 * WORD + call.div_ceil(WORD) * WORD)
+                    .sum::<usize>()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(hops: usize) -> Route {
+        let mut route = Route::new(Vec::new(), U256::ZERO, U256::ZERO, 0.0, 0);
+        let hop = crate::routing::RouteHop::new(
+            crate::graph::PoolEdge::new([1; 32], Default::default(), Default::default(), 3000, 60, 0, U256::ZERO, 0),
+            Default::default(),
+            Default::default(),
+            U256::ZERO,
+            U256::ZERO,
+        );
+        route.hops = vec![hop; hops];
+        route
+    }
+
+    #[test]
+    fn test_calldata_grows_with_hops_and_calls() {
+        let single = SplitRoute::single(route(1));
+        assert_eq!(calldata_size(&single), 4 + 7 * 32 + 6 * 32);
+
+        let two_hops = SplitRoute::single(route(2));
+        assert_eq!(calldata_size(&two_hops) - calldata_size(&single), 6 * 32);
+
+        // Wrapping turns the swap into a multicall
+        let wrapped = SplitRoute::single(route(1)).with_native(true, false);
+        let padded = |n: usize| n.div_ceil(32) * 32;
+        assert_eq!(
+            calldata_size(&wrapped),
+            4 + 2 * 32 + (2 * 32 + padded(4 + 7 * 32 + 6 * 32)) + (2 * 32 + padded(4 + 2 * 32))
+        );
+
+        let split = SplitRoute::new(vec![(route(1), 50), (route(2), 50)], U256::ZERO, U256::ZERO, 0.0, 0);
+        assert!(calldata_size(&split) > calldata_size(&single) + calldata_size(&two_hops));
+    }
+
+    #[test]
+    fn test_fee_transfer_encodes_recipient_and_amount() {
+        let (token, recipient) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let calldata = fee_transfer_calldata(token, recipient, U256::from(2_500));
+        assert_eq!(calldata.len(), FEE_TRANSFER);
+
+        let call = payCall::abi_decode(&calldata).unwrap();
+        assert_eq!(call, payCall::new((token, recipient, U256::from(2_500))));
+    }
+}
@@ -0,0 +1,9 @@
+pub mod calldata;
+pub mod model;
+pub mod oracle;
+pub mod rpc;
+
+pub use calldata::{calldata_size, fee_transfer_calldata};
+pub use model::GasModel;
+pub use oracle::{FeeEstimate, GasConfig, GasOracle, GasPrices, L1FeeParams, GAS_PRICE_ORACLE};
+pub use rpc::RpcClient;
@@ -0,0 +1,382 @@
+//! Gas used by a swap transaction, by component
+//!
+//! A swap transaction calls the SwapRouter once per route leg (batched
+//! in a `multicall` when there is more than one call, as in
+//! [`calldata_size`](super::calldata_size)). Each call pulls the input
+//! token, swaps through its hops and sends the output. An integrator fee
+//! adds one more call and token transfer. A hop costs the
+//! pool's swap, plus every initialized tick it crosses and each
+//! `beforeSwap`/`afterSwap` callback its hook's permission flags enable.
+//!
+//! The defaults are rough figures for the contracts. [`GasModel::from_snapshots`]
+//! calibrates them from forge-std gas snapshots (`vm.snapshotGasLastCall`),
+//! which are JSON files of `name -> gas` per group. `GasSnapshotsTest`
+//! (`contracts/test/core/GasSnapshots.t.sol`) measures them:
+//! `forge test --match-contract GasSnapshotsTest` in `contracts/` writes
+//! `contracts/snapshots/`, the directory to set as `gas.snapshot_dir`.
+//!
+//! | File                  | Entry                  | Measures                                   |
+//! |-----------------------|------------------------|--------------------------------------------|
+//! | `PoolManager.json`    | `swap`                 | `swap` within one tick range, no hooks     |
+//! |                       | `swap_cross_tick`      | the same swap crossing one initialized tick |
+//! |                       | `swap_hooks`           | the same swap on a pool with no-op hooks   |
+//! | `SwapRouter.json`     | `exactInputSingle`     | a one-hop `exactInputSingle` call          |
+//! |                       | `multicall_2_legs`     | two `exactInputSingle` calls in a `multicall` |
+//! |                       | `wrapETH`, `unwrapWETH` | the native steps                          |
+//! | `ERC20.json`          | `transfer`             | one token transfer                         |
+//!
+//! Missing files and entries keep the default for that component. The
+//! SwapRouter has no `multicall` or WETH steps yet, so the test does not
+//! write `multicall_2_legs`, `wrapETH` or `unwrapWETH`.
+
+use crate::graph::{PoolEdge, PoolType};
+use crate::liquidity::{LiquiditySource, PoolModel};
+use crate::routing::{Route, SplitRoute};
+use crate::utils::{Result, RouterError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Gas of each part of a swap transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GasModel {
+    /// Intrinsic gas of any transaction
+    pub transaction: u64,
+    /// SwapRouter entry: dispatch, deadline and pause checks, events
+    pub router_call: u64,
+    /// `multicall` overhead when a transaction makes several calls
+    pub multicall: u64,
+    /// One ERC-20 transfer (each call pulls the input and pays the output)
+    pub token_transfer: u64,
+    /// `PoolManager.swap` within one tick range
+    pub concentrated_swap: u64,
+    /// Swap through a v2 pair: two balance reads, two transfers and the
+    /// reserve update
+    pub constant_product_swap: u64,
+    /// Swap through a stableswap pool: the invariant is solved on-chain
+    /// by iteration
+    pub stable_swap: u64,
+    /// Each initialized tick a concentrated swap crosses
+    pub tick_crossed: u64,
+    /// Each hook callback (`beforeSwap`, `afterSwap`) a hooked pool's
+    /// permission flags enable
+    pub hook_call: u64,
+    /// `WETH.deposit()` before the first hop
+    pub wrap: u64,
+    /// `WETH.withdraw()` and the ETH transfer after the last hop
+    pub unwrap: u64,
+}
+
+impl Default for GasModel {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl GasModel {
+    /// Uncalibrated figures
+    pub const DEFAULT: GasModel = GasModel {
+        transaction: 21_000,
+        router_call: 22_000,
+        multicall: 6_000,
+        token_transfer: 25_000,
+        concentrated_swap: 45_000,
+        constant_product_swap: 35_000,
+        stable_swap: 90_000,
+        tick_crossed: 22_000,
+        hook_call: 25_000,
+        wrap: 28_000,
+        unwrap: 35_000,
+    };
+
+    /// Calibrate from the forge-std snapshot files in `dir`
+    pub fn from_snapshots(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref();
+        if !dir.is_dir() {
+            return Err(RouterError::ConfigError(format!(
+                "Gas snapshot directory {} not found",
+                dir.display()
+            )));
+        }
+        let pool_manager = read_group(dir, "PoolManager")?;
+        let swap_router = read_group(dir, "SwapRouter")?;
+        let erc20 = read_group(dir, "ERC20")?;
+        Ok(Self::DEFAULT.calibrated(&pool_manager, &swap_router, &erc20))
+    }
+
+    /// Replace the components the snapshots measure
+    ///
+    /// Each component is the difference between two measurements, so
+    /// the swap and transfers a router call makes are not counted twice.
+    fn calibrated(
+        mut self,
+        pool_manager: &HashMap<String, u64>,
+        swap_router: &HashMap<String, u64>,
+        erc20: &HashMap<String, u64>,
+    ) -> Self {
+        if let Some(&transfer) = erc20.get("transfer") {
+            self.token_transfer = transfer;
+        }
+        if let Some(&swap) = pool_manager.get("swap") {
+            self.concentrated_swap = swap;
+        }
+        if let Some(&crossing) = pool_manager.get("swap_cross_tick") {
+            self.tick_crossed = crossing.saturating_sub(self.concentrated_swap);
+        }
+        if let Some(&hooked) = pool_manager.get("swap_hooks") {
+            self.hook_call = hooked.saturating_sub(self.concentrated_swap) / 2;
+        }
+        if let Some(&single) = swap_router.get("exactInputSingle") {
+            self.router_call =
+                single.saturating_sub(self.concentrated_swap + 2 * self.token_transfer);
+            if let Some(&two_legs) = swap_router.get("multicall_2_legs") {
+                self.multicall = two_legs.saturating_sub(2 * single);
+            }
+        }
+        if let Some(&wrap) = swap_router.get("wrapETH") {
+            self.wrap = wrap;
+        }
+        if let Some(&unwrap) = swap_router.get("unwrapWETH") {
+            self.unwrap = unwrap;
+        }
+        self
+    }
+
+    /// Gas of one hop through `pool` that crosses `ticks_crossed`
+    /// initialized ticks
+    pub fn hop_gas(&self, pool: &PoolEdge, ticks_crossed: u32) -> u64 {
+        let swap = match pool.pool_type {
+            PoolType::Concentrated => {
+                self.concentrated_swap + ticks_crossed as u64 * self.tick_crossed
+            }
+            PoolType::ConstantProduct => self.constant_product_swap,
+            PoolType::StableSwap => self.stable_swap,
+        };
+        swap + pool.hook_swap_callbacks() as u64 * self.hook_call
+    }
+
+    /// Gas of the hops of `route`, with the ticks each hop crosses
+    /// re-simulated from the hop's pool and input
+    pub fn swap_gas(&self, route: &Route) -> u64 {
+        route
+            .hops
+            .iter()
+            .map(|hop| {
+                let ticks_crossed = hop
+                    .pool
+                    .zero_for_one(hop.token_in)
+                    .and_then(|zero_for_one| {
+                        PoolModel::of(&hop.pool).simulate(zero_for_one, hop.amount_in).ok()
+                    })
+                    .map_or(0, |(_, ticks_crossed)| ticks_crossed);
+                self.hop_gas(&hop.pool, ticks_crossed)
+            })
+            .sum()
+    }
+
+    /// Gas of the SwapRouter call executing one route leg
+    pub fn call_gas(&self, route: &Route) -> u64 {
+        self.router_call + 2 * self.token_transfer + self.swap_gas(route)
+    }
+
+    /// Gas of the whole transaction executing `split`
+    pub fn transaction_gas(&self, split: &SplitRoute) -> u64 {
+        let legs: u64 = split.routes.iter().map(|(route, _)| self.call_gas(route)).sum();
+        let calls = split.routes.len()
+            + split.wrap as usize
+            + split.unwrap as usize
+            + split.fee_transfer as usize;
+
+        let mut gas = self.transaction + legs;
+        if calls > 1 {
+            gas += self.multicall;
+        }
+        if split.wrap {
+            gas += self.wrap;
+        }
+        if split.unwrap {
+            gas += self.unwrap;
+        }
+        if split.fee_transfer {
+            gas += self.token_transfer;
+        }
+        gas
+    }
+
+    /// Set the gas estimate of `route` to its whole transaction
+    pub fn price_route(&self, mut route: Route) -> Route {
+        route.gas_estimate = self.transaction_gas(&SplitRoute::single(route.clone()));
+        route
+    }
+
+    /// Set the gas estimate of `split` to its whole transaction, and
+    /// those of its legs to their router calls
+    pub fn price_split(&self, mut split: SplitRoute) -> SplitRoute {
+        split.total_gas_estimate = self.transaction_gas(&split);
+        if let [(route, _)] = split.routes.as_mut_slice() {
+            route.gas_estimate = split.total_gas_estimate;
+        } else {
+            for (route, _) in &mut split.routes {
+                route.gas_estimate = self.call_gas(route);
+            }
+        }
+        split
+    }
+}
+
+/// Entries of one snapshot group (empty if the file does not exist)
+///
+/// forge-std writes gas values as strings; plain numbers are accepted too.
+fn read_group(dir: &Path, group: &str) -> Result<HashMap<String, u64>> {
+    let path = dir.join(format!("{}.json", group));
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    let invalid = |reason: String| {
+        RouterError::ConfigError(format!("Invalid gas snapshot {}: {}", path.display(), reason))
+    };
+
+    let contents = std::fs::read_to_string(&path).map_err(|e| invalid(e.to_string()))?;
+    let entries: HashMap<String, serde_json::Value> =
+        serde_json::from_str(&contents).map_err(|e| invalid(e.to_string()))?;
+    entries
+        .into_iter()
+        .map(|(name, value)| {
+            let gas = match &value {
+                serde_json::Value::String(gas) => gas.parse().ok(),
+                value => value.as_u64(),
+            };
+            gas.map(|gas| (name.clone(), gas))
+                .ok_or_else(|| invalid(format!("{} is not a gas value: {}", name, value)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::PoolEdge;
+    use crate::routing::RouteHop;
+    use crate::utils::address_from_u64;
+    use crate::utils::math::tick_to_sqrt_price_x96;
+    use alloy_primitives::U256;
+
+    /// Pool with a hook whose address carries `flags`
+    fn pool(flags: u64) -> PoolEdge {
+        let mut pool = PoolEdge::new(
+            [1; 32],
+            address_from_u64(1),
+            address_from_u64(2),
+            3000,
+            60,
+            10u128.pow(18),
+            tick_to_sqrt_price_x96(0),
+            0,
+        );
+        if flags != 0 {
+            pool.hook_address = address_from_u64(0x1_0000 | flags);
+        }
+        pool
+    }
+
+    fn route(pool: PoolEdge, amount_in: U256) -> Route {
+        let (token_in, token_out) = (pool.token0, pool.token1);
+        let hop = RouteHop::new(pool, token_in, token_out, amount_in, U256::ZERO);
+        Route::new(vec![hop], amount_in, U256::ZERO, 0.0, 0)
+    }
+
+    #[test]
+    fn test_hop_gas_counts_ticks_and_hooks() {
+        let model = GasModel::DEFAULT;
+        let plain = model.hop_gas(&pool(0), 0);
+        assert_eq!(plain, model.concentrated_swap);
+        assert_eq!(model.hop_gas(&pool(0), 2), plain + 2 * model.tick_crossed);
+
+        // Only the swap callbacks the hook's flags enable are charged
+        assert_eq!(model.hop_gas(&pool(0xc0), 0), plain + 2 * model.hook_call);
+        assert_eq!(model.hop_gas(&pool(0x80), 0), plain + model.hook_call);
+        assert_eq!(model.hop_gas(&pool(0x40), 0), plain + model.hook_call);
+        assert_eq!(model.hop_gas(&pool(0x3f), 0), plain);
+
+        // Without tick data, a swap too large for the current tick range
+        // crosses into the next
+        let small = route(pool(0), U256::from(10u128.pow(12)));
+        let large = route(pool(0), U256::from(10u128.pow(21)));
+        assert_eq!(model.swap_gas(&small), plain);
+        assert_eq!(model.swap_gas(&large), plain + model.tick_crossed);
+
+        // With tick data, only the initialized ticks it passes count
+        let mut bare = pool(0);
+        bare.ticks = Some(Vec::new().into());
+        assert_eq!(model.swap_gas(&route(bare, U256::from(10u128.pow(21)))), plain);
+    }
+
+    #[test]
+    fn test_transaction_gas_adds_calls_and_native_steps() {
+        let model = GasModel::DEFAULT;
+        let leg = route(pool(0), U256::from(10u128.pow(12)));
+        let call = model.router_call + 2 * model.token_transfer + model.concentrated_swap;
+
+        let single = SplitRoute::single(leg.clone());
+        assert_eq!(model.transaction_gas(&single), model.transaction + call);
+
+        let split = SplitRoute::new(vec![(leg.clone(), 50), (leg.clone(), 50)], U256::ZERO, U256::ZERO, 0.0, 0);
+        let priced = model.price_split(split);
+        assert_eq!(priced.total_gas_estimate, model.transaction + model.multicall + 2 * call);
+        assert_eq!(priced.routes[0].0.gas_estimate, call);
+        assert_eq!(model.price_split(single.clone()).total_gas_estimate, model.transaction + call);
+
+        let wrapped = single.clone().with_native(true, true);
+        assert_eq!(
+            model.transaction_gas(&wrapped),
+            model.transaction + model.multicall + call + model.wrap + model.unwrap
+        );
+
+        let with_fee = SplitRoute {
+            fee_transfer: true,
+            ..single
+        };
+        let priced = model.price_split(with_fee);
+        let total = model.transaction + model.multicall + call + model.token_transfer;
+        assert_eq!(priced.total_gas_estimate, total);
+        assert_eq!(priced.routes[0].0.gas_estimate, total);
+    }
+
+    #[test]
+    fn test_snapshots_calibrate_measured_components() {
+        let dir = std::env::temp_dir().join(format!("gas-snapshots-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("PoolManager.json"),
+            r#"{"swap": "40000", "swap_cross_tick": "61000", "swap_hooks": 48000}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("SwapRouter.json"),
+            r#"{"exactInputSingle": "110000", "multicall_2_legs": "224000"}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.join("ERC20.json"), r#"{"transfer": "30000"}"#).unwrap();
+
+        let model = GasModel::from_snapshots(&dir).unwrap();
+        assert_eq!(model.concentrated_swap, 40_000);
+        assert_eq!(model.tick_crossed, 21_000);
+        assert_eq!(model.hook_call, 4_000);
+        assert_eq!(model.token_transfer, 30_000);
+        assert_eq!(model.router_call, 10_000);
+        assert_eq!(model.multicall, 4_000);
+        // Not measured
+        assert_eq!(model.stable_swap, GasModel::DEFAULT.stable_swap);
+        assert_eq!(model.wrap, GasModel::DEFAULT.wrap);
+
+        std::fs::write(dir.join("ERC20.json"), r#"{"transfer": "lots"}"#).unwrap();
+        assert!(matches!(
+            GasModel::from_snapshots(&dir),
+            Err(RouterError::ConfigError(_))
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(GasModel::from_snapshots(&dir).is_err());
+    }
+}
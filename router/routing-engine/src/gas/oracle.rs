@@ -0,0 +1,335 @@
+use crate::gas::rpc::RpcClient;
+use crate::gas::GasModel;
+use crate::sync::SyncStatus;
+use crate::utils::{Result, RouterError};
+use alloy_primitives::{address, keccak256, Address, U256};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// OP-stack `GasPriceOracle` predeploy
+pub const GAS_PRICE_ORACLE: Address = address!("420000000000000000000000000000000000000F");
+
+/// L2 gas price used until the oracle has fetched one (0.01 gwei)
+///
+/// Base mainnet typically runs at 0.001-0.1 gwei.
+pub const DEFAULT_GAS_PRICE_WEI: u128 = 10_000_000;
+
+/// Bytes of an unsigned EIP-1559 transaction besides its calldata
+/// (type, chain id, nonce, fees, gas limit, `to`, value, RLP headers)
+pub const TX_ENVELOPE_BYTES: usize = 60;
+
+/// Gas oracle refresh settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GasConfig {
+    /// Fetch gas prices over RPC (off keeps the default L2 price and no
+    /// L1 fee)
+    pub enabled: bool,
+    /// How often gas prices are refetched (seconds)
+    pub refresh_interval_secs: u64,
+    /// Blocks of `eth_feeHistory` the priority fee is taken from
+    pub fee_history_blocks: u64,
+    /// Priority fee percentile of those blocks
+    pub priority_fee_percentile: f64,
+    /// Timeout of each RPC request (milliseconds)
+    pub rpc_timeout_ms: u64,
+    /// Directory of forge gas snapshots to calibrate the gas model from
+    /// (`None` for the built-in figures)
+    pub snapshot_dir: Option<String>,
+}
+
+impl Default for GasConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            refresh_interval_secs: 12, // ~6 Base blocks
+            fee_history_blocks: 10,
+            priority_fee_percentile: 50.0,
+            rpc_timeout_ms: 2000,
+            snapshot_dir: None,
+        }
+    }
+}
+
+impl GasConfig {
+    /// The gas model routes are priced with
+    pub fn model(&self) -> Result<GasModel> {
+        match &self.snapshot_dir {
+            Some(dir) => GasModel::from_snapshots(dir),
+            None => Ok(GasModel::default()),
+        }
+    }
+}
+
+/// L1 data fee parameters of the `GasPriceOracle` (Ecotone and later)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct L1FeeParams {
+    pub l1_base_fee: u128,
+    pub blob_base_fee: u128,
+    pub base_fee_scalar: u32,
+    pub blob_base_fee_scalar: u32,
+}
+
+impl L1FeeParams {
+    /// Upper bound of the L1 data fee of an unsigned transaction of
+    /// `unsigned_tx_size` bytes
+    ///
+    /// Same as `GasPriceOracle.getL1FeeUpperBound` since Fjord: the
+    /// FastLZ-compressed size is bounded by the raw size, then fed to
+    /// the Fjord cost regression.
+    pub fn fee_upper_bound(&self, unsigned_tx_size: usize) -> u128 {
+        // The signature adds 68 bytes
+        let tx_size = unsigned_tx_size as i128 + 68;
+        let fastlz_size = tx_size + tx_size / 255 + 16;
+        let estimated_size = (fastlz_size * 836_500 - 42_585_600).max(100 * 1_000_000) as u128;
+
+        let fee_scaled = (self.base_fee_scalar as u128)
+            .saturating_mul(16)
+            .saturating_mul(self.l1_base_fee)
+            .saturating_add((self.blob_base_fee_scalar as u128).saturating_mul(self.blob_base_fee));
+        estimated_size.saturating_mul(fee_scaled) / 1_000_000_000_000
+    }
+}
+
+/// Gas prices of one chain at one point in time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GasPrices {
+    /// Base fee of the next block
+    pub base_fee: u128,
+    pub priority_fee: u128,
+    /// Price per L2 gas a swap pays (`base_fee + priority_fee`)
+    pub gas_price: u128,
+    pub l1: L1FeeParams,
+    /// Unix time the prices were fetched (`None` for the defaults)
+    pub updated_at: Option<u64>,
+}
+
+impl Default for GasPrices {
+    fn default() -> Self {
+        Self {
+            base_fee: DEFAULT_GAS_PRICE_WEI,
+            priority_fee: 0,
+            gas_price: DEFAULT_GAS_PRICE_WEI,
+            l1: L1FeeParams::default(),
+            updated_at: None,
+        }
+    }
+}
+
+impl GasPrices {
+    /// Fee of a transaction using `l2_gas` with `calldata_bytes` of
+    /// calldata
+    pub fn estimate(&self, l2_gas: u64, calldata_bytes: usize) -> FeeEstimate {
+        let l2_fee = U256::from(l2_gas) * U256::from(self.gas_price);
+        let l1_fee = U256::from(self.l1.fee_upper_bound(calldata_bytes + TX_ENVELOPE_BYTES));
+        let total_fee = l2_fee + l1_fee;
+
+        FeeEstimate {
+            l2_gas,
+            l2_gas_price_wei: U256::from(self.gas_price),
+            l2_fee_wei: l2_fee,
+            calldata_bytes,
+            l1_fee_wei: l1_fee,
+            total_fee_wei: total_fee,
+            total_fee_eth: crate::utils::u256_ext::to_f64(total_fee) / 1e18,
+            total_fee_usd: None,
+        }
+    }
+}
+
+/// Fee of executing a quote on an OP-stack L2
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FeeEstimate {
+    /// L2 execution gas of the route
+    pub l2_gas: u64,
+    pub l2_gas_price_wei: U256,
+    pub l2_fee_wei: U256,
+    /// Estimated calldata of the swap transaction
+    pub calldata_bytes: usize,
+    /// L1 data fee (upper bound) for posting the transaction to L1
+    pub l1_fee_wei: U256,
+    pub total_fee_wei: U256,
+    pub total_fee_eth: f64,
+    /// `None` while ETH has no price
+    pub total_fee_usd: Option<f64>,
+}
+
+/// Keeps the gas prices of one chain fresh
+///
+/// Failed refreshes keep the last fetched prices (or the defaults), so
+/// quotes never wait on the RPC.
+pub struct GasOracle {
+    rpc: RpcClient,
+    config: GasConfig,
+    prices: RwLock<GasPrices>,
+    /// Where refresh outcomes are reported as the chain's RPC health
+    status: Option<Arc<SyncStatus>>,
+}
+
+impl GasOracle {
+    pub fn new(rpc_url: impl Into<String>, config: GasConfig) -> Self {
+        let timeout = Duration::from_millis(config.rpc_timeout_ms);
+        Self {
+            rpc: RpcClient::new(rpc_url, timeout),
+            config,
+            prices: RwLock::new(GasPrices::default()),
+            status: None,
+        }
+    }
+
+    /// Report every periodic refresh as the outcome of talking to the
+    /// chain RPC (the `rpc` subsystem of `/health`)
+    pub fn with_status(mut self, status: Arc<SyncStatus>) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// The latest prices
+    pub fn current(&self) -> GasPrices {
+        self.prices.read().clone()
+    }
+
+    /// Fetch gas prices and L1 fee parameters
+    ///
+    /// The priority fee comes from `eth_feeHistory`; if that fails the
+    /// node's `eth_gasPrice` is used as the whole price.
+    pub async fn refresh(&self) -> Result<GasPrices> {
+        let (gas_price, history, l1) = tokio::join!(
+            self.rpc.gas_price(),
+            self.rpc.fee_history(
+                self.config.fee_history_blocks,
+                self.config.priority_fee_percentile
+            ),
+            self.l1_params(),
+        );
+        let gas_price = to_u128(gas_price?);
+        let l1 = l1?;
+
+        let (base_fee, priority_fee) = match history {
+            Ok(history) => match history.base_fee_per_gas.last() {
+                Some(&next_base_fee) => {
+                    let rewards: Vec<u128> = history
+                        .reward
+                        .iter()
+                        .filter_map(|block| block.first().copied().map(to_u128))
+                        .collect();
+                    let priority_fee = match rewards.len() {
+                        0 => 0,
+                        n => rewards.iter().sum::<u128>() / n as u128,
+                    };
+                    (to_u128(next_base_fee), priority_fee)
+                }
+                None => (gas_price, 0),
+            },
+            Err(e) => {
+                tracing::warn!("eth_feeHistory failed, using eth_gasPrice: {}", e);
+                (gas_price, 0)
+            }
+        };
+
+        let prices = GasPrices {
+            base_fee,
+            priority_fee,
+            gas_price: base_fee.saturating_add(priority_fee),
+            l1,
+            updated_at: Some(chrono::Utc::now().timestamp() as u64),
+        };
+        *self.prices.write() = prices.clone();
+        Ok(prices)
+    }
+
+    async fn l1_params(&self) -> Result<L1FeeParams> {
+        let (l1_base_fee, blob_base_fee, base_fee_scalar, blob_base_fee_scalar) = tokio::join!(
+            self.read_oracle("l1BaseFee()"),
+            self.read_oracle("blobBaseFee()"),
+            self.read_oracle("baseFeeScalar()"),
+            self.read_oracle("blobBaseFeeScalar()"),
+        );
+        Ok(L1FeeParams {
+            l1_base_fee: to_u128(l1_base_fee?),
+            blob_base_fee: to_u128(blob_base_fee?),
+            base_fee_scalar: base_fee_scalar?.saturating_to(),
+            blob_base_fee_scalar: blob_base_fee_scalar?.saturating_to(),
+        })
+    }
+
+    /// Call a parameterless `uint256` getter of the `GasPriceOracle`
+    async fn read_oracle(&self, signature: &str) -> Result<U256> {
+        let selector = &keccak256(signature)[..4];
+        let output = self.rpc.eth_call(GAS_PRICE_ORACLE, selector).await?;
+        if output.len() != 32 {
+            return Err(RouterError::RpcError(format!(
+                "GasPriceOracle.{} returned {} bytes",
+                signature,
+                output.len()
+            )));
+        }
+        Ok(U256::from_be_slice(&output))
+    }
+
+    /// Refresh every `refresh_interval_secs` until `shutdown` is cancelled
+    pub async fn run_periodic(
+        self: Arc<Self>,
+        shutdown: CancellationToken,
+    ) -> std::result::Result<(), String> {
+        let interval = Duration::from_secs(self.config.refresh_interval_secs.max(1));
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.cancelled() => return Ok(()),
+            }
+
+            let result = self.refresh().await.map(|_| ()).map_err(|e| e.to_string());
+            if let Err(e) = &result {
+                tracing::warn!("Gas price refresh from {} failed: {}", self.rpc.url(), e);
+            }
+            if let Some(status) = &self.status {
+                status.record_rpc_result(result);
+            }
+        }
+    }
+}
+
+fn to_u128(value: U256) -> u128 {
+    value.saturating_to()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_l1_fee_upper_bound_matches_fjord_formula() {
+        let params = L1FeeParams {
+            l1_base_fee: 10_000_000_000, // 10 gwei
+            blob_base_fee: 1,
+            base_fee_scalar: 2269,
+            blob_base_fee_scalar: 1_055_762,
+        };
+        // tx 400 + 68 = 468 bytes, FastLZ bound 468 + 1 + 16 = 485
+        let estimated_size = 485u128 * 836_500 - 42_585_600;
+        let fee_scaled = 2269u128 * 16 * 10_000_000_000 + 1_055_762;
+        assert_eq!(
+            params.fee_upper_bound(400),
+            estimated_size * fee_scaled / 1_000_000_000_000
+        );
+
+        // Tiny transactions are charged as 100 bytes
+        let minimum = 100_000_000 * fee_scaled / 1_000_000_000_000;
+        assert_eq!(params.fee_upper_bound(0), minimum);
+    }
+
+    #[test]
+    fn test_default_prices_have_no_l1_fee() {
+        let fee = GasPrices::default().estimate(100_000, 300);
+        assert_eq!(fee.l1_fee_wei, U256::ZERO);
+        assert_eq!(fee.total_fee_wei, U256::from(100_000u128 * DEFAULT_GAS_PRICE_WEI));
+        assert!((fee.total_fee_eth - 1e-6).abs() < 1e-15);
+    }
+}
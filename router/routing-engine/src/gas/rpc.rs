@@ -0,0 +1,112 @@
+use crate::utils::{Result, RouterError};
+use alloy_primitives::{Address, Bytes, U256};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Minimal JSON-RPC client for the few reads the engine needs
+pub struct RpcClient {
+    http: reqwest::Client,
+    url: String,
+    next_id: AtomicU64,
+}
+
+#[derive(Deserialize)]
+struct RpcResponse {
+    result: Option<Value>,
+    error: Option<RpcErrorObject>,
+}
+
+#[derive(Deserialize)]
+struct RpcErrorObject {
+    code: i64,
+    message: String,
+}
+
+/// `eth_feeHistory` result
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeHistory {
+    /// Base fee of each block, plus the next block's
+    pub base_fee_per_gas: Vec<U256>,
+    /// Priority fees at the requested percentiles, per block
+    #[serde(default)]
+    pub reward: Vec<Vec<U256>>,
+}
+
+impl RpcClient {
+    pub fn new(url: impl Into<String>, timeout: Duration) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .expect("HTTP client configuration is valid");
+        Self {
+            http,
+            url: url.into(),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Call `method` and decode its result
+    pub async fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": self.next_id.fetch_add(1, Ordering::Relaxed),
+            "method": method,
+            "params": params,
+        });
+
+        let response: RpcResponse = self
+            .http
+            .post(&self.url)
+            .json(&request)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| RouterError::RpcError(format!("{}: {}", method, e)))?
+            .json()
+            .await
+            .map_err(|e| RouterError::RpcError(format!("{}: invalid response: {}", method, e)))?;
+
+        if let Some(error) = response.error {
+            return Err(RouterError::RpcError(format!(
+                "{}: {} (code {})",
+                method, error.message, error.code
+            )));
+        }
+        let result = response
+            .result
+            .ok_or_else(|| RouterError::RpcError(format!("{}: response has no result", method)))?;
+        serde_json::from_value(result)
+            .map_err(|e| RouterError::RpcError(format!("{}: invalid result: {}", method, e)))
+    }
+
+    pub async fn gas_price(&self) -> Result<U256> {
+        self.call("eth_gasPrice", json!([])).await
+    }
+
+    /// Fee history of the last `blocks` blocks with priority fees at
+    /// `percentile`
+    pub async fn fee_history(&self, blocks: u64, percentile: f64) -> Result<FeeHistory> {
+        self.call(
+            "eth_feeHistory",
+            json!([format!("0x{:x}", blocks), "latest", [percentile]]),
+        )
+        .await
+    }
+
+    /// `eth_call` against the latest block
+    pub async fn eth_call(&self, to: Address, data: &[u8]) -> Result<Bytes> {
+        self.call(
+            "eth_call",
+            json!([{ "to": to, "data": Bytes::copy_from_slice(data) }, "latest"]),
+        )
+        .await
+    }
+}
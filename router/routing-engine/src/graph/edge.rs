@@ -1,7 +1,50 @@
+use super::TickInfo;
+use crate::utils::math::{sqrt_price_x96_to_tick, sqrt_u256};
+use crate::utils::u256_ext::to_f64;
 use alloy_primitives::{Address, U256};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Hook permission flags in the low bits of a hook address (as in
+/// Uniswap v4's `Hooks` library): which swap callbacks the pool makes
+const BEFORE_SWAP_FLAG: u16 = 1 << 7;
+const AFTER_SWAP_FLAG: u16 = 1 << 6;
+
+/// Pricing model of a pool
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PoolType {
+    /// Concentrated liquidity (our v4-style pools)
+    #[default]
+    Concentrated,
+    /// Uniswap-v2 style `x * y = k` pair
+    ConstantProduct,
+    /// Curve-style stableswap pool
+    StableSwap,
+}
+
+impl PoolType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PoolType::Concentrated => "concentrated",
+            PoolType::ConstantProduct => "constant_product",
+            PoolType::StableSwap => "stable_swap",
+        }
+    }
+}
+
+impl std::fmt::Display for PoolType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
 
 /// Represents a pool connecting two tokens
+///
+/// `liquidity`, `sqrt_price_x96` and `tick` describe concentrated pools.
+/// Reserve-based pools fill them from their reserves (geometric mean
+/// and reserve ratio) so liquidity filters and price displays work
+/// across pool types, but they are priced from `reserves`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PoolEdge {
     pub pool_id: [u8; 32],
@@ -13,9 +56,26 @@ pub struct PoolEdge {
     pub sqrt_price_x96: U256,
     pub tick: i32,
     pub hook_address: Address,
+    #[serde(default)]
+    pub pool_type: PoolType,
+    /// Token balances of constant-product and stableswap pools
+    #[serde(default)]
+    pub reserves: [U256; 2],
+    /// Stableswap amplification coefficient `A`
+    #[serde(default)]
+    pub amplification: u64,
+    /// Token decimals; stableswap pools compare balances scaled to 18
+    #[serde(default)]
+    pub decimals: [u8; 2],
+    /// Initialized ticks of a concentrated pool, sorted by index, as
+    /// stored with [`PoolGraph::set_ticks`](super::PoolGraph::set_ticks)
+    /// (`None` without tick data). Snapshots store them separately.
+    #[serde(skip)]
+    pub ticks: Option<Arc<[TickInfo]>>,
 }
 
 impl PoolEdge {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         pool_id: [u8; 32],
         token0: Address,
@@ -36,9 +96,15 @@ impl PoolEdge {
             sqrt_price_x96,
             tick,
             hook_address: Address::ZERO,
+            pool_type: PoolType::Concentrated,
+            reserves: [U256::ZERO; 2],
+            amplification: 0,
+            decimals: [0; 2],
+            ticks: None,
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn with_hook(
         pool_id: [u8; 32],
         token0: Address,
@@ -60,6 +126,61 @@ impl PoolEdge {
             sqrt_price_x96,
             tick,
             hook_address,
+            pool_type: PoolType::Concentrated,
+            reserves: [U256::ZERO; 2],
+            amplification: 0,
+            decimals: [0; 2],
+            ticks: None,
+        }
+    }
+
+    /// Uniswap-v2 style pair holding `reserves` of token0 and token1
+    pub fn constant_product(
+        pool_id: [u8; 32],
+        token0: Address,
+        token1: Address,
+        fee: u32,
+        reserves: [U256; 2],
+    ) -> Self {
+        let mut pool = Self::new(pool_id, token0, token1, fee, 0, 0, U256::ZERO, 0);
+        pool.pool_type = PoolType::ConstantProduct;
+        pool.set_reserves(reserves);
+        pool
+    }
+
+    /// Curve-style stableswap pool with amplification `amplification`
+    pub fn stable_swap(
+        pool_id: [u8; 32],
+        token0: Address,
+        token1: Address,
+        fee: u32,
+        reserves: [U256; 2],
+        amplification: u64,
+        decimals: [u8; 2],
+    ) -> Self {
+        let mut pool = Self::new(pool_id, token0, token1, fee, 0, 0, U256::ZERO, 0);
+        pool.pool_type = PoolType::StableSwap;
+        pool.amplification = amplification;
+        pool.decimals = decimals;
+        pool.set_reserves(reserves);
+        pool
+    }
+
+    /// Update the balances of a reserve-based pool, along with the
+    /// liquidity and price fields derived from them
+    pub fn set_reserves(&mut self, reserves: [U256; 2]) {
+        self.reserves = reserves;
+        let product = reserves[0].saturating_mul(reserves[1]);
+        self.liquidity = u128::try_from(sqrt_u256(product)).unwrap_or(u128::MAX);
+
+        let ratio = to_f64(reserves[1]) / to_f64(reserves[0]);
+        let sqrt_price = ratio.sqrt() * 2f64.powi(96);
+        if sqrt_price.is_finite() && sqrt_price >= 1.0 {
+            self.sqrt_price_x96 = U256::from(sqrt_price.min(u128::MAX as f64) as u128);
+            self.tick = sqrt_price_x96_to_tick(self.sqrt_price_x96);
+        } else {
+            self.sqrt_price_x96 = U256::ZERO;
+            self.tick = 0;
         }
     }
 
@@ -79,6 +200,40 @@ impl PoolEdge {
         self.token0 == token || self.token1 == token
     }
 
+    /// Balance of `token` in the pool: the reserves of reserve-based
+    /// pools, the virtual reserves at the current price of concentrated
+    /// pools
+    pub fn balance(&self, token: Address) -> Option<U256> {
+        let is_token0 = self.zero_for_one(token)?;
+        let balance = match self.pool_type {
+            PoolType::ConstantProduct | PoolType::StableSwap => {
+                self.reserves[usize::from(!is_token0)]
+            }
+            PoolType::Concentrated if self.sqrt_price_x96.is_zero() => U256::ZERO,
+            PoolType::Concentrated => {
+                let liquidity = U256::from(self.liquidity);
+                if is_token0 {
+                    // x = L / sqrt(P)
+                    (liquidity << 96) / self.sqrt_price_x96
+                } else {
+                    // y = L * sqrt(P)
+                    liquidity.saturating_mul(self.sqrt_price_x96) >> 96
+                }
+            }
+        };
+        Some(balance)
+    }
+
+    /// Swap callbacks (`beforeSwap`, `afterSwap`) the pool's hook is
+    /// called with, by the permission flags in its address
+    pub fn hook_swap_callbacks(&self) -> u32 {
+        if self.hook_address == Address::ZERO {
+            return 0;
+        }
+        let flags = u16::from_be_bytes([self.hook_address[18], self.hook_address[19]]);
+        (flags & BEFORE_SWAP_FLAG != 0) as u32 + (flags & AFTER_SWAP_FLAG != 0) as u32
+    }
+
     /// Get token direction (true if token is token0)
     pub fn zero_for_one(&self, token_in: Address) -> Option<bool> {
         if token_in == self.token0 {
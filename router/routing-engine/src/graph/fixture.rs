@@ -0,0 +1,591 @@
+use super::{PoolEdge, PoolGraph, PoolType, TickInfo, TokenNode};
+use crate::utils::math::tick_to_sqrt_price_x96;
+use alloy_primitives::{Address, B256, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Hand-editable description of a pool graph
+///
+/// Used for test and bench graphs, the mock sync mode and dumps of the
+/// live graph. See `fixtures/README.md` for the format. Large integers
+/// (liquidity, prices) are decimal strings because TOML integers are
+/// limited to 64 bits.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GraphFixture {
+    /// Block the pool state corresponds to
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub block_number: u64,
+    pub tokens: Vec<FixtureToken>,
+    pub pools: Vec<FixturePool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FixtureToken {
+    pub address: Address,
+    pub symbol: String,
+    pub decimals: u8,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_native: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FixturePool {
+    pub id: B256,
+    /// Token address, or symbol if unique within the fixture
+    pub token0: String,
+    /// Token address, or symbol if unique within the fixture
+    pub token1: String,
+    /// Fee in hundredths of a bip (3000 = 0.3%)
+    pub fee: u32,
+    #[serde(default, skip_serializing_if = "is_concentrated")]
+    pub pool_type: PoolType,
+    #[serde(default)]
+    pub tick_spacing: i32,
+    /// Concentrated pools only
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub liquidity: String,
+    #[serde(default)]
+    pub tick: i32,
+    /// Defaults to the price at `tick`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sqrt_price_x96: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hook: Option<Address>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ticks: Vec<FixtureTick>,
+    /// Token0 and token1 balances of constant-product and stableswap pools
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reserves: Option<[String; 2]>,
+    /// Stableswap amplification coefficient
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amplification: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FixtureTick {
+    pub index: i32,
+    pub liquidity_net: String,
+    pub liquidity_gross: String,
+}
+
+/// Serialization format of a fixture file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FixtureFormat {
+    #[default]
+    Json,
+    Toml,
+}
+
+impl FixtureFormat {
+    /// Pick the format from a file extension (`.toml`, otherwise JSON)
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => FixtureFormat::Toml,
+            _ => FixtureFormat::Json,
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            FixtureFormat::Json => "application/json",
+            FixtureFormat::Toml => "application/toml",
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum FixtureError {
+    #[error("Fixture I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Malformed fixture: {0}")]
+    Malformed(String),
+
+    #[error("Unknown token in pool {pool}: {token}")]
+    UnknownToken { pool: B256, token: String },
+
+    #[error("Ambiguous token symbol in pool {pool}: {symbol}")]
+    AmbiguousSymbol { pool: B256, symbol: String },
+
+    #[error("Invalid {field} in pool {pool}: {value}")]
+    InvalidValue {
+        pool: B256,
+        field: &'static str,
+        value: String,
+    },
+
+    #[error("Duplicate {kind}: {id}")]
+    Duplicate { kind: &'static str, id: String },
+}
+
+impl GraphFixture {
+    pub fn from_json(input: &str) -> Result<Self, FixtureError> {
+        serde_json::from_str(input).map_err(|e| FixtureError::Malformed(e.to_string()))
+    }
+
+    pub fn from_toml(input: &str) -> Result<Self, FixtureError> {
+        toml::from_str(input).map_err(|e| FixtureError::Malformed(e.to_string()))
+    }
+
+    pub fn parse(input: &str, format: FixtureFormat) -> Result<Self, FixtureError> {
+        match format {
+            FixtureFormat::Json => Self::from_json(input),
+            FixtureFormat::Toml => Self::from_toml(input),
+        }
+    }
+
+    pub fn render(&self, format: FixtureFormat) -> Result<String, FixtureError> {
+        match format {
+            FixtureFormat::Json => serde_json::to_string_pretty(self)
+                .map_err(|e| FixtureError::Malformed(e.to_string())),
+            FixtureFormat::Toml => {
+                toml::to_string(self).map_err(|e| FixtureError::Malformed(e.to_string()))
+            }
+        }
+    }
+
+    /// Read a fixture file, choosing the format from its extension
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, FixtureError> {
+        let path = path.as_ref();
+        let input = std::fs::read_to_string(path)?;
+        Self::parse(&input, FixtureFormat::from_path(path))
+    }
+
+    /// Write a fixture file, choosing the format from its extension
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), FixtureError> {
+        let path = path.as_ref();
+        std::fs::write(path, self.render(FixtureFormat::from_path(path))?)?;
+        Ok(())
+    }
+}
+
+impl PoolGraph {
+    /// Describe the current graph as a fixture
+    ///
+    /// Pools refer to tokens by symbol where the symbol is unique, and
+    /// carry their exact sqrtPriceX96, so importing the result rebuilds
+    /// the same graph.
+    pub fn export_fixture(&self) -> GraphFixture {
+        let tokens = self.get_all_tokens();
+
+        // Symbols are matched case-insensitively on import
+        let mut symbol_counts: HashMap<String, usize> = HashMap::new();
+        for token in &tokens {
+            *symbol_counts
+                .entry(token.symbol.to_ascii_lowercase())
+                .or_default() += 1;
+        }
+        let token_ref: HashMap<Address, String> = tokens
+            .iter()
+            .map(|t| {
+                let unique = symbol_counts[&t.symbol.to_ascii_lowercase()] == 1
+                    && Address::from_str(&t.symbol).is_err();
+                let reference = if unique {
+                    t.symbol.clone()
+                } else {
+                    t.address.to_string()
+                };
+                (t.address, reference)
+            })
+            .collect();
+        let reference = |address: Address| {
+            token_ref
+                .get(&address)
+                .cloned()
+                .unwrap_or_else(|| address.to_string())
+        };
+
+        let mut pools = self.get_all_pools();
+        pools.sort_unstable_by_key(|p| p.pool_id);
+
+        let pools = pools
+            .into_iter()
+            .map(|pool| {
+                let ticks = self
+                    .get_ticks(pool.pool_id)
+                    .map(|ticks| {
+                        ticks
+                            .iter()
+                            .map(|t| FixtureTick {
+                                index: t.index,
+                                liquidity_net: t.liquidity_net.to_string(),
+                                liquidity_gross: t.liquidity_gross.to_string(),
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                let concentrated = pool.pool_type == PoolType::Concentrated;
+                FixturePool {
+                    id: B256::from(pool.pool_id),
+                    token0: reference(pool.token0),
+                    token1: reference(pool.token1),
+                    fee: pool.fee,
+                    pool_type: pool.pool_type,
+                    tick_spacing: pool.tick_spacing,
+                    liquidity: if concentrated {
+                        pool.liquidity.to_string()
+                    } else {
+                        String::new()
+                    },
+                    tick: pool.tick,
+                    sqrt_price_x96: concentrated.then(|| pool.sqrt_price_x96.to_string()),
+                    hook: (pool.hook_address != Address::ZERO).then_some(pool.hook_address),
+                    ticks,
+                    reserves: (!concentrated).then(|| pool.reserves.map(|r| r.to_string())),
+                    amplification: (pool.pool_type == PoolType::StableSwap)
+                        .then_some(pool.amplification),
+                }
+            })
+            .collect();
+
+        GraphFixture {
+            block_number: self.block_number(),
+            tokens: tokens
+                .into_iter()
+                .map(|t| FixtureToken {
+                    address: t.address,
+                    symbol: t.symbol,
+                    decimals: t.decimals,
+                    is_native: t.is_native,
+                })
+                .collect(),
+            pools,
+        }
+    }
+
+    /// Add (or update) every token and pool described by a fixture
+    ///
+    /// The whole fixture is validated before the graph is touched.
+    pub fn import_fixture(&self, fixture: &GraphFixture) -> Result<(), FixtureError> {
+        let mut by_address: HashMap<Address, TokenNode> = HashMap::new();
+        let mut by_symbol: HashMap<String, Vec<Address>> = HashMap::new();
+        for token in &fixture.tokens {
+            let node = TokenNode {
+                address: token.address,
+                symbol: token.symbol.clone(),
+                decimals: token.decimals,
+                is_native: token.is_native,
+            };
+            if by_address.insert(token.address, node).is_some() {
+                return Err(FixtureError::Duplicate {
+                    kind: "token",
+                    id: token.address.to_string(),
+                });
+            }
+            by_symbol
+                .entry(token.symbol.to_ascii_lowercase())
+                .or_default()
+                .push(token.address);
+        }
+
+        let resolve = |pool: B256, value: &str| -> Result<TokenNode, FixtureError> {
+            let address = match Address::from_str(value) {
+                Ok(address) => address,
+                Err(_) => match by_symbol.get(&value.to_ascii_lowercase()).map(Vec::as_slice) {
+                    Some([address]) => *address,
+                    Some(_) => {
+                        return Err(FixtureError::AmbiguousSymbol {
+                            pool,
+                            symbol: value.to_string(),
+                        })
+                    }
+                    None => {
+                        return Err(FixtureError::UnknownToken {
+                            pool,
+                            token: value.to_string(),
+                        })
+                    }
+                },
+            };
+            by_address
+                .get(&address)
+                .cloned()
+                .ok_or_else(|| FixtureError::UnknownToken {
+                    pool,
+                    token: value.to_string(),
+                })
+        };
+
+        let mut seen = HashSet::new();
+        let mut entries = Vec::with_capacity(fixture.pools.len());
+        for pool in &fixture.pools {
+            if !seen.insert(pool.id) {
+                return Err(FixtureError::Duplicate {
+                    kind: "pool",
+                    id: pool.id.to_string(),
+                });
+            }
+
+            let token0 = resolve(pool.id, &pool.token0)?;
+            let token1 = resolve(pool.id, &pool.token1)?;
+            let ticks = pool
+                .ticks
+                .iter()
+                .map(|t| {
+                    Ok(TickInfo::new(
+                        t.index,
+                        parse_value(pool.id, "liquidity_net", &t.liquidity_net)?,
+                        parse_value(pool.id, "liquidity_gross", &t.liquidity_gross)?,
+                    ))
+                })
+                .collect::<Result<Vec<_>, FixtureError>>()?;
+
+            let edge = match pool.pool_type {
+                PoolType::Concentrated => {
+                    let liquidity = parse_value(pool.id, "liquidity", &pool.liquidity)?;
+                    let sqrt_price_x96 = match &pool.sqrt_price_x96 {
+                        Some(value) => parse_value(pool.id, "sqrt_price_x96", value)?,
+                        None => tick_to_sqrt_price_x96(pool.tick),
+                    };
+                    PoolEdge::with_hook(
+                        pool.id.0,
+                        token0.address,
+                        token1.address,
+                        pool.fee,
+                        pool.tick_spacing,
+                        liquidity,
+                        sqrt_price_x96,
+                        pool.tick,
+                        pool.hook.unwrap_or(Address::ZERO),
+                    )
+                }
+                PoolType::ConstantProduct => PoolEdge::constant_product(
+                    pool.id.0,
+                    token0.address,
+                    token1.address,
+                    pool.fee,
+                    parse_reserves(pool)?,
+                ),
+                PoolType::StableSwap => PoolEdge::stable_swap(
+                    pool.id.0,
+                    token0.address,
+                    token1.address,
+                    pool.fee,
+                    parse_reserves(pool)?,
+                    match pool.amplification {
+                        Some(amplification) if amplification > 0 => amplification,
+                        other => {
+                            return Err(FixtureError::InvalidValue {
+                                pool: pool.id,
+                                field: "amplification",
+                                value: format!("{:?}", other),
+                            })
+                        }
+                    },
+                    [token0.decimals, token1.decimals],
+                ),
+            };
+            entries.push((edge, token0, token1, ticks));
+        }
+
+        for (edge, token0, token1, ticks) in entries {
+            let pool_id = edge.pool_id;
+            self.upsert_pool(edge, token0, token1);
+            if !ticks.is_empty() {
+                self.set_ticks(pool_id, ticks);
+            }
+        }
+        self.set_block_number(fixture.block_number);
+
+        Ok(())
+    }
+}
+
+fn parse_value<T: FromStr>(pool: B256, field: &'static str, value: &str) -> Result<T, FixtureError> {
+    value.trim().parse().map_err(|_| FixtureError::InvalidValue {
+        pool,
+        field,
+        value: value.to_string(),
+    })
+}
+
+/// Token0 and token1 balances of a reserve-based pool
+fn parse_reserves(pool: &FixturePool) -> Result<[U256; 2], FixtureError> {
+    let Some([reserve0, reserve1]) = &pool.reserves else {
+        return Err(FixtureError::InvalidValue {
+            pool: pool.id,
+            field: "reserves",
+            value: "missing".to_string(),
+        });
+    };
+    Ok([
+        parse_value(pool.id, "reserves", reserve0)?,
+        parse_value(pool.id, "reserves", reserve1)?,
+    ])
+}
+
+fn is_concentrated(pool_type: &PoolType) -> bool {
+    *pool_type == PoolType::Concentrated
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = r#"
+block_number = 42
+
+[[tokens]]
+address = "0x0000000000000000000000000000000000000001"
+symbol = "AAA"
+decimals = 18
+
+[[tokens]]
+address = "0x0000000000000000000000000000000000000002"
+symbol = "BBB"
+decimals = 6
+
+[[pools]]
+id = "0x0101010101010101010101010101010101010101010101010101010101010101"
+token0 = "AAA"
+token1 = "0x0000000000000000000000000000000000000002"
+fee = 3000
+tick_spacing = 60
+liquidity = "1000000000000000000000"
+tick = 0
+
+[[pools.ticks]]
+index = -60
+liquidity_net = "1000"
+liquidity_gross = "1000"
+
+[[pools.ticks]]
+index = 60
+liquidity_net = "-1000"
+liquidity_gross = "1000"
+"#;
+
+    #[test]
+    fn test_import_toml_fixture() {
+        let graph = PoolGraph::new();
+        graph
+            .import_fixture(&GraphFixture::from_toml(FIXTURE).unwrap())
+            .unwrap();
+
+        let stats = graph.stats();
+        assert_eq!(stats.token_count, 2);
+        assert_eq!(stats.pool_count, 1);
+        assert_eq!(stats.block_number, 42);
+
+        let pool = graph.get_pool([1u8; 32]).unwrap();
+        assert_eq!(pool.liquidity, 1_000_000_000_000_000_000_000);
+        assert_eq!(pool.sqrt_price_x96, U256::from(1u128 << 96), "Derived from tick");
+        assert_eq!(graph.get_ticks([1u8; 32]).unwrap()[1].liquidity_net, -1000);
+    }
+
+    #[test]
+    fn test_export_import_roundtrip() {
+        let graph = PoolGraph::new();
+        graph
+            .import_fixture(&GraphFixture::from_toml(FIXTURE).unwrap())
+            .unwrap();
+        let exported = graph.export_fixture();
+
+        for format in [FixtureFormat::Json, FixtureFormat::Toml] {
+            let text = exported.render(format).unwrap();
+            let parsed = GraphFixture::parse(&text, format).unwrap();
+            assert_eq!(parsed, exported);
+
+            let copy = PoolGraph::new();
+            copy.import_fixture(&parsed).unwrap();
+            assert_eq!(copy.export_fixture(), exported);
+        }
+    }
+
+    const RESERVE_POOLS: &str = r#"
+[[pools]]
+id = "0x0202020202020202020202020202020202020202020202020202020202020202"
+token0 = "AAA"
+token1 = "BBB"
+fee = 3000
+pool_type = "constant_product"
+reserves = ["1000000000000000000000", "2000000000"]
+
+[[pools]]
+id = "0x0303030303030303030303030303030303030303030303030303030303030303"
+token0 = "AAA"
+token1 = "BBB"
+fee = 400
+pool_type = "stable_swap"
+reserves = ["1000000000000000000000", "1000000000"]
+amplification = 200
+"#;
+
+    #[test]
+    fn test_reserve_pools_roundtrip() {
+        let graph = PoolGraph::new();
+        let fixture = GraphFixture::from_toml(&format!("{}{}", FIXTURE, RESERVE_POOLS)).unwrap();
+        graph.import_fixture(&fixture).unwrap();
+
+        let pair = graph.get_pool([2u8; 32]).unwrap();
+        assert_eq!(pair.pool_type, PoolType::ConstantProduct);
+        assert_eq!(pair.reserves[1], U256::from(2_000_000_000u64));
+        let stable = graph.get_pool([3u8; 32]).unwrap();
+        assert_eq!(stable.pool_type, PoolType::StableSwap);
+        assert_eq!((stable.amplification, stable.decimals), (200, [18, 6]));
+
+        let exported = graph.export_fixture();
+        let text = exported.render(FixtureFormat::Toml).unwrap();
+        let copy = PoolGraph::new();
+        copy.import_fixture(&GraphFixture::from_toml(&text).unwrap())
+            .unwrap();
+        assert_eq!(copy.export_fixture(), exported);
+
+        let missing = fixture_without(&fixture, |p| p.amplification = None);
+        let err = PoolGraph::new().import_fixture(&missing).unwrap_err();
+        assert!(matches!(err, FixtureError::InvalidValue { field: "amplification", .. }));
+        let missing = fixture_without(&fixture, |p| p.reserves = None);
+        let err = PoolGraph::new().import_fixture(&missing).unwrap_err();
+        assert!(matches!(err, FixtureError::InvalidValue { field: "reserves", .. }));
+    }
+
+    fn fixture_without(fixture: &GraphFixture, edit: impl Fn(&mut FixturePool)) -> GraphFixture {
+        let mut fixture = fixture.clone();
+        fixture.pools.iter_mut().skip(1).for_each(edit);
+        fixture
+    }
+
+    #[test]
+    fn test_unknown_token_is_rejected() {
+        let fixture = GraphFixture::from_toml(&FIXTURE.replace("token0 = \"AAA\"", "token0 = \"ZZZ\""))
+            .unwrap();
+
+        let graph = PoolGraph::new();
+        let err = graph.import_fixture(&fixture).unwrap_err();
+        assert!(matches!(err, FixtureError::UnknownToken { .. }));
+        assert_eq!(graph.stats().pool_count, 0, "Nothing imported on error");
+    }
+
+    #[test]
+    fn test_invalid_liquidity_is_rejected() {
+        let fixture = GraphFixture::from_toml(&FIXTURE.replace(
+            "liquidity = \"1000000000000000000000\"",
+            "liquidity = \"lots\"",
+        ))
+        .unwrap();
+
+        let err = PoolGraph::new().import_fixture(&fixture).unwrap_err();
+        assert!(matches!(
+            err,
+            FixtureError::InvalidValue {
+                field: "liquidity",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(FixtureFormat::from_path(Path::new("a/b.toml")), FixtureFormat::Toml);
+        assert_eq!(FixtureFormat::from_path(Path::new("a/b.json")), FixtureFormat::Json);
+    }
+}
@@ -1,7 +1,14 @@
 pub mod edge;
+pub mod fixture;
 pub mod node;
 pub mod pool_graph;
+pub mod snapshot;
+pub mod synthetic;
+pub mod tick;
 
-pub use edge::PoolEdge;
+pub use edge::{PoolEdge, PoolType};
+pub use fixture::{FixtureError, FixtureFormat, GraphFixture};
 pub use node::TokenNode;
 pub use pool_graph::PoolGraph;
+pub use snapshot::{GraphSnapshot, SnapshotError, SnapshotStore, SNAPSHOT_VERSION};
+pub use tick::TickInfo;
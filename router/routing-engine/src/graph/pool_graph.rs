@@ -1,8 +1,9 @@
-use super::{PoolEdge, TokenNode};
+use super::snapshot::{GraphSnapshot, PoolTicks};
+use super::{PoolEdge, TickInfo, TokenNode};
 use dashmap::DashMap;
 use alloy_primitives::Address;
 use parking_lot::RwLock;
-use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::graph::{DiGraph, EdgeIndex, NodeIndex};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
@@ -18,11 +19,20 @@ pub struct PoolGraph {
     /// Fast lookup: token address -> node index
     token_index: DashMap<Address, NodeIndex>,
 
-    /// Fast lookup: pool_id -> edge indices
-    pool_index: DashMap<[u8; 32], Vec<(NodeIndex, NodeIndex)>>,
+    /// Fast lookup: pool_id -> both directed edges of the pool
+    pool_index: DashMap<[u8; 32], [EdgeIndex; 2]>,
+
+    /// Initialized ticks per pool, sorted by tick index
+    ticks: DashMap<[u8; 32], Arc<[TickInfo]>>,
 
     /// Last update timestamp
     last_update: AtomicU64,
+
+    /// Block the pool state was last synced at (0 if unknown)
+    block_number: AtomicU64,
+
+    /// Bumped on every pool insert or update, and on tick updates
+    version: AtomicU64,
 }
 
 impl PoolGraph {
@@ -31,7 +41,10 @@ impl PoolGraph {
             graph: Arc::new(RwLock::new(DiGraph::new())),
             token_index: DashMap::new(),
             pool_index: DashMap::new(),
+            ticks: DashMap::new(),
             last_update: AtomicU64::new(0),
+            block_number: AtomicU64::new(0),
+            version: AtomicU64::new(0),
         }
     }
 
@@ -48,25 +61,88 @@ impl PoolGraph {
     }
 
     /// Add or update a pool in the graph
-    pub fn upsert_pool(&self, pool: PoolEdge, token0_node: TokenNode, token1_node: TokenNode) {
+    ///
+    /// A pool that is already present has its state replaced in place,
+    /// so repeated syncs never duplicate edges. The pool keeps the ticks
+    /// stored for it with [`set_ticks`](Self::set_ticks).
+    pub fn upsert_pool(&self, mut pool: PoolEdge, token0_node: TokenNode, token1_node: TokenNode) {
         let node0 = self.get_or_create_node(token0_node);
         let node1 = self.get_or_create_node(token1_node);
 
         let mut graph = self.graph.write();
+        pool.ticks = self.get_ticks(pool.pool_id);
+
+        let existing = self.pool_index.get(&pool.pool_id).map(|edges| *edges);
+        if let Some(edges) = existing {
+            for edge in edges {
+                graph[edge] = pool.clone();
+            }
+        } else {
+            // Add bidirectional edges (can swap both directions)
+            let forward = graph.add_edge(node0, node1, pool.clone());
+            let backward = graph.add_edge(node1, node0, pool.clone());
 
-        // Add bidirectional edges (can swap both directions)
-        graph.add_edge(node0, node1, pool.clone());
-        graph.add_edge(node1, node0, pool.clone());
+            // Update pool index
+            self.pool_index.insert(pool.pool_id, [forward, backward]);
+        }
 
-        // Update pool index
-        self.pool_index
-            .insert(pool.pool_id, vec![(node0, node1), (node1, node0)]);
+        self.version.fetch_add(1, Ordering::Release);
 
         // Update timestamp
         self.last_update
             .store(chrono::Utc::now().timestamp() as u64, Ordering::Relaxed);
     }
 
+    /// Replace the initialized ticks of a pool
+    ///
+    /// The ticks are attached to the pool's edges (see
+    /// [`PoolEdge::ticks`]), where swaps count the ticks they cross.
+    pub fn set_ticks(&self, pool_id: [u8; 32], mut ticks: Vec<TickInfo>) {
+        ticks.sort_unstable_by_key(|t| t.index);
+        let ticks: Arc<[TickInfo]> = ticks.into();
+
+        // Under the graph lock, so a concurrent upsert cannot put back
+        // the ticks being replaced
+        let mut graph = self.graph.write();
+        self.ticks.insert(pool_id, ticks.clone());
+        if let Some(edges) = self.pool_index.get(&pool_id) {
+            for edge in *edges {
+                graph[edge].ticks = Some(ticks.clone());
+            }
+            self.version.fetch_add(1, Ordering::Release);
+        }
+    }
+
+    /// Get the initialized ticks of a pool, sorted by tick index
+    pub fn get_ticks(&self, pool_id: [u8; 32]) -> Option<Arc<[TickInfo]>> {
+        self.ticks.get(&pool_id).map(|t| t.clone())
+    }
+
+    /// Record the block the graph state corresponds to
+    ///
+    /// The block number never moves backwards.
+    pub fn set_block_number(&self, block: u64) {
+        self.block_number.fetch_max(block, Ordering::Relaxed);
+    }
+
+    /// Block the graph state corresponds to (0 if unknown)
+    pub fn block_number(&self) -> u64 {
+        self.block_number.load(Ordering::Relaxed)
+    }
+
+    /// Counter that changes whenever any pool changes
+    ///
+    /// Lets derived structures (e.g. the router's candidate index) tell
+    /// whether they are stale without comparing pool state.
+    pub fn version(&self) -> u64 {
+        self.version.load(Ordering::Acquire)
+    }
+
+    /// Whether `token` has any pool in the graph
+    pub fn contains_token(&self, token: Address) -> bool {
+        self.token_index.contains_key(&token)
+    }
+
     /// Get all pools connected to a token
     pub fn get_pools_for_token(&self, token: Address) -> Vec<PoolEdge> {
         let graph = self.graph.read();
@@ -89,15 +165,9 @@ impl PoolGraph {
     pub fn get_pool(&self, pool_id: [u8; 32]) -> Option<PoolEdge> {
         let graph = self.graph.read();
 
-        if let Some(indices) = self.pool_index.get(&pool_id) {
-            if let Some((from, to)) = indices.first() {
-                if let Some(edge) = graph.find_edge(*from, *to) {
-                    return Some(graph[edge].clone());
-                }
-            }
-        }
-
-        None
+        self.pool_index
+            .get(&pool_id)
+            .and_then(|edges| graph.edge_weight(edges[0]).cloned())
     }
 
     /// Check if a path exists between two tokens
@@ -119,6 +189,15 @@ impl PoolGraph {
         has_path_connecting(&*graph, start, end, None)
     }
 
+    /// Get every pool in the graph (one entry per pool)
+    pub fn get_all_pools(&self) -> Vec<PoolEdge> {
+        let graph = self.graph.read();
+        self.pool_index
+            .iter()
+            .filter_map(|entry| graph.edge_weight(entry.value()[0]).cloned())
+            .collect()
+    }
+
     /// Get all tokens in the graph
     pub fn get_all_tokens(&self) -> Vec<TokenNode> {
         let graph = self.graph.read();
@@ -132,8 +211,50 @@ impl PoolGraph {
             token_count: graph.node_count(),
             pool_count: graph.edge_count() / 2, // Bidirectional
             last_update: self.last_update.load(Ordering::Relaxed),
+            block_number: self.block_number(),
+        }
+    }
+
+    /// Capture a consistent copy of the graph for persistence
+    ///
+    /// Pools are ordered by id so identical graphs produce identical
+    /// snapshots.
+    pub fn snapshot(&self, chain_id: u64) -> GraphSnapshot {
+        let graph = self.graph.read();
+
+        let tokens: Vec<TokenNode> = graph.node_weights().cloned().collect();
+        let mut pools: Vec<PoolEdge> = self
+            .pool_index
+            .iter()
+            .filter_map(|entry| graph.edge_weight(entry.value()[0]).cloned())
+            .collect();
+        pools.sort_unstable_by_key(|p| p.pool_id);
+
+        let mut ticks: Vec<PoolTicks> = self
+            .ticks
+            .iter()
+            .filter(|entry| self.pool_index.contains_key(entry.key()))
+            .map(|entry| PoolTicks {
+                pool_id: *entry.key(),
+                ticks: entry.value().to_vec(),
+            })
+            .collect();
+        ticks.sort_unstable_by_key(|t| t.pool_id);
+
+        GraphSnapshot {
+            chain_id,
+            block_number: self.block_number(),
+            last_update: self.last_update.load(Ordering::Relaxed),
+            tokens,
+            pools,
+            ticks,
         }
     }
+
+    /// Override the last update timestamp (e.g. when restoring a snapshot)
+    pub(crate) fn set_last_update(&self, timestamp: u64) {
+        self.last_update.store(timestamp, Ordering::Relaxed);
+    }
 }
 
 impl Default for PoolGraph {
@@ -147,6 +268,7 @@ pub struct GraphStats {
     pub token_count: usize,
     pub pool_count: usize,
     pub last_update: u64,
+    pub block_number: u64,
 }
 
 #[cfg(test)]
@@ -197,4 +319,110 @@ mod tests {
 
         assert!(graph.has_path(token0.address, token1.address));
     }
+
+    #[test]
+    fn test_upsert_existing_pool_updates_in_place() {
+        let graph = PoolGraph::new();
+
+        let token0 = TokenNode::new(address_from_u64(1), "TOKEN0".to_string(), 18);
+        let token1 = TokenNode::new(address_from_u64(2), "TOKEN1".to_string(), 18);
+
+        let mut pool = PoolEdge::new(
+            [7u8; 32],
+            token0.address,
+            token1.address,
+            3000,
+            60,
+            1_000_000,
+            U256::from(1u128 << 96),
+            0,
+        );
+        graph.upsert_pool(pool.clone(), token0.clone(), token1.clone());
+        let version = graph.version();
+
+        pool.liquidity = 2_000_000;
+        graph.upsert_pool(pool, token0.clone(), token1.clone());
+        assert!(graph.version() > version);
+
+        assert_eq!(graph.stats().pool_count, 1);
+        assert_eq!(graph.get_pools_for_token(token0.address).len(), 1);
+        assert_eq!(graph.get_pool([7u8; 32]).unwrap().liquidity, 2_000_000);
+    }
+
+    #[test]
+    fn test_get_pool_with_parallel_pools() {
+        let graph = PoolGraph::new();
+
+        let token0 = TokenNode::new(address_from_u64(1), "TOKEN0".to_string(), 18);
+        let token1 = TokenNode::new(address_from_u64(2), "TOKEN1".to_string(), 18);
+
+        for (id, fee) in [(1u8, 500u32), (2u8, 3000u32)] {
+            let pool = PoolEdge::new(
+                [id; 32],
+                token0.address,
+                token1.address,
+                fee,
+                60,
+                1_000_000,
+                U256::from(1u128 << 96),
+                0,
+            );
+            graph.upsert_pool(pool, token0.clone(), token1.clone());
+        }
+
+        assert_eq!(graph.get_pool([1u8; 32]).unwrap().fee, 500);
+        assert_eq!(graph.get_pool([2u8; 32]).unwrap().fee, 3000);
+    }
+
+    #[test]
+    fn test_ticks_are_sorted() {
+        let graph = PoolGraph::new();
+        graph.set_ticks(
+            [1u8; 32],
+            vec![
+                TickInfo::new(60, -5, 5),
+                TickInfo::new(-60, 5, 5),
+            ],
+        );
+
+        let ticks = graph.get_ticks([1u8; 32]).unwrap();
+        assert_eq!(ticks[0].index, -60);
+        assert_eq!(ticks[1].index, 60);
+        assert!(graph.get_ticks([2u8; 32]).is_none());
+
+        // Stored ticks are attached to the pool, whichever comes first
+        let token0 = TokenNode::new(address_from_u64(1), "TOKEN0".to_string(), 18);
+        let token1 = TokenNode::new(address_from_u64(2), "TOKEN1".to_string(), 18);
+        let pool = |id| {
+            PoolEdge::new(
+                id,
+                token0.address,
+                token1.address,
+                3000,
+                60,
+                1_000_000,
+                U256::from(1u128 << 96),
+                0,
+            )
+        };
+        graph.upsert_pool(pool([1u8; 32]), token0.clone(), token1.clone());
+        graph.upsert_pool(pool([2u8; 32]), token0.clone(), token1.clone());
+        assert!(graph.get_pool([2u8; 32]).unwrap().ticks.is_none());
+        let version = graph.version();
+        graph.set_ticks([2u8; 32], vec![TickInfo::new(0, 5, 5)]);
+        assert!(graph.version() > version);
+        assert_eq!(graph.get_pool([1u8; 32]).unwrap().ticks.unwrap().len(), 2);
+        assert_eq!(graph.get_pool([2u8; 32]).unwrap().ticks.unwrap().len(), 1);
+        let pools = graph.get_pools_for_token(token0.address);
+        assert!(pools.iter().all(|pool| pool.ticks.is_some()));
+    }
+
+    #[test]
+    fn test_block_number_is_monotonic() {
+        let graph = PoolGraph::new();
+        graph.set_block_number(100);
+        graph.set_block_number(90);
+        assert_eq!(graph.block_number(), 100);
+        assert_eq!(graph.stats().block_number, 100);
+    }
 }
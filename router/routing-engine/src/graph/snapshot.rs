@@ -0,0 +1,415 @@
+use super::{PoolEdge, PoolGraph, TickInfo, TokenNode};
+use alloy_primitives::{keccak256, Address, B256};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tokio_util::sync::CancellationToken;
+
+/// Current on-disk snapshot schema version
+///
+/// Bump this whenever the serialized layout of [`GraphSnapshot`] (or of
+/// the types it contains) changes; older files are then rejected and the
+/// engine falls back to a full sync.
+pub const SNAPSHOT_VERSION: u32 = 2;
+
+#[derive(Error, Debug)]
+pub enum SnapshotError {
+    #[error("Snapshot I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Malformed snapshot: {0}")]
+    Malformed(String),
+
+    #[error("Unsupported snapshot version {found} (expected {expected})")]
+    UnsupportedVersion { found: u32, expected: u32 },
+
+    #[error("Snapshot checksum mismatch: stored {stored}, computed {computed}")]
+    ChecksumMismatch { stored: B256, computed: B256 },
+
+    #[error("Snapshot is for chain {found}, expected {expected}")]
+    ChainMismatch { expected: u64, found: u64 },
+
+    #[error("Inconsistent snapshot: {0}")]
+    Inconsistent(String),
+}
+
+/// Initialized ticks of one pool
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PoolTicks {
+    pub pool_id: [u8; 32],
+    pub ticks: Vec<TickInfo>,
+}
+
+/// Point-in-time copy of the pool graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphSnapshot {
+    pub chain_id: u64,
+    /// Block the pool state corresponds to (0 if unknown)
+    pub block_number: u64,
+    /// Unix time of the last graph update captured
+    pub last_update: u64,
+    pub tokens: Vec<TokenNode>,
+    pub pools: Vec<PoolEdge>,
+    pub ticks: Vec<PoolTicks>,
+}
+
+/// On-disk envelope: schema version and checksum around the snapshot
+#[derive(Serialize, Deserialize)]
+struct SnapshotFile {
+    version: u32,
+    checksum: B256,
+    snapshot: GraphSnapshot,
+}
+
+/// Read first so an incompatible layout is reported as a version error
+/// rather than a parse error
+#[derive(Deserialize)]
+struct SnapshotHeader {
+    version: u32,
+}
+
+impl GraphSnapshot {
+    /// Serialize with version header and checksum
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SnapshotError> {
+        let file = SnapshotFile {
+            version: SNAPSHOT_VERSION,
+            checksum: self.checksum()?,
+            snapshot: self.clone(),
+        };
+        serde_json::to_vec(&file).map_err(|e| SnapshotError::Malformed(e.to_string()))
+    }
+
+    /// Parse and verify a serialized snapshot
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        let header: SnapshotHeader =
+            serde_json::from_slice(bytes).map_err(|e| SnapshotError::Malformed(e.to_string()))?;
+        if header.version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion {
+                found: header.version,
+                expected: SNAPSHOT_VERSION,
+            });
+        }
+
+        let file: SnapshotFile =
+            serde_json::from_slice(bytes).map_err(|e| SnapshotError::Malformed(e.to_string()))?;
+
+        let computed = file.snapshot.checksum()?;
+        if computed != file.checksum {
+            return Err(SnapshotError::ChecksumMismatch {
+                stored: file.checksum,
+                computed,
+            });
+        }
+
+        file.snapshot.validate()?;
+        Ok(file.snapshot)
+    }
+
+    /// Check that every pool and tick list refers to known entries
+    pub fn validate(&self) -> Result<(), SnapshotError> {
+        let mut tokens = HashSet::with_capacity(self.tokens.len());
+        for token in &self.tokens {
+            if !tokens.insert(token.address) {
+                return Err(SnapshotError::Inconsistent(format!(
+                    "duplicate token {}",
+                    token.address
+                )));
+            }
+        }
+
+        let mut pools = HashSet::with_capacity(self.pools.len());
+        for pool in &self.pools {
+            if !pools.insert(pool.pool_id) {
+                return Err(SnapshotError::Inconsistent(format!(
+                    "duplicate pool {}",
+                    B256::from(pool.pool_id)
+                )));
+            }
+            for token in [pool.token0, pool.token1] {
+                if !tokens.contains(&token) {
+                    return Err(SnapshotError::Inconsistent(format!(
+                        "pool {} references unknown token {}",
+                        B256::from(pool.pool_id),
+                        token
+                    )));
+                }
+            }
+        }
+
+        for entry in &self.ticks {
+            if !pools.contains(&entry.pool_id) {
+                return Err(SnapshotError::Inconsistent(format!(
+                    "ticks for unknown pool {}",
+                    B256::from(entry.pool_id)
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Load the snapshot contents into a graph
+    ///
+    /// The graph keeps the snapshot's block number and update time, so
+    /// staleness checks treat the data as exactly as old as it is.
+    pub fn restore_into(&self, graph: &PoolGraph) -> Result<(), SnapshotError> {
+        self.validate()?;
+
+        let tokens: HashMap<Address, &TokenNode> =
+            self.tokens.iter().map(|t| (t.address, t)).collect();
+
+        for pool in &self.pools {
+            let token0 = tokens[&pool.token0].clone();
+            let token1 = tokens[&pool.token1].clone();
+            graph.upsert_pool(pool.clone(), token0, token1);
+        }
+
+        for entry in &self.ticks {
+            graph.set_ticks(entry.pool_id, entry.ticks.clone());
+        }
+
+        graph.set_block_number(self.block_number);
+        graph.set_last_update(self.last_update);
+        Ok(())
+    }
+
+    fn checksum(&self) -> Result<B256, SnapshotError> {
+        let bytes =
+            serde_json::to_vec(self).map_err(|e| SnapshotError::Malformed(e.to_string()))?;
+        Ok(keccak256(bytes))
+    }
+}
+
+/// Reads and writes graph snapshots at a fixed path
+pub struct SnapshotStore {
+    path: PathBuf,
+    chain_id: u64,
+}
+
+impl SnapshotStore {
+    pub fn new(path: impl Into<PathBuf>, chain_id: u64) -> Self {
+        Self {
+            path: path.into(),
+            chain_id,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Write the current graph to disk
+    ///
+    /// The file is replaced atomically, so a crash mid-write leaves the
+    /// previous snapshot intact. An empty graph is not written, to avoid
+    /// replacing a good snapshot after a failed startup sync. Returns
+    /// whether a snapshot was written.
+    pub fn save(&self, graph: &PoolGraph) -> Result<bool, SnapshotError> {
+        let snapshot = graph.snapshot(self.chain_id);
+        if snapshot.pools.is_empty() {
+            tracing::debug!("Graph is empty, not writing snapshot");
+            return Ok(false);
+        }
+
+        let bytes = snapshot.to_bytes()?;
+
+        if let Some(parent) = self.path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+
+        let tmp_path = self.path.with_extension("tmp");
+        {
+            use std::io::Write;
+            let mut file = std::fs::File::create(&tmp_path)?;
+            file.write_all(&bytes)?;
+            file.sync_all()?;
+        }
+        std::fs::rename(&tmp_path, &self.path)?;
+
+        tracing::debug!(
+            "Wrote graph snapshot: {} pools at block {} ({} bytes)",
+            snapshot.pools.len(),
+            snapshot.block_number,
+            bytes.len()
+        );
+        Ok(true)
+    }
+
+    /// Read the snapshot from disk, if one exists
+    pub fn load(&self) -> Result<Option<GraphSnapshot>, SnapshotError> {
+        let bytes = match std::fs::read(&self.path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let snapshot = GraphSnapshot::from_bytes(&bytes)?;
+        if snapshot.chain_id != self.chain_id {
+            return Err(SnapshotError::ChainMismatch {
+                expected: self.chain_id,
+                found: snapshot.chain_id,
+            });
+        }
+
+        Ok(Some(snapshot))
+    }
+
+    /// Write a snapshot every `interval` until `shutdown` is cancelled
+    pub async fn run_periodic(
+        self: Arc<Self>,
+        graph: Arc<PoolGraph>,
+        interval: Duration,
+        shutdown: CancellationToken,
+    ) -> Result<(), String> {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        ticker.tick().await;
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.cancelled() => return Ok(()),
+            }
+
+            let store = self.clone();
+            let graph = graph.clone();
+            match tokio::task::spawn_blocking(move || store.save(&graph)).await {
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => tracing::warn!("Failed to write graph snapshot: {}", e),
+                Err(e) => return Err(format!("Snapshot writer panicked: {}", e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::address_from_u64;
+    use alloy_primitives::U256;
+
+    fn sample_graph() -> PoolGraph {
+        let graph = PoolGraph::new();
+        let token0 = TokenNode::new(address_from_u64(1), "TOKEN0".to_string(), 18);
+        let token1 = TokenNode::new(address_from_u64(2), "TOKEN1".to_string(), 6);
+
+        let pool = PoolEdge::new(
+            [1u8; 32],
+            token0.address,
+            token1.address,
+            3000,
+            60,
+            u128::MAX / 3,
+            U256::from(1u128 << 96),
+            0,
+        );
+        graph.upsert_pool(pool, token0, token1);
+        graph.set_ticks(
+            [1u8; 32],
+            vec![TickInfo::new(-60, 1_000, 1_000), TickInfo::new(60, -1_000, 1_000)],
+        );
+        graph.set_block_number(12_345);
+        graph
+    }
+
+    fn temp_path() -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("routing-engine-{}", uuid::Uuid::new_v4()))
+            .join("graph-snapshot.json")
+    }
+
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let graph = sample_graph();
+        let snapshot = graph.snapshot(8453);
+
+        let restored = GraphSnapshot::from_bytes(&snapshot.to_bytes().unwrap()).unwrap();
+        let new_graph = PoolGraph::new();
+        restored.restore_into(&new_graph).unwrap();
+
+        let stats = new_graph.stats();
+        assert_eq!(stats.token_count, 2);
+        assert_eq!(stats.pool_count, 1);
+        assert_eq!(stats.block_number, 12_345);
+        assert_eq!(stats.last_update, graph.stats().last_update);
+        assert_eq!(new_graph.get_pool([1u8; 32]).unwrap().liquidity, u128::MAX / 3);
+        assert_eq!(new_graph.get_ticks([1u8; 32]).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_tampered_snapshot_is_rejected() {
+        let bytes = sample_graph().snapshot(8453).to_bytes().unwrap();
+        let tampered = String::from_utf8(bytes)
+            .unwrap()
+            .replace("\"fee\":3000", "\"fee\":500");
+
+        let err = GraphSnapshot::from_bytes(tampered.as_bytes()).unwrap_err();
+        assert!(matches!(err, SnapshotError::ChecksumMismatch { .. }));
+    }
+
+    #[test]
+    fn test_unsupported_version_is_rejected() {
+        let bytes = br#"{"version": 999, "layout": "from the future"}"#;
+
+        let err = GraphSnapshot::from_bytes(bytes).unwrap_err();
+        assert!(matches!(
+            err,
+            SnapshotError::UnsupportedVersion { found: 999, .. }
+        ));
+    }
+
+    #[test]
+    fn test_inconsistent_snapshot_is_rejected() {
+        let mut snapshot = sample_graph().snapshot(8453);
+        snapshot.tokens.pop();
+
+        let err = snapshot.restore_into(&PoolGraph::new()).unwrap_err();
+        assert!(matches!(err, SnapshotError::Inconsistent(_)));
+    }
+
+    #[test]
+    fn test_store_save_and_load() {
+        let path = temp_path();
+        let store = SnapshotStore::new(&path, 8453);
+
+        assert!(store.load().unwrap().is_none(), "No snapshot yet");
+        assert!(!store.save(&PoolGraph::new()).unwrap(), "Empty graph is skipped");
+        assert!(store.save(&sample_graph()).unwrap());
+
+        let snapshot = store.load().unwrap().expect("Snapshot should exist");
+        assert_eq!(snapshot.block_number, 12_345);
+        assert_eq!(snapshot.pools.len(), 1);
+
+        let other_chain = SnapshotStore::new(&path, 1);
+        assert!(matches!(
+            other_chain.load().unwrap_err(),
+            SnapshotError::ChainMismatch { .. }
+        ));
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_periodic_snapshot_writes_file() {
+        let path = temp_path();
+        let store = Arc::new(SnapshotStore::new(&path, 8453));
+        let shutdown = CancellationToken::new();
+
+        let handle = tokio::spawn(store.clone().run_periodic(
+            Arc::new(sample_graph()),
+            Duration::from_millis(20),
+            shutdown.clone(),
+        ));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        shutdown.cancel();
+        handle.await.unwrap().unwrap();
+
+        assert!(store.load().unwrap().is_some());
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
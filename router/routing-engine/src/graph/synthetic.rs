@@ -0,0 +1,74 @@
+//! Synthetic pool graphs for tests and benchmarks
+
+use crate::graph::{PoolEdge, PoolGraph, TokenNode};
+use crate::utils::address_from_u64;
+use crate::utils::addresses::{usdc, weth};
+use crate::utils::math::tick_to_sqrt_price_x96;
+use alloy_primitives::Address;
+
+/// Small deterministic generator so generated graphs are reproducible
+pub struct Lcg(pub u64);
+
+impl Lcg {
+    /// Next value in `0..bound`
+    pub fn next(&mut self, bound: u64) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 33) % bound
+    }
+}
+
+/// Long-tail graph of `tokens` tokens (`address_from_u64(1..=tokens)`)
+///
+/// Every token has three WETH pools (one per fee tier) and a USDC pool,
+/// plus `tokens` random token/token pools, most of them too shallow to
+/// be worth routing through. With 10k tokens this is about 50k pools.
+pub fn long_tail_graph(tokens: u64) -> PoolGraph {
+    let graph = PoolGraph::new();
+    let mut rng = Lcg(42);
+    let node = |address: Address| TokenNode::new(address, format!("{}", address), 18);
+    let mut id = 0u32;
+    let mut add = |token0: Address, token1: Address, fee: u32, liquidity: u128, rng: &mut Lcg| {
+        id += 1;
+        let mut pool_id = [0u8; 32];
+        pool_id[28..].copy_from_slice(&id.to_be_bytes());
+        let tick = rng.next(2001) as i32 - 1000;
+        let pool = PoolEdge::new(
+            pool_id,
+            token0,
+            token1,
+            fee,
+            60,
+            liquidity,
+            tick_to_sqrt_price_x96(tick),
+            tick,
+        );
+        graph.upsert_pool(pool, node(token0), node(token1));
+    };
+
+    for fee in [500, 3000, 10000] {
+        add(weth(), usdc(), fee, 10u128.pow(24), &mut rng);
+    }
+    for i in 1..=tokens {
+        let token = address_from_u64(i);
+        for fee in [500, 3000, 10000] {
+            let liquidity = 10u128.pow(19 + rng.next(4) as u32);
+            add(token, weth(), fee, liquidity, &mut rng);
+        }
+        let liquidity = 10u128.pow(19 + rng.next(4) as u32);
+        add(token, usdc(), 3000, liquidity, &mut rng);
+    }
+    for _ in 0..tokens {
+        let token0 = address_from_u64(1 + rng.next(tokens));
+        let token1 = address_from_u64(1 + rng.next(tokens));
+        if token0 == token1 {
+            continue;
+        }
+        let liquidity = 10u128.pow(15 + rng.next(8) as u32);
+        add(token0, token1, 3000, liquidity, &mut rng);
+    }
+
+    graph
+}
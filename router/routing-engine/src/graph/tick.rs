@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+/// An initialized tick of a concentrated liquidity pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TickInfo {
+    /// Tick index
+    pub index: i32,
+    /// Net liquidity added (positive) or removed (negative) when the
+    /// price crosses this tick moving up
+    pub liquidity_net: i128,
+    /// Total liquidity referencing this tick
+    pub liquidity_gross: u128,
+}
+
+impl TickInfo {
+    pub fn new(index: i32, liquidity_net: i128, liquidity_gross: u128) -> Self {
+        Self {
+            index,
+            liquidity_net,
+            liquidity_gross,
+        }
+    }
+}
@@ -4,10 +4,16 @@
 //! including multi-hop routing, split routing, and gas-aware optimization.
 
 pub mod api;
+pub mod backtest;
 pub mod cache;
 pub mod config;
+pub mod gas;
 pub mod graph;
+pub mod liquidity;
+pub mod metrics;
+pub mod pricing;
 pub mod routing;
+pub mod service;
 pub mod simulation;
 pub mod sync;
 pub mod utils;
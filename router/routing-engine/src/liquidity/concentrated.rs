@@ -0,0 +1,168 @@
+use super::LiquiditySource;
+use crate::graph::{PoolEdge, PoolType, TickInfo};
+use crate::utils::math::{compute_swap_step, max_step_output, tick_to_sqrt_price_x96};
+use crate::utils::{Result, RouterError};
+use alloy_primitives::U256;
+
+/// A concentrated-liquidity pool, priced with the Uniswap v3/v4 swap math
+#[derive(Debug, Clone, Copy)]
+pub struct ConcentratedPool<'a>(pub &'a PoolEdge);
+
+impl LiquiditySource for ConcentratedPool<'_> {
+    fn pool_type(&self) -> PoolType {
+        PoolType::Concentrated
+    }
+
+    fn amount_out(&self, zero_for_one: bool, amount_in: U256) -> Result<U256> {
+        Ok(self.simulate(zero_for_one, amount_in)?.0)
+    }
+
+    /// Uses `compute_swap_step` to calculate the exact output given the
+    /// pool's current sqrtPriceX96, liquidity, fee, and swap direction.
+    /// Ticks crossed are counted by [`ticks_crossed`].
+    fn simulate(&self, zero_for_one: bool, amount_in: U256) -> Result<(U256, u32)> {
+        let pool = self.0;
+        if pool.liquidity == 0 {
+            return Err(RouterError::InsufficientLiquidity {
+                required: amount_in.to_string(),
+                available: "0".to_string(),
+            });
+        }
+
+        let target = step_target(pool, zero_for_one);
+        let step = compute_swap_step(
+            pool.sqrt_price_x96,
+            target,
+            pool.liquidity,
+            amount_in,
+            pool.fee,
+        );
+
+        Ok((
+            step.amount_out,
+            ticks_crossed(pool, zero_for_one, step.sqrt_price_next),
+        ))
+    }
+
+    fn depth(&self, zero_for_one: bool) -> U256 {
+        let pool = self.0;
+        if pool.liquidity == 0 {
+            return U256::ZERO;
+        }
+        max_step_output(
+            pool.sqrt_price_x96,
+            step_target(pool, zero_for_one),
+            pool.liquidity,
+        )
+    }
+}
+
+/// Target sqrt price of a swap: the next tick boundary in the swap direction.
+/// In a full implementation we would consult a tick bitmap.
+pub(crate) fn step_target(pool: &PoolEdge, zero_for_one: bool) -> U256 {
+    if zero_for_one {
+        tick_to_sqrt_price_x96(pool.tick - pool.tick_spacing)
+    } else {
+        tick_to_sqrt_price_x96(pool.tick + pool.tick_spacing)
+    }
+}
+
+/// Initialized ticks a swap crosses moving the price of `pool` to
+/// `sqrt_price_next`
+///
+/// Walks the pool's stored ticks (see [`PoolEdge::ticks`]). A pool
+/// without tick data is assumed to have an initialized tick at the step
+/// target, crossed if the swap reaches it.
+pub(crate) fn ticks_crossed(pool: &PoolEdge, zero_for_one: bool, sqrt_price_next: U256) -> u32 {
+    let Some(ticks) = &pool.ticks else {
+        return (sqrt_price_next == step_target(pool, zero_for_one)) as u32;
+    };
+    if sqrt_price_next == pool.sqrt_price_x96 {
+        return 0;
+    }
+
+    let at_or_below_current = |t: &TickInfo| t.index <= pool.tick;
+    let (first, end) = if zero_for_one {
+        // Moving down crosses every tick from the current one to the
+        // lowest one at or above the final price
+        (
+            ticks.partition_point(|t| tick_to_sqrt_price_x96(t.index) < sqrt_price_next),
+            ticks.partition_point(at_or_below_current),
+        )
+    } else {
+        // Moving up crosses every tick above the current one up to the
+        // final price
+        (
+            ticks.partition_point(at_or_below_current),
+            ticks.partition_point(|t| tick_to_sqrt_price_x96(t.index) <= sqrt_price_next),
+        )
+    };
+    end.saturating_sub(first) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::address_from_u64;
+
+    #[test]
+    fn test_insufficient_liquidity() {
+        let pool = PoolEdge::new(
+            [1u8; 32],
+            address_from_u64(1),
+            address_from_u64(2),
+            3000,
+            60,
+            0, // No liquidity!
+            U256::from(1u128 << 96),
+            0,
+        );
+
+        let result = ConcentratedPool(&pool).amount_out(true, U256::from(1000));
+
+        match result {
+            Err(RouterError::InsufficientLiquidity { .. }) => (),
+            _ => panic!("Expected InsufficientLiquidity error"),
+        }
+        assert_eq!(ConcentratedPool(&pool).depth(true), U256::ZERO);
+    }
+
+    #[test]
+    fn test_ticks_crossed_walks_stored_ticks() {
+        let mut pool = PoolEdge::new(
+            [1u8; 32],
+            address_from_u64(1),
+            address_from_u64(2),
+            3000,
+            60,
+            10u128.pow(18),
+            tick_to_sqrt_price_x96(0),
+            0,
+        );
+        // Without tick data, only reaching the step target crosses a tick
+        let down = step_target(&pool, true);
+        let just_below = pool.sqrt_price_x96 - U256::from(1);
+        assert_eq!(ticks_crossed(&pool, true, down), 1);
+        assert_eq!(ticks_crossed(&pool, true, just_below), 0);
+
+        // Moving down from tick 0 crosses it at once, and -60 at the target
+        let tick = |index| TickInfo::new(index, 1000, 1000);
+        pool.ticks = Some(vec![tick(-120), tick(-60), tick(0), tick(60)].into());
+        assert_eq!(ticks_crossed(&pool, true, down), 2);
+        assert_eq!(ticks_crossed(&pool, true, just_below), 1);
+        assert_eq!(ticks_crossed(&pool, true, pool.sqrt_price_x96), 0);
+        assert_eq!(ticks_crossed(&pool, false, tick_to_sqrt_price_x96(90)), 1);
+        assert_eq!(ticks_crossed(&pool, false, tick_to_sqrt_price_x96(59)), 0);
+
+        // A large swap through the stored ticks
+        let (_, crossed) = ConcentratedPool(&pool)
+            .simulate(true, U256::from(10u128.pow(21)))
+            .unwrap();
+        assert_eq!(crossed, 2);
+        pool.ticks = Some(Vec::new().into());
+        let (_, crossed) = ConcentratedPool(&pool)
+            .simulate(true, U256::from(10u128.pow(21)))
+            .unwrap();
+        assert_eq!(crossed, 0);
+    }
+}
@@ -0,0 +1,89 @@
+use super::{directed, LiquiditySource};
+use crate::graph::{PoolEdge, PoolType};
+use crate::utils::{Result, RouterError};
+use alloy_primitives::{U256, U512};
+
+/// Fees are in hundredths of a bip, as for concentrated pools
+const FEE_DENOMINATOR: u64 = 1_000_000;
+
+/// A Uniswap-v2 style `x * y = k` pair
+#[derive(Debug, Clone, Copy)]
+pub struct ConstantProductPool<'a>(pub &'a PoolEdge);
+
+impl LiquiditySource for ConstantProductPool<'_> {
+    fn pool_type(&self) -> PoolType {
+        PoolType::ConstantProduct
+    }
+
+    /// `getAmountOut` of the v2 router: the fee is taken from the input
+    /// and the output rounds down.
+    fn amount_out(&self, zero_for_one: bool, amount_in: U256) -> Result<U256> {
+        let (reserve_in, reserve_out) = directed(self.0.reserves, zero_for_one);
+        if reserve_in.is_zero() || reserve_out.is_zero() {
+            return Err(RouterError::InsufficientLiquidity {
+                required: amount_in.to_string(),
+                available: reserve_out.to_string(),
+            });
+        }
+
+        let fee = U512::from(FEE_DENOMINATOR.saturating_sub(self.0.fee as u64));
+        let amount_in_with_fee = U512::from(amount_in) * fee;
+        let numerator = amount_in_with_fee * U512::from(reserve_out);
+        let denominator = U512::from(reserve_in) * U512::from(FEE_DENOMINATOR) + amount_in_with_fee;
+
+        // Below `reserve_out`, so it fits
+        Ok(U256::saturating_from(numerator / denominator))
+    }
+
+    fn depth(&self, zero_for_one: bool) -> U256 {
+        let (reserve_in, reserve_out) = directed(self.0.reserves, zero_for_one);
+        if reserve_in.is_zero() {
+            U256::ZERO
+        } else {
+            reserve_out
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::address_from_u64;
+
+    #[test]
+    fn test_matches_v2_get_amount_out() {
+        let pool = PoolEdge::constant_product(
+            [1; 32],
+            address_from_u64(1),
+            address_from_u64(2),
+            3000,
+            [U256::from(1_000_000u64), U256::from(2_000_000u64)],
+        );
+        let model = ConstantProductPool(&pool);
+
+        // 1000 * 997 * 2e6 / (1e6 * 1000 + 1000 * 997)
+        assert_eq!(model.amount_out(true, U256::from(1000)).unwrap(), U256::from(1992));
+        assert_eq!(model.amount_out(false, U256::from(1000)).unwrap(), U256::from(498));
+        assert_eq!(model.depth(true), U256::from(2_000_000u64));
+
+        // k never decreases
+        let huge = model.amount_out(true, U256::MAX).unwrap();
+        assert!(huge < U256::from(2_000_000u64));
+    }
+
+    #[test]
+    fn test_empty_pair_cannot_swap() {
+        let pool = PoolEdge::constant_product(
+            [1; 32],
+            address_from_u64(1),
+            address_from_u64(2),
+            3000,
+            [U256::ZERO, U256::from(1_000_000u64)],
+        );
+        let model = ConstantProductPool(&pool);
+
+        assert!(model.amount_out(true, U256::from(1000)).is_err());
+        assert_eq!(model.depth(true), U256::ZERO);
+        assert_eq!(pool.liquidity, 0);
+    }
+}
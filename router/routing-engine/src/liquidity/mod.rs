@@ -0,0 +1,124 @@
+//! Pool pricing models
+//!
+//! Every pool type the router can route through implements
+//! [`LiquiditySource`]: its own swap simulation and depth bound. Gas is
+//! priced by [`GasModel`](crate::gas::GasModel) from the ticks a swap
+//! crosses. The search prices hops through [`PoolModel::of`], so adding a
+//! pool type means adding a model here and a [`PoolType`] variant.
+
+pub mod concentrated;
+pub mod constant_product;
+pub mod stable_swap;
+
+pub use concentrated::ConcentratedPool;
+pub use constant_product::ConstantProductPool;
+pub use stable_swap::StableSwapPool;
+
+use crate::graph::{PoolEdge, PoolType};
+use crate::utils::Result;
+use alloy_primitives::U256;
+
+/// A pool the router can swap through
+pub trait LiquiditySource {
+    fn pool_type(&self) -> PoolType;
+
+    /// Output of swapping `amount_in` of token0 (`zero_for_one`) or
+    /// token1 through the pool
+    fn amount_out(&self, zero_for_one: bool, amount_in: U256) -> Result<U256>;
+
+    /// [`amount_out`](Self::amount_out) and the number of initialized
+    /// ticks the swap crosses (always zero for pools without ticks)
+    fn simulate(&self, zero_for_one: bool, amount_in: U256) -> Result<(U256, u32)> {
+        Ok((self.amount_out(zero_for_one, amount_in)?, 0))
+    }
+
+    /// Upper bound on [`amount_out`](Self::amount_out) in one direction
+    /// (zero if the pool cannot be swapped that way)
+    fn depth(&self, zero_for_one: bool) -> U256;
+}
+
+/// The pricing model of a pool, chosen by its [`PoolType`]
+#[derive(Debug, Clone, Copy)]
+pub enum PoolModel<'a> {
+    Concentrated(ConcentratedPool<'a>),
+    ConstantProduct(ConstantProductPool<'a>),
+    StableSwap(StableSwapPool<'a>),
+}
+
+impl<'a> PoolModel<'a> {
+    pub fn of(pool: &'a PoolEdge) -> Self {
+        match pool.pool_type {
+            PoolType::Concentrated => PoolModel::Concentrated(ConcentratedPool(pool)),
+            PoolType::ConstantProduct => PoolModel::ConstantProduct(ConstantProductPool(pool)),
+            PoolType::StableSwap => PoolModel::StableSwap(StableSwapPool(pool)),
+        }
+    }
+
+    fn source(&self) -> &dyn LiquiditySource {
+        match self {
+            PoolModel::Concentrated(pool) => pool,
+            PoolModel::ConstantProduct(pool) => pool,
+            PoolModel::StableSwap(pool) => pool,
+        }
+    }
+}
+
+impl LiquiditySource for PoolModel<'_> {
+    fn pool_type(&self) -> PoolType {
+        self.source().pool_type()
+    }
+
+    fn amount_out(&self, zero_for_one: bool, amount_in: U256) -> Result<U256> {
+        self.source().amount_out(zero_for_one, amount_in)
+    }
+
+    fn simulate(&self, zero_for_one: bool, amount_in: U256) -> Result<(U256, u32)> {
+        self.source().simulate(zero_for_one, amount_in)
+    }
+
+    fn depth(&self, zero_for_one: bool) -> U256 {
+        self.source().depth(zero_for_one)
+    }
+}
+
+/// Reserves as `(in, out)` for a swap direction
+fn directed(reserves: [U256; 2], zero_for_one: bool) -> (U256, U256) {
+    if zero_for_one {
+        (reserves[0], reserves[1])
+    } else {
+        (reserves[1], reserves[0])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::address_from_u64;
+
+    #[test]
+    fn test_model_follows_pool_type() {
+        let (a, b) = (address_from_u64(1), address_from_u64(2));
+        let reserves = [U256::from(10u128.pow(24)); 2];
+        let pools = [
+            PoolEdge::new([1; 32], a, b, 3000, 60, 10u128.pow(24), U256::from(1u128 << 96), 0),
+            PoolEdge::constant_product([2; 32], a, b, 3000, reserves),
+            PoolEdge::stable_swap([3; 32], a, b, 3000, reserves, 200, [18, 18]),
+        ];
+
+        for pool in &pools {
+            let model = PoolModel::of(pool);
+            assert_eq!(model.pool_type(), pool.pool_type);
+            let out = model.amount_out(true, U256::from(10u128.pow(18))).unwrap();
+            assert!(out > U256::ZERO && out < model.depth(true));
+        }
+
+        // Same balances and fee: the stableswap curve is flat near
+        // balance, so it gives more for a sizeable trade
+        let amount = U256::from(10u128.pow(22));
+        let outputs: Vec<_> = pools[1..]
+            .iter()
+            .map(|pool| PoolModel::of(pool).amount_out(true, amount).unwrap())
+            .collect();
+        assert!(outputs[1] > outputs[0]);
+    }
+}
@@ -0,0 +1,209 @@
+use super::{directed, LiquiditySource};
+use crate::graph::{PoolEdge, PoolType};
+use crate::utils::{Result, RouterError};
+use alloy_primitives::{U256, U512};
+
+const N_COINS: u64 = 2;
+
+/// Fees are in hundredths of a bip, as for concentrated pools
+const FEE_DENOMINATOR: u64 = 1_000_000;
+
+/// Newton iterations before giving up, as in the Curve contracts
+const MAX_ITERATIONS: usize = 255;
+
+/// A Curve-style two-coin stableswap pool
+///
+/// Follows the original StableSwap contracts: balances are scaled to 18
+/// decimals, `A * n` weights the constant-sum part of the invariant,
+/// and the fee is taken from the output.
+#[derive(Debug, Clone, Copy)]
+pub struct StableSwapPool<'a>(pub &'a PoolEdge);
+
+impl StableSwapPool<'_> {
+    /// Multipliers scaling each balance to 18 decimals
+    fn rates(&self) -> [U512; 2] {
+        self.0
+            .decimals
+            .map(|decimals| U512::from(10u64).pow(U512::from(18 - decimals.min(18))))
+    }
+
+    fn ann(&self) -> U512 {
+        U512::from(self.0.amplification) * U512::from(N_COINS)
+    }
+
+    fn invariant(&self, xp: [U512; 2]) -> Result<U512> {
+        get_d(xp, self.ann()).ok_or_else(|| not_converged(self.0))
+    }
+}
+
+impl LiquiditySource for StableSwapPool<'_> {
+    fn pool_type(&self) -> PoolType {
+        PoolType::StableSwap
+    }
+
+    fn amount_out(&self, zero_for_one: bool, amount_in: U256) -> Result<U256> {
+        let (reserve_in, reserve_out) = directed(self.0.reserves, zero_for_one);
+        if reserve_in.is_zero() || reserve_out.is_zero() || self.0.amplification == 0 {
+            return Err(RouterError::InsufficientLiquidity {
+                required: amount_in.to_string(),
+                available: reserve_out.to_string(),
+            });
+        }
+
+        let rates = self.rates();
+        let (rate_in, rate_out) = directed_rates(rates, zero_for_one);
+        let xp_in = U512::from(reserve_in) * rate_in;
+        let xp_out = U512::from(reserve_out) * rate_out;
+        let d = self.invariant([xp_in, xp_out])?;
+
+        let x = xp_in + U512::from(amount_in) * rate_in;
+        let y = get_y(x, d, self.ann()).ok_or_else(|| not_converged(self.0))?;
+
+        // One wei less, so rounding in y never favours the trader
+        let dy = xp_out.saturating_sub(y).saturating_sub(U512::from(1));
+        let fee = dy * U512::from(self.0.fee) / U512::from(FEE_DENOMINATOR);
+
+        Ok(U256::saturating_from((dy - fee) / rate_out))
+    }
+
+    fn depth(&self, zero_for_one: bool) -> U256 {
+        let (reserve_in, reserve_out) = directed(self.0.reserves, zero_for_one);
+        if reserve_in.is_zero() || self.0.amplification == 0 {
+            U256::ZERO
+        } else {
+            reserve_out
+        }
+    }
+}
+
+fn directed_rates(rates: [U512; 2], zero_for_one: bool) -> (U512, U512) {
+    if zero_for_one {
+        (rates[0], rates[1])
+    } else {
+        (rates[1], rates[0])
+    }
+}
+
+fn not_converged(pool: &PoolEdge) -> RouterError {
+    RouterError::SimulationError(format!(
+        "stableswap invariant did not converge for pool {}",
+        alloy_primitives::B256::from(pool.pool_id)
+    ))
+}
+
+/// The invariant `D` of scaled balances `xp`
+fn get_d(xp: [U512; 2], ann: U512) -> Option<U512> {
+    let n = U512::from(N_COINS);
+    let sum = xp[0] + xp[1];
+    if sum.is_zero() {
+        return Some(U512::ZERO);
+    }
+
+    let mut d = sum;
+    for _ in 0..MAX_ITERATIONS {
+        let mut d_p = d;
+        for x in xp {
+            d_p = d_p * d / (x * n);
+        }
+        let previous = d;
+        d = (ann * sum + d_p * n) * d / ((ann - U512::from(1)) * d + (n + U512::from(1)) * d_p);
+        if d.abs_diff(previous) <= U512::from(1) {
+            return Some(d);
+        }
+    }
+    None
+}
+
+/// The other scaled balance once one side holds `x`, keeping `D`
+fn get_y(x: U512, d: U512, ann: U512) -> Option<U512> {
+    let n = U512::from(N_COINS);
+    let c = d * d / (x * n) * d / (ann * n);
+    let b = x + d / ann;
+
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        let previous = y;
+        let denominator = (y * U512::from(2) + b).checked_sub(d)?;
+        y = (y * y + c) / denominator;
+        if y.abs_diff(previous) <= U512::from(1) {
+            return Some(y);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::liquidity::ConstantProductPool;
+    use crate::utils::address_from_u64;
+
+    fn pool(reserves: [u128; 2], amplification: u64, decimals: [u8; 2]) -> PoolEdge {
+        PoolEdge::stable_swap(
+            [1; 32],
+            address_from_u64(1),
+            address_from_u64(2),
+            400,
+            reserves.map(U256::from),
+            amplification,
+            decimals,
+        )
+    }
+
+    #[test]
+    fn test_balanced_pool_trades_near_par() {
+        let pool = pool([10u128.pow(30); 2], 200, [18, 18]);
+        let model = StableSwapPool(&pool);
+
+        let amount = U256::from(10u128.pow(24));
+        let out = model.amount_out(true, amount).unwrap();
+        // 0.04% fee plus a sliver of slippage on a 0.1% trade
+        let out = out.to::<u128>() as f64 / 1e24;
+        assert!(out < 0.9996 && out > 0.9995, "{}", out);
+    }
+
+    #[test]
+    fn test_scales_balances_by_decimals() {
+        // 1M of an 18-decimal and a 6-decimal stablecoin
+        let pool = pool([10u128.pow(24), 10u128.pow(12)], 100, [18, 6]);
+        let model = StableSwapPool(&pool);
+
+        let out = model.amount_out(true, U256::from(10u128.pow(18))).unwrap();
+        assert!(out > U256::from(999_000) && out < U256::from(1_000_000));
+        let back = model.amount_out(false, U256::from(1_000_000)).unwrap();
+        assert!(back > U256::from(999 * 10u128.pow(15)) && back < U256::from(10u128.pow(18)));
+    }
+
+    #[test]
+    fn test_amplification_flattens_the_curve() {
+        let amount = U256::from(10u128.pow(29));
+        let out = |amplification| {
+            let pool = pool([10u128.pow(30); 2], amplification, [18, 18]);
+            StableSwapPool(&pool).amount_out(true, amount).unwrap()
+        };
+
+        let pair = PoolEdge::constant_product(
+            [2; 32],
+            address_from_u64(1),
+            address_from_u64(2),
+            400,
+            [U256::from(10u128.pow(30)); 2],
+        );
+        let constant_product = ConstantProductPool(&pair).amount_out(true, amount).unwrap();
+
+        assert!(out(1) > constant_product);
+        assert!(out(10) > out(1));
+        assert!(out(1000) > out(10));
+        assert!(out(1000) < amount);
+    }
+
+    #[test]
+    fn test_output_is_below_reserves() {
+        let pool = pool([10u128.pow(24), 10u128.pow(22)], 50, [18, 18]);
+        let model = StableSwapPool(&pool);
+
+        let out = model.amount_out(true, U256::from(10u128.pow(30))).unwrap();
+        assert!(out < model.depth(true));
+        assert!(model.amount_out(true, U256::ZERO).unwrap().is_zero());
+    }
+}
@@ -1,8 +1,15 @@
 use routing_engine::{
-    api::{create_router, AppState},
+    api::{create_admin_router, create_router, AppState, ChainState},
     config::Settings,
-    sync::PoolSyncer,
+    graph::SnapshotStore,
+    service::{wait_for_signal, RestartPolicy, Supervisor},
+    sync::{PoolSyncer, SyncConfig},
 };
+use std::future::IntoFuture;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 #[tokio::main]
@@ -21,33 +28,26 @@ async fn main() {
     // Load settings
     let settings = Settings::load();
     tracing::info!(
-        "Configuration loaded: chain_id={}, max_hops={}, max_splits={}",
-        settings.chain.chain_id,
+        "Configuration loaded: chains={:?}, max_hops={}, max_splits={}",
+        settings.chains.iter().map(|c| c.chain_id).collect::<Vec<_>>(),
         settings.routing.max_hops,
         settings.routing.max_splits
     );
 
     // Create application state
     let state = AppState::new(settings.clone());
+    let shutdown = state.shutdown.clone();
+    // Background tasks have their own token: they keep syncing while
+    // in-flight requests drain
+    let mut supervisor =
+        Supervisor::new(CancellationToken::new()).with_metrics(state.metrics.clone());
 
-    // Sync pools
-    tracing::info!("Syncing pool data...");
-    let syncer = PoolSyncer::new(state.graph.clone());
-    if let Err(e) = syncer.sync_pools().await {
-        tracing::error!("Failed to sync pools: {}", e);
-    } else {
-        let stats = state.graph.stats();
-        tracing::info!(
-            "Pool sync complete: {} tokens, {} pools",
-            stats.token_count,
-            stats.pool_count
-        );
-    }
+    // Create routers
+    let admin = create_admin_router(state.clone());
+    let app = create_router(state.clone());
 
-    // Create router
-    let app = create_router(state);
-
-    // Start server
+    // Bind before the chains start: probes answer (not ready) while
+    // snapshots load, and quotes are served as soon as a chain has data
     let addr = format!("{}:{}", settings.server.host, settings.server.port);
     tracing::info!("Starting server on {}", addr);
 
@@ -55,11 +55,202 @@ async fn main() {
         .await
         .expect("Failed to bind");
 
+    // Metrics and admin endpoints listen on their own, internal port
+    let admin_addr = format!("{}:{}", settings.server.host, settings.server.admin_port);
+    let admin_listener = tokio::net::TcpListener::bind(&admin_addr)
+        .await
+        .expect("Failed to bind admin listener");
+
     tracing::info!("🚀 Routing Engine is running on http://{}", addr);
     tracing::info!("📊 Health check: http://{}/health", addr);
+    tracing::info!("📈 Metrics: http://{}/metrics", admin_addr);
     tracing::info!("💱 Quote API: http://{}/v1/quote", addr);
 
-    axum::serve(listener, app)
-        .await
-        .expect("Failed to start server");
+    let mut server = tokio::spawn(
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+            .with_graceful_shutdown(shutdown.clone().cancelled_owned())
+            .into_future(),
+    );
+
+    let admin_server = tokio::spawn(
+        axum::serve(admin_listener, admin)
+            .with_graceful_shutdown(shutdown.clone().cancelled_owned())
+            .into_future(),
+    );
+
+    // Each chain has its own graph, snapshot and syncer. Snapshots load
+    // concurrently, and each chain starts syncing once its own snapshot
+    // is in; readiness is tracked per chain (`/ready?chain_id=`). A
+    // restore that fails, even by panicking, leaves the chain to start
+    // from its first sync as if it had no snapshot
+    let mut restores = tokio::task::JoinSet::new();
+    for chain in state.chains.values() {
+        let chain = chain.clone();
+        let store = settings
+            .snapshot
+            .path_for(chain.chain_id)
+            .map(|path| Arc::new(SnapshotStore::new(path, chain.chain_id)));
+        restores.spawn(async move {
+            if let Some(store) = store.clone() {
+                let restoring = chain.clone();
+                let restored =
+                    tokio::task::spawn_blocking(move || restore_snapshot(&restoring, &store)).await;
+                if let Err(e) = restored {
+                    tracing::error!(
+                        "Chain {} graph snapshot restore failed, starting without it: {}",
+                        chain.chain_id,
+                        e
+                    );
+                }
+            }
+            (chain, store)
+        });
+    }
+
+    // A signal during a slow restore shuts down without waiting for it
+    let signal = wait_for_signal();
+    tokio::pin!(signal);
+    let started = async {
+        while let Some(restored) = restores.join_next().await {
+            match restored {
+                Ok((chain, store)) => start_chain(&chain, store, &settings, &mut supervisor),
+                Err(e) => tracing::error!("Chain startup failed: {}", e),
+            }
+        }
+    };
+    let signalled = tokio::select! {
+        _ = started => false,
+        _ = &mut signal => true,
+    };
+
+    if signalled {
+        tracing::info!("Shutdown signal received while restoring snapshots, shutting down...");
+    } else {
+        tokio::select! {
+            _ = &mut signal => {
+                tracing::info!("Shutdown signal received, draining in-flight requests...");
+            }
+            result = &mut server => {
+                tracing::error!("Server exited unexpectedly: {:?}", result);
+            }
+        }
+    }
+
+    // Readiness turns 503 and the listener stops accepting. Draining
+    // in-flight requests, stopping background tasks and the shutdown hooks
+    // then share one deadline, which has to fit the pod's grace period
+    shutdown.cancel();
+    let shutdown_timeout = Duration::from_secs(settings.server.shutdown_timeout_secs);
+    let deadline = tokio::time::Instant::now() + shutdown_timeout;
+    if !server.is_finished() {
+        match tokio::time::timeout_at(deadline, &mut server).await {
+            Ok(_) => tracing::info!("All in-flight requests completed"),
+            Err(_) => {
+                tracing::warn!(
+                    "In-flight requests did not finish within {:?}, closing",
+                    shutdown_timeout
+                );
+                server.abort();
+            }
+        }
+    }
+    admin_server.abort();
+
+    supervisor.shutdown_until(deadline).await;
+    tracing::info!("Routing Engine stopped");
+}
+
+/// Warm start a chain from its last snapshot, if any
+fn restore_snapshot(chain: &ChainState, store: &SnapshotStore) {
+    let chain_id = chain.chain_id;
+    match store.load() {
+        Ok(Some(snapshot)) => match snapshot.restore_into(&chain.graph) {
+            Ok(()) => {
+                chain.sync_status.record_restored(snapshot.last_update);
+                tracing::info!(
+                    "Restored chain {} graph snapshot from {}: {} tokens, {} pools at block {}",
+                    chain_id,
+                    store.path().display(),
+                    snapshot.tokens.len(),
+                    snapshot.pools.len(),
+                    snapshot.block_number
+                );
+            }
+            Err(e) => tracing::warn!("Ignoring chain {} graph snapshot: {}", chain_id, e),
+        },
+        Ok(None) => tracing::info!("No graph snapshot at {}", store.path().display()),
+        Err(e) => tracing::warn!("Ignoring chain {} graph snapshot: {}", chain_id, e),
+    }
+}
+
+/// Hand a chain's pool sync (which runs the first sync right away) and
+/// snapshot tasks to the supervisor
+fn start_chain(
+    chain: &Arc<ChainState>,
+    snapshot_store: Option<Arc<SnapshotStore>>,
+    settings: &Settings,
+    supervisor: &mut Supervisor,
+) {
+    let chain_id = chain.chain_id;
+
+    // Sync pools in the background, serving the restored snapshot until
+    // the first sync lands
+    let sync_config = SyncConfig {
+        rpc_url: chain.settings.rpc_url.clone(),
+        refresh_interval_secs: settings.sync.refresh_interval_secs,
+        fixture_path: chain
+            .settings
+            .fixture_path
+            .as_ref()
+            .or(settings.sync.fixture_path.as_ref())
+            .map(Into::into),
+        ..Default::default()
+    };
+    let syncer = Arc::new(
+        PoolSyncer::with_config(chain.graph.clone(), sync_config)
+            .with_metrics(chain.metrics.clone())
+            .with_status(chain.sync_status.clone())
+            .with_router(chain.router.clone()),
+    );
+
+    // Start supervised background tasks
+    supervisor.spawn(
+        format!("pool-sync-{}", chain_id),
+        RestartPolicy::default(),
+        move |cancel| syncer.clone().run_periodic_sync(cancel),
+    );
+    if settings.gas.enabled {
+        let gas = chain.gas.clone();
+        supervisor.spawn(
+            format!("gas-oracle-{}", chain_id),
+            RestartPolicy::default(),
+            move |cancel| gas.clone().run_periodic(cancel),
+        );
+    }
+    if let Some(store) = snapshot_store {
+        let interval = Duration::from_secs(settings.snapshot.interval_secs);
+        let graph = chain.graph.clone();
+        let periodic_store = store.clone();
+        supervisor.spawn(
+            format!("graph-snapshot-{}", chain_id),
+            RestartPolicy::default(),
+            move |cancel| {
+                periodic_store
+                    .clone()
+                    .run_periodic(graph.clone(), interval, cancel)
+            },
+        );
+
+        let graph = chain.graph.clone();
+        supervisor.on_shutdown(format!("graph-snapshot-{}", chain_id), move || async move {
+            tokio::task::spawn_blocking(move || store.save(&graph))
+                .await
+                .map_err(|e| e.to_string())?
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        });
+    }
 }
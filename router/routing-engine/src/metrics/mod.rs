@@ -0,0 +1,3 @@
+pub mod recorder;
+
+pub use recorder::{InFlightGuard, Metrics, RouteStrategy};
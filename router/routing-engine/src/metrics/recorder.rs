@@ -0,0 +1,578 @@
+use crate::cache::CacheStatistics;
+use crate::graph::PoolGraph;
+use crate::utils::RouterError;
+use prometheus::proto::MetricFamily;
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge,
+    IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Latency buckets for route calculation (seconds)
+const ROUTE_BUCKETS: &[f64] = &[
+    0.000_05, 0.000_1, 0.000_25, 0.000_5, 0.001, 0.002_5, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25,
+    0.5, 1.0,
+];
+
+/// Latency buckets for pool sync (seconds)
+const SYNC_BUCKETS: &[f64] = &[0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+/// Routing strategy used to compute a route
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteStrategy {
+    Single,
+    Multi,
+    Parallel,
+    Split,
+}
+
+impl RouteStrategy {
+    /// Label value used in metrics
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RouteStrategy::Single => "single",
+            RouteStrategy::Multi => "multi",
+            RouteStrategy::Parallel => "parallel",
+            RouteStrategy::Split => "split",
+        }
+    }
+}
+
+/// Metrics that describe the whole process rather than one chain
+const PROCESS_METRICS: &[&str] = &[
+    "http_requests_in_flight",
+    "background_task_restarts_total",
+    "compute_queue_depth",
+    "compute_jobs_running",
+    "compute_jobs_rejected_total",
+    "compute_queue_wait_seconds",
+    "api_requests_total",
+    "api_requests_rejected_total",
+    "api_key_quota_remaining",
+];
+
+/// Prometheus metrics for the routing engine
+///
+/// Each instance owns its own registry, so several routers (or tests)
+/// can live in one process without colliding on metric names.
+/// Graph and cache gauges are sampled when the registry is scraped.
+/// Instances made with [`Metrics::for_chain`] label every series with
+/// the chain; [`Metrics::encode_all`] renders several of them as one
+/// scrape.
+pub struct Metrics {
+    registry: Registry,
+
+    route_duration: HistogramVec,
+    route_total: IntCounterVec,
+    route_failed: IntCounterVec,
+    routes_not_found: IntCounter,
+    errors: IntCounterVec,
+
+    cache_hits: IntCounterVec,
+    cache_misses: IntCounterVec,
+    cache_entries: IntGaugeVec,
+
+    graph_tokens: IntGauge,
+    graph_pools: IntGauge,
+    graph_last_update: IntGauge,
+    graph_staleness: IntGauge,
+
+    sync_duration: Histogram,
+    sync_total: IntCounterVec,
+
+    in_flight: IntGaugeVec,
+    task_restarts: IntCounterVec,
+
+    compute_queue_depth: IntGauge,
+    compute_running: IntGauge,
+    compute_rejected: IntCounterVec,
+    compute_wait: Histogram,
+
+    api_requests: IntCounterVec,
+    api_rejected: IntCounterVec,
+    api_quota_remaining: IntGaugeVec,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::with_registry(Registry::new())
+    }
+
+    /// Metrics for one chain of a multi-chain engine: every series
+    /// carries a `chain_id` label
+    pub fn for_chain(chain_id: u64) -> Self {
+        let labels = HashMap::from([("chain_id".to_string(), chain_id.to_string())]);
+        Self::with_registry(Registry::new_custom(None, Some(labels)).expect("valid label"))
+    }
+
+    fn with_registry(registry: Registry) -> Self {
+
+        let route_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "route_calculation_duration_seconds",
+                "Time spent computing a route, by strategy",
+            )
+            .buckets(ROUTE_BUCKETS.to_vec()),
+            &["strategy"],
+        )
+        .expect("valid metric");
+        let route_total = IntCounterVec::new(
+            Opts::new("route_calculations_total", "Route calculations, by strategy"),
+            &["strategy"],
+        )
+        .expect("valid metric");
+        let route_failed = IntCounterVec::new(
+            Opts::new(
+                "route_calculations_failed_total",
+                "Failed route calculations, by strategy",
+            ),
+            &["strategy"],
+        )
+        .expect("valid metric");
+        let routes_not_found = IntCounter::new(
+            "routes_not_found_total",
+            "Route calculations that found no path",
+        )
+        .expect("valid metric");
+        let errors = IntCounterVec::new(
+            Opts::new("router_errors_total", "Router errors returned to clients, by kind"),
+            &["kind"],
+        )
+        .expect("valid metric");
+
+        let cache_hits = IntCounterVec::new(
+            Opts::new("route_cache_hits_total", "Route cache hits, by layer"),
+            &["layer"],
+        )
+        .expect("valid metric");
+        let cache_misses = IntCounterVec::new(
+            Opts::new("route_cache_misses_total", "Route cache misses, by layer"),
+            &["layer"],
+        )
+        .expect("valid metric");
+        let cache_entries = IntGaugeVec::new(
+            Opts::new("route_cache_entries", "Entries currently held, by layer"),
+            &["layer"],
+        )
+        .expect("valid metric");
+
+        let graph_tokens =
+            IntGauge::new("graph_token_count", "Tokens in the pool graph").expect("valid metric");
+        let graph_pools =
+            IntGauge::new("graph_pool_count", "Pools in the pool graph").expect("valid metric");
+        let graph_last_update = IntGauge::new(
+            "graph_last_update_timestamp_seconds",
+            "Unix time of the last pool graph update",
+        )
+        .expect("valid metric");
+        let graph_staleness = IntGauge::new(
+            "graph_staleness_seconds",
+            "Seconds since the last pool graph update (0 if never updated)",
+        )
+        .expect("valid metric");
+
+        let sync_duration = Histogram::with_opts(
+            HistogramOpts::new("pool_sync_duration_seconds", "Time spent syncing pool data")
+                .buckets(SYNC_BUCKETS.to_vec()),
+        )
+        .expect("valid metric");
+        let sync_total = IntCounterVec::new(
+            Opts::new("pool_syncs_total", "Pool sync attempts, by result"),
+            &["result"],
+        )
+        .expect("valid metric");
+
+        let in_flight = IntGaugeVec::new(
+            Opts::new("http_requests_in_flight", "Requests currently being served, by endpoint"),
+            &["endpoint"],
+        )
+        .expect("valid metric");
+
+        let task_restarts = IntCounterVec::new(
+            Opts::new("background_task_restarts_total", "Supervised task restarts, by task"),
+            &["task"],
+        )
+        .expect("valid metric");
+
+        let compute_queue_depth = IntGauge::new(
+            "compute_queue_depth",
+            "Routing jobs waiting for a compute worker",
+        )
+        .expect("valid metric");
+        let compute_running =
+            IntGauge::new("compute_jobs_running", "Routing jobs running on the compute pool")
+                .expect("valid metric");
+        let compute_rejected = IntCounterVec::new(
+            Opts::new(
+                "compute_jobs_rejected_total",
+                "Routing jobs shed by the compute pool, by reason",
+            ),
+            &["reason"],
+        )
+        .expect("valid metric");
+        let compute_wait = Histogram::with_opts(
+            HistogramOpts::new(
+                "compute_queue_wait_seconds",
+                "Time routing jobs waited for a compute worker",
+            )
+            .buckets(ROUTE_BUCKETS.to_vec()),
+        )
+        .expect("valid metric");
+
+        let api_requests = IntCounterVec::new(
+            Opts::new("api_requests_total", "Requests admitted, by API key name"),
+            &["key"],
+        )
+        .expect("valid metric");
+        let api_rejected = IntCounterVec::new(
+            Opts::new(
+                "api_requests_rejected_total",
+                "Requests refused by access control, by API key name and reason",
+            ),
+            &["key", "reason"],
+        )
+        .expect("valid metric");
+        let api_quota_remaining = IntGaugeVec::new(
+            Opts::new(
+                "api_key_quota_remaining",
+                "Requests left in the current quota window, by API key name",
+            ),
+            &["key"],
+        )
+        .expect("valid metric");
+
+        let collectors: Vec<Box<dyn prometheus::core::Collector>> = vec![
+            Box::new(route_duration.clone()),
+            Box::new(route_total.clone()),
+            Box::new(route_failed.clone()),
+            Box::new(routes_not_found.clone()),
+            Box::new(errors.clone()),
+            Box::new(cache_hits.clone()),
+            Box::new(cache_misses.clone()),
+            Box::new(cache_entries.clone()),
+            Box::new(graph_tokens.clone()),
+            Box::new(graph_pools.clone()),
+            Box::new(graph_last_update.clone()),
+            Box::new(graph_staleness.clone()),
+            Box::new(sync_duration.clone()),
+            Box::new(sync_total.clone()),
+            Box::new(in_flight.clone()),
+            Box::new(task_restarts.clone()),
+            Box::new(compute_queue_depth.clone()),
+            Box::new(compute_running.clone()),
+            Box::new(compute_rejected.clone()),
+            Box::new(compute_wait.clone()),
+            Box::new(api_requests.clone()),
+            Box::new(api_rejected.clone()),
+            Box::new(api_quota_remaining.clone()),
+        ];
+        for collector in collectors {
+            registry
+                .register(collector)
+                .expect("metric names are unique");
+        }
+
+        Self {
+            registry,
+            route_duration,
+            route_total,
+            route_failed,
+            routes_not_found,
+            errors,
+            cache_hits,
+            cache_misses,
+            cache_entries,
+            graph_tokens,
+            graph_pools,
+            graph_last_update,
+            graph_staleness,
+            sync_duration,
+            sync_total,
+            in_flight,
+            task_restarts,
+            compute_queue_depth,
+            compute_running,
+            compute_rejected,
+            compute_wait,
+            api_requests,
+            api_rejected,
+            api_quota_remaining,
+        }
+    }
+
+    /// Record a completed route calculation
+    pub fn observe_route(
+        &self,
+        strategy: RouteStrategy,
+        elapsed: Duration,
+        error: Option<&RouterError>,
+    ) {
+        let label = strategy.as_str();
+        self.route_duration
+            .with_label_values(&[label])
+            .observe(elapsed.as_secs_f64());
+        self.route_total.with_label_values(&[label]).inc();
+
+        if let Some(err) = error {
+            self.route_failed.with_label_values(&[label]).inc();
+            if matches!(err, RouterError::NoRouteFound { .. }) {
+                self.routes_not_found.inc();
+            }
+        }
+    }
+
+    /// Record an error returned to a client
+    pub fn record_error(&self, error: &RouterError) {
+        self.errors.with_label_values(&[error.kind()]).inc();
+    }
+
+    /// Record a pool sync attempt
+    pub fn observe_sync(&self, elapsed: Duration, success: bool) {
+        self.sync_duration.observe(elapsed.as_secs_f64());
+        let result = if success { "success" } else { "failure" };
+        self.sync_total.with_label_values(&[result]).inc();
+    }
+
+    /// Record a restart of a supervised background task
+    pub fn record_task_restart(&self, task: &str) {
+        self.task_restarts.with_label_values(&[task]).inc();
+    }
+
+    /// Track a request for as long as the returned guard is alive
+    pub fn in_flight(&self, endpoint: &str) -> InFlightGuard {
+        let gauge = self.in_flight.with_label_values(&[endpoint]);
+        gauge.inc();
+        InFlightGuard { gauge }
+    }
+
+    /// Record the compute pool's waiting and running job counts
+    pub fn observe_compute_queue(&self, queued: usize, running: usize) {
+        self.compute_queue_depth.set(queued as i64);
+        self.compute_running.set(running as i64);
+    }
+
+    /// Record a job the compute pool refused to run
+    pub fn record_compute_rejected(&self, reason: &str) {
+        self.compute_rejected.with_label_values(&[reason]).inc();
+    }
+
+    /// Record how long a job waited for a compute worker
+    pub fn observe_compute_wait(&self, waited: Duration) {
+        self.compute_wait.observe(waited.as_secs_f64());
+    }
+
+    /// Record a request admitted for an API key (`anonymous` without one)
+    pub fn record_api_request(&self, key: &str) {
+        self.api_requests.with_label_values(&[key]).inc();
+    }
+
+    /// Record a request refused by access control
+    pub fn record_api_rejected(&self, key: &str, reason: &str) {
+        self.api_rejected.with_label_values(&[key, reason]).inc();
+    }
+
+    /// Record the requests an API key has left in its quota window
+    pub fn observe_api_quota(&self, key: &str, remaining: u64) {
+        self.api_quota_remaining
+            .with_label_values(&[key])
+            .set(remaining as i64);
+    }
+
+    /// Sample graph size and freshness
+    pub fn observe_graph(&self, graph: &PoolGraph) {
+        let stats = graph.stats();
+        self.graph_tokens.set(stats.token_count as i64);
+        self.graph_pools.set(stats.pool_count as i64);
+        self.graph_last_update.set(stats.last_update as i64);
+
+        let staleness = if stats.last_update == 0 {
+            0
+        } else {
+            (chrono::Utc::now().timestamp() - stats.last_update as i64).max(0)
+        };
+        self.graph_staleness.set(staleness);
+    }
+
+    /// Sample per-layer cache counters
+    ///
+    /// The caches keep their own monotonic hit/miss counts; these are
+    /// mirrored into the Prometheus counters on each scrape.
+    pub fn observe_cache(&self, stats: &CacheStatistics) {
+        let layers = [
+            ("route", &stats.route_stats),
+            ("split", &stats.split_stats),
+            ("quote", &stats.quote_stats),
+        ];
+
+        for (layer, layer_stats) in layers {
+            advance_counter(&self.cache_hits.with_label_values(&[layer]), layer_stats.hits);
+            advance_counter(
+                &self.cache_misses.with_label_values(&[layer]),
+                layer_stats.misses,
+            );
+            self.cache_entries
+                .with_label_values(&[layer])
+                .set(layer_stats.size as i64);
+        }
+    }
+
+    /// Render the process-wide metrics of `process` and the per-chain
+    /// metrics of each of `chains` as one exposition
+    ///
+    /// Series of the same metric from different chains are grouped
+    /// under one family, as the text format requires.
+    pub fn encode_all(process: &Metrics, chains: &[&Metrics]) -> String {
+        let is_process = |family: &MetricFamily| PROCESS_METRICS.contains(&family.get_name());
+        let mut families: Vec<MetricFamily> = process
+            .registry
+            .gather()
+            .into_iter()
+            .filter(is_process)
+            .collect();
+
+        for chain in chains {
+            for mut family in chain.registry.gather() {
+                if is_process(&family) {
+                    continue;
+                }
+                match families.iter_mut().find(|f| f.get_name() == family.get_name()) {
+                    Some(existing) => existing.mut_metric().extend(family.take_metric()),
+                    None => families.push(family),
+                }
+            }
+        }
+        families.sort_by(|a, b| a.get_name().cmp(b.get_name()));
+
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&families, &mut buffer)
+            .expect("text encoding cannot fail");
+        String::from_utf8(buffer).expect("text encoding is UTF-8")
+    }
+
+    /// Render all metrics in the Prometheus text exposition format
+    pub fn encode(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("text encoding cannot fail");
+        String::from_utf8(buffer).expect("text encoding is UTF-8")
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Decrements the in-flight gauge when dropped
+pub struct InFlightGuard {
+    gauge: IntGauge,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.gauge.dec();
+    }
+}
+
+/// Move a counter forward to an externally tracked total
+fn advance_counter(counter: &IntCounter, total: u64) {
+    let current = counter.get();
+    if total > current {
+        counter.inc_by(total - current);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::EnhancedRouteCache;
+    use crate::utils::address_from_u64;
+    use alloy_primitives::U256;
+
+    #[test]
+    fn test_route_metrics() {
+        let metrics = Metrics::new();
+
+        metrics.observe_route(RouteStrategy::Single, Duration::from_micros(200), None);
+        metrics.observe_route(
+            RouteStrategy::Multi,
+            Duration::from_millis(2),
+            Some(&RouterError::NoRouteFound {
+                from: address_from_u64(1),
+                to: address_from_u64(2),
+            }),
+        );
+
+        let output = metrics.encode();
+        assert!(output.contains("route_calculations_total{strategy=\"single\"} 1"));
+        assert!(output.contains("route_calculations_failed_total{strategy=\"multi\"} 1"));
+        assert!(output.contains("routes_not_found_total 1"));
+        assert!(output.contains("route_calculation_duration_seconds_bucket"));
+    }
+
+    #[test]
+    fn test_in_flight_guard() {
+        let metrics = Metrics::new();
+
+        let guard = metrics.in_flight("quote");
+        assert!(metrics
+            .encode()
+            .contains("http_requests_in_flight{endpoint=\"quote\"} 1"));
+
+        drop(guard);
+        assert!(metrics
+            .encode()
+            .contains("http_requests_in_flight{endpoint=\"quote\"} 0"));
+    }
+
+    #[test]
+    fn test_cache_counters_follow_stats() {
+        let metrics = Metrics::new();
+        let cache = EnhancedRouteCache::new(10, 10, 60);
+
+        let token_in = address_from_u64(1);
+        let token_out = address_from_u64(2);
+        cache.get_route(token_in, token_out, U256::from(1000), 2);
+        cache.get_route(token_in, token_out, U256::from(1000), 2);
+
+        metrics.observe_cache(&cache.stats());
+        metrics.observe_cache(&cache.stats());
+
+        let output = metrics.encode();
+        assert!(output.contains("route_cache_misses_total{layer=\"route\"} 2"));
+        assert!(output.contains("route_cache_hits_total{layer=\"route\"} 0"));
+    }
+
+    #[test]
+    fn test_error_kinds() {
+        let metrics = Metrics::new();
+        metrics.record_error(&RouterError::InvalidAmount("zero".to_string()));
+
+        assert!(metrics
+            .encode()
+            .contains("router_errors_total{kind=\"invalid_amount\"} 1"));
+    }
+
+    #[test]
+    fn test_encode_all_groups_chains() {
+        let process = Metrics::new();
+        let base = Metrics::for_chain(8453);
+        let sepolia = Metrics::for_chain(84532);
+        base.record_error(&RouterError::InvalidAmount("zero".to_string()));
+        sepolia.record_error(&RouterError::InvalidAmount("zero".to_string()));
+        process.record_compute_rejected("queue_full");
+        // Chain instances' process-wide metrics are not reported
+        base.record_compute_rejected("queue_full");
+
+        let output = Metrics::encode_all(&process, &[&base, &sepolia]);
+        assert_eq!(output.matches("# TYPE router_errors_total counter").count(), 1);
+        assert!(output.contains("router_errors_total{kind=\"invalid_amount\",chain_id=\"8453\"} 1"));
+        assert!(output.contains("router_errors_total{kind=\"invalid_amount\",chain_id=\"84532\"} 1"));
+        assert!(output.contains("compute_jobs_rejected_total{reason=\"queue_full\"} 1"));
+        assert!(!output.contains("compute_jobs_rejected_total{reason=\"queue_full\",chain_id"));
+    }
+}
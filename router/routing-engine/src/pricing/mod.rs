@@ -0,0 +1,5 @@
+pub mod oracle;
+pub mod prices;
+
+pub use oracle::PriceOracle;
+pub use prices::{PricingConfig, TokenPrice, TokenPrices};
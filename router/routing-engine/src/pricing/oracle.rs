@@ -0,0 +1,80 @@
+use crate::graph::PoolGraph;
+use crate::pricing::{PricingConfig, TokenPrices};
+use parking_lot::RwLock;
+use std::sync::Arc;
+
+/// USD prices for a pool graph, recomputed when the graph changes
+pub struct PriceOracle {
+    graph: Arc<PoolGraph>,
+    config: PricingConfig,
+    prices: RwLock<Arc<TokenPrices>>,
+}
+
+impl PriceOracle {
+    pub fn new(graph: Arc<PoolGraph>, config: PricingConfig) -> Self {
+        let prices = RwLock::new(Arc::new(TokenPrices::build(&graph, &config)));
+        Self {
+            graph,
+            config,
+            prices,
+        }
+    }
+
+    /// Prices for the current graph state
+    ///
+    /// Like the router's candidate index, the prices are rebuilt on first
+    /// use after the graph changes; call this after a sync to take the
+    /// rebuild off the quote path.
+    pub fn prices(&self) -> Arc<TokenPrices> {
+        let current = self.prices.read().clone();
+        let version = self.graph.version();
+        if current.version() == version {
+            return current;
+        }
+
+        let rebuilt = Arc::new(TokenPrices::build(&self.graph, &self.config));
+        let mut slot = self.prices.write();
+        // A concurrent rebuild may have priced a newer graph already
+        if slot.version() <= rebuilt.version() {
+            *slot = rebuilt.clone();
+        }
+        rebuilt
+    }
+
+    pub fn config(&self) -> &PricingConfig {
+        &self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{PoolEdge, TokenNode};
+    use crate::utils::addresses::{usdc, weth};
+    use alloy_primitives::U256;
+
+    #[test]
+    fn test_prices_follow_graph_updates() {
+        let graph = Arc::new(PoolGraph::new());
+        let oracle = PriceOracle::new(graph.clone(), PricingConfig::default());
+        assert!(oracle.prices().get(weth()).is_none());
+
+        let pool = PoolEdge::constant_product(
+            [1; 32],
+            weth(),
+            usdc(),
+            3000,
+            [U256::from(10u128.pow(20)), U256::from(3 * 10u128.pow(11))],
+        );
+        graph.upsert_pool(
+            pool,
+            TokenNode::new(weth(), "WETH".to_string(), 18),
+            TokenNode::new(usdc(), "USDC".to_string(), 6),
+        );
+
+        let prices = oracle.prices();
+        assert_eq!(prices.version(), graph.version());
+        assert!((prices.get(weth()).unwrap().price_usd - 3000.0).abs() < 1e-6);
+        assert!(Arc::ptr_eq(&prices, &oracle.prices()));
+    }
+}
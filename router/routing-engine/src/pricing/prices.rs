@@ -0,0 +1,442 @@
+use crate::graph::{PoolEdge, PoolGraph, PoolType};
+use crate::routing::native::{self, NATIVE};
+use crate::utils::addresses::{usdc, weth};
+use alloy_primitives::{Address, U256};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+const Q96: f64 = 79_228_162_514_264_337_593_543_950_336.0;
+
+/// How token prices are derived from the pool graph
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PricingConfig {
+    /// Stablecoins priced at $1 that all other prices are derived from
+    pub anchors: Vec<Address>,
+    /// Pools holding less than this (in USD, on the already priced side)
+    /// do not contribute to a token's price
+    pub min_liquidity_usd: f64,
+    /// Wrapped native token, whose price native ETH falls back to (set
+    /// from the chain's `wrapped_native`)
+    #[serde(skip)]
+    pub wrapped_native: Address,
+}
+
+impl Default for PricingConfig {
+    fn default() -> Self {
+        Self {
+            anchors: vec![usdc()],
+            min_liquidity_usd: 10_000.0,
+            wrapped_native: weth(),
+        }
+    }
+}
+
+/// USD price of one token
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenPrice {
+    pub token: Address,
+    pub symbol: String,
+    pub decimals: u8,
+    pub price_usd: f64,
+    /// USD liquidity of the pools the price was derived from
+    pub liquidity_usd: f64,
+    /// Pools between the token and the nearest anchor (0 for anchors)
+    pub hops: u8,
+}
+
+impl TokenPrice {
+    /// USD value of a base-unit amount of the token
+    pub fn value_of(&self, amount: U256) -> f64 {
+        crate::utils::u256_ext::to_f64(amount) / 10f64.powi(self.decimals as i32) * self.price_usd
+    }
+}
+
+/// USD prices for the tokens of one graph version
+///
+/// Prices spread outwards from the anchors, strongest link first. When
+/// a token is priced, each pool to an unpriced neighbour proposes a
+/// price for that neighbour from the pool's spot price, weighted by the
+/// USD value the pool holds of the priced token. The unpriced token with
+/// the largest total weight is priced next, at the weighted average of
+/// its proposals. So every token is priced through the deepest pools
+/// available, and tokens reachable only through thin pools stay unpriced.
+#[derive(Debug, Clone, Default)]
+pub struct TokenPrices {
+    /// Graph version the prices were computed from
+    version: u64,
+    block_number: u64,
+    prices: HashMap<Address, TokenPrice>,
+}
+
+impl TokenPrices {
+    pub fn build(graph: &PoolGraph, config: &PricingConfig) -> Self {
+        let version = graph.version();
+        let tokens: HashMap<Address, (String, u8)> = graph
+            .get_all_tokens()
+            .into_iter()
+            .map(|t| (t.address, (t.symbol, t.decimals)))
+            .collect();
+
+        let mut pools_by_token: HashMap<Address, Vec<PoolEdge>> = HashMap::new();
+        for pool in graph.get_all_pools() {
+            pools_by_token.entry(pool.token0).or_default().push(pool.clone());
+            pools_by_token.entry(pool.token1).or_default().push(pool);
+        }
+
+        let mut prices: HashMap<Address, TokenPrice> = HashMap::new();
+        let mut proposals: HashMap<Address, Proposal> = HashMap::new();
+        let mut queue = BinaryHeap::new();
+
+        for &anchor in &config.anchors {
+            if let Some((symbol, decimals)) = tokens.get(&anchor) {
+                proposals.insert(
+                    anchor,
+                    Proposal {
+                        weight: f64::INFINITY,
+                        weighted_price: 1.0,
+                        hops: 0,
+                    },
+                );
+                // Liquidity is filled in from the anchor's pools below
+                let price = TokenPrice {
+                    token: anchor,
+                    symbol: symbol.clone(),
+                    decimals: *decimals,
+                    price_usd: 1.0,
+                    liquidity_usd: 0.0,
+                    hops: 0,
+                };
+                queue.push(Candidate(f64::INFINITY, anchor));
+                prices.insert(anchor, price);
+            }
+        }
+        // Anchors are queued as priced; everything else is priced on pop
+        let mut settled = HashSet::new();
+
+        while let Some(Candidate(weight, token)) = queue.pop() {
+            if settled.contains(&token) {
+                continue;
+            }
+            let Some(proposal) = proposals.get(&token) else {
+                continue;
+            };
+            // Stale entry: the token gained weight after this was queued
+            if proposal.weight != weight {
+                continue;
+            }
+
+            let price = match prices.get(&token) {
+                Some(anchor) => anchor.clone(),
+                None => {
+                    let (symbol, decimals) = &tokens[&token];
+                    let price = TokenPrice {
+                        token,
+                        symbol: symbol.clone(),
+                        decimals: *decimals,
+                        price_usd: proposal.weighted_price / proposal.weight,
+                        liquidity_usd: proposal.weight,
+                        hops: proposal.hops,
+                    };
+                    prices.insert(token, price.clone());
+                    price
+                }
+            };
+            settled.insert(token);
+
+            let mut anchor_liquidity = 0.0;
+            for pool in pools_by_token.get(&token).into_iter().flatten() {
+                let Some(other) = pool.other_token(token) else {
+                    continue;
+                };
+                if prices.contains_key(&other) {
+                    continue;
+                }
+                let Some(&(_, other_decimals)) = tokens.get(&other) else {
+                    continue;
+                };
+                let Some((liquidity_usd, other_price)) = propose(pool, &price, other_decimals)
+                else {
+                    continue;
+                };
+                anchor_liquidity += liquidity_usd;
+                if liquidity_usd < config.min_liquidity_usd {
+                    continue;
+                }
+
+                let entry = proposals.entry(other).or_insert(Proposal {
+                    weight: 0.0,
+                    weighted_price: 0.0,
+                    hops: price.hops.saturating_add(1),
+                });
+                entry.weight += liquidity_usd;
+                entry.weighted_price += liquidity_usd * other_price;
+                queue.push(Candidate(entry.weight, other));
+            }
+            if price.hops == 0 {
+                if let Some(anchor) = prices.get_mut(&token) {
+                    anchor.liquidity_usd = anchor_liquidity;
+                }
+            }
+        }
+
+        // Native ETH without priced native pools is worth its wrapped token
+        if !prices.contains_key(&NATIVE) {
+            if let Some(wrapped) = prices.get(&config.wrapped_native) {
+                let price = TokenPrice {
+                    token: NATIVE,
+                    symbol: "ETH".to_string(),
+                    ..wrapped.clone()
+                };
+                prices.insert(NATIVE, price);
+            }
+        }
+
+        Self {
+            version,
+            block_number: graph.block_number(),
+            prices,
+        }
+    }
+
+    /// Graph version the prices were computed from
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Block of the pool state the prices were computed from
+    pub fn block_number(&self) -> u64 {
+        self.block_number
+    }
+
+    /// Price of a token; native ETH may be given as either native address
+    pub fn get(&self, token: Address) -> Option<&TokenPrice> {
+        self.prices.get(&native::normalize(token))
+    }
+
+    /// USD value of a base-unit amount of `token`
+    pub fn value_of(&self, token: Address, amount: U256) -> Option<f64> {
+        self.get(token).map(|price| price.value_of(amount))
+    }
+
+    /// All prices, deepest liquidity first
+    pub fn all(&self) -> Vec<&TokenPrice> {
+        let mut prices: Vec<_> = self.prices.values().collect();
+        prices.sort_by(|a, b| {
+            b.liquidity_usd
+                .total_cmp(&a.liquidity_usd)
+                .then_with(|| a.token.cmp(&b.token))
+        });
+        prices
+    }
+
+    pub fn len(&self) -> usize {
+        self.prices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prices.is_empty()
+    }
+}
+
+/// Accumulated price proposals for a token that is not priced yet
+struct Proposal {
+    weight: f64,
+    weighted_price: f64,
+    hops: u8,
+}
+
+/// Queue entry ordered by weight
+struct Candidate(f64, Address);
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0).then_with(|| other.1.cmp(&self.1))
+    }
+}
+
+/// USD liquidity on the priced side of `pool` and the price it implies
+/// for the other token
+fn propose(pool: &PoolEdge, priced: &TokenPrice, other_decimals: u8) -> Option<(f64, f64)> {
+    let priced_is_token0 = pool.token0 == priced.token;
+    let (decimals0, decimals1) = if priced_is_token0 {
+        (priced.decimals, other_decimals)
+    } else {
+        (other_decimals, priced.decimals)
+    };
+
+    // Raw token1 per raw token0, and the raw balances backing it
+    let (raw_price, balances) = match pool.pool_type {
+        PoolType::Concentrated => {
+            let sqrt_price = crate::utils::u256_ext::to_f64(pool.sqrt_price_x96) / Q96;
+            let liquidity = pool.liquidity as f64;
+            if sqrt_price <= 0.0 {
+                return None;
+            }
+            (
+                sqrt_price * sqrt_price,
+                [liquidity / sqrt_price, liquidity * sqrt_price],
+            )
+        }
+        PoolType::ConstantProduct | PoolType::StableSwap => {
+            let reserves = pool.reserves.map(crate::utils::u256_ext::to_f64);
+            if reserves[0] <= 0.0 || reserves[1] <= 0.0 {
+                return None;
+            }
+            let raw_price = match pool.pool_type {
+                PoolType::ConstantProduct => reserves[1] / reserves[0],
+                // Balanced stableswap pools trade close to 1:1
+                _ => 10f64.powi(decimals1 as i32 - decimals0 as i32),
+            };
+            (raw_price, reserves)
+        }
+    };
+
+    // Whole token1 per whole token0
+    let price = raw_price * 10f64.powi(decimals0 as i32 - decimals1 as i32);
+    let (balance, other_price) = if priced_is_token0 {
+        (balances[0], priced.price_usd / price)
+    } else {
+        (balances[1], priced.price_usd * price)
+    };
+    let liquidity_usd = balance / 10f64.powi(priced.decimals as i32) * priced.price_usd;
+
+    (liquidity_usd.is_finite() && other_price.is_finite() && other_price > 0.0)
+        .then_some((liquidity_usd, other_price))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::TokenNode;
+    use crate::utils::address_from_u64;
+    use crate::utils::math::tick_to_sqrt_price_x96;
+
+    fn token(address: Address, symbol: &str, decimals: u8) -> TokenNode {
+        TokenNode::new(address, symbol.to_string(), decimals)
+    }
+
+    /// Concentrated pool whose token0 is worth `price` token1 (whole units)
+    fn pool_at(id: u8, token0: &TokenNode, token1: &TokenNode, price: f64, liquidity: u128) -> PoolEdge {
+        let raw = price * 10f64.powi(token1.decimals as i32 - token0.decimals as i32);
+        let tick = (raw.ln() / 1.0001f64.ln()).round() as i32;
+        PoolEdge::new(
+            [id; 32],
+            token0.address,
+            token1.address,
+            3000,
+            60,
+            liquidity,
+            tick_to_sqrt_price_x96(tick),
+            tick,
+        )
+    }
+
+    #[test]
+    fn test_prices_propagate_from_anchors() {
+        let graph = PoolGraph::new();
+        let usdc_node = token(usdc(), "USDC", 6);
+        let weth_node = token(weth(), "WETH", 18);
+        let x = token(address_from_u64(7), "X", 8);
+        let thin = token(address_from_u64(8), "THIN", 18);
+
+        // WETH at $3000 and X at 2 WETH
+        graph.upsert_pool(
+            pool_at(1, &weth_node, &usdc_node, 3000.0, 10u128.pow(17)),
+            weth_node.clone(),
+            usdc_node.clone(),
+        );
+        graph.upsert_pool(
+            pool_at(2, &x, &weth_node, 2.0, 10u128.pow(16)),
+            x.clone(),
+            weth_node.clone(),
+        );
+        graph.upsert_pool(
+            pool_at(3, &thin, &weth_node, 1.0, 10u128.pow(6)),
+            thin.clone(),
+            weth_node.clone(),
+        );
+
+        let prices = TokenPrices::build(&graph, &PricingConfig::default());
+        let eth = prices.get(weth()).unwrap().price_usd;
+        assert!((eth / 3000.0 - 1.0).abs() < 1e-3, "{}", eth);
+        let x_price = prices.get(x.address).unwrap();
+        assert!((x_price.price_usd / 6000.0 - 1.0).abs() < 1e-3);
+        assert_eq!(x_price.hops, 2);
+        assert!(prices.get(thin.address).is_none());
+
+        // Native ETH falls back to WETH
+        assert_eq!(prices.get(native::NATIVE_ALIAS).unwrap().price_usd, eth);
+        let one_eth = U256::from(10u128.pow(18));
+        assert!((prices.value_of(NATIVE, one_eth).unwrap() - eth).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_deeper_pools_outweigh_thin_ones() {
+        let graph = PoolGraph::new();
+        let usdc_node = token(usdc(), "USDC", 6);
+        let weth_node = token(weth(), "WETH", 18);
+
+        graph.upsert_pool(
+            pool_at(1, &weth_node, &usdc_node, 3000.0, 10u128.pow(18)),
+            weth_node.clone(),
+            usdc_node.clone(),
+        );
+        // Off-market pool with 1% of the liquidity
+        graph.upsert_pool(
+            pool_at(2, &weth_node, &usdc_node, 2000.0, 10u128.pow(16)),
+            weth_node.clone(),
+            usdc_node.clone(),
+        );
+
+        let prices = TokenPrices::build(&graph, &PricingConfig::default());
+        let eth = prices.get(weth()).unwrap().price_usd;
+        assert!(eth > 2980.0 && eth < 3000.0, "{}", eth);
+    }
+
+    #[test]
+    fn test_reserve_pools_are_priced() {
+        let graph = PoolGraph::new();
+        let usdc_node = token(usdc(), "USDC", 6);
+        let dai = token(address_from_u64(9), "DAI", 18);
+        let y = token(address_from_u64(10), "Y", 18);
+
+        let stable = PoolEdge::stable_swap(
+            [1; 32],
+            dai.address,
+            usdc(),
+            100,
+            [U256::from(10u128.pow(24)), U256::from(10u128.pow(12))],
+            100,
+            [18, 6],
+        );
+        let pair = PoolEdge::constant_product(
+            [2; 32],
+            y.address,
+            dai.address,
+            3000,
+            [U256::from(10u128.pow(24)), U256::from(5 * 10u128.pow(23))],
+        );
+        graph.upsert_pool(stable, dai.clone(), usdc_node);
+        graph.upsert_pool(pair, y.clone(), dai.clone());
+
+        let prices = TokenPrices::build(&graph, &PricingConfig::default());
+        assert!((prices.get(dai.address).unwrap().price_usd - 1.0).abs() < 1e-9);
+        assert!((prices.get(y.address).unwrap().price_usd - 0.5).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,63 @@
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+/// Time and cancellation limit for one route search
+///
+/// The search checks the budget before expanding each partial route and
+/// stops once it is spent, returning the routes completed so far marked
+/// `partial`. Routes are completed in order of hop count, so a spent
+/// budget costs the longer routes first.
+#[derive(Debug, Clone, Default)]
+pub struct SearchBudget {
+    deadline: Option<Instant>,
+    cancel: Option<CancellationToken>,
+}
+
+impl SearchBudget {
+    /// No deadline and no cancellation
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// Budget ending `timeout` from now
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self {
+            deadline: Some(Instant::now() + timeout),
+            cancel: None,
+        }
+    }
+
+    /// Also stop when `cancel` is cancelled (e.g. the client went away)
+    pub fn with_cancel(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Whether the search should stop now
+    pub fn is_spent(&self) -> bool {
+        self.cancel.as_ref().is_some_and(|c| c.is_cancelled())
+            || self.deadline.is_some_and(|d| Instant::now() >= d)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_spent_on_deadline_or_cancel() {
+        assert!(!SearchBudget::unlimited().is_spent());
+        assert!(!SearchBudget::with_timeout(Duration::from_secs(60)).is_spent());
+        assert!(SearchBudget::with_timeout(Duration::ZERO).is_spent());
+
+        let cancel = CancellationToken::new();
+        let budget = SearchBudget::unlimited().with_cancel(cancel.clone());
+        assert!(!budget.is_spent());
+        cancel.cancel();
+        assert!(budget.is_spent());
+    }
+}
@@ -0,0 +1,407 @@
+use crate::graph::{PoolEdge, PoolGraph};
+use crate::routing::simulate::{hop_depth, HopEstimate};
+use crate::utils::MIN_LIQUIDITY;
+use alloy_primitives::{Address, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Pools whose depth in a direction is below this can never produce a
+/// non-dust output in that direction (see `simulate_hop`)
+const MIN_DEPTH: u64 = 100;
+
+/// Which pools and tokens the route search considers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CandidateConfig {
+    /// Pools with less active liquidity than this are never routed through
+    pub min_liquidity: u128,
+    /// Pools kept per token pair and swap direction, deepest first
+    /// (0 keeps every pool)
+    pub max_pools_per_pair: usize,
+    /// Tokens a route may pass through between `token_in` and
+    /// `token_out` (empty allows any token)
+    pub connector_tokens: Vec<Address>,
+}
+
+impl CandidateConfig {
+    /// Keep every pool that can be swapped, through any token
+    pub fn unfiltered() -> Self {
+        Self {
+            min_liquidity: 0,
+            max_pools_per_pair: 0,
+            connector_tokens: Vec::new(),
+        }
+    }
+}
+
+impl Default for CandidateConfig {
+    fn default() -> Self {
+        Self {
+            min_liquidity: MIN_LIQUIDITY,
+            max_pools_per_pair: 3,
+            connector_tokens: Vec::new(),
+        }
+    }
+}
+
+/// Pools the route search may use, indexed by directed token pair
+///
+/// Built once per graph version instead of filtering pools on every
+/// expansion. For each `(from, to)` pair it holds the pools that pass
+/// the [`CandidateConfig`] filters, deepest first, where depth is the
+/// amount of `to` the pool can pay out for `from` (see
+/// [`hop_depth`]). Depth is directional: a pool drained on one side is
+/// only dropped for swaps into that side.
+///
+/// Tokens and pools are numbered densely and edges are stored in one
+/// flat array, so the search works on `u32` indices and never clones a
+/// pool while expanding routes.
+#[derive(Debug, Default)]
+pub struct CandidateGraph {
+    /// Graph version the index was built from
+    version: u64,
+    /// Address of each token index
+    tokens: Vec<Address>,
+    token_index: HashMap<Address, u32>,
+    /// Every pool with at least one usable direction
+    pools: Vec<PoolEdge>,
+    /// `f64` pricing state of each pool, for screening
+    estimates: Vec<HopEstimate>,
+    /// Edges out of token `t` are `edges[offsets[t]..offsets[t + 1]]`,
+    /// ordered by target token, then deepest first
+    offsets: Vec<u32>,
+    edges: Vec<Edge>,
+    /// Sorted token indices of the connectors present in the graph
+    connectors: Vec<u32>,
+    /// Whether connector tokens were configured at all
+    restricted: bool,
+}
+
+/// One usable swap direction of a pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Edge {
+    pub to: u32,
+    pub pool: u32,
+    pub zero_for_one: bool,
+}
+
+impl CandidateGraph {
+    /// Index the pools of `graph` that pass `config`
+    pub fn build(graph: &PoolGraph, config: &CandidateConfig) -> Self {
+        // Read the version first: an update racing with the build
+        // leaves the index marked stale rather than silently missed
+        let version = graph.version();
+
+        let mut tokens = Vec::new();
+        let mut token_index = HashMap::new();
+        let mut intern = |token: Address| {
+            *token_index.entry(token).or_insert_with(|| {
+                tokens.push(token);
+                (tokens.len() - 1) as u32
+            })
+        };
+
+        // (from, to, depth, pool, zero_for_one) for every usable direction
+        let mut directed: Vec<(u32, u32, U256, u32, bool)> = Vec::new();
+        let mut pools = Vec::new();
+        for pool in graph.get_all_pools() {
+            if pool.liquidity < config.min_liquidity {
+                continue;
+            }
+            let index = pools.len() as u32;
+            let before = directed.len();
+            for (from, to, zero_for_one) in [
+                (pool.token0, pool.token1, true),
+                (pool.token1, pool.token0, false),
+            ] {
+                let depth = hop_depth(&pool, from);
+                if depth >= U256::from(MIN_DEPTH) {
+                    directed.push((intern(from), intern(to), depth, index, zero_for_one));
+                }
+            }
+            if directed.len() > before {
+                pools.push(pool);
+            }
+        }
+
+        directed.sort_unstable_by(|a, b| {
+            (a.0, a.1)
+                .cmp(&(b.0, b.1))
+                .then(b.2.cmp(&a.2))
+                .then_with(|| {
+                    pools[a.3 as usize]
+                        .pool_id
+                        .cmp(&pools[b.3 as usize].pool_id)
+                })
+        });
+
+        let mut offsets = vec![0u32; tokens.len() + 1];
+        let mut edges = Vec::with_capacity(directed.len());
+        let mut pair = None;
+        let mut pair_count = 0;
+        for (from, to, _, pool, zero_for_one) in directed {
+            if pair != Some((from, to)) {
+                pair = Some((from, to));
+                pair_count = 0;
+            }
+            pair_count += 1;
+            if config.max_pools_per_pair > 0 && pair_count > config.max_pools_per_pair {
+                continue;
+            }
+            offsets[from as usize + 1] += 1;
+            edges.push(Edge {
+                to,
+                pool,
+                zero_for_one,
+            });
+        }
+        for t in 1..offsets.len() {
+            offsets[t] += offsets[t - 1];
+        }
+
+        let mut connectors: Vec<u32> = config
+            .connector_tokens
+            .iter()
+            .filter_map(|token| token_index.get(token).copied())
+            .collect();
+        connectors.sort_unstable();
+        connectors.dedup();
+
+        let estimates = pools.iter().map(HopEstimate::new).collect();
+
+        Self {
+            version,
+            tokens,
+            token_index,
+            pools,
+            estimates,
+            offsets,
+            edges,
+            connectors,
+            restricted: !config.connector_tokens.is_empty(),
+        }
+    }
+
+    /// Graph version the index was built from (see [`PoolGraph::version`])
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Number of directed pool edges kept
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Pools that swap `from` into `to`, deepest first
+    pub fn pools_between(&self, from: Address, to: Address) -> impl Iterator<Item = &PoolEdge> {
+        let edges = match (self.token_id(from), self.token_id(to)) {
+            (Some(from), Some(to)) => self.edges_between(from, to),
+            _ => &[],
+        };
+        edges.iter().map(|edge| self.pool(edge.pool))
+    }
+
+    /// Whether routes may pass through `token`
+    pub fn is_connector(&self, token: Address) -> bool {
+        !self.restricted
+            || self
+                .token_id(token)
+                .is_some_and(|id| self.connectors.binary_search(&id).is_ok())
+    }
+
+    pub(crate) fn token_id(&self, token: Address) -> Option<u32> {
+        self.token_index.get(&token).copied()
+    }
+
+    pub(crate) fn token(&self, id: u32) -> Address {
+        self.tokens[id as usize]
+    }
+
+    pub(crate) fn pool(&self, id: u32) -> &PoolEdge {
+        &self.pools[id as usize]
+    }
+
+    pub(crate) fn estimate(&self, id: u32) -> &HopEstimate {
+        &self.estimates[id as usize]
+    }
+
+    fn edges_from(&self, from: u32) -> &[Edge] {
+        let from = from as usize;
+        &self.edges[self.offsets[from] as usize..self.offsets[from + 1] as usize]
+    }
+
+    fn edges_between(&self, from: u32, to: u32) -> &[Edge] {
+        let edges = self.edges_from(from);
+        let start = edges.partition_point(|edge| edge.to < to);
+        let end = start + edges[start..].partition_point(|edge| edge.to == to);
+        &edges[start..end]
+    }
+
+    /// Every edge a route at `from` heading for `token_out` may take next
+    ///
+    /// With connector tokens configured this only looks up the
+    /// connectors and `token_out`, so the cost does not grow with the
+    /// number of pools at `from`.
+    pub(crate) fn next_hops(&self, from: u32, token_out: u32) -> impl Iterator<Item = Edge> + '_ {
+        let all = (!self.restricted).then(|| self.edges_from(from));
+        let targets = self
+            .restricted
+            .then(|| {
+                self.connectors
+                    .iter()
+                    .copied()
+                    .filter(move |&token| token != token_out)
+                    .chain(std::iter::once(token_out))
+            })
+            .into_iter()
+            .flatten();
+
+        all.into_iter()
+            .flatten()
+            .copied()
+            .chain(targets.flat_map(move |to| self.edges_between(from, to).iter().copied()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::TokenNode;
+    use crate::utils::address_from_u64;
+
+    fn pool(id: u8, token0: u64, token1: u64, liquidity: u128) -> PoolEdge {
+        PoolEdge::new(
+            [id; 32],
+            address_from_u64(token0),
+            address_from_u64(token1),
+            3000,
+            60,
+            liquidity,
+            U256::from(1u128 << 96),
+            0,
+        )
+    }
+
+    fn graph(pools: &[PoolEdge]) -> PoolGraph {
+        let graph = PoolGraph::new();
+        let node = |a: Address| TokenNode::new(a, format!("T{}", a), 18);
+        for pool in pools {
+            graph.upsert_pool(pool.clone(), node(pool.token0), node(pool.token1));
+        }
+        graph
+    }
+
+    fn ids<'a>(pools: impl Iterator<Item = &'a PoolEdge>) -> Vec<u8> {
+        pools.map(|p| p.pool_id[0]).collect()
+    }
+
+    #[test]
+    fn test_filters_shallow_pools() {
+        let graph = graph(&[
+            pool(1, 1, 2, 10u128.pow(21)),
+            pool(2, 1, 2, 10u128.pow(17)),
+            pool(3, 1, 2, 0),
+        ]);
+
+        let candidates = CandidateGraph::build(&graph, &CandidateConfig::default());
+        assert_eq!(
+            ids(candidates.pools_between(address_from_u64(1), address_from_u64(2))),
+            [1]
+        );
+        assert_eq!(
+            ids(candidates.pools_between(address_from_u64(2), address_from_u64(1))),
+            [1]
+        );
+
+        // Unfiltered still drops the pool that cannot be swapped at all
+        let candidates = CandidateGraph::build(&graph, &CandidateConfig::unfiltered());
+        assert_eq!(
+            ids(candidates.pools_between(address_from_u64(1), address_from_u64(2))),
+            [1, 2]
+        );
+        assert_eq!(candidates.edge_count(), 4);
+    }
+
+    #[test]
+    fn test_depth_is_directional() {
+        // Near the bottom of the price range there is almost no token1
+        // left to pay out, but plenty of token0
+        let mut drained = pool(1, 1, 2, 10u128.pow(18));
+        drained.tick = -800_000;
+        drained.sqrt_price_x96 = crate::utils::math::tick_to_sqrt_price_x96(drained.tick);
+        let graph = graph(&[drained]);
+
+        let candidates = CandidateGraph::build(&graph, &CandidateConfig::default());
+        assert!(ids(candidates.pools_between(address_from_u64(1), address_from_u64(2))).is_empty());
+        assert_eq!(
+            ids(candidates.pools_between(address_from_u64(2), address_from_u64(1))),
+            [1]
+        );
+    }
+
+    #[test]
+    fn test_keeps_deepest_pools_per_pair() {
+        let graph = graph(&[
+            pool(1, 1, 2, 10u128.pow(19)),
+            pool(2, 1, 2, 10u128.pow(22)),
+            pool(3, 1, 2, 10u128.pow(20)),
+            pool(4, 1, 2, 10u128.pow(21)),
+        ]);
+        let config = CandidateConfig {
+            max_pools_per_pair: 2,
+            ..Default::default()
+        };
+
+        let candidates = CandidateGraph::build(&graph, &config);
+        assert_eq!(
+            ids(candidates.pools_between(address_from_u64(1), address_from_u64(2))),
+            [2, 4]
+        );
+        assert_eq!(
+            ids(candidates.pools_between(address_from_u64(2), address_from_u64(1))),
+            [2, 4]
+        );
+    }
+
+    #[test]
+    fn test_next_hops_only_reach_connectors_and_target() {
+        let liquidity = 10u128.pow(21);
+        let graph = graph(&[
+            pool(1, 1, 2, liquidity),
+            pool(2, 1, 3, liquidity),
+            pool(3, 1, 4, liquidity),
+        ]);
+        let config = CandidateConfig {
+            connector_tokens: vec![address_from_u64(2), address_from_u64(9)],
+            ..Default::default()
+        };
+        let candidates = CandidateGraph::build(&graph, &config);
+        let next_hops = |candidates: &CandidateGraph, from: u64, to: u64| {
+            let from = candidates.token_id(address_from_u64(from)).unwrap();
+            let to = candidates.token_id(address_from_u64(to)).unwrap();
+            let mut next: Vec<u8> = candidates
+                .next_hops(from, to)
+                .map(|edge| candidates.pool(edge.pool).pool_id[0])
+                .collect();
+            next.sort_unstable();
+            next
+        };
+
+        assert_eq!(next_hops(&candidates, 1, 4), [1, 3]);
+        assert!(candidates.is_connector(address_from_u64(2)));
+        assert!(!candidates.is_connector(address_from_u64(3)));
+        assert!(!candidates.is_connector(address_from_u64(9)));
+
+        let open = CandidateGraph::build(&graph, &CandidateConfig::default());
+        assert_eq!(next_hops(&open, 1, 4), [1, 2, 3]);
+        assert!(open.is_connector(address_from_u64(3)));
+
+        // Connectors missing from the graph still restrict the search
+        let absent = CandidateConfig {
+            connector_tokens: vec![address_from_u64(9)],
+            ..Default::default()
+        };
+        let candidates = CandidateGraph::build(&graph, &absent);
+        assert_eq!(next_hops(&candidates, 1, 4), [3]);
+    }
+}
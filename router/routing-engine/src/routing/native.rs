@@ -0,0 +1,58 @@
+//! Native currency (ETH) as a routable pseudo-token
+//!
+//! PoolManager pools hold native ETH under the zero `Currency`, so the
+//! graph uses `Address::ZERO` for it. Every other pool trades the wrapped
+//! token instead. A swap from or to ETH can therefore start or end in
+//! either set of pools, with an implicit WETH deposit or withdrawal when
+//! the route goes through the wrapped token.
+
+use alloy_primitives::{address, Address};
+
+/// Native currency as stored in the graph (PoolManager `Currency` zero)
+pub const NATIVE: Address = Address::ZERO;
+
+/// Alternative address clients commonly use for the native currency
+pub const NATIVE_ALIAS: Address = address!("EeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE");
+
+/// Whether `token` refers to the native currency
+pub fn is_native(token: Address) -> bool {
+    token == NATIVE || token == NATIVE_ALIAS
+}
+
+/// Map the native alias onto [`NATIVE`], leaving other tokens unchanged
+pub fn normalize(token: Address) -> Address {
+    if is_native(token) {
+        NATIVE
+    } else {
+        token
+    }
+}
+
+/// Graph tokens a swap endpoint can be routed through
+///
+/// The flag marks the wrapped token, which needs a wrap (input side) or
+/// unwrap (output side) step.
+pub fn endpoints(token: Address, wrapped_native: Address) -> Vec<(Address, bool)> {
+    if is_native(token) {
+        vec![(NATIVE, false), (wrapped_native, true)]
+    } else {
+        vec![(token, false)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::addresses::{usdc, weth};
+
+    #[test]
+    fn test_native_endpoints() {
+        assert_eq!(normalize(NATIVE_ALIAS), NATIVE);
+        assert_eq!(normalize(usdc()), usdc());
+        assert_eq!(
+            endpoints(NATIVE_ALIAS, weth()),
+            vec![(NATIVE, false), (weth(), true)]
+        );
+        assert_eq!(endpoints(weth(), weth()), vec![(weth(), false)]);
+    }
+}
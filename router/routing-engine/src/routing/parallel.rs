@@ -1,21 +1,19 @@
 use crate::graph::PoolGraph;
-use crate::routing::multi_hop::find_top_routes;
-use crate::routing::single_hop::find_all_single_hop_routes;
+use crate::routing::search::{search_routes, SearchParams};
 use crate::routing::Route;
 use crate::utils::MAX_HOPS;
 use alloy_primitives::{Address, U256};
 use rayon::prelude::*;
 use std::sync::Arc;
 
-/// Find routes in parallel across different strategies
-///
-/// This evaluates multiple routing strategies concurrently:
-/// - Single-hop direct routes
-/// - 2-hop routes
-/// - 3-hop routes
-/// - 4-hop routes
+/// Routes returned per allowed hop by `find_routes_parallel`
+const ROUTES_PER_HOP: usize = 5;
+
+/// Find routes with the pool simulations spread across threads
 ///
-/// Returns all routes sorted by output amount
+/// Runs the route search with each hop layer's expansions simulated in
+/// parallel on the rayon pool. Returns up to 5 routes per allowed hop,
+/// sorted by output amount.
 pub fn find_routes_parallel(
     graph: Arc<PoolGraph>,
     token_in: Address,
@@ -25,30 +23,13 @@ pub fn find_routes_parallel(
 ) -> Vec<Route> {
     let max_hops = max_hops.min(MAX_HOPS);
 
-    // Create parallel tasks for different hop counts
-    let hop_counts: Vec<usize> = (1..=max_hops).collect();
-
-    // Execute all strategies in parallel
-    let all_routes: Vec<Vec<Route>> = hop_counts
-        .par_iter()
-        .map(|&hops| {
-            if hops == 1 {
-                // Use optimized single-hop
-                find_all_single_hop_routes(&graph, token_in, token_out, amount_in)
-            } else {
-                // Use multi-hop
-                find_top_routes(&graph, token_in, token_out, amount_in, hops, 5)
-            }
-        })
-        .collect();
-
-    // Flatten and sort all routes
-    let mut routes: Vec<Route> = all_routes.into_iter().flatten().collect();
-
-    // Sort by output (descending)
-    routes.par_sort_by(|a, b| b.total_amount_out.cmp(&a.total_amount_out));
-
-    routes
+    search_routes(
+        &graph,
+        token_in,
+        token_out,
+        amount_in,
+        SearchParams::new(max_hops, max_hops * ROUTES_PER_HOP).parallel(),
+    )
 }
 
 /// Find best route using parallel evaluation
@@ -59,9 +40,15 @@ pub fn find_best_route_parallel(
     amount_in: U256,
     max_hops: usize,
 ) -> Option<Route> {
-    find_routes_parallel(graph, token_in, token_out, amount_in, max_hops)
-        .into_iter()
-        .next()
+    search_routes(
+        &graph,
+        token_in,
+        token_out,
+        amount_in,
+        SearchParams::new(max_hops, 1).parallel(),
+    )
+    .into_iter()
+    .next()
 }
 
 /// Evaluate multiple token pairs in parallel
@@ -104,6 +91,7 @@ pub fn simulate_amounts_parallel(
 }
 
 #[cfg(test)]
+#[allow(clippy::redundant_closure)]
 mod tests {
     use super::*;
     use crate::graph::{PoolEdge, TokenNode};
@@ -1,5 +1,9 @@
+use crate::gas::{calldata_size, fee_transfer_calldata, FeeEstimate, GasPrices};
+use crate::pricing::TokenPrices;
+use crate::routing::native::NATIVE;
 use crate::routing::SplitRoute;
 use crate::utils::math::apply_slippage;
+use alloy_primitives::{Address, Bytes, U256};
 use serde::{Deserialize, Serialize};
 
 /// Quote response
@@ -10,9 +14,85 @@ pub struct Quote {
     pub amount_out_min: String,
     pub price_impact: f64,
     pub gas_estimate: u64,
-    pub gas_estimate_usd: f64,
+    /// Total transaction fee (L2 execution plus L1 data fee) in USD,
+    /// `None` while ETH has no price
+    pub gas_estimate_usd: Option<f64>,
+    /// Transaction fee breakdown
+    #[serde(default)]
+    pub fee: FeeEstimate,
+    /// USD value of `amount_in` (`None` if the token has no price)
+    #[serde(default)]
+    pub amount_in_usd: Option<f64>,
+    /// USD value of `amount_out` (`None` if the token has no price)
+    #[serde(default)]
+    pub amount_out_usd: Option<f64>,
     pub route_string: String,
     pub route: SplitRoute,
+    /// The search ran out of its time budget; this is the best route
+    /// found until then
+    #[serde(default)]
+    pub partial: bool,
+    /// Fee charged for the integrator that requested the quote
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub integrator_fee: Option<IntegratorFee>,
+    /// Block of the graph state the route was computed from (reported
+    /// on the issued quote, not in this object)
+    #[serde(skip)]
+    pub block_number: u64,
+    /// Graph version the route was computed from
+    #[serde(skip)]
+    pub graph_version: u64,
+}
+
+/// Side of the swap an integrator fee is taken from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeSide {
+    /// Deducted from the output before it reaches the user
+    #[default]
+    Output,
+    /// Deducted from the input before it is swapped
+    Input,
+}
+
+/// An integrator's fee on a swap: `bps` of one side, paid to `recipient`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeTerms {
+    pub bps: u16,
+    pub recipient: Address,
+    #[serde(default)]
+    pub side: FeeSide,
+}
+
+impl FeeTerms {
+    /// Fee on `amount`, rounded down
+    pub fn fee_on(&self, amount: U256) -> U256 {
+        amount * U256::from(self.bps) / U256::from(10_000)
+    }
+
+    /// Input left to swap once an input-side fee is taken from
+    /// `amount_in`
+    pub fn routed_input(&self, amount_in: U256) -> U256 {
+        match self.side {
+            FeeSide::Input => amount_in - self.fee_on(amount_in),
+            FeeSide::Output => amount_in,
+        }
+    }
+}
+
+/// Integrator fee charged on a quote
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntegratorFee {
+    #[serde(flatten)]
+    pub terms: FeeTerms,
+    /// Token the fee is paid in
+    pub token: Address,
+    /// Fee amount (wei, string)
+    pub amount: String,
+    /// Encoded call paying the fee to `recipient`
+    /// ([`fee_transfer_calldata`])
+    #[serde(default)]
+    pub calldata: Bytes,
 }
 
 impl Quote {
@@ -26,16 +106,7 @@ impl Quote {
             String::new()
         };
 
-        // Base L2 gas price estimate.
-        // In production this would be fetched from RPC via eth_gasPrice.
-        // Base mainnet typically runs at 0.001-0.1 gwei; we use a
-        // conservative 0.01 gwei default.  With ETH ~$3000:
-        //   gas_cost_eth = gas_used * gas_price_gwei / 1e9
-        //   gas_cost_usd = gas_cost_eth * eth_price_usd
-        let gas_price_gwei = 0.01_f64;
-        let eth_price_usd = 3000.0_f64;
-        let gas_cost_eth = route.total_gas_estimate as f64 * gas_price_gwei / 1_000_000_000.0;
-        let gas_estimate_usd = gas_cost_eth * eth_price_usd;
+        let fee = GasPrices::default().estimate(route.total_gas_estimate, calldata_size(&route));
 
         Self {
             amount_in: route.total_amount_in.to_string(),
@@ -43,9 +114,111 @@ impl Quote {
             amount_out_min: amount_out_min.to_string(),
             price_impact: route.combined_price_impact,
             gas_estimate: route.total_gas_estimate,
-            gas_estimate_usd,
+            gas_estimate_usd: None,
+            fee,
+            amount_in_usd: None,
+            amount_out_usd: None,
             route_string,
+            partial: route.partial,
             route,
+            integrator_fee: None,
+            block_number: 0,
+            graph_version: 0,
         }
     }
+
+    /// Record the graph state the route was computed from
+    pub fn computed_at(mut self, block_number: u64, graph_version: u64) -> Self {
+        self.block_number = block_number;
+        self.graph_version = graph_version;
+        self
+    }
+
+    /// Charge an integrator fee
+    ///
+    /// The quote must be for the input left after the fee
+    /// ([`FeeTerms::routed_input`] of `amount_in`). An output-side fee
+    /// lowers `amount_out` and `amount_out_min`; an input-side fee is
+    /// added back to `amount_in`. The fee transfer joins the route's
+    /// calls and is encoded in the fee; re-price the route's gas
+    /// afterwards.
+    pub fn with_integrator_fee(
+        mut self,
+        terms: FeeTerms,
+        amount_in: U256,
+        token_in: Address,
+        token_out: Address,
+    ) -> Self {
+        let (token, amount) = match terms.side {
+            FeeSide::Input => {
+                self.amount_in = amount_in.to_string();
+                (token_in, terms.fee_on(amount_in))
+            }
+            FeeSide::Output => {
+                let amount = terms.fee_on(self.route.total_amount_out);
+                let remaining = |amount: U256| amount - terms.fee_on(amount);
+                self.amount_out = remaining(self.route.total_amount_out).to_string();
+                // Slippage applies to what the user receives
+                let min_out: U256 = self.amount_out_min.parse().unwrap_or_default();
+                self.amount_out_min = remaining(min_out).to_string();
+                (token_out, amount)
+            }
+        };
+
+        self.route.fee_transfer = true;
+        self.integrator_fee = Some(IntegratorFee {
+            terms,
+            token,
+            amount: amount.to_string(),
+            calldata: fee_transfer_calldata(token, terms.recipient, amount),
+        });
+        self
+    }
+
+    /// Input the user pays, fee included
+    pub fn gross_amount_in(&self) -> U256 {
+        match &self.integrator_fee {
+            Some(fee) if fee.terms.side == FeeSide::Input => {
+                self.route.total_amount_in + fee.amount.parse::<U256>().unwrap_or_default()
+            }
+            _ => self.route.total_amount_in,
+        }
+    }
+
+    /// Output the user receives, fee deducted
+    pub fn net_amount_out(&self) -> U256 {
+        match &self.integrator_fee {
+            Some(fee) if fee.terms.side == FeeSide::Output => self
+                .route
+                .total_amount_out
+                .saturating_sub(fee.amount.parse().unwrap_or_default()),
+            _ => self.route.total_amount_out,
+        }
+    }
+
+    /// Output the user would receive if the route returned `amount_out`,
+    /// with this quote's output-side fee deducted
+    pub fn net_output(&self, amount_out: U256) -> U256 {
+        match &self.integrator_fee {
+            Some(fee) if fee.terms.side == FeeSide::Output => {
+                amount_out - fee.terms.fee_on(amount_out)
+            }
+            _ => amount_out,
+        }
+    }
+
+    /// Recompute the fee with current gas prices
+    pub fn with_gas_prices(mut self, gas: &GasPrices) -> Self {
+        self.fee = gas.estimate(self.route.total_gas_estimate, calldata_size(&self.route));
+        self
+    }
+
+    /// Fill in the USD fields from token prices
+    pub fn with_prices(mut self, prices: &TokenPrices, token_in: Address, token_out: Address) -> Self {
+        self.fee.total_fee_usd = prices.value_of(NATIVE, self.fee.total_fee_wei);
+        self.gas_estimate_usd = self.fee.total_fee_usd;
+        self.amount_in_usd = prices.value_of(token_in, self.gross_amount_in());
+        self.amount_out_usd = prices.value_of(token_out, self.net_amount_out());
+        self
+    }
 }
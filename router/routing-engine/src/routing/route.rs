@@ -1,4 +1,5 @@
-use crate::graph::PoolEdge;
+use crate::graph::{PoolEdge, PoolType};
+use crate::routing::native::NATIVE;
 use alloy_primitives::{Address, U256};
 use serde::{Deserialize, Serialize};
 
@@ -10,6 +11,9 @@ pub struct RouteHop {
     pub token_out: Address,
     pub amount_in: U256,
     pub amount_out: U256,
+    /// Pricing model of the pool (same as `pool.pool_type`)
+    #[serde(default)]
+    pub pool_type: PoolType,
 }
 
 impl RouteHop {
@@ -21,6 +25,7 @@ impl RouteHop {
         amount_out: U256,
     ) -> Self {
         Self {
+            pool_type: pool.pool_type,
             pool,
             token_in,
             token_out,
@@ -37,7 +42,19 @@ pub struct Route {
     pub total_amount_in: U256,
     pub total_amount_out: U256,
     pub price_impact: f64,
+    /// Gas of the hops from the route search; routes from the
+    /// [`Router`](crate::routing::Router) are priced as a whole
+    /// transaction with its [`GasModel`](crate::gas::GasModel)
     pub gas_estimate: u64,
+    /// Found by a search that ran out of budget, so better routes may exist
+    #[serde(default)]
+    pub partial: bool,
+    /// Native ETH is wrapped before the first hop
+    #[serde(default)]
+    pub wrap: bool,
+    /// The output is unwrapped to native ETH after the last hop
+    #[serde(default)]
+    pub unwrap: bool,
 }
 
 impl Route {
@@ -54,9 +71,19 @@ impl Route {
             total_amount_out,
             price_impact,
             gas_estimate,
+            partial: false,
+            wrap: false,
+            unwrap: false,
         }
     }
 
+    /// Add implicit wrap/unwrap steps
+    pub fn with_native(mut self, wrap: bool, unwrap: bool) -> Self {
+        self.wrap |= wrap;
+        self.unwrap |= unwrap;
+        self
+    }
+
     pub fn hop_count(&self) -> usize {
         self.hops.len()
     }
@@ -66,10 +93,17 @@ impl Route {
             return String::new();
         }
 
-        let mut parts = vec![format!("{:?}", self.hops[0].token_in)];
+        let mut parts = Vec::with_capacity(self.hops.len() + 3);
+        if self.wrap {
+            parts.push(format!("{:?}", NATIVE));
+        }
+        parts.push(format!("{:?}", self.hops[0].token_in));
         for hop in &self.hops {
             parts.push(format!("{:?}", hop.token_out));
         }
+        if self.unwrap {
+            parts.push(format!("{:?}", NATIVE));
+        }
 
         parts.join(" → ")
     }
@@ -83,6 +117,18 @@ pub struct SplitRoute {
     pub total_amount_out: U256,
     pub combined_price_impact: f64,
     pub total_gas_estimate: u64,
+    /// Built from routes of a search that ran out of budget
+    #[serde(default)]
+    pub partial: bool,
+    /// Native ETH is wrapped once before the routes
+    #[serde(default)]
+    pub wrap: bool,
+    /// The combined output is unwrapped to native ETH
+    #[serde(default)]
+    pub unwrap: bool,
+    /// An integrator fee is paid out of the swap in its own call
+    #[serde(default)]
+    pub fee_transfer: bool,
 }
 
 impl SplitRoute {
@@ -99,7 +145,25 @@ impl SplitRoute {
             total_amount_out,
             combined_price_impact,
             total_gas_estimate,
+            partial: false,
+            wrap: false,
+            unwrap: false,
+            fee_transfer: false,
+        }
+    }
+
+    /// Add implicit wrap/unwrap steps around all routes
+    ///
+    /// Each step runs once for the whole swap (see
+    /// [`GasModel::transaction_gas`](crate::gas::GasModel::transaction_gas)).
+    pub fn with_native(mut self, wrap: bool, unwrap: bool) -> Self {
+        self.wrap |= wrap;
+        self.unwrap |= unwrap;
+        for (route, _) in &mut self.routes {
+            route.wrap = self.wrap;
+            route.unwrap = self.unwrap;
         }
+        self
     }
 
     pub fn single(route: Route) -> Self {
@@ -107,6 +171,8 @@ impl SplitRoute {
         let total_amount_out = route.total_amount_out;
         let price_impact = route.price_impact;
         let gas_estimate = route.gas_estimate;
+        let partial = route.partial;
+        let (wrap, unwrap) = (route.wrap, route.unwrap);
 
         Self {
             routes: vec![(route, 100)],
@@ -114,6 +180,10 @@ impl SplitRoute {
             total_amount_out,
             combined_price_impact: price_impact,
             total_gas_estimate: gas_estimate,
+            partial,
+            wrap,
+            unwrap,
+            fee_transfer: false,
         }
     }
 
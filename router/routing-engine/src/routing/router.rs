@@ -1,23 +1,45 @@
 use crate::cache::EnhancedRouteCache;
 use crate::graph::PoolGraph;
-use crate::routing::multi_hop::{find_best_multi_hop_route, find_top_routes};
-use crate::routing::parallel::find_best_route_parallel;
-use crate::routing::single_hop::find_best_single_hop_route;
+use crate::gas::{GasModel, GasOracle, GasPrices};
+use crate::metrics::{Metrics, RouteStrategy};
+use crate::pricing::{PriceOracle, TokenPrices};
+use crate::routing::budget::SearchBudget;
+use crate::routing::candidates::{CandidateConfig, CandidateGraph};
+use crate::routing::native;
+use crate::routing::search::{search_candidates_within, Screening, SearchParams};
 use crate::routing::split::optimize_split_route;
-use crate::routing::{Quote, Route, SplitRoute};
-use crate::utils::{Result, MAX_HOPS, MAX_SPLITS};
+use crate::routing::{FeeTerms, Quote, Route, SplitRoute};
+use crate::utils::addresses::weth;
+use crate::utils::{Result, RouterError, MAX_HOPS, MAX_SPLITS};
 use alloy_primitives::{Address, U256};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 /// Router configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct RouterConfig {
     pub enable_cache: bool,
     pub enable_parallel: bool,
     pub cache_ttl_seconds: u64,
     pub max_routes_cached: usize,
     pub max_quotes_cached: usize,
+    /// Pools and connector tokens the route search may use
+    pub candidates: CandidateConfig,
+    /// Approximate pre-screen for the route search, `None` for exact only
+    pub screening: Option<Screening>,
+    /// Time budget per route search in milliseconds, `0` for no limit
+    pub search_timeout_ms: u64,
+    /// Upper bound for per-request timeouts in milliseconds
+    pub max_search_timeout_ms: u64,
+    /// Wrapped native token, used for native ETH swaps alongside the
+    /// native-currency pools
+    pub wrapped_native: Address,
+    /// Gas of the swap transactions routes are priced at
+    pub gas_model: GasModel,
 }
 
 impl Default for RouterConfig {
@@ -28,6 +50,12 @@ impl Default for RouterConfig {
             cache_ttl_seconds: 15,
             max_routes_cached: 1000,
             max_quotes_cached: 2000,
+            candidates: CandidateConfig::default(),
+            screening: Some(Screening::default()),
+            search_timeout_ms: 200,
+            max_search_timeout_ms: 2000,
+            wrapped_native: weth(),
+            gas_model: GasModel::default(),
         }
     }
 }
@@ -37,6 +65,15 @@ pub struct Router {
     graph: Arc<PoolGraph>,
     cache: Arc<EnhancedRouteCache>,
     config: RouterConfig,
+    metrics: Arc<Metrics>,
+    /// Graph version the cached routes and quotes were computed at
+    cache_version: AtomicU64,
+    /// Candidate index for the graph, rebuilt when the graph changes
+    candidates: RwLock<Arc<CandidateGraph>>,
+    /// USD prices for the quotes' USD fields
+    prices: Option<Arc<PriceOracle>>,
+    /// Gas prices for the quotes' fees
+    gas: Option<Arc<GasOracle>>,
 }
 
 impl Router {
@@ -45,34 +82,79 @@ impl Router {
     }
 
     pub fn with_config(graph: Arc<PoolGraph>, config: RouterConfig) -> Self {
+        Self::with_metrics(graph, config, Arc::new(Metrics::new()))
+    }
+
+    pub fn with_metrics(
+        graph: Arc<PoolGraph>,
+        config: RouterConfig,
+        metrics: Arc<Metrics>,
+    ) -> Self {
         let cache = Arc::new(EnhancedRouteCache::new(
             config.max_routes_cached,
             config.max_quotes_cached,
             config.cache_ttl_seconds,
         ));
 
+        let candidates = RwLock::new(Arc::new(CandidateGraph::build(&graph, &config.candidates)));
+
         Self {
+            cache_version: AtomicU64::new(graph.version()),
             graph,
             cache,
             config,
+            metrics,
+            candidates,
+            prices: None,
+            gas: None,
         }
     }
 
+    /// Fill in USD values of quotes from `oracle`
+    pub fn with_price_oracle(mut self, oracle: Arc<PriceOracle>) -> Self {
+        self.prices = Some(oracle);
+        self
+    }
+
+    /// Price quote fees with the gas prices of `oracle` instead of the
+    /// defaults
+    pub fn with_gas_oracle(mut self, oracle: Arc<GasOracle>) -> Self {
+        self.gas = Some(oracle);
+        self
+    }
+
     /// Find the best route for a swap with caching
     ///
     /// Automatically selects the best strategy:
     /// - Single-hop for max_hops = 1
     /// - Parallel evaluation for max_hops > 2 (if enabled)
     /// - Sequential evaluation otherwise
-    pub async fn find_route(
+    pub fn find_route(
+        &self,
+        token_in: Address,
+        token_out: Address,
+        amount_in: U256,
+        max_hops: Option<usize>,
+    ) -> Result<Route> {
+        self.find_route_within(token_in, token_out, amount_in, max_hops, &self.budget(None))
+    }
+
+    /// [`Router::find_route`] within an explicit search budget
+    ///
+    /// If the budget runs out the best route found so far is returned
+    /// with `partial` set, and not cached.
+    pub fn find_route_within(
         &self,
         token_in: Address,
         token_out: Address,
         amount_in: U256,
         max_hops: Option<usize>,
+        budget: &SearchBudget,
     ) -> Result<Route> {
+        let (token_in, token_out) = (native::normalize(token_in), native::normalize(token_out));
         let max_hops = max_hops.unwrap_or(MAX_HOPS);
         let start = Instant::now();
+        let version = self.cache_version();
 
         // Check cache first
         if self.config.enable_cache {
@@ -82,41 +164,34 @@ impl Router {
             }
         }
 
-        // Compute route
-        let route = if max_hops == 1 {
-            // Single-hop optimization
-            find_best_single_hop_route(&self.graph, token_in, token_out, amount_in)?
+        // Compute route; the search covers direct pools as well as
+        // longer routes
+        let (strategy, params) = if max_hops == 1 {
+            (RouteStrategy::Single, SearchParams::new(1, 1))
         } else if self.config.enable_parallel && max_hops > 2 {
-            // Parallel evaluation for multi-hop
-            find_best_route_parallel(self.graph.clone(), token_in, token_out, amount_in, max_hops)
-                .ok_or_else(|| crate::utils::RouterError::NoRouteFound {
-                    from: token_in,
-                    to: token_out,
-                })?
+            (
+                RouteStrategy::Parallel,
+                SearchParams::new(max_hops, 1).parallel(),
+            )
         } else {
-            // Try single-hop first
-            if let Ok(single_hop) =
-                find_best_single_hop_route(&self.graph, token_in, token_out, amount_in)
-            {
-                // Check if multi-hop can beat it
-                if let Ok(multi_hop) =
-                    find_best_multi_hop_route(&self.graph, token_in, token_out, amount_in, max_hops)
-                {
-                    if multi_hop.total_amount_out > single_hop.total_amount_out {
-                        multi_hop
-                    } else {
-                        single_hop
-                    }
-                } else {
-                    single_hop
-                }
-            } else {
-                find_best_multi_hop_route(&self.graph, token_in, token_out, amount_in, max_hops)?
-            }
+            (RouteStrategy::Multi, SearchParams::new(max_hops, 1))
         };
 
+        let result = self.via_endpoints(
+            token_in,
+            token_out,
+            |from, to, wrap, unwrap| {
+                self.compute_route(params, from, to, amount_in, budget)
+                    .map(|route| self.config.gas_model.price_route(route.with_native(wrap, unwrap)))
+            },
+            |route| route.total_amount_out,
+        );
+        self.metrics
+            .observe_route(strategy, start.elapsed(), result.as_ref().err());
+        let route = result?;
+
         // Cache result
-        if self.config.enable_cache {
+        if self.config.enable_cache && !route.partial && self.graph.version() == version {
             self.cache
                 .insert_route(token_in, token_out, amount_in, max_hops, route.clone());
         }
@@ -125,24 +200,62 @@ impl Router {
         Ok(route)
     }
 
+    /// Find the best route with `params` without touching the cache
+    fn compute_route(
+        &self,
+        params: SearchParams,
+        token_in: Address,
+        token_out: Address,
+        amount_in: U256,
+        budget: &SearchBudget,
+    ) -> Result<Route> {
+        search_candidates_within(
+            &self.candidates(),
+            token_in,
+            token_out,
+            amount_in,
+            self.search_params(params),
+            budget,
+        )
+        .into_iter()
+        .next()
+        .ok_or_else(|| no_route(token_in, token_out, budget))
+    }
+
     /// Find the best route with split support
-    pub async fn find_split_route(
+    pub fn find_split_route(
+        &self,
+        token_in: Address,
+        token_out: Address,
+        amount_in: U256,
+        max_hops: Option<usize>,
+        max_splits: Option<usize>,
+    ) -> Result<SplitRoute> {
+        let budget = self.budget(None);
+        self.find_split_route_within(token_in, token_out, amount_in, max_hops, max_splits, &budget)
+    }
+
+    /// [`Router::find_split_route`] within an explicit search budget
+    pub fn find_split_route_within(
         &self,
         token_in: Address,
         token_out: Address,
         amount_in: U256,
         max_hops: Option<usize>,
         max_splits: Option<usize>,
+        budget: &SearchBudget,
     ) -> Result<SplitRoute> {
+        let (token_in, token_out) = (native::normalize(token_in), native::normalize(token_out));
         let max_hops = max_hops.unwrap_or(MAX_HOPS);
         let max_splits = max_splits.unwrap_or(MAX_SPLITS);
         let start = Instant::now();
+        let version = self.cache_version();
 
         // Check cache
         if self.config.enable_cache {
             if let Some(cached) =
                 self.cache
-                    .get_split_route(token_in, token_out, amount_in, max_hops)
+                    .get_split_route(token_in, token_out, amount_in, max_hops, max_splits)
             {
                 tracing::debug!("Cache hit for split route in {:?}", start.elapsed());
                 return Ok(cached);
@@ -153,38 +266,29 @@ impl Router {
         let min_split_amount = U256::from(100_000_000_000_000_000u128);
         if amount_in < min_split_amount {
             let route = self
-                .find_route(token_in, token_out, amount_in, Some(max_hops))
-                .await?;
+                .find_route_within(token_in, token_out, amount_in, Some(max_hops), budget)?;
             return Ok(SplitRoute::single(route));
         }
 
-        // Find top routes
-        let top_routes = find_top_routes(
-            &self.graph,
+        let split_route = self.via_endpoints(
             token_in,
             token_out,
-            amount_in,
-            max_hops,
-            max_splits * 2,
-        );
-
-        if top_routes.is_empty() {
-            let route = self
-                .find_route(token_in, token_out, amount_in, Some(max_hops))
-                .await?;
-            return Ok(SplitRoute::single(route));
-        }
-
-        // Optimize split
-        let split_route = optimize_split_route(top_routes, amount_in)?;
+            |from, to, wrap, unwrap| {
+                self.compute_split(from, to, amount_in, max_hops, max_splits, budget)
+                    .map(|split| self.config.gas_model.price_split(split.with_native(wrap, unwrap)))
+            },
+            |split| split.total_amount_out,
+        )?;
+        let partial = split_route.partial;
 
         // Cache result
-        if self.config.enable_cache {
+        if self.config.enable_cache && !partial && self.graph.version() == version {
             self.cache.insert_split_route(
                 token_in,
                 token_out,
                 amount_in,
                 max_hops,
+                max_splits,
                 split_route.clone(),
             );
         }
@@ -193,8 +297,101 @@ impl Router {
         Ok(split_route)
     }
 
+    /// Split a swap between two graph tokens across the top routes
+    fn compute_split(
+        &self,
+        token_in: Address,
+        token_out: Address,
+        amount_in: U256,
+        max_hops: usize,
+        max_splits: usize,
+        budget: &SearchBudget,
+    ) -> Result<SplitRoute> {
+        // Find top routes
+        let split_start = Instant::now();
+        let top_routes = search_candidates_within(
+            &self.candidates(),
+            token_in,
+            token_out,
+            amount_in,
+            self.search_params(SearchParams::new(max_hops, max_splits * 2)),
+            budget,
+        );
+        let partial = top_routes.iter().any(|route| route.partial);
+
+        if top_routes.is_empty() {
+            let route = self.compute_route(
+                SearchParams::new(max_hops, 1),
+                token_in,
+                token_out,
+                amount_in,
+                budget,
+            )?;
+            return Ok(SplitRoute::single(route));
+        }
+
+        // Optimize split
+        let result = optimize_split_route(top_routes, amount_in);
+        self.metrics.observe_route(
+            RouteStrategy::Split,
+            split_start.elapsed(),
+            result.as_ref().err(),
+        );
+        let mut split_route = result?;
+        split_route.partial = partial;
+        Ok(split_route)
+    }
+
+    /// Run `search` between every pair of graph tokens the swap endpoints
+    /// map onto and keep the result with the largest output
+    ///
+    /// Only native ETH maps onto more than one token: the native-currency
+    /// pools, and the wrapped token with a wrap or unwrap step. `search`
+    /// gets the graph tokens and whether each step is needed.
+    fn via_endpoints<T>(
+        &self,
+        token_in: Address,
+        token_out: Address,
+        mut search: impl FnMut(Address, Address, bool, bool) -> Result<T>,
+        amount_out: impl Fn(&T) -> U256,
+    ) -> Result<T> {
+        let inputs = native::endpoints(token_in, self.config.wrapped_native);
+        let outputs = native::endpoints(token_out, self.config.wrapped_native);
+        if token_in == token_out || (inputs.len() == 1 && outputs.len() == 1) {
+            return search(token_in, token_out, false, false);
+        }
+
+        let mut best: Option<T> = None;
+        let mut error = None;
+        for &(from, wrap) in &inputs {
+            for &(to, unwrap) in &outputs {
+                // Plain wrapping and unwrapping is not a swap
+                if from == to {
+                    continue;
+                }
+                match search(from, to, wrap, unwrap) {
+                    Ok(found) => {
+                        if best.as_ref().is_none_or(|b| amount_out(&found) > amount_out(b)) {
+                            best = Some(found);
+                        }
+                    }
+                    Err(e) => {
+                        error.get_or_insert(e);
+                    }
+                }
+            }
+        }
+
+        best.ok_or_else(|| {
+            error.unwrap_or(RouterError::NoRouteFound {
+                from: token_in,
+                to: token_out,
+            })
+        })
+    }
+
     /// Get a quote for a swap
-    pub async fn get_quote(
+    pub fn get_quote(
         &self,
         token_in: Address,
         token_out: Address,
@@ -202,8 +399,25 @@ impl Router {
         slippage: f64,
         max_hops: Option<usize>,
     ) -> Result<Quote> {
+        let budget = self.budget(None);
+        self.get_quote_within(token_in, token_out, amount_in, slippage, max_hops, &budget)
+    }
+
+    /// [`Router::get_quote`] within an explicit search budget
+    pub fn get_quote_within(
+        &self,
+        token_in: Address,
+        token_out: Address,
+        amount_in: U256,
+        slippage: f64,
+        max_hops: Option<usize>,
+        budget: &SearchBudget,
+    ) -> Result<Quote> {
+        let (token_in, token_out) = (native::normalize(token_in), native::normalize(token_out));
         let max_hops = max_hops.unwrap_or(MAX_HOPS);
         let start = Instant::now();
+        let block_number = self.graph.block_number();
+        let version = self.cache_version();
 
         // Check cache
         if self.config.enable_cache {
@@ -217,12 +431,16 @@ impl Router {
         }
 
         let route = self
-            .find_route(token_in, token_out, amount_in, Some(max_hops))
-            .await?;
-        let quote = Quote::from_route(SplitRoute::single(route), slippage);
+            .find_route_within(token_in, token_out, amount_in, Some(max_hops), budget)?;
+        let quote = self.price_quote(
+            Quote::from_route(SplitRoute::single(route), slippage)
+                .computed_at(block_number, version),
+            token_in,
+            token_out,
+        );
 
         // Cache result
-        if self.config.enable_cache {
+        if self.config.enable_cache && !quote.partial && self.graph.version() == version {
             self.cache
                 .insert_quote(token_in, token_out, amount_in, slippage, max_hops, quote.clone());
         }
@@ -232,7 +450,7 @@ impl Router {
     }
 
     /// Get a quote with split routing
-    pub async fn get_split_quote(
+    pub fn get_split_quote(
         &self,
         token_in: Address,
         token_out: Address,
@@ -241,10 +459,95 @@ impl Router {
         max_hops: Option<usize>,
         max_splits: Option<usize>,
     ) -> Result<Quote> {
+        let budget = self.budget(None);
+        self.get_split_quote_within(
+            token_in, token_out, amount_in, slippage, max_hops, max_splits, &budget,
+        )
+    }
+
+    /// [`Router::get_split_quote`] within an explicit search budget
+    #[allow(clippy::too_many_arguments)]
+    pub fn get_split_quote_within(
+        &self,
+        token_in: Address,
+        token_out: Address,
+        amount_in: U256,
+        slippage: f64,
+        max_hops: Option<usize>,
+        max_splits: Option<usize>,
+        budget: &SearchBudget,
+    ) -> Result<Quote> {
+        let (block_number, version) = (self.graph.block_number(), self.graph.version());
         let split_route = self
-            .find_split_route(token_in, token_out, amount_in, max_hops, max_splits)
-            .await?;
-        Ok(Quote::from_route(split_route, slippage))
+            .find_split_route_within(token_in, token_out, amount_in, max_hops, max_splits, budget)?;
+        let quote = Quote::from_route(split_route, slippage).computed_at(block_number, version);
+        Ok(self.price_quote(quote, token_in, token_out))
+    }
+
+    /// Charge an integrator fee on a quote of this router and re-price
+    /// it with the fee transfer (see [`Quote::with_integrator_fee`])
+    pub fn with_integrator_fee(
+        &self,
+        quote: Quote,
+        terms: FeeTerms,
+        amount_in: U256,
+        token_in: Address,
+        token_out: Address,
+    ) -> Quote {
+        let (token_in, token_out) = (native::normalize(token_in), native::normalize(token_out));
+        let mut quote = quote.with_integrator_fee(terms, amount_in, token_in, token_out);
+        quote.route = self.config.gas_model.price_split(quote.route);
+        quote.gas_estimate = quote.route.total_gas_estimate;
+        self.price_quote(quote, token_in, token_out)
+    }
+
+    fn price_quote(&self, quote: Quote, token_in: Address, token_out: Address) -> Quote {
+        let quote = quote.with_gas_prices(&self.gas_prices());
+        match self.prices() {
+            Some(prices) => quote.with_prices(&prices, token_in, token_out),
+            None => quote,
+        }
+    }
+
+    /// Search budget for a request, starting now
+    ///
+    /// `timeout_ms` overrides the configured timeout, capped at
+    /// `max_search_timeout_ms`.
+    pub fn budget(&self, timeout_ms: Option<u64>) -> SearchBudget {
+        let timeout_ms = match timeout_ms {
+            Some(ms) => ms.min(self.config.max_search_timeout_ms).max(1),
+            None => self.config.search_timeout_ms,
+        };
+        if timeout_ms == 0 {
+            SearchBudget::unlimited()
+        } else {
+            SearchBudget::with_timeout(Duration::from_millis(timeout_ms))
+        }
+    }
+
+    /// Whether swaps can start or end at `token`: it has pools, or it
+    /// is native ETH and the native-currency or wrapped token has pools
+    pub fn is_routable(&self, token: Address) -> bool {
+        native::endpoints(token, self.config.wrapped_native)
+            .into_iter()
+            .any(|(token, _)| self.graph.contains_token(token))
+    }
+
+    /// Total balance of `token` in its pools, across the native and
+    /// wrapped pools for native ETH
+    ///
+    /// A cheap bound on the input a quote can sensibly route, checked
+    /// before any search starts.
+    pub fn token_liquidity(&self, token: Address) -> U256 {
+        native::endpoints(token, self.config.wrapped_native)
+            .into_iter()
+            .flat_map(|(token, _)| {
+                self.graph
+                    .get_pools_for_token(token)
+                    .into_iter()
+                    .filter_map(move |pool| pool.balance(token))
+            })
+            .fold(U256::ZERO, U256::saturating_add)
     }
 
     /// Get graph reference
@@ -252,21 +555,92 @@ impl Router {
         &self.graph
     }
 
+    /// Apply the configured gas model and screening to a search
+    fn search_params(&self, params: SearchParams) -> SearchParams {
+        let params = params.with_gas_model(self.config.gas_model);
+        match self.config.screening {
+            Some(screening) => params.screened(screening),
+            None => params,
+        }
+    }
+
+    /// Candidate pools for the current graph state
+    ///
+    /// The index is rebuilt on first use after the graph changes; call
+    /// this after a sync to take the rebuild off the quote path.
+    pub fn candidates(&self) -> Arc<CandidateGraph> {
+        let current = self.candidates.read().clone();
+        let version = self.graph.version();
+        if current.version() == version {
+            return current;
+        }
+
+        let rebuilt = Arc::new(CandidateGraph::build(&self.graph, &self.config.candidates));
+        let mut slot = self.candidates.write();
+        // A concurrent rebuild may have indexed a newer graph already
+        if slot.version() <= rebuilt.version() {
+            *slot = rebuilt.clone();
+        }
+        rebuilt
+    }
+
+    /// Token prices for the current graph state, if the router has a
+    /// price oracle
+    pub fn prices(&self) -> Option<Arc<TokenPrices>> {
+        self.prices.as_ref().map(|oracle| oracle.prices())
+    }
+
+    /// Gas prices quotes are charged with
+    pub fn gas_prices(&self) -> GasPrices {
+        self.gas
+            .as_ref()
+            .map(|oracle| oracle.current())
+            .unwrap_or_default()
+    }
+
     /// Get cache statistics
     pub fn cache_stats(&self) -> crate::cache::CacheStatistics {
         self.cache.stats()
     }
 
+    /// Get the metrics this router reports to
+    pub fn metrics(&self) -> &Arc<Metrics> {
+        &self.metrics
+    }
+
     /// Clear all caches
     pub fn clear_cache(&self) {
         self.cache.clear_all();
     }
+
+    /// Current graph version, after dropping cached results computed
+    /// at an older one
+    ///
+    /// Results are only cached if the graph is still at this version
+    /// once they are computed.
+    fn cache_version(&self) -> u64 {
+        let version = self.graph.version();
+        if self.cache_version.fetch_max(version, Ordering::AcqRel) < version {
+            self.cache.clear_all();
+        }
+        version
+    }
+}
+
+/// Error for a search that returned nothing
+fn no_route(from: Address, to: Address, budget: &SearchBudget) -> RouterError {
+    if budget.is_spent() {
+        RouterError::SearchTimeout { from, to }
+    } else {
+        RouterError::NoRouteFound { from, to }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::graph::{PoolEdge, TokenNode};
+    use crate::routing::FeeSide;
     use crate::utils::address_from_u64;
 
     fn create_test_graph() -> Arc<PoolGraph> {
@@ -332,8 +706,8 @@ mod tests {
         graph
     }
 
-    #[tokio::test]
-    async fn test_router_with_cache() {
+    #[test]
+    fn test_router_with_cache() {
         let graph = create_test_graph();
         let router = Router::new(graph);
 
@@ -344,13 +718,11 @@ mod tests {
         // First call - cache miss
         let route1 = router
             .find_route(token_a, token_c, amount, Some(4))
-            .await
             .expect("Should find route");
 
         // Second call - cache hit
         let route2 = router
             .find_route(token_a, token_c, amount, Some(4))
-            .await
             .expect("Should find route");
 
         assert_eq!(route1.total_amount_out, route2.total_amount_out);
@@ -359,8 +731,34 @@ mod tests {
         println!("Cache stats: {:?}", stats);
     }
 
-    #[tokio::test]
-    async fn test_router_parallel_mode() {
+    #[test]
+    fn test_graph_changes_retire_cached_routes() {
+        let graph = create_test_graph();
+        let router = Router::new(graph.clone());
+        let token_a = address_from_u64(1);
+        let token_c = address_from_u64(3);
+        let amount = U256::from(1_000_000_000_000_000_000u128);
+
+        let before = router.find_route(token_a, token_c, amount, Some(4)).unwrap();
+        assert_eq!(router.cache_stats().route_stats.size, 1);
+
+        // Drain the A/C pool the route went through
+        let mut pool = before.hops[0].pool.clone();
+        pool.liquidity = 1_000_000_000_000_000_000;
+        graph.upsert_pool(
+            pool,
+            TokenNode::new(token_a, "A".to_string(), 18),
+            TokenNode::new(token_c, "C".to_string(), 18),
+        );
+
+        let after = router.find_route(token_a, token_c, amount, Some(4)).unwrap();
+        assert!(after.total_amount_out < before.total_amount_out);
+        let quote = router.get_quote(token_a, token_c, amount, 0.5, Some(4)).unwrap();
+        assert_eq!(quote.graph_version, graph.version());
+    }
+
+    #[test]
+    fn test_router_parallel_mode() {
         let graph = create_test_graph();
 
         let config = RouterConfig {
@@ -376,9 +774,175 @@ mod tests {
 
         let route = router
             .find_route(token_a, token_c, amount, Some(4))
-            .await
             .expect("Should find route");
 
         assert!(route.total_amount_out > U256::ZERO);
     }
+
+    #[test]
+    fn test_spent_budget_is_a_timeout_and_not_cached() {
+        let router = Router::new(create_test_graph());
+        let token_a = address_from_u64(1);
+        let token_c = address_from_u64(3);
+        let amount = U256::from(1_000_000_000_000_000_000u128);
+
+        let spent = SearchBudget::with_timeout(Duration::ZERO);
+        let err = router
+            .find_route_within(token_a, token_c, amount, Some(4), &spent)
+            .unwrap_err();
+        assert!(matches!(err, RouterError::SearchTimeout { .. }));
+        assert_eq!(router.cache_stats().route_stats.size, 0);
+
+        let route = router
+            .find_route(token_a, token_c, amount, Some(4))
+            .expect("Should find route");
+        assert!(!route.partial);
+    }
+
+    #[test]
+    fn test_request_timeout_is_capped() {
+        let router = Router::with_config(
+            create_test_graph(),
+            RouterConfig {
+                search_timeout_ms: 0,
+                max_search_timeout_ms: 50,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(router.budget(None).deadline(), None);
+        let deadline = router.budget(Some(60_000)).deadline().unwrap();
+        assert!(deadline <= Instant::now() + Duration::from_millis(50));
+        // A request cannot lift the limit with 0
+        assert!(router.budget(Some(0)).deadline().is_some());
+    }
+
+    /// Native ETH pool with a low fee but little liquidity, and a deep
+    /// WETH pool, both against token C
+    fn create_native_graph() -> Arc<PoolGraph> {
+        let graph = Arc::new(PoolGraph::new());
+        let token_c = address_from_u64(3);
+        let eth = TokenNode::native(native::NATIVE, "ETH".to_string(), 18);
+        let weth_node = TokenNode::new(weth(), "WETH".to_string(), 18);
+        let node_c = TokenNode::new(token_c, "C".to_string(), 18);
+
+        let native_pool = PoolEdge::new(
+            [5u8; 32],
+            native::NATIVE,
+            token_c,
+            500,
+            10,
+            100_000_000_000_000_000_000,
+            U256::from(1u128 << 96),
+            0,
+        );
+        let weth_pool = PoolEdge::new(
+            [6u8; 32],
+            token_c,
+            weth(),
+            3000,
+            60,
+            1_000_000_000_000_000_000_000_000,
+            U256::from(1u128 << 96),
+            0,
+        );
+
+        graph.upsert_pool(native_pool, eth, node_c.clone());
+        graph.upsert_pool(weth_pool, node_c, weth_node);
+        graph
+    }
+
+    #[test]
+    fn test_native_eth_routes_through_native_and_wrapped_pools() {
+        let router = Router::new(create_native_graph());
+        let token_c = address_from_u64(3);
+
+        // Small swaps prefer the cheaper native pool, without wrapping
+        let small = U256::from(1_000_000_000_000_000u128);
+        let route = router
+            .find_route(native::NATIVE_ALIAS, token_c, small, Some(1))
+            .unwrap();
+        assert!(!route.wrap && !route.unwrap);
+        assert_eq!(route.hops[0].token_in, native::NATIVE);
+
+        // Large swaps wrap and use the deep WETH pool
+        let large = U256::from(50_000_000_000_000_000_000u128);
+        let route = router
+            .find_route(native::NATIVE, token_c, large, Some(1))
+            .unwrap();
+        assert!(route.wrap && !route.unwrap);
+        assert_eq!(route.hops[0].token_in, weth());
+        let gas = GasModel::default();
+        assert_eq!(
+            route.gas_estimate,
+            gas.transaction + gas.multicall + gas.call_gas(&route) + gas.wrap
+        );
+        assert!(route.route_string().starts_with(&format!("{:?}", native::NATIVE)));
+
+        let quote = router
+            .get_split_quote(token_c, native::NATIVE_ALIAS, large, 0.5, Some(2), None)
+            .unwrap();
+        assert!(quote.route.unwrap && !quote.route.wrap);
+        assert!(quote.route.routes.iter().all(|(route, _)| route.unwrap));
+        assert!(quote.route_string.ends_with(&format!("{:?}", native::NATIVE)));
+    }
+
+    #[test]
+    fn test_wrapping_alone_is_not_a_route() {
+        let router = Router::new(create_native_graph());
+        let amount = U256::from(1_000_000_000_000_000u128);
+
+        // ETH -> WETH has to go through pools; there is no direct ETH/WETH pool
+        let route = router
+            .find_route(native::NATIVE, weth(), amount, Some(2))
+            .unwrap();
+        assert_eq!(route.hops[0].token_in, native::NATIVE);
+        assert!(!route.wrap && !route.unwrap);
+        let err = router
+            .find_route(native::NATIVE, weth(), amount, Some(1))
+            .unwrap_err();
+        assert!(matches!(err, RouterError::NoRouteFound { .. }));
+    }
+
+    #[test]
+    fn test_integrator_fee_on_output_and_input() {
+        let router = Router::new(create_test_graph());
+        let (token_a, token_c) = (address_from_u64(1), address_from_u64(3));
+        let amount = U256::from(1_000_000_000_000_000_000u128);
+        let recipient = address_from_u64(99);
+        let quote = router.get_quote(token_a, token_c, amount, 0.5, None).unwrap();
+        let gross_out = quote.route.total_amount_out;
+
+        let output_fee = FeeTerms {
+            bps: 50,
+            recipient,
+            side: FeeSide::Output,
+        };
+        let charged = router.with_integrator_fee(quote.clone(), output_fee, amount, token_a, token_c);
+        let fee = gross_out * U256::from(50) / U256::from(10_000);
+        let integrator_fee = charged.integrator_fee.clone().unwrap();
+        assert_eq!(integrator_fee.token, token_c);
+        assert_eq!(integrator_fee.amount, fee.to_string());
+        assert_eq!(charged.amount_out, (gross_out - fee).to_string());
+        assert!(charged.amount_out_min.parse::<U256>().unwrap() < gross_out - fee);
+        assert!(charged.route.fee_transfer);
+        let gas = GasModel::default();
+        assert_eq!(
+            charged.gas_estimate,
+            quote.gas_estimate + gas.multicall + gas.token_transfer
+        );
+
+        // An input-side fee is taken before routing
+        let input_fee = FeeTerms {
+            side: FeeSide::Input,
+            ..output_fee
+        };
+        let routed = input_fee.routed_input(amount);
+        let quote = router.get_quote(token_a, token_c, routed, 0.5, None).unwrap();
+        let charged = router.with_integrator_fee(quote, input_fee, amount, token_a, token_c);
+        assert_eq!(charged.amount_in, amount.to_string());
+        assert_eq!(charged.gross_amount_in(), amount);
+        assert_eq!(charged.route.total_amount_in, routed);
+        assert_eq!(charged.integrator_fee.unwrap().amount, (amount - routed).to_string());
+    }
 }
@@ -0,0 +1,1264 @@
+use crate::gas::GasModel;
+use crate::graph::PoolGraph;
+use crate::routing::budget::SearchBudget;
+use crate::routing::candidates::{CandidateConfig, CandidateGraph, Edge};
+use crate::routing::simulate::{calculate_price_impact, simulate_hop};
+use crate::routing::{Route, RouteHop};
+use crate::utils::u256_ext::to_f64;
+use crate::utils::MAX_HOPS;
+use alloy_primitives::{Address, U256};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Limits for a route search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchParams {
+    /// Maximum hops per route (capped at `MAX_HOPS`)
+    pub max_hops: usize,
+    /// Number of routes to return
+    pub k: usize,
+    /// Simulate the expansions of each hop layer on the rayon pool
+    pub parallel: bool,
+    /// Screen routes with approximate pricing before simulating them
+    /// exactly (`None` prices every expansion exactly)
+    pub screening: Option<Screening>,
+    /// Gas of each hop, for ranking routes of equal output
+    pub gas_model: GasModel,
+}
+
+impl SearchParams {
+    pub fn new(max_hops: usize, k: usize) -> Self {
+        Self {
+            max_hops,
+            k,
+            parallel: false,
+            screening: None,
+            gas_model: GasModel::DEFAULT,
+        }
+    }
+
+    pub fn parallel(mut self) -> Self {
+        self.parallel = true;
+        self
+    }
+
+    pub fn screened(mut self, screening: Screening) -> Self {
+        self.screening = Some(screening);
+        self
+    }
+
+    pub fn with_gas_model(mut self, gas_model: GasModel) -> Self {
+        self.gas_model = gas_model;
+        self
+    }
+}
+
+/// Two-phase search settings
+///
+/// The first phase runs the search on `f64` hop estimates and keeps
+/// `width * k` routes; only those are simulated exactly. The result is
+/// accepted if no route outside the screened set can beat the `k`-th
+/// returned route by more than `tolerance_bps`, otherwise the search is
+/// rerun exactly. Either way the `i`-th route returned is within
+/// `tolerance_bps` of the `i`-th best route of the exact search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Screening {
+    /// Routes kept by the approximate phase per route requested
+    pub width: usize,
+    /// Output shortfall allowed against the exact search (basis points)
+    pub tolerance_bps: u32,
+}
+
+impl Default for Screening {
+    fn default() -> Self {
+        Self {
+            width: 4,
+            tolerance_bps: 1,
+        }
+    }
+}
+
+/// Relative error allowed between a route's estimated and exact output
+/// when bounding the routes the screen left out
+///
+/// Estimates are far more precise than this (see `HopEstimate`) unless
+/// a hop moves only a few thousand wei, where the per-hop rounding of
+/// the exact math dominates; [`ESTIMATE_SLACK`] covers that.
+const ESTIMATE_ERROR: f64 = 1e-6;
+
+/// Absolute error allowed per hop on top of [`ESTIMATE_ERROR`] (wei):
+/// the exact math rounds each hop by about a wei, either way
+const ESTIMATE_SLACK: f64 = 4.0;
+
+/// Expansions between two checks of the search budget
+const BUDGET_CHECK_INTERVAL: usize = 64;
+
+/// Tokens on a path (at most `MAX_HOPS + 1`), stored inline
+///
+/// `bits` is a 64-bit visited set over `token % 64`: exact while the
+/// candidate graph has at most 64 tokens, and otherwise a filter that
+/// answers most "not on the path" queries without scanning `tokens`.
+#[derive(Clone, Copy)]
+struct TokenPath {
+    tokens: [u32; MAX_HOPS + 1],
+    len: u8,
+    bits: u64,
+}
+
+impl TokenPath {
+    fn new(token: u32) -> Self {
+        let mut tokens = [0; MAX_HOPS + 1];
+        tokens[0] = token;
+        Self {
+            tokens,
+            len: 1,
+            bits: Self::bit(token),
+        }
+    }
+
+    fn bit(token: u32) -> u64 {
+        1 << (token % 64)
+    }
+
+    fn tokens(&self) -> &[u32] {
+        &self.tokens[..self.len as usize]
+    }
+
+    fn with(mut self, token: u32) -> Self {
+        self.tokens[self.len as usize] = token;
+        self.len += 1;
+        self.bits |= Self::bit(token);
+        self
+    }
+
+    fn contains(&self, token: u32) -> bool {
+        self.bits & Self::bit(token) != 0 && self.tokens().contains(&token)
+    }
+
+    fn is_subset_of(&self, other: &TokenPath) -> bool {
+        self.bits & !other.bits == 0 && self.tokens().iter().all(|&t| other.contains(t))
+    }
+}
+
+
+/// No label (end of a parent or same-token chain)
+const NONE: u32 = u32::MAX;
+
+/// Amount a search runs on: exact `U256` or an `f64` estimate
+trait SearchAmount: Copy + Send + Sync {
+    fn compare(&self, other: &Self) -> Ordering;
+
+    /// Price a hop from `token_in` along `edge` (`None` if the pool
+    /// cannot fill it)
+    fn hop(
+        candidates: &CandidateGraph,
+        edge: Edge,
+        token_in: Address,
+        amount: Self,
+        gas: &GasModel,
+    ) -> Option<(Self, u64)>;
+}
+
+impl SearchAmount for U256 {
+    fn compare(&self, other: &Self) -> Ordering {
+        self.cmp(other)
+    }
+
+    fn hop(
+        candidates: &CandidateGraph,
+        edge: Edge,
+        token_in: Address,
+        amount: Self,
+        gas: &GasModel,
+    ) -> Option<(Self, u64)> {
+        simulate_hop(candidates.pool(edge.pool), token_in, amount, gas).ok()
+    }
+}
+
+impl SearchAmount for f64 {
+    fn compare(&self, other: &Self) -> Ordering {
+        self.total_cmp(other)
+    }
+
+    fn hop(
+        candidates: &CandidateGraph,
+        edge: Edge,
+        _: Address,
+        amount: Self,
+        gas: &GasModel,
+    ) -> Option<(Self, u64)> {
+        let pool = candidates.pool(edge.pool);
+        candidates
+            .estimate(edge.pool)
+            .hop(pool, edge.zero_for_one, amount, gas)
+    }
+}
+
+/// A partial route ending at `token`
+#[derive(Clone, Copy)]
+struct Label<A> {
+    token: u32,
+    amount: A,
+    gas: u64,
+    /// Pool of the last hop (`NONE` for the start label)
+    pool: u32,
+    parent: u32,
+    /// Previously kept label at the same token (`NONE` if first)
+    same_token: u32,
+    path: TokenPath,
+}
+
+impl<A: SearchAmount> Label<A> {
+    /// Whether every continuation of `other` can be matched by this
+    /// label with at least the same output
+    ///
+    /// Output is monotone in the input amount, so a label with at
+    /// least the same amount whose path uses a subset of the other's
+    /// tokens (and so at most as many hops) can follow any suffix the
+    /// other can.
+    fn dominates(&self, amount: &A, path: &TokenPath) -> bool {
+        self.amount.compare(amount) != Ordering::Less && self.path.is_subset_of(path)
+    }
+
+    /// Route order short of pool ids: higher output, fewer hops, lower gas
+    fn rank(&self, other: &Self) -> Ordering {
+        other
+            .amount
+            .compare(&self.amount)
+            .then(self.path.len.cmp(&other.path.len))
+            .then(self.gas.cmp(&other.gas))
+    }
+}
+
+/// An extension of a label by one pool, not yet accepted
+#[derive(Clone, Copy)]
+struct Candidate<A> {
+    parent: u32,
+    pool: u32,
+    token: u32,
+    amount: A,
+    gas: u64,
+}
+
+/// Labels left by a search, with the completed ones best first
+struct Labels<A> {
+    labels: Vec<Label<A>>,
+    completed: Vec<u32>,
+    /// The budget ran out before every label was expanded
+    interrupted: bool,
+}
+
+/// Find the `k` best routes from `token_in` to `token_out`
+///
+/// Exact k-best simple-path search over amount-dependent edges. Routes
+/// are built one hop layer at a time; every hop is priced with
+/// [`simulate_hop`] for the amount actually arriving at that pool. A
+/// partial route is only dropped once `k` other partial routes at the
+/// same token dominate it (see [`Label::dominates`]), so the outputs
+/// returned are the same as enumerating every route up to `max_hops`
+/// and keeping the best `k`. Among routes with equal output (e.g. when
+/// the last pool caps the output) which ones are kept is unspecified.
+///
+/// Routes are ordered by output, then fewer hops, lower gas and pool ids.
+///
+/// Every pool in `graph` is considered; see [`search_candidates`] for
+/// searching a filtered candidate set.
+pub fn search_routes(
+    graph: &PoolGraph,
+    token_in: Address,
+    token_out: Address,
+    amount_in: U256,
+    params: SearchParams,
+) -> Vec<Route> {
+    let candidates = CandidateGraph::build(graph, &CandidateConfig::unfiltered());
+    search_candidates(&candidates, token_in, token_out, amount_in, params)
+}
+
+/// Find the `k` best routes using only the pools and connector tokens
+/// of `candidates`
+///
+/// The search is the same as [`search_routes`]; it is exact with
+/// respect to the routes the candidate set allows, or within the
+/// tolerance of [`Screening`] when screening is enabled.
+pub fn search_candidates(
+    candidates: &CandidateGraph,
+    token_in: Address,
+    token_out: Address,
+    amount_in: U256,
+    params: SearchParams,
+) -> Vec<Route> {
+    search_candidates_within(
+        candidates,
+        token_in,
+        token_out,
+        amount_in,
+        params,
+        &SearchBudget::unlimited(),
+    )
+}
+
+/// [`search_candidates`] that stops once `budget` is spent
+///
+/// An interrupted search returns the best routes it completed, each
+/// marked `partial`. Without screening these are the best routes with
+/// fewer hops than the layer being searched; the routes of that layer
+/// are only a subset.
+pub fn search_candidates_within(
+    candidates: &CandidateGraph,
+    token_in: Address,
+    token_out: Address,
+    amount_in: U256,
+    params: SearchParams,
+    budget: &SearchBudget,
+) -> Vec<Route> {
+    let max_hops = params.max_hops.min(MAX_HOPS);
+    let k = params.k;
+
+    if k == 0 || max_hops == 0 || token_in == token_out || amount_in.is_zero() {
+        return Vec::new();
+    }
+    let (Some(start), Some(target)) = (candidates.token_id(token_in), candidates.token_id(token_out))
+    else {
+        return Vec::new();
+    };
+    let request = Request {
+        candidates,
+        start,
+        target,
+        max_hops,
+        k,
+        parallel: params.parallel,
+        gas: params.gas_model,
+        budget,
+    };
+
+    if let Some(screening) = params.screening {
+        if let Some(routes) = request.screened(amount_in, screening) {
+            return routes;
+        }
+        tracing::debug!("Route screen inconclusive, falling back to exact search");
+    }
+    request.exact(amount_in)
+}
+
+/// A search request resolved against the candidate index
+struct Request<'a> {
+    candidates: &'a CandidateGraph,
+    start: u32,
+    target: u32,
+    max_hops: usize,
+    k: usize,
+    parallel: bool,
+    gas: GasModel,
+    budget: &'a SearchBudget,
+}
+
+impl Request<'_> {
+    /// Price every expansion exactly
+    fn exact(&self, amount_in: U256) -> Vec<Route> {
+        let search = self.run(amount_in, self.k);
+
+        // Routes tied with the k-th are built too, so pool ids can break the tie
+        let mut end = search.completed.len().min(self.k);
+        while let Some(&next) = search.completed.get(end) {
+            let last = &search.labels[search.completed[end - 1] as usize];
+            if last.rank(&search.labels[next as usize]) != Ordering::Equal {
+                break;
+            }
+            end += 1;
+        }
+
+        let mut routes: Vec<Route> = search.completed[..end]
+            .iter()
+            .map(|&end| self.build_route(&search.labels, end, amount_in))
+            .collect();
+        routes.sort_by(compare_routes);
+        routes.truncate(self.k);
+        if search.interrupted {
+            mark_partial(&mut routes);
+        }
+        routes
+    }
+
+    /// Screen with `f64` estimates, then simulate the screened routes
+    /// exactly (see [`Screening`])
+    ///
+    /// Returns `None` if the screen cannot certify the result.
+    fn screened(&self, amount_in: U256, screening: Screening) -> Option<Vec<Route>> {
+        let width = self.k.saturating_mul(screening.width.max(1));
+        let search = self.run(to_f64(amount_in), width);
+
+        let mut routes: Vec<Route> = search
+            .completed
+            .iter()
+            .take(width)
+            .filter_map(|&end| self.resimulate(&search.labels, end, amount_in))
+            .collect();
+        routes.sort_by(compare_routes);
+        routes.truncate(self.k);
+
+        // An interrupted screen has no time left for the exact search
+        if search.interrupted {
+            mark_partial(&mut routes);
+            return Some(routes);
+        }
+
+        // With fewer completions than the width nothing was pruned on
+        // the way to the target, so every route was screened
+        if search.completed.len() < width {
+            return Some(routes);
+        }
+
+        // Any route left out estimates at most the width-th estimate, so
+        // its exact output is at most that plus the estimate error of
+        // each of its (at most `max_hops`) hops
+        let cutoff = search.labels[search.completed[width - 1] as usize].amount;
+        let bound = cutoff * (1.0 + ESTIMATE_ERROR) + ESTIMATE_SLACK * self.max_hops as f64;
+        let tolerance = 1.0 + screening.tolerance_bps as f64 / 10_000.0;
+        let certified = routes
+            .get(self.k - 1)
+            .is_some_and(|worst| to_f64(worst.total_amount_out) * tolerance >= bound);
+        if certified {
+            return Some(routes);
+        }
+        if self.budget.is_spent() {
+            mark_partial(&mut routes);
+            return Some(routes);
+        }
+        None
+    }
+
+    /// Hop-layered label search shared by both phases, keeping `k`
+    /// labels per token (see [`search_routes`] for the pruning rule)
+    fn run<A: SearchAmount>(&self, amount_in: A, k: usize) -> Labels<A> {
+        let candidates = self.candidates;
+        let mut labels = vec![Label {
+            token: self.start,
+            amount: amount_in,
+            gas: 0,
+            pool: NONE,
+            parent: NONE,
+            same_token: NONE,
+            path: TokenPath::new(self.start),
+        }];
+        // Most recently kept label per token; older ones via `same_token`
+        let mut kept: HashMap<u32, u32> = HashMap::new();
+        kept.insert(self.start, 0);
+        let mut frontier = vec![0u32];
+        let mut next_frontier = Vec::new();
+        let mut expansions = Vec::new();
+        let mut completed = Vec::new();
+        let mut interrupted = false;
+
+        for layer in 1..=self.max_hops {
+            let last_layer = layer == self.max_hops;
+            let expand = |&parent: &u32| self.expand(&labels, parent, last_layer);
+
+            expansions.clear();
+            if self.parallel {
+                expansions.par_extend(frontier.par_iter().flat_map_iter(expand));
+            } else {
+                expansions.extend(frontier.iter().flat_map(expand));
+            }
+            // Expansions may have been skipped if the budget ran out
+            // mid-layer; then only the routes completed by it are of use
+            interrupted = self.budget.is_spent();
+            if interrupted {
+                expansions.retain(|candidate| candidate.token == self.target);
+            }
+
+            // Best first, so that a candidate can only be dominated by
+            // labels that were accepted before it
+            expansions.sort_unstable_by(|a: &Candidate<A>, b: &Candidate<A>| {
+                b.amount
+                    .compare(&a.amount)
+                    .then(a.parent.cmp(&b.parent))
+                    .then_with(|| {
+                        let id = |c: &Candidate<A>| candidates.pool(c.pool).pool_id;
+                        id(a).cmp(&id(b))
+                    })
+            });
+
+            next_frontier.clear();
+            for (i, candidate) in expansions.iter().enumerate() {
+                // Candidates are best first, so stopping keeps the best ones
+                if i % BUDGET_CHECK_INTERVAL == 0 && self.budget.is_spent() {
+                    interrupted = true;
+                    break;
+                }
+                let path = labels[candidate.parent as usize].path.with(candidate.token);
+                let index = labels.len() as u32;
+                let mut same_token = NONE;
+
+                if candidate.token != self.target {
+                    let head = kept.entry(candidate.token).or_insert(NONE);
+                    let mut dominated = 0;
+                    let mut at_token = *head;
+                    while at_token != NONE && dominated < k {
+                        let label = &labels[at_token as usize];
+                        if label.dominates(&candidate.amount, &path) {
+                            dominated += 1;
+                        }
+                        at_token = label.same_token;
+                    }
+                    if dominated >= k {
+                        continue;
+                    }
+                    same_token = *head;
+                    *head = index;
+                    next_frontier.push(index);
+                } else {
+                    completed.push(index);
+                }
+
+                labels.push(Label {
+                    token: candidate.token,
+                    amount: candidate.amount,
+                    gas: candidate.gas,
+                    pool: candidate.pool,
+                    parent: candidate.parent,
+                    same_token,
+                    path,
+                });
+            }
+
+            if interrupted || next_frontier.is_empty() {
+                break;
+            }
+            std::mem::swap(&mut frontier, &mut next_frontier);
+        }
+
+        completed.sort_unstable_by(|&a, &b| labels[a as usize].rank(&labels[b as usize]));
+        Labels {
+            labels,
+            completed,
+            interrupted,
+        }
+    }
+
+    /// Price every candidate pool out of a label's token
+    fn expand<'a, A: SearchAmount>(
+        &'a self,
+        labels: &'a [Label<A>],
+        parent: u32,
+        last_layer: bool,
+    ) -> impl Iterator<Item = Candidate<A>> + 'a {
+        let label = &labels[parent as usize];
+        let token_in = self.candidates.token(label.token);
+
+        self.candidates
+            .next_hops(label.token, self.target)
+            .enumerate()
+            // Once the budget is spent the remaining edges are skipped
+            .take_while(|(i, _)| i % BUDGET_CHECK_INTERVAL != 0 || !self.budget.is_spent())
+            .filter_map(move |(_, edge)| {
+                if label.path.contains(edge.to) || (last_layer && edge.to != self.target) {
+                    return None;
+                }
+
+                let (amount, gas) =
+                    A::hop(self.candidates, edge, token_in, label.amount, &self.gas)?;
+                Some(Candidate {
+                    parent,
+                    pool: edge.pool,
+                    token: edge.to,
+                    amount,
+                    gas: label.gas + gas,
+                })
+            })
+    }
+
+    /// Build the route of an exact search label from the amounts it
+    /// was priced with
+    fn build_route(&self, labels: &[Label<U256>], end: u32, amount_in: U256) -> Route {
+        let mut hops = Vec::with_capacity(labels[end as usize].path.len as usize - 1);
+        let mut index = end as usize;
+
+        while labels[index].pool != NONE {
+            let label = &labels[index];
+            let parent = &labels[label.parent as usize];
+            hops.push(RouteHop::new(
+                self.candidates.pool(label.pool).clone(),
+                self.candidates.token(parent.token),
+                self.candidates.token(label.token),
+                parent.amount,
+                label.amount,
+            ));
+            index = label.parent as usize;
+        }
+        hops.reverse();
+
+        let end = &labels[end as usize];
+        Route::new(
+            hops,
+            amount_in,
+            end.amount,
+            calculate_price_impact(amount_in, end.amount),
+            end.gas,
+        )
+    }
+
+    /// Price the path of a screening label exactly, hop by hop
+    fn resimulate(&self, labels: &[Label<f64>], end: u32, amount_in: U256) -> Option<Route> {
+        let path = labels[end as usize].path;
+        let hop_count = path.len as usize - 1;
+        let mut pools = [0u32; MAX_HOPS];
+        let mut index = end as usize;
+        for slot in (0..hop_count).rev() {
+            pools[slot] = labels[index].pool;
+            index = labels[index].parent as usize;
+        }
+
+        let mut hops = Vec::with_capacity(hop_count);
+        let mut amount = amount_in;
+        let mut gas = 0;
+        for (i, &pool) in pools[..hop_count].iter().enumerate() {
+            let pool = self.candidates.pool(pool);
+            let token_in = self.candidates.token(path.tokens[i]);
+            let (amount_out, hop_gas) = simulate_hop(pool, token_in, amount, &self.gas).ok()?;
+            hops.push(RouteHop::new(
+                pool.clone(),
+                token_in,
+                self.candidates.token(path.tokens[i + 1]),
+                amount,
+                amount_out,
+            ));
+            amount = amount_out;
+            gas += hop_gas;
+        }
+
+        Some(Route::new(
+            hops,
+            amount_in,
+            amount,
+            calculate_price_impact(amount_in, amount),
+            gas,
+        ))
+    }
+}
+
+fn mark_partial(routes: &mut [Route]) {
+    for route in routes {
+        route.partial = true;
+    }
+}
+
+/// Result order: highest output, then fewer hops, lower gas and pool ids
+pub(crate) fn compare_routes(a: &Route, b: &Route) -> Ordering {
+    b.total_amount_out
+        .cmp(&a.total_amount_out)
+        .then(a.hops.len().cmp(&b.hops.len()))
+        .then(a.gas_estimate.cmp(&b.gas_estimate))
+        .then_with(|| {
+            let ids = |r: &Route| r.hops.iter().map(|h| h.pool.pool_id).collect::<Vec<_>>();
+            ids(a).cmp(&ids(b))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{GraphFixture, PoolEdge, TokenNode};
+    use crate::routing::resimulate_route;
+    use crate::routing::simulate::HopEstimate;
+    use crate::utils::address_from_u64;
+    use crate::utils::math::tick_to_sqrt_price_x96;
+    use crate::utils::u256_ext::from_f64;
+    use tokio_util::sync::CancellationToken;
+
+    /// Small deterministic generator so graphs are reproducible per seed
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next(&mut self, bound: u64) -> u64 {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (self.0 >> 33) % bound
+        }
+    }
+
+    fn pool(
+        id: u32,
+        token0: Address,
+        token1: Address,
+        fee: u32,
+        liquidity: u128,
+        tick: i32,
+    ) -> PoolEdge {
+        let mut pool_id = [0u8; 32];
+        pool_id[28..].copy_from_slice(&id.to_be_bytes());
+        PoolEdge::new(
+            pool_id,
+            token0,
+            token1,
+            fee,
+            60,
+            liquidity,
+            tick_to_sqrt_price_x96(tick),
+            tick,
+        )
+    }
+
+    fn random_graph(seed: u64, tokens: u64) -> PoolGraph {
+        let mut rng = Lcg(seed);
+        let graph = PoolGraph::new();
+        let nodes: Vec<TokenNode> = (1..=tokens)
+            .map(|i| TokenNode::new(address_from_u64(i), format!("T{}", i), 18))
+            .collect();
+        let fees = [100, 500, 3000, 10000];
+
+        let mut id = 0;
+        for i in 0..nodes.len() {
+            for j in i + 1..nodes.len() {
+                // 0-2 pools per pair
+                for _ in 0..rng.next(3) {
+                    id += 1;
+                    let liquidity = 10u128.pow(20 + rng.next(5) as u32);
+                    let tick = rng.next(2001) as i32 - 1000;
+                    graph.upsert_pool(
+                        pool(
+                            id,
+                            nodes[i].address,
+                            nodes[j].address,
+                            fees[rng.next(4) as usize],
+                            liquidity,
+                            tick,
+                        ),
+                        nodes[i].clone(),
+                        nodes[j].clone(),
+                    );
+                }
+            }
+        }
+        graph
+    }
+
+    /// `random_graph` with a third of the pools turned into
+    /// constant-product pairs and a third into stableswap pools holding
+    /// the same virtual reserves
+    fn mixed_graph(seed: u64, tokens: u64) -> PoolGraph {
+        let graph = random_graph(seed, tokens);
+        let all_tokens = graph.get_all_tokens();
+        let token = |a| all_tokens.iter().find(|t: &&TokenNode| t.address == a).unwrap().clone();
+        for pool in graph.get_all_pools() {
+            let sqrt_price = 1.0001f64.powf(pool.tick as f64 / 2.0);
+            let liquidity = pool.liquidity as f64;
+            let reserves = [liquidity / sqrt_price, liquidity * sqrt_price].map(from_f64);
+            let mixed = match pool.pool_id[31] % 3 {
+                1 => PoolEdge::constant_product(pool.pool_id, pool.token0, pool.token1, pool.fee, reserves),
+                2 => PoolEdge::stable_swap(
+                    pool.pool_id,
+                    pool.token0,
+                    pool.token1,
+                    pool.fee,
+                    reserves,
+                    100,
+                    [18, 18],
+                ),
+                _ => continue,
+            };
+            graph.upsert_pool(mixed, token(pool.token0), token(pool.token1));
+        }
+        graph
+    }
+
+    /// Every simple route up to `max_hops`, priced with the same hop function
+    fn brute_force(
+        graph: &PoolGraph,
+        token_in: Address,
+        token_out: Address,
+        amount_in: U256,
+        max_hops: usize,
+    ) -> Vec<Route> {
+        fn walk(
+            graph: &PoolGraph,
+            token: Address,
+            amount: U256,
+            token_out: Address,
+            max_hops: usize,
+            path: &mut Vec<RouteHop>,
+            routes: &mut Vec<Route>,
+        ) {
+            if token == token_out {
+                let amount_in = path[0].amount_in;
+                let gas = path
+                    .iter()
+                    .map(|h| {
+                        simulate_hop(&h.pool, h.token_in, h.amount_in, &GasModel::DEFAULT)
+                            .unwrap()
+                            .1
+                    })
+                    .sum();
+                routes.push(Route::new(
+                    path.clone(),
+                    amount_in,
+                    amount,
+                    calculate_price_impact(amount_in, amount),
+                    gas,
+                ));
+                return;
+            }
+            if path.len() == max_hops {
+                return;
+            }
+
+            for pool in graph.get_pools_for_token(token) {
+                let next = pool.other_token(token).unwrap();
+                let seen = next == path.first().map_or(token, |h| h.token_in)
+                    || path.iter().any(|h| h.token_out == next);
+                if seen {
+                    continue;
+                }
+                if let Ok((out, _)) = simulate_hop(&pool, token, amount, &GasModel::DEFAULT) {
+                    path.push(RouteHop::new(pool, token, next, amount, out));
+                    walk(graph, next, out, token_out, max_hops, path, routes);
+                    path.pop();
+                }
+            }
+        }
+
+        let mut routes = Vec::new();
+        walk(
+            graph,
+            token_in,
+            amount_in,
+            token_out,
+            max_hops,
+            &mut Vec::new(),
+            &mut routes,
+        );
+        routes.sort_by(compare_routes);
+        routes
+    }
+
+    fn pool_ids(route: &Route) -> Vec<[u8; 32]> {
+        route.hops.iter().map(|h| h.pool.pool_id).collect()
+    }
+
+    #[test]
+    fn test_top_k_matches_brute_force() {
+        let amounts = [
+            U256::from(10u128.pow(15)),
+            U256::from(10u128.pow(18)),
+            U256::from(10u128.pow(21)),
+        ];
+
+        for seed in 0..40 {
+            let graph = random_graph(seed, 6);
+            let token_in = address_from_u64(1);
+            let token_out = address_from_u64(1 + (seed % 5) + 1);
+
+            for &amount in &amounts {
+                for max_hops in 1..=4 {
+                    let all = brute_force(&graph, token_in, token_out, amount, max_hops);
+                    for k in [1, 2, 3, 5, 8, 50] {
+                        let expected: Vec<U256> =
+                            all.iter().take(k).map(|r| r.total_amount_out).collect();
+                        for params in [
+                            SearchParams::new(max_hops, k),
+                            SearchParams::new(max_hops, k).parallel(),
+                        ] {
+                            let context = format!(
+                                "seed {} amount {} max_hops {} k {} parallel {}",
+                                seed, amount, max_hops, k, params.parallel
+                            );
+                            let found = search_routes(&graph, token_in, token_out, amount, params);
+
+                            // Same top-k outputs; ties may be broken differently
+                            let outputs: Vec<U256> =
+                                found.iter().map(|r| r.total_amount_out).collect();
+                            assert_eq!(outputs, expected, "{}", context);
+
+                            // Every route returned exists, with the same pricing
+                            for route in &found {
+                                assert!(
+                                    all.iter().any(|r| pool_ids(r) == pool_ids(route)
+                                        && r.total_amount_out == route.total_amount_out
+                                        && r.gas_estimate == route.gas_estimate),
+                                    "{}: unknown route {:?}",
+                                    context,
+                                    pool_ids(route)
+                                );
+                            }
+                            let mut distinct: Vec<_> = found.iter().map(pool_ids).collect();
+                            distinct.dedup();
+                            assert_eq!(distinct.len(), found.len(), "{}", context);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// The `i`-th screened output is within the tolerance of the `i`-th exact one
+    fn assert_within_tolerance(screened: &[Route], exact: &[Route], tolerance_bps: u32, context: &str) {
+        assert_eq!(screened.len(), exact.len(), "{}", context);
+        for (screened, exact) in screened.iter().zip(exact) {
+            let bound = screened.total_amount_out * U256::from(10_000 + tolerance_bps);
+            assert!(
+                bound >= exact.total_amount_out * U256::from(10_000),
+                "{}: screened {} exact {}",
+                context,
+                screened.total_amount_out,
+                exact.total_amount_out
+            );
+        }
+    }
+
+    #[test]
+    fn test_screened_search_matches_exact_search() {
+        let amounts = [
+            U256::from(10u128.pow(15)),
+            U256::from(10u128.pow(18)),
+            U256::from(10u128.pow(21)),
+        ];
+        let screenings = [
+            Screening::default(),
+            // Narrow screen with no tolerance: relies on the fallback
+            Screening {
+                width: 1,
+                tolerance_bps: 0,
+            },
+        ];
+
+        for seed in 0..40 {
+            let graph = random_graph(seed, 6);
+            let candidates = CandidateGraph::build(&graph, &CandidateConfig::unfiltered());
+            let token_in = address_from_u64(1);
+            let token_out = address_from_u64(1 + (seed % 5) + 1);
+
+            for &amount in &amounts {
+                for (max_hops, k) in [(2, 1), (3, 3), (4, 1), (4, 6)] {
+                    let params = SearchParams::new(max_hops, k);
+                    let exact = search_candidates(&candidates, token_in, token_out, amount, params);
+                    for screening in screenings {
+                        let context = format!(
+                            "seed {} amount {} max_hops {} k {} {:?}",
+                            seed, amount, max_hops, k, screening
+                        );
+                        let screened = search_candidates(
+                            &candidates,
+                            token_in,
+                            token_out,
+                            amount,
+                            params.screened(screening),
+                        );
+                        assert_within_tolerance(&screened, &exact, screening.tolerance_bps, &context);
+                        // Every route is priced exactly
+                        for route in &screened {
+                            let replayed =
+                                resimulate_route(&graph, route, amount, &GasModel::DEFAULT)
+                                    .unwrap();
+                            assert_eq!(replayed.total_amount_out, route.total_amount_out);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_screen_covers_rounding_of_tiny_hops() {
+        let (token0, token1) = (address_from_u64(1), address_from_u64(2));
+        let graph = PoolGraph::new();
+        let node = |address| TokenNode::new(address, format!("{}", address), 18);
+        // Rounds its output up by about a wei
+        let concentrated = pool(1, token0, token1, 500, 10u128.pow(12), 68493);
+        // Rounds its output down, and estimates higher than the other
+        let reserves = [U256::from(1_068_204_720u64), U256::from(10u64.pow(12))];
+        let pair = PoolEdge::constant_product([2; 32], token0, token1, 3000, reserves);
+        for pool in [concentrated, pair] {
+            graph.upsert_pool(pool, node(token0), node(token1));
+        }
+        let candidates = CandidateGraph::build(&graph, &CandidateConfig::unfiltered());
+        let amount = U256::from(100_000);
+
+        let gas = GasModel::DEFAULT;
+        let estimates: Vec<f64> = candidates
+            .pools_between(token1, token0)
+            .map(|pool| {
+                let (estimate, _) = HopEstimate::new(pool).hop(pool, false, 1e5, &gas).unwrap();
+                estimate
+            })
+            .collect();
+        let exact: Vec<U256> = candidates
+            .pools_between(token1, token0)
+            .map(|pool| simulate_hop(pool, token1, amount, &gas).unwrap().0)
+            .collect();
+        let rankings_differ = (estimates[0] > estimates[1]) != (exact[0] > exact[1]);
+        assert!(rankings_differ, "{:?} {:?}", estimates, exact);
+        let most = exact.iter().max().copied().unwrap();
+
+        // The tolerance covers the relative error of the estimates, but
+        // not the wei the screened route loses to the other
+        let params = SearchParams::new(1, 1);
+        let screening = Screening {
+            width: 1,
+            tolerance_bps: 48,
+        };
+        let search = |params| search_candidates(&candidates, token1, token0, amount, params);
+        assert_eq!(search(params)[0].total_amount_out, most);
+        assert_eq!(search(params.screened(screening))[0].total_amount_out, most);
+    }
+
+    #[test]
+    fn test_screened_search_matches_exact_search_on_fixtures() {
+        let fixtures = [
+            GraphFixture::from_toml(include_str!("../../fixtures/base-mainnet.toml")).unwrap(),
+            GraphFixture::from_toml(include_str!("../../fixtures/multi-path.toml")).unwrap(),
+            GraphFixture::from_json(include_str!("../../fixtures/dense-10.json")).unwrap(),
+        ];
+
+        for fixture in &fixtures {
+            let graph = PoolGraph::new();
+            graph.import_fixture(fixture).unwrap();
+            let candidates = CandidateGraph::build(&graph, &CandidateConfig::unfiltered());
+            let tokens = graph.get_all_tokens();
+
+            for token_in in &tokens {
+                for token_out in &tokens {
+                    for exponent in [12, 16, 18, 21] {
+                        let amount = U256::from(10u128.pow(exponent));
+                        let params = SearchParams::new(4, 3);
+                        let search = |params| {
+                            search_candidates(
+                                &candidates,
+                                token_in.address,
+                                token_out.address,
+                                amount,
+                                params,
+                            )
+                        };
+
+                        let exact: Vec<U256> =
+                            search(params).iter().map(|r| r.total_amount_out).collect();
+                        let screened: Vec<U256> = search(params.screened(Screening::default()))
+                            .iter()
+                            .map(|r| r.total_amount_out)
+                            .collect();
+                        assert_eq!(
+                            screened, exact,
+                            "{} -> {} amount {}",
+                            token_in.symbol, token_out.symbol, amount
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_mixed_pool_types_match_brute_force() {
+        let amounts = [U256::from(10u128.pow(16)), U256::from(10u128.pow(20))];
+        let mut routed = std::collections::HashSet::new();
+
+        for seed in 0..15 {
+            let graph = mixed_graph(seed, 6);
+            let candidates = CandidateGraph::build(&graph, &CandidateConfig::unfiltered());
+            let token_in = address_from_u64(1);
+            let token_out = address_from_u64(1 + (seed % 5) + 1);
+
+            for &amount in &amounts {
+                let context = format!("seed {} amount {}", seed, amount);
+                let expected: Vec<U256> = brute_force(&graph, token_in, token_out, amount, 3)
+                    .iter()
+                    .take(3)
+                    .map(|r| r.total_amount_out)
+                    .collect();
+
+                let params = SearchParams::new(3, 3);
+                for params in [params, params.screened(Screening::default())] {
+                    let found = search_candidates(&candidates, token_in, token_out, amount, params);
+                    let outputs: Vec<U256> = found.iter().map(|r| r.total_amount_out).collect();
+                    assert_eq!(outputs, expected, "{}", context);
+                    for hop in found.iter().flat_map(|r| &r.hops) {
+                        assert_eq!(hop.pool_type, hop.pool.pool_type);
+                        routed.insert(hop.pool_type);
+                    }
+                }
+            }
+        }
+        assert_eq!(routed.len(), 3, "routes use every pool type");
+    }
+
+    #[test]
+    fn test_connector_search_matches_brute_force() {
+        let connectors = [2, 3].map(address_from_u64);
+        let config = CandidateConfig {
+            connector_tokens: connectors.to_vec(),
+            ..CandidateConfig::unfiltered()
+        };
+        let amount = U256::from(10u128.pow(18));
+
+        for seed in 0..20 {
+            let graph = random_graph(seed, 6);
+            let candidates = CandidateGraph::build(&graph, &config);
+            let token_in = address_from_u64(1);
+            let token_out = address_from_u64(6);
+
+            // Brute force restricted to routes whose intermediate
+            // tokens are all connectors
+            let expected: Vec<U256> = brute_force(&graph, token_in, token_out, amount, 4)
+                .into_iter()
+                .filter(|r| {
+                    r.hops[..r.hops.len() - 1]
+                        .iter()
+                        .all(|h| connectors.contains(&h.token_out))
+                })
+                .take(5)
+                .map(|r| r.total_amount_out)
+                .collect();
+
+            let found =
+                search_candidates(&candidates, token_in, token_out, amount, SearchParams::new(4, 5));
+            let outputs: Vec<U256> = found.iter().map(|r| r.total_amount_out).collect();
+            assert_eq!(outputs, expected, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn test_search_prices_hops_with_the_given_gas_model() {
+        let graph = random_graph(5, 6);
+        let [token_in, token_out] = [1, 6].map(address_from_u64);
+        let amount = U256::from(10u128.pow(18));
+        let model = GasModel {
+            concentrated_swap: 500_000,
+            tick_crossed: 90_000,
+            ..GasModel::DEFAULT
+        };
+
+        let params = SearchParams::new(4, 5).with_gas_model(model);
+        for params in [params, params.screened(Screening::default())] {
+            let routes = search_routes(&graph, token_in, token_out, amount, params);
+            assert!(!routes.is_empty());
+            for route in &routes {
+                let gas: u64 = route
+                    .hops
+                    .iter()
+                    .map(|h| {
+                        simulate_hop(&h.pool, h.token_in, h.amount_in, &model)
+                            .unwrap()
+                            .1
+                    })
+                    .sum();
+                assert_eq!(route.gas_estimate, gas);
+                assert!(route.gas_estimate >= route.hops.len() as u64 * model.concentrated_swap);
+            }
+        }
+    }
+
+    #[test]
+    fn test_search_stops_when_budget_is_spent() {
+        let graph = random_graph(3, 6);
+        let candidates = CandidateGraph::build(&graph, &CandidateConfig::unfiltered());
+        let [token_in, token_out] = [1, 6].map(address_from_u64);
+        let amount = U256::from(10u128.pow(18));
+        let cancel = CancellationToken::new();
+        let budget = SearchBudget::unlimited().with_cancel(cancel.clone());
+
+        for params in [SearchParams::new(4, 3), SearchParams::new(4, 3).screened(Screening::default())] {
+            let full = search_candidates(&candidates, token_in, token_out, amount, params);
+            let within =
+                search_candidates_within(&candidates, token_in, token_out, amount, params, &budget);
+            assert!(!full.is_empty());
+            assert_eq!(pool_ids(&within[0]), pool_ids(&full[0]));
+            assert!(within.iter().all(|r| !r.partial));
+        }
+
+        cancel.cancel();
+        let params = SearchParams::new(4, 3);
+        let spent = search_candidates_within(&candidates, token_in, token_out, amount, params, &budget);
+        assert!(spent.is_empty());
+    }
+
+    #[test]
+    fn test_keeps_routes_far_below_best_at_a_token() {
+        // A-B direct is far better than A-C-B, but the detour is still
+        // the second best way to reach D
+        let [a, b, c, d] = [1, 2, 3, 4].map(address_from_u64);
+        let node = |t: Address| TokenNode::new(t, String::new(), 18);
+        let graph = PoolGraph::new();
+        let liquidity = 10u128.pow(24);
+        graph.upsert_pool(pool(1, a, b, 100, liquidity, 0), node(a), node(b));
+        graph.upsert_pool(pool(2, a, c, 50_000, liquidity, 0), node(a), node(c));
+        graph.upsert_pool(pool(3, c, b, 50_000, liquidity, 0), node(c), node(b));
+        graph.upsert_pool(pool(4, b, d, 3000, liquidity, 0), node(b), node(d));
+
+        let amount = U256::from(10u128.pow(18));
+        let routes = search_routes(&graph, a, d, amount, SearchParams::new(4, 2));
+
+        assert_eq!(routes.len(), 2);
+        assert_eq!(routes[0].hops.len(), 2);
+        let detour: Vec<Address> = routes[1].hops.iter().map(|h| h.token_out).collect();
+        assert_eq!(detour, vec![c, b, d]);
+        // The detour reaches B with less than 95% of the direct amount
+        assert!(
+            routes[1].hops[1].amount_out * U256::from(100)
+                < routes[0].hops[0].amount_out * U256::from(95)
+        );
+    }
+
+    #[test]
+    fn test_hop_directions_follow_the_path() {
+        let graph = random_graph(7, 5);
+        let routes = search_routes(
+            &graph,
+            address_from_u64(1),
+            address_from_u64(5),
+            U256::from(10u128.pow(18)),
+            SearchParams::new(4, 10),
+        );
+
+        for route in &routes {
+            for pair in route.hops.windows(2) {
+                assert_eq!(pair[0].token_out, pair[1].token_in);
+                assert_eq!(pair[0].amount_out, pair[1].amount_in);
+            }
+            for hop in &route.hops {
+                let (out, _) =
+                    simulate_hop(&hop.pool, hop.token_in, hop.amount_in, &GasModel::DEFAULT)
+                        .unwrap();
+                assert_eq!(out, hop.amount_out);
+            }
+        }
+    }
+
+    #[test]
+    fn test_token_path_is_exact_past_64_tokens() {
+        // 1, 65 and 129 share a bit in the visited set
+        let path = TokenPath::new(1).with(7);
+        assert!(path.contains(1) && path.contains(7));
+        assert!(!path.contains(65));
+        assert!(!path.with(129).contains(65));
+
+        assert!(TokenPath::new(1).is_subset_of(&path));
+        assert!(!TokenPath::new(65).is_subset_of(&path));
+        assert!(!TokenPath::new(1).with(65).is_subset_of(&path.with(129)));
+        assert!(path.is_subset_of(&TokenPath::new(7).with(65).with(1)));
+    }
+
+    #[test]
+    fn test_degenerate_requests() {
+        let graph = random_graph(1, 4);
+        let [a, b] = [1, 2].map(address_from_u64);
+        let amount = U256::from(10u128.pow(18));
+
+        assert!(search_routes(&graph, a, a, amount, SearchParams::new(4, 5)).is_empty());
+        assert!(search_routes(&graph, a, b, amount, SearchParams::new(4, 0)).is_empty());
+        assert!(search_routes(&graph, a, b, amount, SearchParams::new(0, 5)).is_empty());
+        assert!(search_routes(&graph, a, b, U256::ZERO, SearchParams::new(4, 5)).is_empty());
+        assert!(search_routes(
+            &graph,
+            a,
+            address_from_u64(99),
+            amount,
+            SearchParams::new(4, 5)
+        )
+        .is_empty());
+    }
+}
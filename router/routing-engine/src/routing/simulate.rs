@@ -0,0 +1,469 @@
+use crate::gas::GasModel;
+use crate::graph::{PoolEdge, PoolGraph, PoolType};
+use crate::liquidity::concentrated::{step_target, ticks_crossed};
+use crate::liquidity::{LiquiditySource, PoolModel};
+use crate::routing::{Route, RouteHop, SplitRoute};
+use crate::utils::u256_ext::{from_f64, to_f64};
+use crate::utils::{Result, RouterError};
+use alloy_primitives::{Address, B256, U256};
+
+/// Simulate a swap of `amount_in` of `token_in` through one pool
+///
+/// Returns the output amount from the pool's [`LiquiditySource`] model
+/// and the hop's gas under `gas`, including the ticks the swap crosses.
+/// This is the edge function of the route search: every route the
+/// router produces is priced hop by hop with it.
+pub fn simulate_hop(
+    pool: &PoolEdge,
+    token_in: Address,
+    amount_in: U256,
+    gas: &GasModel,
+) -> Result<(U256, u64)> {
+    // Determine swap direction
+    let zero_for_one = pool.zero_for_one(token_in).ok_or_else(|| {
+        RouterError::InternalError("Token not in pool".to_string())
+    })?;
+
+    let (amount_out, ticks_crossed) = PoolModel::of(pool).simulate(zero_for_one, amount_in)?;
+
+    // Check for dust
+    if amount_out < U256::from(100) {
+        return Err(RouterError::InsufficientLiquidity {
+            required: amount_in.to_string(),
+            available: amount_out.to_string(),
+        });
+    }
+
+    Ok((amount_out, gas.hop_gas(pool, ticks_crossed)))
+}
+
+/// Liquidity of a pool in one swap direction: a bound on what
+/// `simulate_hop` can return for `token_in`, in units of the other token
+///
+/// Zero if the pool cannot be swapped at all (no liquidity, or
+/// `token_in` is not one of its tokens).
+pub fn hop_depth(pool: &PoolEdge, token_in: Address) -> U256 {
+    match pool.zero_for_one(token_in) {
+        Some(zero_for_one) => PoolModel::of(pool).depth(zero_for_one),
+        None => U256::ZERO,
+    }
+}
+
+/// Pool state in `f64` for pricing hops approximately
+///
+/// Mirrors [`simulate_hop`] in floating point, so a hop costs a few
+/// multiplications instead of `U256` division. Estimates are within
+/// about 1e-12 of the exact output for amounts far above dust; the
+/// exact integer math rounds each hop by a few wei. Stableswap pools
+/// have no closed form, and solving their invariant in `f64` loses too
+/// much precision on small trades, so they are priced exactly.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum HopEstimate {
+    Concentrated {
+        /// Current sqrt price (not scaled by 2^96)
+        sqrt_price: f64,
+        /// Distance to the step target for zero-for-one and one-for-zero swaps
+        down: f64,
+        up: f64,
+        liquidity: f64,
+        fee_factor: f64,
+    },
+    ConstantProduct {
+        reserves: [f64; 2],
+        fee_factor: f64,
+    },
+    Exact,
+}
+
+/// Estimated outputs below this are treated as dust. Slightly below
+/// the exact threshold so that rounding never drops a hop the exact
+/// simulation would accept.
+const ESTIMATE_DUST: f64 = 99.0;
+
+impl HopEstimate {
+    pub(crate) fn new(pool: &PoolEdge) -> Self {
+        let fee_factor = 1.0 - pool.fee as f64 / 1_000_000.0;
+        match pool.pool_type {
+            PoolType::Concentrated => {
+                let q96 = 2f64.powi(96);
+                let scaled = |value: U256| to_f64(value) / q96;
+                let current = pool.sqrt_price_x96;
+                let distance = |target: U256| {
+                    if target > current {
+                        scaled(target - current)
+                    } else {
+                        scaled(current - target)
+                    }
+                };
+
+                HopEstimate::Concentrated {
+                    sqrt_price: scaled(current),
+                    down: distance(step_target(pool, true)),
+                    up: distance(step_target(pool, false)),
+                    liquidity: pool.liquidity as f64,
+                    fee_factor,
+                }
+            }
+            PoolType::ConstantProduct => HopEstimate::ConstantProduct {
+                reserves: pool.reserves.map(to_f64),
+                fee_factor,
+            },
+            PoolType::StableSwap => HopEstimate::Exact,
+        }
+    }
+
+    /// Estimated output and gas under `gas` for `amount_in` through
+    /// `pool` (the pool this estimate was built from); `None` where
+    /// [`simulate_hop`] would fail
+    pub(crate) fn hop(
+        &self,
+        pool: &PoolEdge,
+        zero_for_one: bool,
+        amount_in: f64,
+        gas: &GasModel,
+    ) -> Option<(f64, u64)> {
+        let (amount_out, gas) = match *self {
+            HopEstimate::Concentrated {
+                sqrt_price,
+                down,
+                up,
+                liquidity,
+                fee_factor,
+            } => {
+                let (amount_out, next_price) =
+                    concentrated_out(sqrt_price, down, up, liquidity, zero_for_one, amount_in * fee_factor)?;
+                // Without tick data only reaching the target crosses a tick
+                let crossed = if pool.ticks.is_none() {
+                    next_price.is_none() as u32
+                } else {
+                    let sqrt_price_next = next_price.map_or_else(
+                        || step_target(pool, zero_for_one),
+                        |price| from_f64(price * 2f64.powi(96)),
+                    );
+                    ticks_crossed(pool, zero_for_one, sqrt_price_next)
+                };
+                (amount_out, gas.hop_gas(pool, crossed))
+            }
+            HopEstimate::ConstantProduct {
+                reserves,
+                fee_factor,
+            } => {
+                let (reserve_in, reserve_out) = if zero_for_one {
+                    (reserves[0], reserves[1])
+                } else {
+                    (reserves[1], reserves[0])
+                };
+                if reserve_in == 0.0 || reserve_out == 0.0 {
+                    return None;
+                }
+                let amount = amount_in * fee_factor;
+                (
+                    amount * reserve_out / (reserve_in + amount),
+                    gas.hop_gas(pool, 0),
+                )
+            }
+            HopEstimate::Exact => {
+                let token_in = if zero_for_one { pool.token0 } else { pool.token1 };
+                let (amount_out, gas) =
+                    simulate_hop(pool, token_in, from_f64(amount_in), gas).ok()?;
+                (to_f64(amount_out), gas)
+            }
+        };
+
+        (amount_out.is_finite() && amount_out >= ESTIMATE_DUST).then_some((amount_out, gas))
+    }
+}
+
+/// Output of one concentrated-liquidity step in `f64`, for an input
+/// already net of fees, and the sqrt price it stops at (`None` if it
+/// reaches its target tick)
+fn concentrated_out(
+    price: f64,
+    down: f64,
+    up: f64,
+    liquidity: f64,
+    zero_for_one: bool,
+    amount: f64,
+) -> Option<(f64, Option<f64>)> {
+    if liquidity == 0.0 || price == 0.0 {
+        return None;
+    }
+
+    let step = if zero_for_one {
+        // Stops at the target: pays L * (sqrtP - sqrtP_target)
+        let target = price - down;
+        if target <= 0.0 {
+            return None;
+        }
+        let max_in = liquidity * down / (price * target);
+        if amount >= max_in {
+            (liquidity * down, None)
+        } else {
+            let next = price * liquidity / (liquidity + amount * price);
+            (amount * price * price * liquidity / (liquidity + amount * price), Some(next))
+        }
+    } else {
+        let target = price + up;
+        let max_in = liquidity * up;
+        if amount >= max_in {
+            (liquidity * up / (price * target), None)
+        } else {
+            let next = price + amount / liquidity;
+            (amount / (price * next), Some(next))
+        }
+    };
+    Some(step)
+}
+
+/// Calculate price impact percentage
+pub(crate) fn calculate_price_impact(amount_in: U256, amount_out: U256) -> f64 {
+    if amount_in.is_zero() || amount_out.is_zero() {
+        return 0.0;
+    }
+
+    // Price impact = (expected_price - actual_price) / expected_price * 100
+    // Simplified: we assume expected 1:1 and calculate deviation
+    let in_f64 = to_f64(amount_in);
+    let out_f64 = to_f64(amount_out);
+
+    let actual_rate = in_f64 / out_f64;
+    let impact = (actual_rate - 1.0).abs() * 100.0;
+
+    impact.min(100.0) // Cap at 100%
+}
+
+
+/// Re-run a route hop by hop against the graph's current pool state
+///
+/// Each hop uses the pool as it is in `graph` now (not the copy stored
+/// in the route), so this answers "what would this route return if
+/// executed against this state". Hop gas is summed under `gas_model`.
+/// Fails if a pool is gone or cannot fill the swap.
+pub fn resimulate_route(
+    graph: &PoolGraph,
+    route: &Route,
+    amount_in: U256,
+    gas_model: &GasModel,
+) -> Result<Route> {
+    let mut hops = Vec::with_capacity(route.hops.len());
+    let mut amount = amount_in;
+    let mut gas = 0u64;
+
+    for hop in &route.hops {
+        let pool = graph
+            .get_pool(hop.pool.pool_id)
+            .ok_or_else(|| RouterError::PoolNotFound(B256::from(hop.pool.pool_id).to_string()))?;
+
+        let (amount_out, hop_gas) = simulate_hop(&pool, hop.token_in, amount, gas_model)?;
+        hops.push(RouteHop::new(
+            pool,
+            hop.token_in,
+            hop.token_out,
+            amount,
+            amount_out,
+        ));
+        gas += hop_gas;
+        amount = amount_out;
+    }
+
+    Ok(Route::new(hops, amount_in, amount, route.price_impact, gas)
+        .with_native(route.wrap, route.unwrap))
+}
+
+/// Total output of a split route re-run against the graph's current state
+///
+/// Every leg is re-simulated with the input amount it was quoted with.
+pub fn resimulate_split(graph: &PoolGraph, split: &SplitRoute) -> Result<U256> {
+    split
+        .routes
+        .iter()
+        .try_fold(U256::ZERO, |total, (route, _)| {
+            // Only the outputs are summed, so the gas model does not matter
+            let leg = resimulate_route(graph, route, route.total_amount_in, &GasModel::DEFAULT)?;
+            Ok(total + leg.total_amount_out)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::GraphFixture;
+    use crate::routing::find_best_single_hop_route;
+    use crate::sync::pool_sync::DEFAULT_FIXTURE;
+    use crate::utils::address_from_u64;
+    use crate::utils::addresses::{usdc, weth};
+    use crate::utils::math::tick_to_sqrt_price_x96;
+
+    fn dev_graph() -> PoolGraph {
+        let graph = PoolGraph::new();
+        graph
+            .import_fixture(&GraphFixture::from_toml(DEFAULT_FIXTURE).unwrap())
+            .unwrap();
+        graph
+    }
+
+    #[test]
+    fn test_resimulate_matches_on_same_state() {
+        let graph = dev_graph();
+        let amount = U256::from(10u128.pow(18));
+        let route = find_best_single_hop_route(&graph, weth(), usdc(), amount).unwrap();
+
+        let replayed = resimulate_route(&graph, &route, amount, &GasModel::DEFAULT).unwrap();
+        assert_eq!(replayed.total_amount_out, route.total_amount_out);
+    }
+
+    #[test]
+    fn test_resimulate_uses_new_pool_state() {
+        let graph = dev_graph();
+        let amount = U256::from(10u128.pow(18));
+        let route = find_best_single_hop_route(&graph, weth(), usdc(), amount).unwrap();
+
+        let mut pool = graph.get_pool(route.hops[0].pool.pool_id).unwrap();
+        pool.liquidity /= 100;
+        let tokens = graph.get_all_tokens();
+        let token = |a| tokens.iter().find(|t| t.address == a).unwrap().clone();
+        graph.upsert_pool(pool.clone(), token(pool.token0), token(pool.token1));
+
+        let replayed = resimulate_route(&graph, &route, amount, &GasModel::DEFAULT).unwrap();
+        assert!(replayed.total_amount_out < route.total_amount_out);
+    }
+
+    #[test]
+    fn test_resimulate_missing_pool_fails() {
+        let graph = dev_graph();
+        let amount = U256::from(10u128.pow(18));
+        let route = find_best_single_hop_route(&graph, weth(), usdc(), amount).unwrap();
+
+        let err =
+            resimulate_route(&PoolGraph::new(), &route, amount, &GasModel::DEFAULT).unwrap_err();
+        assert!(matches!(err, RouterError::PoolNotFound(_)));
+    }
+
+    #[test]
+    fn test_price_impact_of_amounts_above_u128() {
+        let amount = U256::from(u128::MAX) * U256::from(4);
+        assert_eq!(calculate_price_impact(amount, amount), 0.0);
+        assert!((calculate_price_impact(amount, amount / U256::from(2)) - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_hop_depth_caps_simulated_output() {
+        let graph = dev_graph();
+        let pool = graph.get_pools_for_token(weth()).remove(0);
+
+        for token_in in [pool.token0, pool.token1] {
+            let depth = hop_depth(&pool, token_in);
+            let hop = |amount: u128| {
+                simulate_hop(&pool, token_in, U256::from(amount), &GasModel::DEFAULT)
+            };
+            let (small, _) = hop(10u128.pow(15)).unwrap();
+            let (huge, _) = hop(10u128.pow(36)).unwrap();
+            assert!(small < depth);
+            assert_eq!(huge, depth);
+        }
+        assert_eq!(hop_depth(&pool, address_from_u64(99)), U256::ZERO);
+    }
+
+    #[test]
+    fn test_hop_estimate_matches_exact_simulation() {
+        let fees = [100, 500, 3000, 10000];
+        let mut checked = 0;
+        for tick in (-200_000..=200_000).step_by(25_000) {
+            for liquidity_exp in [15, 18, 21, 24] {
+                for (i, fee) in fees.iter().enumerate() {
+                    let pool = PoolEdge::new(
+                        [i as u8; 32],
+                        address_from_u64(1),
+                        address_from_u64(2),
+                        *fee,
+                        [1, 10, 60, 200][i],
+                        10u128.pow(liquidity_exp),
+                        tick_to_sqrt_price_x96(tick),
+                        tick,
+                    );
+                    let estimate = HopEstimate::new(&pool);
+
+                    for token_in in [pool.token0, pool.token1] {
+                        let zero_for_one = token_in == pool.token0;
+                        for amount_exp in [9, 12, 15, 18, 21, 24] {
+                            let amount = U256::from(10u128.pow(amount_exp));
+                            let exact = simulate_hop(&pool, token_in, amount, &GasModel::DEFAULT);
+                            let approx = estimate.hop(
+                                &pool,
+                                zero_for_one,
+                                to_f64(amount),
+                                &GasModel::DEFAULT,
+                            );
+                            let Ok((exact, gas)) = exact else {
+                                continue;
+                            };
+                            let (approx, approx_gas) = approx.expect("exact hop succeeded");
+                            assert_eq!(gas, approx_gas);
+
+                            let exact = to_f64(exact);
+                            let error = (approx - exact).abs() / exact;
+                            // A few wei of integer rounding on small outputs
+                            let allowed = 1e-9 + 4.0 / exact;
+                            assert!(
+                                error <= allowed,
+                                "tick {} L 1e{} fee {} zero_for_one {} amount 1e{}: {} vs {}",
+                                tick, liquidity_exp, fee, zero_for_one, amount_exp, approx, exact
+                            );
+                            checked += 1;
+                        }
+                    }
+                }
+            }
+        }
+        assert!(checked > 1000);
+    }
+
+    #[test]
+    fn test_reserve_pool_estimates_match_exact_simulation() {
+        let (a, b) = (address_from_u64(1), address_from_u64(2));
+        for reserve_exp in [18, 21, 24] {
+            let reserves = [U256::from(10u128.pow(reserve_exp)), U256::from(3 * 10u128.pow(reserve_exp))];
+            let pools = [
+                PoolEdge::constant_product([1; 32], a, b, 3000, reserves),
+                PoolEdge::stable_swap([2; 32], a, b, 400, reserves, 100, [18, 18]),
+            ];
+            for pool in &pools {
+                let estimate = HopEstimate::new(pool);
+                for zero_for_one in [true, false] {
+                    let token_in = if zero_for_one { a } else { b };
+                    for amount_exp in [9, 15, 21, 27] {
+                        let amount = U256::from(10u128.pow(amount_exp));
+                        let exact = simulate_hop(pool, token_in, amount, &GasModel::DEFAULT);
+                        let approx =
+                            estimate.hop(pool, zero_for_one, to_f64(amount), &GasModel::DEFAULT);
+                        let Ok((exact, gas)) = exact else {
+                            assert!(approx.is_none());
+                            continue;
+                        };
+                        let (approx, approx_gas) = approx.expect("exact hop succeeded");
+                        assert_eq!(gas, approx_gas);
+
+                        let exact = to_f64(exact);
+                        assert!(
+                            (approx - exact).abs() / exact <= 1e-9 + 2.0 / exact,
+                            "{} reserves 1e{} amount 1e{}: {} vs {}",
+                            pool.pool_type, reserve_exp, amount_exp, approx, exact
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_calculate_price_impact() {
+        let amount_in = U256::from(1_000_000);
+        let amount_out = U256::from(997_000); // ~0.3% loss
+
+        let impact = calculate_price_impact(amount_in, amount_out);
+
+        // Should be very small impact
+        assert!(impact < 1.0);
+        assert!(impact >= 0.0);
+    }
+}
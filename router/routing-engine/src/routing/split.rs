@@ -266,6 +266,7 @@ fn calculate_combined_price_impact(routes: &[(Route, u8)]) -> f64 {
 }
 
 #[cfg(test)]
+#[allow(clippy::len_zero)]
 mod tests {
     use super::*;
     use crate::graph::PoolEdge;
@@ -0,0 +1,288 @@
+use crate::metrics::Metrics;
+use serde::{Deserialize, Serialize};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::sync::oneshot;
+
+/// Limits for the compute pool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ComputeConfig {
+    /// Worker threads (`0` for one per CPU)
+    pub threads: usize,
+    /// Jobs allowed to wait for a worker; further jobs are rejected
+    pub max_queue: usize,
+    /// Jobs that waited longer than this for a worker are dropped
+    /// unstarted (milliseconds)
+    pub max_queue_wait_ms: u64,
+}
+
+impl Default for ComputeConfig {
+    fn default() -> Self {
+        Self {
+            threads: 0,
+            max_queue: 64,
+            max_queue_wait_ms: 1000,
+        }
+    }
+}
+
+/// Why a job did not run to completion
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ComputeError {
+    #[error("Compute queue is full ({0} jobs waiting)")]
+    QueueFull(usize),
+
+    #[error("Job waited {0:?} for a compute worker")]
+    QueueTimeout(Duration),
+
+    #[error("Compute job panicked")]
+    Panicked,
+}
+
+/// Dedicated thread pool for CPU-bound routing work
+///
+/// Jobs run on their own rayon pool, so route searches (including their
+/// `par_iter` sections) never occupy the async runtime's workers.
+/// Admission is bounded: once `max_queue` jobs are waiting, new jobs are
+/// rejected with [`ComputeError::QueueFull`], and a job that waited past
+/// `max_queue_wait_ms` is dropped with [`ComputeError::QueueTimeout`]
+/// instead of running for a caller that has likely given up. A job
+/// whose caller went away before it started is skipped.
+pub struct ComputePool {
+    pool: rayon::ThreadPool,
+    config: ComputeConfig,
+    counters: Arc<Counters>,
+}
+
+/// Job counts shared with the workers
+struct Counters {
+    queued: AtomicUsize,
+    running: AtomicUsize,
+    metrics: Option<Arc<Metrics>>,
+}
+
+impl Counters {
+    fn publish(&self) {
+        if let Some(metrics) = &self.metrics {
+            metrics.observe_compute_queue(
+                self.queued.load(Ordering::Relaxed),
+                self.running.load(Ordering::Relaxed),
+            );
+        }
+    }
+}
+
+impl ComputePool {
+    pub fn new(config: ComputeConfig) -> Self {
+        Self::build(config, None)
+    }
+
+    /// Report queue depth, rejections and queue wait to the given metrics
+    pub fn with_metrics(config: ComputeConfig, metrics: Arc<Metrics>) -> Self {
+        Self::build(config, Some(metrics))
+    }
+
+    fn build(config: ComputeConfig, metrics: Option<Arc<Metrics>>) -> Self {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(config.threads)
+            .thread_name(|i| format!("routing-compute-{}", i))
+            .build()
+            .expect("compute pool threads can be spawned");
+
+        Self {
+            pool,
+            config,
+            counters: Arc::new(Counters {
+                queued: AtomicUsize::new(0),
+                running: AtomicUsize::new(0),
+                metrics,
+            }),
+        }
+    }
+
+    /// Run `job` on the pool and wait for its result
+    pub async fn run<T, F>(&self, job: F) -> Result<T, ComputeError>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let counters = self.counters.clone();
+        let waiting = counters.queued.fetch_add(1, Ordering::AcqRel);
+        if waiting >= self.config.max_queue {
+            counters.queued.fetch_sub(1, Ordering::AcqRel);
+            if let Some(metrics) = &counters.metrics {
+                metrics.record_compute_rejected("queue_full");
+            }
+            return Err(ComputeError::QueueFull(waiting));
+        }
+        counters.publish();
+
+        let max_wait = Duration::from_millis(self.config.max_queue_wait_ms);
+        let enqueued = Instant::now();
+        let (tx, rx) = oneshot::channel();
+        self.pool.spawn(move || {
+            counters.queued.fetch_sub(1, Ordering::AcqRel);
+            let waited = enqueued.elapsed();
+            if let Some(metrics) = &counters.metrics {
+                metrics.observe_compute_wait(waited);
+            }
+
+            if tx.is_closed() {
+                counters.publish();
+                return;
+            }
+            if waited > max_wait {
+                counters.publish();
+                if let Some(metrics) = &counters.metrics {
+                    metrics.record_compute_rejected("queue_timeout");
+                }
+                let _ = tx.send(Err(ComputeError::QueueTimeout(waited)));
+                return;
+            }
+
+            counters.running.fetch_add(1, Ordering::AcqRel);
+            counters.publish();
+            let result = catch_unwind(AssertUnwindSafe(job)).map_err(|_| ComputeError::Panicked);
+            counters.running.fetch_sub(1, Ordering::AcqRel);
+            counters.publish();
+            let _ = tx.send(result);
+        });
+
+        rx.await.unwrap_or(Err(ComputeError::Panicked))
+    }
+
+    /// Jobs waiting for a worker
+    pub fn queue_depth(&self) -> usize {
+        self.counters.queued.load(Ordering::Acquire)
+    }
+
+    /// Jobs currently running
+    pub fn running(&self) -> usize {
+        self.counters.running.load(Ordering::Acquire)
+    }
+
+    /// Whether new jobs are being rejected
+    pub fn is_saturated(&self) -> bool {
+        self.queue_depth() >= self.config.max_queue
+    }
+
+    pub fn threads(&self) -> usize {
+        self.pool.current_num_threads()
+    }
+
+    pub fn config(&self) -> &ComputeConfig {
+        &self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn single_thread(max_queue: usize, max_queue_wait_ms: u64) -> ComputePool {
+        ComputePool::new(ComputeConfig {
+            threads: 1,
+            max_queue,
+            max_queue_wait_ms,
+        })
+    }
+
+    /// Occupy the only worker until the returned sender is dropped
+    async fn block_worker(pool: &Arc<ComputePool>) -> mpsc::Sender<()> {
+        let (started_tx, started_rx) = oneshot::channel();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let blocker = pool.clone();
+        tokio::spawn(async move {
+            blocker
+                .run(move || {
+                    let _ = started_tx.send(());
+                    let _ = release_rx.recv();
+                })
+                .await
+        });
+        started_rx.await.unwrap();
+        release_tx
+    }
+
+    #[tokio::test]
+    async fn test_runs_jobs_off_the_runtime() {
+        let pool = single_thread(4, 1000);
+        let name = pool
+            .run(|| std::thread::current().name().map(String::from))
+            .await
+            .unwrap();
+
+        assert_eq!(name.as_deref(), Some("routing-compute-0"));
+        assert_eq!(pool.run(|| 2 + 2).await, Ok(4));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_jobs_when_queue_is_full() {
+        let pool = Arc::new(single_thread(1, 10_000));
+        let release = block_worker(&pool).await;
+
+        let queued = tokio::spawn({
+            let pool = pool.clone();
+            async move { pool.run(|| 1).await }
+        });
+        while pool.queue_depth() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        assert!(pool.is_saturated());
+        assert_eq!(pool.run(|| 2).await, Err(ComputeError::QueueFull(1)));
+
+        drop(release);
+        assert_eq!(queued.await.unwrap(), Ok(1));
+        assert_eq!(pool.queue_depth(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_drops_jobs_that_waited_too_long() {
+        let pool = Arc::new(single_thread(4, 10));
+        let release = block_worker(&pool).await;
+
+        let queued = tokio::spawn({
+            let pool = pool.clone();
+            async move { pool.run(|| 1).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        drop(release);
+
+        assert!(matches!(
+            queued.await.unwrap(),
+            Err(ComputeError::QueueTimeout(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_panicking_job_keeps_the_pool() {
+        let pool = single_thread(4, 1000);
+
+        let result: Result<(), _> = pool.run(|| panic!("boom")).await;
+        assert_eq!(result, Err(ComputeError::Panicked));
+        assert_eq!(pool.run(|| 7).await, Ok(7));
+        assert_eq!(pool.running(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_reports_queue_metrics() {
+        let metrics = Arc::new(Metrics::new());
+        let config = ComputeConfig {
+            threads: 1,
+            max_queue: 0,
+            max_queue_wait_ms: 1000,
+        };
+        let pool = ComputePool::with_metrics(config, metrics.clone());
+
+        assert_eq!(pool.run(|| ()).await, Err(ComputeError::QueueFull(0)));
+        let output = metrics.encode();
+        assert!(output.contains("compute_jobs_rejected_total{reason=\"queue_full\"} 1"));
+        assert!(output.contains("compute_queue_depth 0"));
+    }
+}
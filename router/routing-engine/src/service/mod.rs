@@ -0,0 +1,7 @@
+pub mod compute;
+pub mod signal;
+pub mod supervisor;
+
+pub use compute::{ComputeConfig, ComputeError, ComputePool};
+pub use signal::wait_for_signal;
+pub use supervisor::{RestartPolicy, Supervisor};
@@ -0,0 +1,330 @@
+use crate::metrics::Metrics;
+use std::borrow::Cow;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+type HookFuture = Pin<Box<dyn Future<Output = Result<(), String>> + Send>>;
+type ShutdownHook = Box<dyn FnOnce() -> HookFuture + Send>;
+
+/// Task names are usually literals, but per-chain tasks are formatted
+pub type TaskName = Cow<'static, str>;
+
+/// Backoff policy for restarting a background task
+#[derive(Debug, Clone)]
+pub struct RestartPolicy {
+    /// Delay before the first restart
+    pub initial_backoff: Duration,
+    /// Upper bound for the doubling delay
+    pub max_backoff: Duration,
+    /// A run at least this long resets the delay to `initial_backoff`
+    pub reset_after: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            reset_after: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Runs background tasks, restarts them when they fail, and stops them
+/// on shutdown.
+///
+/// Every task receives the supervisor's cancellation token and is
+/// expected to return once it is cancelled. A task that returns (with
+/// or without an error) or panics before shutdown is restarted
+/// according to its [`RestartPolicy`].
+pub struct Supervisor {
+    shutdown: CancellationToken,
+    tasks: Vec<(TaskName, JoinHandle<()>)>,
+    hooks: Vec<(TaskName, ShutdownHook)>,
+    metrics: Option<Arc<Metrics>>,
+}
+
+impl Supervisor {
+    pub fn new(shutdown: CancellationToken) -> Self {
+        Self {
+            shutdown,
+            tasks: Vec::new(),
+            hooks: Vec::new(),
+            metrics: None,
+        }
+    }
+
+    /// Report task restarts to the given metrics
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Token cancelled when shutdown begins
+    pub fn shutdown_token(&self) -> CancellationToken {
+        self.shutdown.clone()
+    }
+
+    /// Start a supervised task
+    ///
+    /// `factory` is called for every (re)start with the shutdown token.
+    pub fn spawn<F, Fut>(&mut self, name: impl Into<TaskName>, policy: RestartPolicy, factory: F)
+    where
+        F: Fn(CancellationToken) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        let shutdown = self.shutdown.clone();
+        let metrics = self.metrics.clone();
+        let name = name.into();
+        let handle = tokio::spawn(supervise(name.clone(), policy, factory, shutdown, metrics));
+        self.tasks.push((name, handle));
+    }
+
+    /// Register work to run after all tasks have stopped (e.g. persisting
+    /// state). Hooks run in registration order.
+    pub fn on_shutdown<F, Fut>(&mut self, name: impl Into<TaskName>, hook: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        self.hooks
+            .push((name.into(), Box::new(move || Box::pin(hook()) as HookFuture)));
+    }
+
+    /// Cancel all tasks, then run the shutdown hooks, all within
+    /// `timeout`.
+    pub async fn shutdown(self, timeout: Duration) {
+        self.shutdown_until(tokio::time::Instant::now() + timeout)
+            .await
+    }
+
+    /// Cancel all tasks, wait until `deadline` for them to stop, then run
+    /// the shutdown hooks within what is left of it.
+    ///
+    /// Tasks still running at the deadline are aborted; a hook still
+    /// running at the deadline is dropped.
+    pub async fn shutdown_until(self, deadline: tokio::time::Instant) {
+        self.shutdown.cancel();
+
+        for (name, handle) in self.tasks {
+            let abort = handle.abort_handle();
+            match tokio::time::timeout_at(deadline, handle).await {
+                Ok(_) => tracing::debug!("Task {} stopped", name),
+                Err(_) => {
+                    tracing::warn!("Task {} did not stop before the deadline, aborting", name);
+                    abort.abort();
+                }
+            }
+        }
+
+        for (name, hook) in self.hooks {
+            match tokio::time::timeout_at(deadline, hook()).await {
+                Ok(Ok(())) => tracing::info!("Shutdown hook {} completed", name),
+                Ok(Err(e)) => tracing::error!("Shutdown hook {} failed: {}", name, e),
+                Err(_) => {
+                    tracing::error!("Shutdown hook {} did not finish before the deadline", name)
+                }
+            }
+        }
+    }
+}
+
+async fn supervise<F, Fut>(
+    name: TaskName,
+    policy: RestartPolicy,
+    factory: F,
+    shutdown: CancellationToken,
+    metrics: Option<Arc<Metrics>>,
+) where
+    F: Fn(CancellationToken) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), String>> + Send + 'static,
+{
+    let mut backoff = policy.initial_backoff;
+
+    while !shutdown.is_cancelled() {
+        tracing::info!("Starting task {}", name);
+        let started = Instant::now();
+
+        // Run in its own task so a panic is reported instead of unwinding
+        // here; abort it if this supervisor is itself aborted
+        let handle = tokio::spawn(factory(shutdown.clone()));
+        let _guard = AbortOnDrop(handle.abort_handle());
+        let outcome = handle.await;
+
+        if shutdown.is_cancelled() {
+            break;
+        }
+
+        match outcome {
+            Ok(Ok(())) => tracing::warn!("Task {} exited unexpectedly", name),
+            Ok(Err(e)) => tracing::error!("Task {} failed: {}", name, e),
+            Err(e) if e.is_panic() => tracing::error!("Task {} panicked", name),
+            Err(e) => tracing::error!("Task {} was cancelled: {}", name, e),
+        }
+
+        if started.elapsed() >= policy.reset_after {
+            backoff = policy.initial_backoff;
+        }
+
+        if let Some(metrics) = &metrics {
+            metrics.record_task_restart(&name);
+        }
+
+        tracing::info!("Restarting task {} in {:?}", name, backoff);
+        tokio::select! {
+            _ = tokio::time::sleep(backoff) => {}
+            _ = shutdown.cancelled() => break,
+        }
+
+        backoff = (backoff * 2).min(policy.max_backoff);
+    }
+
+    tracing::info!("Task {} stopped", name);
+}
+
+struct AbortOnDrop(tokio::task::AbortHandle);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn fast_policy() -> RestartPolicy {
+        RestartPolicy {
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(5),
+            reset_after: Duration::from_secs(60),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_task_is_restarted() {
+        let mut supervisor = Supervisor::new(CancellationToken::new());
+        let runs = Arc::new(AtomicUsize::new(0));
+
+        let counter = runs.clone();
+        supervisor.spawn("flaky", fast_policy(), move |_| {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Err("boom".to_string())
+            }
+        });
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        supervisor.shutdown(Duration::from_secs(1)).await;
+
+        assert!(runs.load(Ordering::SeqCst) >= 3, "Task should be restarted");
+    }
+
+    #[tokio::test]
+    async fn test_panicking_task_is_restarted() {
+        let metrics = Arc::new(Metrics::new());
+        let mut supervisor =
+            Supervisor::new(CancellationToken::new()).with_metrics(metrics.clone());
+        let runs = Arc::new(AtomicUsize::new(0));
+
+        let counter = runs.clone();
+        supervisor.spawn("panicky", fast_policy(), move |shutdown| {
+            let counter = counter.clone();
+            async move {
+                if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                    panic!("first run panics");
+                }
+                shutdown.cancelled().await;
+                Ok(())
+            }
+        });
+
+        // Panic reporting can be slow (backtraces), so poll for the restart
+        for _ in 0..200 {
+            if runs.load(Ordering::SeqCst) >= 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        supervisor.shutdown(Duration::from_secs(1)).await;
+
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+        assert!(metrics
+            .encode()
+            .contains("background_task_restarts_total{task=\"panicky\"} 1"));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_stops_tasks_then_runs_hooks() {
+        let mut supervisor = Supervisor::new(CancellationToken::new());
+        let stopped = Arc::new(AtomicUsize::new(0));
+        let hook_ran_after_stop = Arc::new(AtomicUsize::new(0));
+
+        let flag = stopped.clone();
+        supervisor.spawn("worker", fast_policy(), move |shutdown| {
+            let flag = flag.clone();
+            async move {
+                shutdown.cancelled().await;
+                flag.store(1, Ordering::SeqCst);
+                Ok(())
+            }
+        });
+
+        let flag = stopped.clone();
+        let result = hook_ran_after_stop.clone();
+        supervisor.on_shutdown("persist", move || async move {
+            result.store(flag.load(Ordering::SeqCst), Ordering::SeqCst);
+            Ok(())
+        });
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        supervisor.shutdown(Duration::from_secs(1)).await;
+
+        assert_eq!(stopped.load(Ordering::SeqCst), 1);
+        assert_eq!(hook_ran_after_stop.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_stuck_task_is_aborted() {
+        let mut supervisor = Supervisor::new(CancellationToken::new());
+
+        supervisor.spawn("stuck", fast_policy(), |_| async {
+            std::future::pending::<()>().await;
+            Ok(())
+        });
+
+        let start = Instant::now();
+        supervisor.shutdown(Duration::from_millis(50)).await;
+
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_hooks_share_the_shutdown_deadline() {
+        let mut supervisor = Supervisor::new(CancellationToken::new());
+        let completed = Arc::new(AtomicUsize::new(0));
+
+        for name in ["first", "second"] {
+            let completed = completed.clone();
+            supervisor.on_shutdown(name, move || async move {
+                tokio::time::sleep(Duration::from_millis(80)).await;
+                completed.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            });
+        }
+
+        // Each hook fits the timeout on its own, but not both together
+        let start = Instant::now();
+        supervisor.shutdown(Duration::from_millis(120)).await;
+
+        assert_eq!(completed.load(Ordering::SeqCst), 1);
+        assert!(start.elapsed() < Duration::from_millis(160));
+    }
+}
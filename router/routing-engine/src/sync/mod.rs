@@ -1,3 +1,5 @@
 pub mod pool_sync;
+pub mod status;
 
 pub use pool_sync::{PoolSyncer, SyncConfig};
+pub use status::SyncStatus;
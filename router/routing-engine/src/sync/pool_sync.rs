@@ -1,9 +1,12 @@
-use crate::graph::{PoolEdge, PoolGraph, TokenNode};
-use crate::utils::address_from_u64;
-use crate::utils::math::tick_to_sqrt_price_x96;
+use crate::graph::{GraphFixture, PoolGraph};
+use crate::metrics::Metrics;
+use crate::routing::Router;
+use crate::sync::SyncStatus;
 use alloy_primitives::{Address, U256};
+use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
 
 /// Configuration for pool syncing
 #[derive(Debug, Clone)]
@@ -16,8 +19,14 @@ pub struct SyncConfig {
     pub refresh_interval_secs: u64,
     /// TTL for cached pool state (seconds)
     pub cache_ttl_secs: u64,
+    /// Fixture file (JSON or TOML) the mock sync loads pools from;
+    /// `None` uses the bundled Base mainnet dev pools
+    pub fixture_path: Option<PathBuf>,
 }
 
+/// Dev pool set used when no fixture file is configured
+pub const DEFAULT_FIXTURE: &str = include_str!("../../fixtures/base-mainnet.toml");
+
 impl Default for SyncConfig {
     fn default() -> Self {
         Self {
@@ -25,6 +34,7 @@ impl Default for SyncConfig {
             subgraph_url: None,
             refresh_interval_secs: 12, // ~1 Base block
             cache_ttl_secs: 30,
+            fixture_path: None,
         }
     }
 }
@@ -37,24 +47,56 @@ impl Default for SyncConfig {
 /// 3. Cache results with TTL
 /// 4. Periodically refresh on a background task
 ///
-/// The current implementation provides realistic mock pool data that
-/// exercises the CLMM swap math and routing algorithms, plus the
-/// structure for adding real RPC calls.
+/// The current implementation loads realistic mock pool data from a
+/// fixture file (see `fixtures/README.md`) that exercises the CLMM swap
+/// math and routing algorithms, plus the structure for adding real RPC
+/// calls.
 pub struct PoolSyncer {
     graph: Arc<PoolGraph>,
     config: SyncConfig,
+    metrics: Option<Arc<Metrics>>,
+    status: Arc<SyncStatus>,
+    router: Option<Arc<Router>>,
 }
 
 impl PoolSyncer {
     pub fn new(graph: Arc<PoolGraph>) -> Self {
+        Self::with_config(graph, SyncConfig::default())
+    }
+
+    pub fn with_config(graph: Arc<PoolGraph>, config: SyncConfig) -> Self {
         Self {
             graph,
-            config: SyncConfig::default(),
+            config,
+            metrics: None,
+            status: Arc::new(SyncStatus::new()),
+            router: None,
         }
     }
 
-    pub fn with_config(graph: Arc<PoolGraph>, config: SyncConfig) -> Self {
-        Self { graph, config }
+    /// Record sync outcomes into a shared status (e.g. the one read by
+    /// the health endpoints)
+    pub fn with_status(mut self, status: Arc<SyncStatus>) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Get the sync status this syncer reports to
+    pub fn status(&self) -> &Arc<SyncStatus> {
+        &self.status
+    }
+
+    /// Report sync duration and results to the given metrics
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Rebuild the router's candidate index and token prices after every
+    /// successful sync, so quotes do not pay for it
+    pub fn with_router(mut self, router: Arc<Router>) -> Self {
+        self.router = Some(router);
+        self
     }
 
     /// Sync pool data.
@@ -62,183 +104,119 @@ impl PoolSyncer {
     /// Attempts RPC-based sync first, falls back to mock pools for
     /// development/testing.
     pub async fn sync_pools(&self) -> Result<(), String> {
+        let start = Instant::now();
+        let result = self.sync_pools_inner().await;
+        if let (Ok(()), Some(router)) = (&result, &self.router) {
+            router.candidates();
+            router.prices();
+        }
+
+        if let Some(metrics) = &self.metrics {
+            metrics.observe_sync(start.elapsed(), result.is_ok());
+        }
+
+        match &result {
+            Ok(()) => self.status.record_success(),
+            Err(e) => self.status.record_failure(e.clone()),
+        }
+
+        result
+    }
+
+    async fn sync_pools_inner(&self) -> Result<(), String> {
         // In production, this would call:
         //   self.sync_pools_from_rpc().await
         //
-        // For now we populate realistic mock pools that exercise the
-        // CLMM math at realistic tick/liquidity values.
+        // For now we load realistic mock pools from a fixture that
+        // exercise the CLMM math at realistic tick/liquidity values.
         tracing::info!(
             "Syncing pools (rpc_url={}, refresh={}s)",
             self.config.rpc_url,
             self.config.refresh_interval_secs
         );
 
-        self.add_base_mainnet_pools();
-        Ok(())
+        // After a warm start the graph already holds state up to the
+        // snapshot's block. Only newer state is applied: with RPC sync,
+        // the pool events after it (see `fetch_pool_events_rpc`); with a
+        // fixture, the fixture if it is not older than the graph.
+        let fixture = self.load_fixture()?;
+        let from_block = self.graph.block_number();
+        if fixture.block_number < from_block {
+            tracing::info!(
+                "Graph at block {} is ahead of the fixture (block {}), keeping it",
+                from_block,
+                fixture.block_number
+            );
+            // Nothing newer exists, so the graph is as fresh as it gets
+            self.graph
+                .set_last_update(chrono::Utc::now().timestamp() as u64);
+            return Ok(());
+        }
+        if from_block > 0 {
+            tracing::info!(
+                "Catching up from block {} to {}",
+                from_block,
+                fixture.block_number
+            );
+        }
+
+        self.import_fixture_pools(&fixture)
     }
 
-    /// Start a background sync loop.
+    /// Run the periodic sync loop until `shutdown` is cancelled.
     ///
-    /// Spawns a tokio task that periodically refreshes pool data.
-    pub fn start_periodic_sync(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+    /// Individual sync failures are logged and retried on the next tick;
+    /// the loop itself only returns on shutdown. Intended to be run under
+    /// a [`crate::service::Supervisor`].
+    pub async fn run_periodic_sync(
+        self: Arc<Self>,
+        shutdown: CancellationToken,
+    ) -> Result<(), String> {
         let interval = Duration::from_secs(self.config.refresh_interval_secs);
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        // The first tick completes immediately, so the first sync starts
+        // right away; until it finishes, a restored snapshot is served.
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.cancelled() => return Ok(()),
+            }
 
-        tokio::spawn(async move {
-            let mut ticker = tokio::time::interval(interval);
-            loop {
-                ticker.tick().await;
-                if let Err(e) = self.sync_pools().await {
-                    tracing::warn!("Periodic pool sync failed: {}", e);
-                }
+            if let Err(e) = self.sync_pools().await {
+                tracing::warn!("Periodic pool sync failed: {}", e);
             }
-        })
+        }
     }
 
-    /// Add realistic Base mainnet pools for development and testing.
+    /// Start a background sync loop.
     ///
-    /// These pools mirror real Base mainnet pool configurations with
-    /// realistic tick values, liquidity, and fee tiers.
-    fn add_base_mainnet_pools(&self) {
-        // ============================================================
-        // Token definitions (using well-known Base addresses)
-        // ============================================================
-        let weth = TokenNode::native(
-            crate::utils::addresses::weth(),
-            "WETH".to_string(),
-            18,
-        );
-
-        let usdc = TokenNode::new(
-            crate::utils::addresses::usdc(),
-            "USDC".to_string(),
-            6,
-        );
-
-        // Additional tokens for multi-hop testing
-        let dai = TokenNode::new(
-            address_from_u64(3),
-            "DAI".to_string(),
-            18,
-        );
-
-        let wbtc = TokenNode::new(
-            address_from_u64(4),
-            "WBTC".to_string(),
-            8,
-        );
-
-        let cbeth = TokenNode::new(
-            address_from_u64(5),
-            "cbETH".to_string(),
-            18,
-        );
-
-        // ============================================================
-        // Pool definitions with realistic CLMM parameters
-        // ============================================================
-
-        // Pool 1: WETH/USDC 0.3% (highest liquidity pair on Base)
-        // tick ~201240 corresponds to ~$3000 ETH/USDC price
-        // (sqrtPriceX96 for USDC/WETH where USDC has 6 decimals)
-        let weth_usdc_tick: i32 = 201240;
-        let pool_weth_usdc = PoolEdge::new(
-            [1u8; 32],
-            weth.address,
-            usdc.address,
-            3000,   // 0.3% fee
-            60,     // tick_spacing
-            50_000_000_000_000_000_000_000, // ~50k tokens liquidity
-            tick_to_sqrt_price_x96(weth_usdc_tick),
-            weth_usdc_tick,
-        );
-
-        // Pool 2: WETH/USDC 0.05% (tighter spread, different tick spacing)
-        let pool_weth_usdc_005 = PoolEdge::new(
-            [2u8; 32],
-            weth.address,
-            usdc.address,
-            500,    // 0.05% fee
-            10,     // tick_spacing
-            30_000_000_000_000_000_000_000,
-            tick_to_sqrt_price_x96(weth_usdc_tick),
-            weth_usdc_tick,
-        );
-
-        // Pool 3: WETH/DAI 0.3%
-        // tick 0 = 1:1 price (both 18 decimals)
-        let pool_weth_dai = PoolEdge::new(
-            [3u8; 32],
-            weth.address,
-            dai.address,
-            3000,
-            60,
-            20_000_000_000_000_000_000_000,
-            tick_to_sqrt_price_x96(0),
-            0,
-        );
-
-        // Pool 4: USDC/DAI 0.01% (stablecoin pair)
-        // tick ~276324 for USDC(6)/DAI(18) at ~1:1 price
-        let usdc_dai_tick: i32 = 276324;
-        let pool_usdc_dai = PoolEdge::new(
-            [4u8; 32],
-            usdc.address,
-            dai.address,
-            100,    // 0.01% fee
-            1,      // tick_spacing
-            100_000_000_000_000_000_000_000, // Deep stablecoin liquidity
-            tick_to_sqrt_price_x96(usdc_dai_tick),
-            usdc_dai_tick,
-        );
-
-        // Pool 5: WETH/WBTC 0.3%
-        let pool_weth_wbtc = PoolEdge::new(
-            [5u8; 32],
-            weth.address,
-            wbtc.address,
-            3000,
-            60,
-            10_000_000_000_000_000_000_000,
-            tick_to_sqrt_price_x96(0),
-            0,
-        );
-
-        // Pool 6: cbETH/WETH 0.05% (liquid staking derivative)
-        // tick ~100 represents cbETH at slight premium to ETH
-        let pool_cbeth_weth = PoolEdge::new(
-            [6u8; 32],
-            cbeth.address,
-            weth.address,
-            500,
-            10,
-            15_000_000_000_000_000_000_000,
-            tick_to_sqrt_price_x96(100),
-            100,
-        );
+    /// Spawns an unsupervised tokio task that periodically refreshes pool
+    /// data until the returned handle is aborted.
+    pub fn start_periodic_sync(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let _ = self.run_periodic_sync(CancellationToken::new()).await;
+        })
+    }
 
-        // Pool 7: WBTC/USDC 0.3%
-        let wbtc_usdc_tick: i32 = 0; // simplified
-        let pool_wbtc_usdc = PoolEdge::new(
-            [7u8; 32],
-            wbtc.address,
-            usdc.address,
-            3000,
-            60,
-            8_000_000_000_000_000_000_000,
-            tick_to_sqrt_price_x96(wbtc_usdc_tick),
-            wbtc_usdc_tick,
-        );
+    /// Read mock pools from the configured fixture file.
+    ///
+    /// The file is re-read on every sync, so editing it changes the
+    /// served pools without a restart.
+    fn load_fixture(&self) -> Result<GraphFixture, String> {
+        match &self.config.fixture_path {
+            Some(path) => GraphFixture::load(path)
+                .map_err(|e| format!("Failed to load fixture {}: {}", path.display(), e)),
+            None => GraphFixture::from_toml(DEFAULT_FIXTURE)
+                .map_err(|e| format!("Failed to load bundled fixture: {}", e)),
+        }
+    }
 
-        // ============================================================
-        // Insert all pools
-        // ============================================================
-        self.graph.upsert_pool(pool_weth_usdc, weth.clone(), usdc.clone());
-        self.graph.upsert_pool(pool_weth_usdc_005, weth.clone(), usdc.clone());
-        self.graph.upsert_pool(pool_weth_dai, weth.clone(), dai.clone());
-        self.graph.upsert_pool(pool_usdc_dai, usdc.clone(), dai.clone());
-        self.graph.upsert_pool(pool_weth_wbtc, weth.clone(), wbtc.clone());
-        self.graph.upsert_pool(pool_cbeth_weth, cbeth, weth);
-        self.graph.upsert_pool(pool_wbtc_usdc, wbtc, usdc);
+    fn import_fixture_pools(&self, fixture: &GraphFixture) -> Result<(), String> {
+        self.graph
+            .import_fixture(fixture)
+            .map_err(|e| format!("Invalid fixture: {}", e))?;
 
         let stats = self.graph.stats();
         tracing::info!(
@@ -246,6 +224,7 @@ impl PoolSyncer {
             stats.token_count,
             stats.pool_count
         );
+        Ok(())
     }
 
     // ================================================================
@@ -275,6 +254,27 @@ impl PoolSyncer {
         Err("RPC sync not yet implemented - using mock data".to_string())
     }
 
+    /// Fetch pool events since a block.
+    ///
+    /// Would call eth_getLogs for PoolManager Initialize, ModifyLiquidity
+    /// and Swap events in `(from_block, latest]` and apply them to the
+    /// graph, returning the latest block applied.
+    #[allow(dead_code)]
+    async fn fetch_pool_events_rpc(&self, _from_block: u64) -> Result<u64, String> {
+        // In production:
+        //
+        // let latest = provider.get_block_number().await?;
+        // let filter = Filter::new()
+        //     .address(pool_manager_addr)
+        //     .from_block(from_block + 1)
+        //     .to_block(latest);
+        // for log in provider.get_logs(&filter).await? { ... }
+        //
+        // Ok(latest)
+
+        Err("RPC event sync not yet implemented".to_string())
+    }
+
     /// Discover pools from Subgraph.
     ///
     /// Would query the BaseBook subgraph for all active pools.
@@ -328,6 +328,7 @@ mod tests {
         syncer.sync_pools().await.expect("Should sync pools");
 
         let stats = graph.stats();
+        assert!(syncer.status().has_synced());
         assert!(stats.token_count > 0, "Should have tokens after sync");
         assert!(stats.pool_count > 0, "Should have pools after sync");
 
@@ -343,6 +344,7 @@ mod tests {
             subgraph_url: None,
             refresh_interval_secs: 30,
             cache_ttl_secs: 60,
+            fixture_path: None,
         };
 
         let syncer = PoolSyncer::with_config(graph.clone(), config);
@@ -352,6 +354,134 @@ mod tests {
         assert!(stats.token_count > 0);
     }
 
+    #[tokio::test]
+    async fn test_periodic_sync_stops_on_shutdown() {
+        let graph = Arc::new(PoolGraph::new());
+        let config = SyncConfig {
+            refresh_interval_secs: 1,
+            ..Default::default()
+        };
+        let syncer = Arc::new(PoolSyncer::with_config(graph, config));
+        let shutdown = CancellationToken::new();
+
+        let handle = tokio::spawn(syncer.clone().run_periodic_sync(shutdown.clone()));
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        shutdown.cancel();
+
+        let result = tokio::time::timeout(Duration::from_secs(1), handle)
+            .await
+            .expect("Loop should stop on shutdown")
+            .unwrap();
+        assert!(result.is_ok());
+        assert!(syncer.status().has_synced(), "Should have synced once");
+    }
+
+    #[tokio::test]
+    async fn test_repeated_sync_does_not_duplicate_pools() {
+        let graph = Arc::new(PoolGraph::new());
+        let syncer = PoolSyncer::new(graph.clone());
+
+        syncer.sync_pools().await.unwrap();
+        let first = graph.stats().pool_count;
+        syncer.sync_pools().await.unwrap();
+
+        assert_eq!(graph.stats().pool_count, first);
+    }
+
+    #[tokio::test]
+    async fn test_sync_after_restore_keeps_snapshot_block() {
+        let source = Arc::new(PoolGraph::new());
+        PoolSyncer::new(source.clone()).sync_pools().await.unwrap();
+        source.set_block_number(1_000);
+
+        let graph = Arc::new(PoolGraph::new());
+        source.snapshot(8453).restore_into(&graph).unwrap();
+        PoolSyncer::new(graph.clone()).sync_pools().await.unwrap();
+
+        assert_eq!(graph.block_number(), 1_000);
+        assert_eq!(graph.stats().pool_count, source.stats().pool_count);
+    }
+
+    #[tokio::test]
+    async fn test_sync_applies_only_state_newer_than_the_snapshot() {
+        let path = std::env::temp_dir().join(format!("fixture-{}.json", uuid::Uuid::new_v4()));
+        let mut fixture = GraphFixture::from_toml(DEFAULT_FIXTURE).unwrap();
+        let pool_id = fixture.pools[0].id.0;
+        let fixture_liquidity: u128 = fixture.pools[0].liquidity.parse().unwrap();
+
+        // The snapshot is at block 1000 with its own state for one pool
+        let mut snapshot = fixture.clone();
+        snapshot.block_number = 1_000;
+        snapshot.pools[0].liquidity = "12345".to_string();
+        let source = PoolGraph::new();
+        source.import_fixture(&snapshot).unwrap();
+        let graph = Arc::new(PoolGraph::new());
+        source.snapshot(8453).restore_into(&graph).unwrap();
+
+        let config = SyncConfig {
+            fixture_path: Some(path.clone()),
+            ..Default::default()
+        };
+        let syncer = PoolSyncer::with_config(graph.clone(), config);
+
+        // An older fixture leaves the restored state alone, but it is
+        // checked as current
+        graph.set_last_update(1);
+        fixture.block_number = 900;
+        fixture.save(&path).unwrap();
+        syncer.sync_pools().await.unwrap();
+        assert_eq!(graph.block_number(), 1_000);
+        assert_eq!(graph.get_pool(pool_id).unwrap().liquidity, 12345);
+        assert!(graph.stats().last_update > 1);
+
+        // A newer one catches the graph up
+        fixture.block_number = 1_100;
+        fixture.save(&path).unwrap();
+        syncer.sync_pools().await.unwrap();
+        assert_eq!(graph.block_number(), 1_100);
+        assert_eq!(graph.get_pool(pool_id).unwrap().liquidity, fixture_liquidity);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_sync_from_fixture_file() {
+        let path = std::env::temp_dir().join(format!("fixture-{}.json", uuid::Uuid::new_v4()));
+        let source = PoolGraph::new();
+        source
+            .import_fixture(&GraphFixture::from_toml(DEFAULT_FIXTURE).unwrap())
+            .unwrap();
+        let mut fixture = source.export_fixture();
+        fixture.pools.truncate(2);
+        fixture.save(&path).unwrap();
+
+        let graph = Arc::new(PoolGraph::new());
+        let config = SyncConfig {
+            fixture_path: Some(path.clone()),
+            ..Default::default()
+        };
+        PoolSyncer::with_config(graph.clone(), config)
+            .sync_pools()
+            .await
+            .unwrap();
+
+        assert_eq!(graph.stats().pool_count, 2);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_missing_fixture_fails_sync() {
+        let graph = Arc::new(PoolGraph::new());
+        let config = SyncConfig {
+            fixture_path: Some(PathBuf::from("/nonexistent/fixture.toml")),
+            ..Default::default()
+        };
+        let syncer = PoolSyncer::with_config(graph, config);
+
+        assert!(syncer.sync_pools().await.is_err());
+        assert_eq!(syncer.status().consecutive_failures(), 1);
+    }
+
     #[test]
     fn test_sync_config_defaults() {
         let config = SyncConfig::default();
@@ -0,0 +1,145 @@
+use parking_lot::RwLock;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// Outcome tracking for pool syncs, shared between the syncer and the
+/// health endpoints.
+///
+/// Timestamps are Unix seconds; zero means "never".
+#[derive(Debug)]
+pub struct SyncStatus {
+    last_success: AtomicU64,
+    last_attempt: AtomicU64,
+    consecutive_failures: AtomicU64,
+    last_error: RwLock<Option<String>>,
+    rpc_healthy: AtomicBool,
+    rpc_error: RwLock<Option<String>>,
+}
+
+impl SyncStatus {
+    pub fn new() -> Self {
+        Self {
+            last_success: AtomicU64::new(0),
+            last_attempt: AtomicU64::new(0),
+            consecutive_failures: AtomicU64::new(0),
+            last_error: RwLock::new(None),
+            rpc_healthy: AtomicBool::new(true),
+            rpc_error: RwLock::new(None),
+        }
+    }
+
+    /// Record a successful sync
+    pub fn record_success(&self) {
+        let now = now_secs();
+        self.last_attempt.store(now, Ordering::Relaxed);
+        self.last_success.store(now, Ordering::Relaxed);
+        self.consecutive_failures.store(0, Ordering::Relaxed);
+        *self.last_error.write() = None;
+    }
+
+    /// Treat restored data (e.g. a graph snapshot) as synced at
+    /// `timestamp`, so it can be served until it becomes stale
+    pub fn record_restored(&self, timestamp: u64) {
+        self.last_success.fetch_max(timestamp, Ordering::Relaxed);
+    }
+
+    /// Record a failed sync
+    pub fn record_failure(&self, error: impl Into<String>) {
+        self.last_attempt.store(now_secs(), Ordering::Relaxed);
+        self.consecutive_failures.fetch_add(1, Ordering::Relaxed);
+        *self.last_error.write() = Some(error.into());
+    }
+
+    /// Record the outcome of talking to the chain RPC
+    pub fn record_rpc_result(&self, result: Result<(), String>) {
+        match result {
+            Ok(()) => {
+                self.rpc_healthy.store(true, Ordering::Relaxed);
+                *self.rpc_error.write() = None;
+            }
+            Err(e) => {
+                self.rpc_healthy.store(false, Ordering::Relaxed);
+                *self.rpc_error.write() = Some(e);
+            }
+        }
+    }
+
+    /// Whether at least one sync has completed successfully
+    pub fn has_synced(&self) -> bool {
+        self.last_success.load(Ordering::Relaxed) > 0
+    }
+
+    pub fn last_success(&self) -> u64 {
+        self.last_success.load(Ordering::Relaxed)
+    }
+
+    pub fn last_attempt(&self) -> u64 {
+        self.last_attempt.load(Ordering::Relaxed)
+    }
+
+    pub fn consecutive_failures(&self) -> u64 {
+        self.consecutive_failures.load(Ordering::Relaxed)
+    }
+
+    pub fn last_error(&self) -> Option<String> {
+        self.last_error.read().clone()
+    }
+
+    pub fn rpc_healthy(&self) -> bool {
+        self.rpc_healthy.load(Ordering::Relaxed)
+    }
+
+    pub fn rpc_error(&self) -> Option<String> {
+        self.rpc_error.read().clone()
+    }
+}
+
+impl Default for SyncStatus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn now_secs() -> u64 {
+    chrono::Utc::now().timestamp() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_initial_state() {
+        let status = SyncStatus::new();
+        assert!(!status.has_synced());
+        assert!(status.rpc_healthy());
+        assert_eq!(status.consecutive_failures(), 0);
+    }
+
+    #[test]
+    fn test_failures_reset_on_success() {
+        let status = SyncStatus::new();
+
+        status.record_failure("timeout");
+        status.record_failure("timeout");
+        assert_eq!(status.consecutive_failures(), 2);
+        assert_eq!(status.last_error().as_deref(), Some("timeout"));
+        assert!(!status.has_synced());
+
+        status.record_success();
+        assert!(status.has_synced());
+        assert_eq!(status.consecutive_failures(), 0);
+        assert!(status.last_error().is_none());
+    }
+
+    #[test]
+    fn test_rpc_result() {
+        let status = SyncStatus::new();
+
+        status.record_rpc_result(Err("connection refused".to_string()));
+        assert!(!status.rpc_healthy());
+        assert_eq!(status.rpc_error().as_deref(), Some("connection refused"));
+
+        status.record_rpc_result(Ok(()));
+        assert!(status.rpc_healthy());
+    }
+}
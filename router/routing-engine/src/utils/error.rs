@@ -35,6 +35,29 @@ pub enum RouterError {
 
     #[error("Internal error: {0}")]
     InternalError(String),
+
+    #[error("Route search ran out of time before finding a route from {from} to {to}")]
+    SearchTimeout { from: Address, to: Address },
+}
+
+impl RouterError {
+    /// Short, stable name of the error variant (used as a metrics label)
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RouterError::NoRouteFound { .. } => "no_route_found",
+            RouterError::InsufficientLiquidity { .. } => "insufficient_liquidity",
+            RouterError::PriceImpactTooHigh { .. } => "price_impact_too_high",
+            RouterError::InvalidTokenAddress(_) => "invalid_token_address",
+            RouterError::InvalidAmount(_) => "invalid_amount",
+            RouterError::PoolNotFound(_) => "pool_not_found",
+            RouterError::SimulationError(_) => "simulation_error",
+            RouterError::RpcError(_) => "rpc_error",
+            RouterError::CacheError(_) => "cache_error",
+            RouterError::ConfigError(_) => "config_error",
+            RouterError::InternalError(_) => "internal_error",
+            RouterError::SearchTimeout { .. } => "search_timeout",
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, RouterError>;
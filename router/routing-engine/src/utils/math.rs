@@ -1,4 +1,4 @@
-use alloy_primitives::U256;
+use alloy_primitives::{U256, U512};
 
 /// Q96 constant: 2^96 used for sqrt price fixed-point representation
 pub fn q96() -> U256 {
@@ -158,20 +158,36 @@ pub fn compute_swap_step(
     }
 }
 
+/// Most output a single `compute_swap_step` towards `sqrt_price_target`
+/// can return: the output for moving the price all the way to the target.
+pub fn max_step_output(sqrt_price_current: U256, sqrt_price_target: U256, liquidity: u128) -> U256 {
+    let liq = U256::from(liquidity);
+    if sqrt_price_current >= sqrt_price_target {
+        get_amount1_delta(sqrt_price_target, sqrt_price_current, liq)
+    } else {
+        get_amount0_delta(sqrt_price_current, sqrt_price_target, liq)
+    }
+}
+
 /// Calculate amount0 delta:  L * Q96 * (sqrtP_upper - sqrtP_lower) / (sqrtP_upper * sqrtP_lower)
 /// Returns the rounded-up amount of token0 needed to move between two prices.
 fn get_amount0_delta(sqrt_price_lower: U256, sqrt_price_upper: U256, liquidity: U256) -> U256 {
     if sqrt_price_lower >= sqrt_price_upper || sqrt_price_lower.is_zero() {
         return U256::ZERO;
     }
-    let q96 = U256::from(1u128) << 96;
-    let numerator = liquidity * q96 * (sqrt_price_upper - sqrt_price_lower);
-    let denominator = sqrt_price_upper * sqrt_price_lower;
-    if denominator.is_zero() {
-        return U256::ZERO;
-    }
+    // L * Q96 * delta exceeds 256 bits for deep pools, so divide in two
+    // steps with a wide intermediate, as Uniswap's SqrtPriceMath does
+    let numerator = liquidity << 96;
+    let step = mul_div_rounding_up(numerator, sqrt_price_upper - sqrt_price_lower, sqrt_price_upper);
     // round up
-    (numerator + denominator - U256::from(1u64)) / denominator
+    step.div_ceil(sqrt_price_lower)
+}
+
+/// `a * b / denominator` rounded up, without overflowing the product
+fn mul_div_rounding_up(a: U256, b: U256, denominator: U256) -> U256 {
+    let product: U512 = a.widening_mul(b);
+    let quotient = product.div_ceil(U512::from(denominator));
+    U256::saturating_from(quotient)
 }
 
 /// Calculate amount1 delta:  L * (sqrtP_upper - sqrtP_lower) / Q96
@@ -354,6 +370,37 @@ mod tests {
         assert!(delta > U256::ZERO);
     }
 
+    #[test]
+    fn test_amount0_delta_deep_liquidity() {
+        // L * Q96 * delta does not fit in 256 bits here
+        let sqrt_lower = tick_to_sqrt_price_x96(-60);
+        let sqrt_upper = tick_to_sqrt_price_x96(0);
+        let liq = U256::from(10u128.pow(24));
+
+        let delta = get_amount0_delta(sqrt_lower, sqrt_upper, liq);
+        // ~ L * (1/sqrt(1.0001^-60) - 1) ~ 3e21
+        assert!(delta > U256::from(29u128 * 10u128.pow(20)));
+        assert!(delta < U256::from(31u128 * 10u128.pow(20)));
+    }
+
+    #[test]
+    fn test_compute_swap_step_output_is_monotone() {
+        for (current, target) in [(0, 60), (0, -60), (-700, -640), (500, 440)] {
+            let sqrt_current = tick_to_sqrt_price_x96(current);
+            let sqrt_target = tick_to_sqrt_price_x96(target);
+            let mut amount = U256::from(10u64.pow(12));
+            let mut previous = U256::ZERO;
+
+            for _ in 0..80 {
+                let step =
+                    compute_swap_step(sqrt_current, sqrt_target, 10u128.pow(24), amount, 3000);
+                assert!(step.amount_out >= previous, "{} -> {} at {}", current, target, amount);
+                previous = step.amount_out;
+                amount = amount * U256::from(13) / U256::from(10);
+            }
+        }
+    }
+
     #[test]
     fn test_amount1_delta_basic() {
         let q96 = U256::from(1u128) << 96;
@@ -51,6 +51,7 @@ pub mod u256_ext {
         result
     }
 
+    #[allow(clippy::unnecessary_min_or_max)]
     pub fn from_f64(value: f64) -> U256 {
         U256::from((value as u128).min(u128::MAX))
     }
@@ -5,20 +5,44 @@ use axum::{
 use alloy_primitives::U256;
 use routing_engine::utils::address_from_u64;
 use routing_engine::{
-    api::{create_router, AppState},
-    cache::RouteCache,
-    config::Settings,
-    graph::{PoolEdge, PoolGraph, TokenNode},
-    routing::Router,
+    api::{
+        access::{ApiKeyConfig, RateLimit},
+        create_admin_router, create_router,
+        dto::QuoteResponse,
+        verify_quote, AppState, SignatureError,
+    },
+    config::{ChainSettings, Settings},
+    gas::fee_transfer_calldata,
+    graph::{GraphFixture, PoolEdge, PoolGraph, TokenNode},
+    service::{ComputeConfig, ComputePool},
 };
 use serde_json::Value;
 use std::sync::Arc;
 use tower::util::ServiceExt;
 
 fn create_test_state() -> AppState {
-    // Create test graph with pools
-    let graph = Arc::new(PoolGraph::new());
+    let state = create_unsynced_test_state();
+    state.default_chain().sync_status.record_success();
+    state
+}
+
+/// Default settings, with routes through any token and no per-IP limit
+/// (test requests have no peer address, so they share one bucket)
+fn test_settings() -> Settings {
+    let mut settings = Settings::default();
+    // The test tokens are not connectors; let routes pass through any token
+    settings.chains[0].connector_tokens = Some(Vec::new());
+    settings.access.ip_rate_limit.per_second = 0.0;
+    settings
+}
 
+fn create_unsynced_test_state() -> AppState {
+    let state = AppState::new(test_settings());
+    add_test_pools(&state.default_chain().graph);
+    state
+}
+
+fn add_test_pools(graph: &PoolGraph) {
     let token_a = address_from_u64(1);
     let token_b = address_from_u64(2);
     let token_c = address_from_u64(3);
@@ -51,18 +75,6 @@ fn create_test_state() -> AppState {
 
     graph.upsert_pool(pool_ab, node_a.clone(), node_b.clone());
     graph.upsert_pool(pool_bc, node_b, node_c);
-
-    // Create router with test graph (use same graph reference)
-    let router = Router::new(graph.clone());
-
-    let settings = Settings::default();
-
-    AppState {
-        router: Arc::new(router),
-        graph,
-        cache: Arc::new(RouteCache::default()),
-        settings,
-    }
 }
 
 #[tokio::test]
@@ -202,7 +214,9 @@ async fn test_quote_with_invalid_amount_returns_400() {
     let json: Value = serde_json::from_slice(&body).unwrap();
 
     assert!(json["error"].is_string());
+    assert_eq!(json["code"], "invalid_amount");
     assert!(json["message"].is_string());
+    assert_eq!(json["details"]["amount_in"], "invalid");
 }
 
 #[tokio::test]
@@ -260,6 +274,58 @@ async fn test_quote_with_custom_slippage() {
     assert!(json["quote"]["amount_out_min"].is_string());
 }
 
+#[tokio::test]
+async fn test_quote_with_timeout_is_complete() {
+    let state = create_test_state();
+    let app = create_router(state);
+
+    let uri = format!(
+        "/v1/quote?token_in={:?}&token_out={:?}&amount_in=1000000000000000000&timeout_ms=1000",
+        address_from_u64(1),
+        address_from_u64(3)
+    );
+
+    let response = app
+        .oneshot(Request::builder().uri(&uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(json["quote"]["partial"], false);
+    assert_eq!(json["quote"]["route"]["partial"], false);
+}
+
+#[tokio::test]
+async fn test_quote_shed_when_compute_queue_is_full() {
+    let mut state = create_test_state();
+    state.compute = Arc::new(ComputePool::new(ComputeConfig {
+        threads: 1,
+        max_queue: 0,
+        ..Default::default()
+    }));
+    let app = create_router(state);
+
+    let uri = format!(
+        "/v1/quote?token_in={:?}&token_out={:?}&amount_in=1000000000000000000",
+        address_from_u64(1),
+        address_from_u64(2)
+    );
+
+    let response = app
+        .oneshot(Request::builder().uri(&uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()["retry-after"], "1");
+}
+
 #[tokio::test]
 async fn test_quote_response_includes_timestamp() {
     let state = create_test_state();
@@ -291,3 +357,1062 @@ async fn test_quote_response_includes_timestamp() {
     let now = chrono::Utc::now().timestamp() as u64;
     assert!(now - timestamp < 60);
 }
+
+#[tokio::test]
+async fn test_metrics_endpoint_reports_quotes() {
+    let state = create_test_state();
+    let admin = create_admin_router(state.clone());
+    let app = create_router(state);
+
+    let uri = format!(
+        "/v1/quote?token_in={:?}&token_out={:?}&amount_in=1000000000000000000&max_hops=1",
+        address_from_u64(1),
+        address_from_u64(2)
+    );
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri(&uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = admin
+        .oneshot(Request::builder().uri("/metrics").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let text = String::from_utf8(body.to_vec()).unwrap();
+
+    assert!(text.contains("route_calculations_total{strategy=\"single\",chain_id=\"8453\"} 1"));
+    assert!(text.contains("graph_pool_count{chain_id=\"8453\"} 2"));
+    assert!(text.contains("graph_token_count{chain_id=\"8453\"} 3"));
+    assert!(text.contains("route_cache_misses_total{layer=\"quote\",chain_id=\"8453\"}"));
+    assert!(text.contains("http_requests_in_flight{endpoint=\"quote\"} 0"));
+}
+
+#[tokio::test]
+async fn test_liveness_always_ok() {
+    let app = create_router(create_unsynced_test_state());
+
+    let response = app
+        .oneshot(Request::builder().uri("/live").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_readiness_requires_sync() {
+    let app = create_router(create_unsynced_test_state());
+
+    let response = app
+        .oneshot(Request::builder().uri("/ready").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(json["ready"], false);
+    assert_eq!(json["status"], "unhealthy");
+    assert_eq!(json["issues"][0]["subsystem"], "sync");
+}
+
+#[tokio::test]
+async fn test_readiness_ok_after_sync() {
+    let app = create_router(create_test_state());
+
+    let response = app
+        .oneshot(Request::builder().uri("/ready").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_health_degraded_on_rpc_failure() {
+    let state = create_test_state();
+    state
+        .default_chain()
+        .sync_status
+        .record_rpc_result(Err("connection refused".to_string()));
+    let app = create_router(state);
+
+    let response = app
+        .oneshot(Request::builder().uri("/health").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(json["status"], "degraded");
+    assert_eq!(json["ready"], true);
+    assert_eq!(json["issues"][0]["subsystem"], "rpc");
+    assert_eq!(json["issues"][0]["message"], "connection refused");
+}
+
+#[tokio::test]
+async fn test_quote_before_sync_returns_503() {
+    let app = create_router(create_unsynced_test_state());
+
+    let uri = format!(
+        "/v1/quote?token_in={:?}&token_out={:?}&amount_in=1000000000000000000",
+        address_from_u64(1),
+        address_from_u64(2)
+    );
+
+    let response = app
+        .oneshot(Request::builder().uri(&uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(json["code"], "not_ready");
+    assert!(json["message"].as_str().unwrap().contains("not been synced"));
+}
+
+#[tokio::test]
+async fn test_not_ready_while_shutting_down() {
+    let state = create_test_state();
+    state.shutdown.cancel();
+    let app = create_router(state);
+
+    let response = app
+        .oneshot(Request::builder().uri("/ready").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn test_admin_graph_dump() {
+    let state = create_test_state();
+    let app = create_admin_router(state);
+
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri("/admin/graph").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let fixture = GraphFixture::from_json(std::str::from_utf8(&body).unwrap()).unwrap();
+    assert_eq!(fixture.tokens.len(), 3);
+    assert_eq!(fixture.pools.len(), 2);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/admin/graph?format=toml")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()["content-type"],
+        "application/toml"
+    );
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let graph = PoolGraph::new();
+    graph
+        .import_fixture(&GraphFixture::from_toml(std::str::from_utf8(&body).unwrap()).unwrap())
+        .unwrap();
+    assert_eq!(graph.stats().pool_count, 2);
+}
+
+#[tokio::test]
+async fn test_admin_endpoints_are_not_public() {
+    // Neither anonymous callers nor partner keys reach metrics or the
+    // graph dump through the public listener
+    let app = create_router(create_test_state());
+    for uri in ["/metrics", "/admin/graph"] {
+        let (status, _) = get_json(&app, uri).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{}", uri);
+    }
+
+    let app = create_router(create_keyed_test_state());
+    for uri in ["/metrics", "/admin/graph"] {
+        let (status, _) = get_json(&app, uri).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{}", uri);
+        let response = get_with_key(&app, uri, "partner-key").await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND, "{}", uri);
+    }
+}
+
+/// Base mainnet with the test pools, and Base Sepolia with a single
+/// direct A/C pool
+fn create_multi_chain_state() -> AppState {
+    let mut settings = test_settings();
+    settings.chains.push(ChainSettings::base_sepolia());
+    for chain in &mut settings.chains {
+        chain.connector_tokens = Some(Vec::new());
+    }
+    let state = AppState::new(settings);
+    add_test_pools(&state.default_chain().graph);
+
+    let sepolia = state.chain(Some(84532)).unwrap();
+    let [token_a, token_c] = [1, 3].map(address_from_u64);
+    sepolia.graph.upsert_pool(
+        PoolEdge::new(
+            [9u8; 32],
+            token_a,
+            token_c,
+            500,
+            10,
+            1_000_000_000_000_000_000_000,
+            U256::from(1u128 << 96),
+            0,
+        ),
+        TokenNode::new(token_a, "TokenA".to_string(), 18),
+        TokenNode::new(token_c, "TokenC".to_string(), 18),
+    );
+
+    for chain in state.chains.values() {
+        chain.sync_status.record_success();
+    }
+    state
+}
+
+async fn get_json(app: &axum::Router, uri: &str) -> (StatusCode, Value) {
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn test_quotes_are_served_per_chain() {
+    let app = create_router(create_multi_chain_state());
+    let query = format!(
+        "token_in={:?}&token_out={:?}&amount_in=1000000000000000000",
+        address_from_u64(1),
+        address_from_u64(3)
+    );
+
+    // Default chain: A -> B -> C
+    let (status, json) = get_json(&app, &format!("/v1/quote?{}", query)).await;
+    assert_eq!(status, StatusCode::OK);
+    let hops = &json["quote"]["route"]["routes"][0][0]["hops"];
+    assert_eq!(hops.as_array().unwrap().len(), 2);
+
+    // Base Sepolia only has the direct pool
+    let (status, json) = get_json(&app, &format!("/v1/quote?chain_id=84532&{}", query)).await;
+    assert_eq!(status, StatusCode::OK);
+    let hops = &json["quote"]["route"]["routes"][0][0]["hops"];
+    assert_eq!(hops.as_array().unwrap().len(), 1);
+    assert_eq!(hops[0]["pool"]["fee"], 500);
+
+    let (status, json) = get_json(&app, &format!("/v1/quote?chain_id=1&{}", query)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["code"], "unsupported_chain");
+    assert_eq!(json["details"]["supported"], serde_json::json!([8453, 84532]));
+    assert!(json["message"].as_str().unwrap().contains("Unsupported chain_id 1"));
+}
+
+#[tokio::test]
+async fn test_health_and_stats_per_chain() {
+    let state = create_multi_chain_state();
+    state
+        .chain(Some(84532))
+        .unwrap()
+        .sync_status
+        .record_failure("rpc down".to_string());
+    let admin = create_admin_router(state.clone());
+    let app = create_router(state);
+
+    let (_, json) = get_json(&app, "/health?chain_id=84532").await;
+    assert_eq!(json["chain_id"], 84532);
+    assert_eq!(json["graph_stats"]["pool_count"], 1);
+    assert_eq!(json["status"], "degraded");
+    let chains = json["chains"].as_array().unwrap();
+    assert_eq!(chains.len(), 2);
+    assert_eq!(chains[0]["chain_id"], 8453);
+    assert_eq!(chains[0]["status"], "healthy");
+    assert_eq!(chains[1]["status"], "degraded");
+
+    let (_, json) = get_json(&app, "/health").await;
+    assert_eq!(json["chain_id"], 8453);
+    assert_eq!(json["graph_stats"]["pool_count"], 2);
+
+    let (status, _) = get_json(&app, "/ready?chain_id=84532").await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = get_json(&app, "/ready?chain_id=10").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let response = admin
+        .clone()
+        .oneshot(Request::builder().uri("/metrics").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let text = String::from_utf8(body.to_vec()).unwrap();
+    assert!(text.contains("graph_pool_count{chain_id=\"8453\"} 2"));
+    assert!(text.contains("graph_pool_count{chain_id=\"84532\"} 1"));
+    assert_eq!(text.matches("# TYPE graph_pool_count gauge").count(), 1);
+
+    let response = admin
+        .oneshot(
+            Request::builder()
+                .uri("/admin/graph?chain_id=84532")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let fixture = GraphFixture::from_json(std::str::from_utf8(&body).unwrap()).unwrap();
+    assert_eq!(fixture.pools.len(), 1);
+}
+
+#[tokio::test]
+async fn test_prices_and_quote_usd_values() {
+    let state = create_test_state();
+    let weth = routing_engine::utils::addresses::weth();
+    let usdc = routing_engine::utils::addresses::usdc();
+    // 1M WETH against 3B USDC
+    state.default_chain().graph.upsert_pool(
+        PoolEdge::constant_product(
+            [7u8; 32],
+            weth,
+            usdc,
+            3000,
+            [U256::from(10u128.pow(24)), U256::from(3 * 10u128.pow(15))],
+        ),
+        TokenNode::new(weth, "WETH".to_string(), 18),
+        TokenNode::new(usdc, "USDC".to_string(), 6),
+    );
+    let app = create_router(state);
+
+    let (status, json) = get_json(&app, "/v1/prices").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["chain_id"], 8453);
+    let symbols: Vec<_> = json["prices"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["symbol"].as_str().unwrap())
+        .collect();
+    assert!(symbols.contains(&"USDC") && symbols.contains(&"WETH"));
+    // The test tokens have no pool to a priced token
+    assert!(!symbols.contains(&"TokenA"));
+
+    let (status, json) = get_json(&app, &format!("/v1/prices/{:?}", weth)).await;
+    assert_eq!(status, StatusCode::OK);
+    let eth_price = json["price"]["price_usd"].as_f64().unwrap();
+    assert!((eth_price - 3000.0).abs() < 1e-6);
+
+    // Native ETH is priced as WETH
+    let (_, json) = get_json(&app, "/v1/prices/0x0000000000000000000000000000000000000000").await;
+    assert_eq!(json["price"]["price_usd"].as_f64().unwrap(), eth_price);
+
+    let (status, _) = get_json(&app, &format!("/v1/prices/{:?}", address_from_u64(1))).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, json) = get_json(
+        &app,
+        &format!(
+            "/v1/quote?token_in={:?}&token_out={:?}&amount_in=1000000000000000000",
+            weth, usdc
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let quote = &json["quote"];
+    assert!((quote["amount_in_usd"].as_f64().unwrap() - 3000.0).abs() < 1e-6);
+    let out_usd = quote["amount_out_usd"].as_f64().unwrap();
+    assert!(out_usd > 2900.0 && out_usd < 3000.0);
+    assert!(quote["gas_estimate_usd"].as_f64().unwrap() > 0.0);
+}
+
+#[tokio::test]
+async fn test_quote_validation_errors_have_codes() {
+    let app = create_router(create_test_state());
+    let quote = |params: &str| {
+        format!(
+            "/v1/quote?token_in={:?}&token_out={:?}&amount_in=1000000000000000000{}",
+            address_from_u64(1),
+            address_from_u64(3),
+            params
+        )
+    };
+
+    let cases = [
+        (quote("&slippage=75"), StatusCode::BAD_REQUEST, "invalid_slippage"),
+        (quote("&slippage=-1"), StatusCode::BAD_REQUEST, "invalid_slippage"),
+        (quote("&max_hops=9"), StatusCode::BAD_REQUEST, "invalid_max_hops"),
+        (quote("&max_hops=0"), StatusCode::BAD_REQUEST, "invalid_max_hops"),
+        (quote("&max_splits=4"), StatusCode::BAD_REQUEST, "invalid_max_splits"),
+        (quote("&max_hops=two"), StatusCode::BAD_REQUEST, "invalid_request"),
+        (
+            format!(
+                "/v1/quote?token_in={:?}&token_out={:?}&amount_in=0",
+                address_from_u64(1),
+                address_from_u64(3)
+            ),
+            StatusCode::BAD_REQUEST,
+            "invalid_amount",
+        ),
+        (
+            format!(
+                "/v1/quote?token_in={:?}&token_out={:?}&amount_in=1000",
+                address_from_u64(2),
+                address_from_u64(2)
+            ),
+            StatusCode::BAD_REQUEST,
+            "same_token",
+        ),
+        (
+            format!(
+                "/v1/quote?token_in={:?}&token_out={:?}&amount_in=1000",
+                address_from_u64(999),
+                address_from_u64(2)
+            ),
+            StatusCode::NOT_FOUND,
+            "unknown_token",
+        ),
+        (
+            "/v1/quote?token_in=0x12&token_out=0x34&amount_in=1000".to_string(),
+            StatusCode::BAD_REQUEST,
+            "invalid_request",
+        ),
+        (
+            "/v1/prices/not-an-address".to_string(),
+            StatusCode::BAD_REQUEST,
+            "invalid_request",
+        ),
+    ];
+    for (uri, status, code) in cases {
+        let (actual, json) = get_json(&app, &uri).await;
+        assert_eq!(actual, status, "{}: {}", uri, json);
+        assert_eq!(json["code"], code, "{}: {}", uri, json);
+        assert!(json["message"].is_string());
+    }
+
+    let (_, json) = get_json(&app, &quote("&max_hops=9")).await;
+    assert_eq!(json["details"]["max_hops"], 9);
+    assert_eq!(json["details"]["max"], 4);
+    assert_eq!(json["details"]["violations"][0]["field"], "max_hops");
+}
+
+#[tokio::test]
+async fn test_quote_validation_reports_every_violation() {
+    let app = create_router(create_test_state());
+
+    let (status, json) = get_json(
+        &app,
+        &format!(
+            "/v1/quote?token_in={:?}&token_out={:?}&amount_in=0&slippage=120&max_hops=7",
+            address_from_u64(1),
+            address_from_u64(1)
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["code"], "validation_failed");
+    let violations = json["details"]["violations"].as_array().unwrap();
+    let fields: Vec<&str> = violations.iter().map(|v| v["field"].as_str().unwrap()).collect();
+    assert_eq!(fields, ["amount_in", "token_out", "slippage", "max_hops"]);
+    assert_eq!(violations[2]["code"], "invalid_slippage");
+    assert_eq!(violations[2]["max"], 50.0);
+
+    // More than the pools hold of the input token is refused before routing
+    let (status, json) = get_json(
+        &app,
+        &format!(
+            "/v1/quote?token_in={:?}&token_out={:?}&amount_in=1{}",
+            address_from_u64(1),
+            address_from_u64(3),
+            "0".repeat(30)
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(json["code"], "insufficient_liquidity");
+    assert_eq!(json["details"]["available"], "1000000000000000000000");
+}
+
+#[tokio::test]
+async fn test_quote_limits_come_from_routing_settings() {
+    let mut settings = test_settings();
+    settings.routing.max_hops = 2;
+    settings.routing.limits.max_slippage = 5.0;
+    settings.routing.limits.max_amount_to_liquidity = 0.0;
+    let state = AppState::new(settings);
+    add_test_pools(&state.default_chain().graph);
+    state.default_chain().sync_status.record_success();
+    let app = create_router(state);
+
+    let quote = |params: &str| {
+        format!(
+            "/v1/quote?token_in={:?}&token_out={:?}{}",
+            address_from_u64(1),
+            address_from_u64(3),
+            params
+        )
+    };
+    let (_, json) = get_json(&app, &quote("&amount_in=1000&max_hops=3")).await;
+    assert_eq!(json["code"], "invalid_max_hops");
+    assert_eq!(json["details"]["max"], 2);
+    let (_, json) = get_json(&app, &quote("&amount_in=1000&slippage=10")).await;
+    assert_eq!(json["code"], "invalid_slippage");
+
+    // With the liquidity cap disabled the amount goes to the search
+    let (status, _) = get_json(&app, &quote(&format!("&amount_in=1{}", "0".repeat(30)))).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+/// Test state that requires API keys: `partner-key` allows a burst of
+/// two requests
+fn create_keyed_test_state() -> AppState {
+    let mut settings = test_settings();
+    settings.access.require_api_key = true;
+    settings.access.keys = vec![ApiKeyConfig {
+        key: "partner-key".to_string(),
+        name: "partner".to_string(),
+        rate_limit: Some(RateLimit {
+            per_second: 0.1,
+            burst: 2,
+        }),
+        quota: Some(100),
+        fee_bps: None,
+        fee_recipient: None,
+    }];
+    settings.access.cors.allowed_origins = vec!["https://app.example".to_string()];
+    let state = AppState::new(settings);
+    add_test_pools(&state.default_chain().graph);
+    state.default_chain().sync_status.record_success();
+    state
+}
+
+async fn get_with_key(app: &axum::Router, uri: &str, key: &str) -> axum::response::Response {
+    app.clone()
+        .oneshot(
+            Request::builder()
+                .uri(uri)
+                .header("x-api-key", key)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_api_keys_and_rate_limits() {
+    let state = create_keyed_test_state();
+    let admin = create_admin_router(state.clone());
+    let app = create_router(state);
+    let prices = "/v1/prices";
+
+    let (status, json) = get_json(&app, prices).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(json["code"], "missing_api_key");
+
+    let response = get_with_key(&app, prices, "wrong-key").await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // Probes need no key
+    let (status, _) = get_json(&app, "/health").await;
+    assert_eq!(status, StatusCode::OK);
+
+    for _ in 0..2 {
+        let response = get_with_key(&app, prices, "partner-key").await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+    let response = get_with_key(&app, prices, "partner-key").await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    // One token refills in 10s
+    assert_eq!(response.headers()["retry-after"], "10");
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["code"], "rate_limited");
+
+    let response = admin
+        .clone()
+        .oneshot(Request::builder().uri("/metrics").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let metrics = String::from_utf8(body.to_vec()).unwrap();
+    assert!(metrics.contains("api_requests_total{key=\"partner\"} 2"));
+    assert!(metrics.contains("api_key_quota_remaining{key=\"partner\"} 98"));
+    assert!(metrics.contains(
+        "api_requests_rejected_total{key=\"partner\",reason=\"rate_limited\"} 1"
+    ));
+}
+
+#[tokio::test]
+async fn test_cors_allows_configured_origins() {
+    let app = create_router(create_keyed_test_state());
+    let preflight = |origin: &str| {
+        Request::builder()
+            .method("OPTIONS")
+            .uri("/v1/quote")
+            .header("origin", origin)
+            .header("access-control-request-method", "GET")
+            .header("access-control-request-headers", "x-api-key")
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    // Preflights are answered without a key
+    let response = app.clone().oneshot(preflight("https://app.example")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()["access-control-allow-origin"],
+        "https://app.example"
+    );
+
+    let response = app.clone().oneshot(preflight("https://evil.example")).await.unwrap();
+    assert!(response.headers().get("access-control-allow-origin").is_none());
+}
+
+#[tokio::test]
+async fn test_integrator_fees() {
+    let recipient = address_from_u64(77);
+    let mut settings = test_settings();
+    settings.access.keys = vec![ApiKeyConfig {
+        key: "fee-key".to_string(),
+        name: "fee-partner".to_string(),
+        rate_limit: None,
+        quota: None,
+        fee_bps: Some(25),
+        fee_recipient: Some(recipient),
+    }];
+    let state = AppState::new(settings);
+    add_test_pools(&state.default_chain().graph);
+    state.default_chain().sync_status.record_success();
+    let app = create_router(state);
+    let quote = |params: &str| {
+        format!(
+            "/v1/quote?token_in={:?}&token_out={:?}&amount_in=1000000000000000000{}",
+            address_from_u64(1),
+            address_from_u64(3),
+            params
+        )
+    };
+
+    let (status, plain) = get_json(&app, &quote("")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(plain["quote"].get("integrator_fee").is_none());
+    let gross_out: U256 = plain["quote"]["amount_out"].as_str().unwrap().parse().unwrap();
+
+    let (status, json) = get_json(
+        &app,
+        &quote(&format!("&fee_bps=100&fee_recipient={:?}", recipient)),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", json);
+    let fee = &json["quote"]["integrator_fee"];
+    assert_eq!(fee["bps"], 100);
+    assert_eq!(fee["side"], "output");
+    let fee_out = gross_out / U256::from(100);
+    assert_eq!(fee["amount"], fee_out.to_string());
+    let calldata = fee_transfer_calldata(address_from_u64(3), recipient, fee_out);
+    assert_eq!(fee["calldata"], calldata.to_string());
+    let net_out: U256 = json["quote"]["amount_out"].as_str().unwrap().parse().unwrap();
+    assert_eq!(net_out, gross_out - fee_out);
+    assert!(json["quote"]["route"]["fee_transfer"].as_bool().unwrap());
+    assert!(json["quote"]["gas_estimate"].as_u64() > plain["quote"]["gas_estimate"].as_u64());
+
+    // The API key's fee applies unless the request sets one
+    let response = get_with_key(&app, &quote("&fee_side=input"), "fee-key").await;
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    let fee = &json["quote"]["integrator_fee"];
+    assert_eq!(fee["bps"], 25);
+    assert_eq!(fee["recipient"], format!("{:?}", recipient));
+    assert_eq!(fee["amount"], "2500000000000000");
+    let fee_in = U256::from(2_500_000_000_000_000u64);
+    let calldata = fee_transfer_calldata(address_from_u64(1), recipient, fee_in);
+    assert_eq!(fee["calldata"], calldata.to_string());
+    assert_eq!(json["quote"]["amount_in"], "1000000000000000000");
+
+    let (status, json) = get_json(&app, &quote("&fee_bps=500")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["code"], "validation_failed");
+    let fields: Vec<&str> = json["details"]["violations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, ["fee_bps", "fee_recipient"]);
+}
+
+async fn post_json(app: &axum::Router, uri: &str) -> (StatusCode, Value) {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn test_issued_quotes_can_be_fetched_and_refreshed() {
+    let state = create_test_state();
+    let graph = state.default_chain().graph.clone();
+    graph.set_block_number(100);
+    let app = create_router(state);
+    let uri = format!(
+        "/v1/quote?token_in={:?}&token_out={:?}&amount_in=1000000000000000000",
+        address_from_u64(1),
+        address_from_u64(2)
+    );
+
+    let (status, issued) = get_json(&app, &uri).await;
+    assert_eq!(status, StatusCode::OK);
+    let id = issued["quote_id"].as_str().unwrap().to_string();
+    assert_eq!(issued["chain_id"], 8453);
+    assert_eq!(issued["block_number"], 100);
+    assert!(issued["graph_version"].is_number());
+    assert_eq!(
+        issued["expires_at"].as_u64().unwrap(),
+        issued["timestamp"].as_u64().unwrap() + 30
+    );
+
+    // A second quote for the same request is a new quote
+    let (_, again) = get_json(&app, &uri).await;
+    assert_eq!(again["cached"], true);
+    assert_ne!(again["quote_id"], issued["quote_id"]);
+
+    let (status, fetched) = get_json(&app, &format!("/v1/quote/{}", id)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(fetched["quote_id"], issued["quote_id"]);
+    assert_eq!(fetched["quote"]["amount_out"], issued["quote"]["amount_out"]);
+
+    // Unchanged pools: no drift
+    let refresh = format!("/v1/quote/{}/refresh", id);
+    let (status, json) = post_json(&app, &refresh).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["quote_id"], issued["quote_id"]);
+    assert_eq!(json["amount_out"], issued["quote"]["amount_out"]);
+    assert_eq!(json["quoted_amount_out"], issued["quote"]["amount_out"]);
+    assert_eq!(json["drift_bps"], 0.0);
+    assert_eq!(json["within_slippage"], true);
+    assert_eq!(json["expired"], false);
+
+    // Most A/B liquidity is withdrawn, so the swap moves the price more
+    let pool_ab = PoolEdge::new(
+        [1u8; 32],
+        address_from_u64(1),
+        address_from_u64(2),
+        3000,
+        60,
+        10_000_000_000_000_000_000,
+        U256::from(1u128 << 96),
+        0,
+    );
+    graph.upsert_pool(
+        pool_ab,
+        TokenNode::new(address_from_u64(1), "TokenA".to_string(), 18),
+        TokenNode::new(address_from_u64(2), "TokenB".to_string(), 18),
+    );
+    graph.set_block_number(101);
+
+    let (status, json) = post_json(&app, &refresh).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["block_number"], 101);
+    assert_eq!(json["quoted_block_number"], 100);
+    assert!(json["drift_bps"].as_f64().unwrap() < -1000.0);
+    assert_eq!(json["within_slippage"], false);
+    let amount_out: U256 = json["amount_out"].as_str().unwrap().parse().unwrap();
+    let quoted: U256 = json["quoted_amount_out"].as_str().unwrap().parse().unwrap();
+    assert!(amount_out < quoted);
+
+    let unknown = "/v1/quote/00000000-0000-4000-8000-000000000000";
+    let (status, json) = get_json(&app, unknown).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(json["code"], "quote_not_found");
+    let (status, json) = post_json(&app, &format!("{}/refresh", unknown)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(json["code"], "quote_not_found");
+
+    let (status, json) = get_json(&app, "/v1/quote/not-an-id").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["code"], "invalid_request");
+}
+
+#[tokio::test]
+async fn test_cached_quotes_keep_the_block_they_were_computed_at() {
+    let state = create_test_state();
+    let graph = state.default_chain().graph.clone();
+    graph.set_block_number(100);
+    let app = create_router(state);
+    let uri = format!(
+        "/v1/quote?token_in={:?}&token_out={:?}&amount_in=1000000000000000000",
+        address_from_u64(1),
+        address_from_u64(2)
+    );
+
+    let (_, first) = get_json(&app, &uri).await;
+    assert_eq!(first["cached"], false);
+    assert_eq!(first["block_number"], 100);
+
+    // A new block with no pool changes: the cached quote still holds,
+    // but was computed at block 100
+    graph.set_block_number(101);
+    let (_, second) = get_json(&app, &uri).await;
+    assert_eq!(second["cached"], true);
+    assert_eq!(second["block_number"], 100);
+    assert_eq!(second["graph_version"], first["graph_version"]);
+
+    // A pool change retires the cached quote
+    let pool_ab = PoolEdge::new(
+        [1u8; 32],
+        address_from_u64(1),
+        address_from_u64(2),
+        3000,
+        60,
+        10_000_000_000_000_000_000,
+        U256::from(1u128 << 96),
+        0,
+    );
+    graph.upsert_pool(
+        pool_ab,
+        TokenNode::new(address_from_u64(1), "TokenA".to_string(), 18),
+        TokenNode::new(address_from_u64(2), "TokenB".to_string(), 18),
+    );
+    graph.set_block_number(102);
+    let (_, third) = get_json(&app, &uri).await;
+    assert_eq!(third["cached"], false);
+    assert_eq!(third["block_number"], 102);
+    assert!(third["graph_version"].as_u64() > first["graph_version"].as_u64());
+    assert_ne!(third["quote"]["amount_out"], first["quote"]["amount_out"]);
+}
+
+#[tokio::test]
+async fn test_expired_quotes_can_only_be_refreshed() {
+    let mut settings = test_settings();
+    settings.quotes.ttl_secs = 0;
+    let state = AppState::new(settings);
+    add_test_pools(&state.default_chain().graph);
+    state.default_chain().sync_status.record_success();
+    let app = create_router(state);
+    let uri = format!(
+        "/v1/quote?token_in={:?}&token_out={:?}&amount_in=1000000000000000000",
+        address_from_u64(1),
+        address_from_u64(2)
+    );
+
+    let (status, issued) = get_json(&app, &uri).await;
+    assert_eq!(status, StatusCode::OK);
+    let id = issued["quote_id"].as_str().unwrap();
+
+    let (status, json) = get_json(&app, &format!("/v1/quote/{}", id)).await;
+    assert_eq!(status, StatusCode::GONE);
+    assert_eq!(json["code"], "quote_expired");
+    assert_eq!(json["details"]["expires_at"], issued["expires_at"]);
+
+    let (status, json) = post_json(&app, &format!("/v1/quote/{}/refresh", id)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["expired"], true);
+    assert_eq!(json["drift_bps"], 0.0);
+}
+
+/// A quote response parsed from the body, as a relaying service would
+async fn get_quote_response(app: &axum::Router, uri: &str) -> QuoteResponse {
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn test_quotes_are_signed_with_the_configured_key() {
+    let mut settings = test_settings();
+    settings.signing.key =
+        Some("0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d".to_string());
+    settings.signing.verifying_contract = Some(address_from_u64(42));
+    let state = AppState::new(settings);
+    add_test_pools(&state.default_chain().graph);
+    state.default_chain().sync_status.record_success();
+    let signer = state.quotes.signer().unwrap();
+    let app = create_router(state);
+    let uri = format!(
+        "/v1/quote?token_in={:?}&token_out={:?}&amount_in=1000000000000000000&fee_bps=30&fee_recipient={:?}",
+        address_from_u64(1),
+        address_from_u64(3),
+        address_from_u64(9)
+    );
+
+    let issued = get_quote_response(&app, &uri).await;
+    assert_eq!(issued.signature.as_ref().unwrap().signer, signer);
+    assert_eq!(verify_quote(&issued, signer, Some(address_from_u64(42))), Ok(()));
+
+    // The stored quote carries the same signature
+    let fetched = get_quote_response(&app, &format!("/v1/quote/{}", issued.quote_id)).await;
+    assert_eq!(fetched.signature, issued.signature);
+
+    // A relay that raises the output no longer verifies
+    let mut tampered = issued;
+    tampered.quote.amount_out = "2000000000000000000".to_string();
+    assert!(matches!(
+        verify_quote(&tampered, signer, Some(address_from_u64(42))),
+        Err(SignatureError::WrongSigner { .. })
+    ));
+
+    // A cached quote is only reused for the exact same request, so the
+    // signed minimum output is always the one asked for
+    let tight = get_quote_response(&app, &format!("{}&slippage=0.1", uri)).await;
+    assert!(!tight.cached);
+    let loose = get_quote_response(&app, &format!("{}&slippage=5", uri)).await;
+    assert!(!loose.cached);
+    let again = get_quote_response(&app, &format!("{}&slippage=0.1", uri)).await;
+    assert!(again.cached);
+    assert_eq!(again.quote.amount_out_min, tight.quote.amount_out_min);
+    assert_ne!(loose.quote.amount_out_min, tight.quote.amount_out_min);
+    assert_eq!(verify_quote(&again, signer, Some(address_from_u64(42))), Ok(()));
+    let hops = get_quote_response(&app, &format!("{}&slippage=0.1&max_hops=2", uri)).await;
+    assert!(!hops.cached);
+    let splits = get_quote_response(&app, &format!("{}&slippage=0.1&max_splits=1", uri)).await;
+    assert!(!splits.cached);
+
+    // Without a key quotes are unsigned
+    let app = create_router(create_test_state());
+    let (_, json) = get_json(&app, &uri).await;
+    assert!(json.get("signature").is_none());
+}
+
+#[tokio::test]
+async fn test_input_fees_are_charged_on_the_requested_amount() {
+    let app = create_router(create_test_state());
+    let recipient = address_from_u64(77);
+    // Both amounts, and both amounts left after the fee, round to the
+    // same two significant figures
+    for amount in [2_150_000_000_000_000_000u128, 2_180_000_000_000_000_000] {
+        let uri = format!(
+            "/v1/quote?token_in={:?}&token_out={:?}&amount_in={}&fee_bps=30&fee_side=input&fee_recipient={:?}",
+            address_from_u64(1),
+            address_from_u64(3),
+            amount,
+            recipient
+        );
+        let (status, json) = get_json(&app, &uri).await;
+        assert_eq!(status, StatusCode::OK, "{}", json);
+        assert_eq!(json["cached"], false);
+
+        let amount = U256::from(amount);
+        let fee = amount * U256::from(30) / U256::from(10_000);
+        assert_eq!(json["quote"]["amount_in"], amount.to_string());
+        assert_eq!(json["quote"]["integrator_fee"]["amount"], fee.to_string());
+        let routed: U256 = json["quote"]["route"]["total_amount_in"]
+            .as_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(routed, amount - fee);
+    }
+}
+
+#[tokio::test]
+async fn test_max_splits_spreads_large_quotes_across_pools() {
+    let state = AppState::new(test_settings());
+    state.default_chain().sync_status.record_success();
+    let graph = &state.default_chain().graph;
+    let (token_a, token_b) = (address_from_u64(1), address_from_u64(2));
+    // Two shallow pools side by side; one alone takes a steep price impact
+    for id in [4u8, 5] {
+        let pool = PoolEdge::new(
+            [id; 32],
+            token_a,
+            token_b,
+            3000,
+            60,
+            100_000_000_000_000_000_000,
+            U256::from(1u128 << 96),
+            0,
+        );
+        graph.upsert_pool(
+            pool,
+            TokenNode::new(token_a, "TokenA".to_string(), 18),
+            TokenNode::new(token_b, "TokenB".to_string(), 18),
+        );
+    }
+    let app = create_router(state);
+    let uri = format!(
+        "/v1/quote?token_in={:?}&token_out={:?}&amount_in=50000000000000000000",
+        token_a, token_b
+    );
+
+    let legs = |json: &Value| json["quote"]["route"]["routes"].as_array().unwrap().len();
+    let amount_out = |json: &Value| {
+        json["quote"]["amount_out"]
+            .as_str()
+            .unwrap()
+            .parse::<U256>()
+            .unwrap()
+    };
+
+    let (status, single) = get_json(&app, &format!("{}&max_splits=1", uri)).await;
+    assert_eq!(status, StatusCode::OK, "{}", single);
+    assert_eq!(legs(&single), 1);
+
+    let (status, split) = get_json(&app, &format!("{}&max_splits=2", uri)).await;
+    assert_eq!(status, StatusCode::OK, "{}", split);
+    assert_eq!(legs(&split), 2);
+    assert!(amount_out(&split) > amount_out(&single));
+}
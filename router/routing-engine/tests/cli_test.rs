@@ -0,0 +1,176 @@
+use routing_engine::{
+    graph::{PoolGraph, SnapshotStore},
+    sync::PoolSyncer,
+};
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use std::sync::Arc;
+
+/// Write a snapshot of the dev pool set to a temporary file
+async fn write_snapshot() -> PathBuf {
+    let graph = Arc::new(PoolGraph::new());
+    PoolSyncer::new(graph.clone())
+        .sync_pools()
+        .await
+        .expect("Should sync pools");
+
+    let path = std::env::temp_dir()
+        .join(format!("routing-cli-{}", uuid::Uuid::new_v4()))
+        .join("graph-snapshot.json");
+    SnapshotStore::new(&path, 8453)
+        .save(&graph)
+        .expect("Should write snapshot");
+    path
+}
+
+fn run_cli(snapshot: &PathBuf, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_routing-cli"))
+        .arg("--snapshot")
+        .arg(snapshot)
+        .args(args)
+        .output()
+        .expect("Should run routing-cli")
+}
+
+fn stdout(output: &Output) -> String {
+    assert!(
+        output.status.success(),
+        "routing-cli failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[tokio::test]
+async fn test_cli_quote() {
+    let snapshot = write_snapshot().await;
+
+    let output = stdout(&run_cli(
+        &snapshot,
+        &["quote", "--from", "WETH", "--to", "USDC", "--amount", "1e18"],
+    ));
+    assert!(output.contains("Quote WETH → USDC"));
+    assert!(output.contains("amount in:     1 WETH"));
+
+    let output = stdout(&run_cli(
+        &snapshot,
+        &["--json", "quote", "--from", "WETH", "--to", "USDC", "--amount", "1e18"],
+    ));
+    let quote: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(quote["amount_in"], "1000000000000000000");
+    assert_ne!(quote["amount_out"], "0");
+
+    std::fs::remove_dir_all(snapshot.parent().unwrap()).unwrap();
+}
+
+#[tokio::test]
+async fn test_cli_split_quote_and_routes() {
+    let snapshot = write_snapshot().await;
+
+    let output = stdout(&run_cli(
+        &snapshot,
+        &[
+            "--json", "split-quote", "--from", "WETH", "--to", "USDC", "--amount", "100e18",
+        ],
+    ));
+    let quote: serde_json::Value = serde_json::from_str(&output).unwrap();
+    let percent_total: u64 = quote["route"]["routes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r[1].as_u64().unwrap())
+        .sum();
+    assert_eq!(percent_total, 100);
+
+    let output = stdout(&run_cli(
+        &snapshot,
+        &[
+            "--json", "routes", "--from", "WETH", "--to", "DAI", "--amount", "1e18", "--top", "3",
+        ],
+    ));
+    let routes: Vec<serde_json::Value> = serde_json::from_str(&output).unwrap();
+    assert!(!routes.is_empty() && routes.len() <= 3);
+
+    std::fs::remove_dir_all(snapshot.parent().unwrap()).unwrap();
+}
+
+#[tokio::test]
+async fn test_cli_graph_and_pool_inspection() {
+    let snapshot = write_snapshot().await;
+
+    let output = stdout(&run_cli(&snapshot, &["--json", "graph", "stats"]));
+    let stats: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert!(stats["pool_count"].as_u64().unwrap() >= 5);
+
+    let pool_id = format!("0x{}", "01".repeat(32));
+    let output = stdout(&run_cli(&snapshot, &["pool", "show", &pool_id]));
+    assert!(output.contains("pair:           WETH / USDC"));
+
+    std::fs::remove_dir_all(snapshot.parent().unwrap()).unwrap();
+}
+
+#[tokio::test]
+async fn test_cli_reports_errors() {
+    let snapshot = write_snapshot().await;
+
+    let output = run_cli(
+        &snapshot,
+        &["quote", "--from", "NOPE", "--to", "USDC", "--amount", "1"],
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown token: NOPE"));
+
+    let output = run_cli(&snapshot.with_file_name("missing.json"), &["graph", "stats"]);
+    assert!(!output.status.success());
+
+    std::fs::remove_dir_all(snapshot.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_cli_loads_fixture() {
+    let fixture = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/multi-path.toml");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_routing-cli"))
+        .args(["--fixture", fixture, "--json", "graph", "stats"])
+        .output()
+        .expect("Should run routing-cli");
+    let stats: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(stats["pool_count"], 8);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_routing-cli"))
+        .args(["--fixture", fixture])
+        .args(["routes", "--from", "TOKENA", "--to", "TOKENE", "--amount", "1e18"])
+        .output()
+        .expect("Should run routing-cli");
+    assert!(stdout(&output).contains(" 1. "));
+}
+
+#[test]
+fn test_cli_backtest() {
+    let dataset = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/backtest-sample.json");
+    let dir = std::env::temp_dir().join(format!("routing-cli-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let candidate = dir.join("single-hop.toml");
+    std::fs::write(&candidate, "max_hops = 1\nmax_splits = 1\n").unwrap();
+    let report_path = dir.join("report.json");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_routing-cli"))
+        .args(["backtest", "--dataset", dataset, "--candidate"])
+        .arg(&candidate)
+        .arg("--output")
+        .arg(&report_path)
+        .output()
+        .expect("Should run routing-cli");
+    let output = stdout(&output);
+    assert!(output.contains("Backtest: 4 blocks, 7 swaps"));
+    assert!(output.contains("comparison (candidate vs baseline):"));
+
+    let report: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&report_path).unwrap()).unwrap();
+    assert_eq!(report["version"], 1);
+    assert_eq!(report["candidate"]["name"], "single-hop");
+    assert_eq!(report["records"].as_array().unwrap().len(), 7);
+    assert_eq!(report["baseline"]["latency_us"]["count"], 7);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
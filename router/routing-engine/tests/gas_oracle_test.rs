@@ -0,0 +1,201 @@
+use alloy_primitives::{hex, keccak256, U256};
+use axum::{extract::State, routing::post, Json, Router as HttpRouter};
+use routing_engine::gas::{GasConfig, GasOracle, GasPrices, L1FeeParams};
+use routing_engine::graph::{PoolEdge, PoolGraph, TokenNode};
+use routing_engine::pricing::{PriceOracle, PricingConfig};
+use routing_engine::routing::{Router, RouterConfig};
+use routing_engine::sync::SyncStatus;
+use routing_engine::utils::addresses::{usdc, weth};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+
+const L1_BASE_FEE: u64 = 8_000_000_000; // 8 gwei
+const BLOB_BASE_FEE: u64 = 2;
+const BASE_FEE_SCALAR: u64 = 2269;
+const BLOB_BASE_FEE_SCALAR: u64 = 1_055_762;
+
+/// How the stand-in node answers
+#[derive(Clone, Copy)]
+struct Node {
+    fee_history: bool,
+}
+
+/// Minimal JSON-RPC node serving the calls the gas oracle makes
+async fn handle(State(node): State<Node>, Json(request): Json<Value>) -> Json<Value> {
+    let id = request["id"].clone();
+    let result = match request["method"].as_str().unwrap() {
+        "eth_gasPrice" => json!("0x1312d00"), // 0.02 gwei
+        "eth_feeHistory" if node.fee_history => json!({
+            "oldestBlock": "0x10",
+            // Last entry is the next block's base fee (0.005 gwei)
+            "baseFeePerGas": ["0x3d0900", "0x4c4b40", "0x4c4b40"],
+            "gasUsedRatio": [0.4, 0.6],
+            "reward": [["0xf4240"], ["0x2dc6c0"]], // 0.001 and 0.003 gwei
+        }),
+        "eth_feeHistory" => {
+            return Json(json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": -32601, "message": "method not found" },
+            }))
+        }
+        "eth_call" => {
+            let data = request["params"][0]["data"].as_str().unwrap();
+            let selector = hex::decode(data).unwrap();
+            let value = [
+                ("l1BaseFee()", L1_BASE_FEE),
+                ("blobBaseFee()", BLOB_BASE_FEE),
+                ("baseFeeScalar()", BASE_FEE_SCALAR),
+                ("blobBaseFeeScalar()", BLOB_BASE_FEE_SCALAR),
+            ]
+            .into_iter()
+            .find(|(signature, _)| keccak256(signature)[..4] == selector[..])
+            .map(|(_, value)| value)
+            .expect("known GasPriceOracle getter");
+            json!(format!("0x{}", hex::encode(U256::from(value).to_be_bytes::<32>())))
+        }
+        method => panic!("unexpected method {}", method),
+    };
+    Json(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+}
+
+/// Start a stand-in node and return its URL
+async fn start_node(node: Node) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let app = HttpRouter::new().route("/", post(handle)).with_state(node);
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    url
+}
+
+fn expected_l1_params() -> L1FeeParams {
+    L1FeeParams {
+        l1_base_fee: L1_BASE_FEE as u128,
+        blob_base_fee: BLOB_BASE_FEE as u128,
+        base_fee_scalar: BASE_FEE_SCALAR as u32,
+        blob_base_fee_scalar: BLOB_BASE_FEE_SCALAR as u32,
+    }
+}
+
+#[tokio::test]
+async fn test_refresh_reads_fee_history_and_l1_parameters() {
+    let url = start_node(Node { fee_history: true }).await;
+    let oracle = GasOracle::new(url, GasConfig::default());
+
+    let prices = oracle.refresh().await.unwrap();
+    assert_eq!(prices.base_fee, 5_000_000);
+    // Mean of the per-block median rewards
+    assert_eq!(prices.priority_fee, 2_000_000);
+    assert_eq!(prices.gas_price, 7_000_000);
+    assert_eq!(prices.l1, expected_l1_params());
+    assert!(prices.updated_at.is_some());
+    assert_eq!(oracle.current(), prices);
+}
+
+#[tokio::test]
+async fn test_gas_price_falls_back_without_fee_history() {
+    let url = start_node(Node { fee_history: false }).await;
+    let oracle = GasOracle::new(url, GasConfig::default());
+
+    let prices = oracle.refresh().await.unwrap();
+    assert_eq!(prices.gas_price, 20_000_000);
+    assert_eq!(prices.priority_fee, 0);
+    assert_eq!(prices.l1, expected_l1_params());
+}
+
+#[tokio::test]
+async fn test_failed_refresh_keeps_last_prices() {
+    // Nothing listens on this port once the listener is dropped
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+
+    let oracle = GasOracle::new(url, GasConfig::default());
+    assert!(oracle.refresh().await.is_err());
+    assert_eq!(oracle.current(), GasPrices::default());
+}
+
+#[tokio::test]
+async fn test_periodic_refresh_reports_rpc_health() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let dead_url = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+
+    let status = Arc::new(SyncStatus::new());
+    let run = |url: String| {
+        let oracle =
+            Arc::new(GasOracle::new(url, GasConfig::default()).with_status(status.clone()));
+        let shutdown = CancellationToken::new();
+        let task = tokio::spawn(oracle.run_periodic(shutdown.clone()));
+        (shutdown, task)
+    };
+    let wait_for = |healthy: bool| {
+        let status = status.clone();
+        async move {
+            tokio::time::timeout(Duration::from_secs(5), async {
+                while status.rpc_healthy() != healthy {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+            })
+            .await
+            .expect("RPC health was reported");
+        }
+    };
+
+    // The first refresh runs immediately
+    let (shutdown, task) = run(dead_url);
+    wait_for(false).await;
+    assert!(status.rpc_error().is_some());
+    shutdown.cancel();
+    task.await.unwrap().unwrap();
+
+    let (shutdown, task) = run(start_node(Node { fee_history: true }).await);
+    wait_for(true).await;
+    assert!(status.rpc_error().is_none());
+    shutdown.cancel();
+    task.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn test_quote_fee_includes_l1_data_fee() {
+    let url = start_node(Node { fee_history: true }).await;
+    let gas = Arc::new(GasOracle::new(url, GasConfig::default()));
+    let prices = gas.refresh().await.unwrap();
+
+    // WETH at $3000
+    let graph = Arc::new(PoolGraph::new());
+    graph.upsert_pool(
+        PoolEdge::constant_product(
+            [1; 32],
+            weth(),
+            usdc(),
+            3000,
+            [U256::from(10u128.pow(24)), U256::from(3 * 10u128.pow(15))],
+        ),
+        TokenNode::new(weth(), "WETH".to_string(), 18),
+        TokenNode::new(usdc(), "USDC".to_string(), 6),
+    );
+    let router = Router::with_config(graph.clone(), RouterConfig::default())
+        .with_price_oracle(Arc::new(PriceOracle::new(graph, PricingConfig::default())))
+        .with_gas_oracle(gas);
+
+    let quote = router
+        .get_quote(weth(), usdc(), U256::from(10u128.pow(18)), 0.5, Some(1))
+        .unwrap();
+    let fee = &quote.fee;
+    assert_eq!(fee.l2_gas, quote.gas_estimate);
+    assert_eq!(fee.l2_fee_wei, U256::from(quote.gas_estimate as u128 * 7_000_000));
+    assert_eq!(
+        fee.l1_fee_wei,
+        U256::from(prices.l1.fee_upper_bound(fee.calldata_bytes + 60))
+    );
+    assert!(fee.l1_fee_wei > U256::ZERO);
+    assert_eq!(fee.total_fee_wei, fee.l1_fee_wei + fee.l2_fee_wei);
+
+    let usd = quote.gas_estimate_usd.unwrap();
+    assert_eq!(fee.total_fee_usd, Some(usd));
+    assert!((usd / (fee.total_fee_eth * 3000.0) - 1.0).abs() < 1e-9);
+}
@@ -0,0 +1,156 @@
+use alloy_primitives::U256;
+use routing_engine::graph::synthetic::{long_tail_graph, Lcg};
+use routing_engine::graph::{PoolGraph, TokenNode};
+use routing_engine::routing::{
+    CandidateConfig, CandidateGraph, Router, RouterConfig, SearchBudget,
+};
+use routing_engine::utils::address_from_u64;
+use routing_engine::utils::addresses::{usdc, weth};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const TOKENS: u64 = 10_000;
+
+/// Per-quote budget for the filtered search
+///
+/// Only checked in release builds (`cargo test --release`). Debug
+/// builds run the U256 math unoptimized, so their latency says nothing
+/// about production; they still check the routes.
+const QUOTE_BUDGET: Duration = Duration::from_millis(10);
+
+/// How far past its deadline an interrupted search may return
+const BUDGET_OVERRUN: Duration = Duration::from_millis(20);
+
+/// The synthetic 50k-pool graph the search benchmark also runs on
+fn large_graph() -> Arc<PoolGraph> {
+    Arc::new(long_tail_graph(TOKENS))
+}
+
+#[test]
+fn test_quotes_on_large_graph_stay_within_budget() {
+    let graph = large_graph();
+    assert!(graph.stats().pool_count >= 49_000);
+
+    let config = RouterConfig {
+        enable_cache: false,
+        candidates: CandidateConfig {
+            connector_tokens: vec![weth(), usdc()],
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    // The router indexes the graph up front; time the same build here
+    let index_start = Instant::now();
+    let candidates = CandidateGraph::build(&graph, &config.candidates);
+    println!(
+        "Candidate index: {} directed edges in {:?}",
+        candidates.edge_count(),
+        index_start.elapsed()
+    );
+    let router = Router::with_config(graph.clone(), config);
+
+    let amount = U256::from(10u128.pow(18));
+    let mut rng = Lcg(7);
+    let mut slowest = Duration::ZERO;
+    for _ in 0..20 {
+        let token_in = address_from_u64(1 + rng.next(TOKENS));
+        let token_out = address_from_u64(1 + rng.next(TOKENS));
+        if token_in == token_out {
+            continue;
+        }
+
+        let start = Instant::now();
+        let route = router
+            .find_split_route(token_in, token_out, amount, Some(4), Some(3))
+            .expect("Every token is connected through WETH");
+        slowest = slowest.max(start.elapsed());
+
+        // Intermediate tokens are connectors only
+        for (split, _) in &route.routes {
+            for hop in &split.hops[..split.hops.len() - 1] {
+                assert!(hop.token_out == weth() || hop.token_out == usdc());
+            }
+        }
+    }
+
+    println!("Slowest quote: {:?}", slowest);
+    if !cfg!(debug_assertions) {
+        assert!(
+            slowest < QUOTE_BUDGET,
+            "slowest quote took {:?}, budget {:?}",
+            slowest,
+            QUOTE_BUDGET
+        );
+    }
+}
+
+#[test]
+fn test_candidate_index_follows_graph_updates() {
+    let graph = large_graph();
+    let router = Router::new(graph.clone());
+    let before = router.candidates();
+
+    // Drain every WETH pool of token 1; USDC remains
+    let token = address_from_u64(1);
+    for mut pool in graph.get_pools_for_token(token) {
+        if pool.other_token(token) == Some(weth()) {
+            pool.liquidity = 0;
+            let tokens = [pool.token0, pool.token1].map(|a| TokenNode::new(a, String::new(), 18));
+            let [node0, node1] = tokens;
+            graph.upsert_pool(pool, node0, node1);
+        }
+    }
+
+    let after = router.candidates();
+    assert!(after.version() > before.version());
+    assert!(before.pools_between(token, weth()).next().is_some());
+    assert!(after.pools_between(token, weth()).next().is_none());
+    assert!(after.pools_between(token, usdc()).next().is_some());
+}
+
+#[test]
+fn test_unfiltered_search_returns_partial_route_within_budget() {
+    let graph = large_graph();
+    // Every pool and every intermediate token: a full 4-hop search
+    // takes far longer than the budget
+    let router = Router::with_config(
+        graph,
+        RouterConfig {
+            enable_cache: false,
+            candidates: CandidateConfig::unfiltered(),
+            screening: None,
+            ..Default::default()
+        },
+    );
+    let budget = if cfg!(debug_assertions) {
+        Duration::from_secs(2)
+    } else {
+        Duration::from_millis(50)
+    };
+
+    let [token_in, token_out] = [1, 2].map(address_from_u64);
+    let amount = U256::from(10u128.pow(18));
+    let start = Instant::now();
+    let route = router
+        .find_route_within(
+            token_in,
+            token_out,
+            amount,
+            Some(4),
+            &SearchBudget::with_timeout(budget),
+        )
+        .expect("Two-hop routes through WETH complete first");
+    let elapsed = start.elapsed();
+
+    println!("Partial route after {:?}: {} hops", elapsed, route.hop_count());
+    assert!(route.partial);
+    assert!(route.total_amount_out > U256::ZERO);
+    // The budget is checked every few simulated hops
+    assert!(
+        elapsed < budget + BUDGET_OVERRUN,
+        "search took {:?}, budget {:?}",
+        elapsed,
+        budget
+    );
+}
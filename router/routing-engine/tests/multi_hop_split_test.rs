@@ -1,136 +1,22 @@
-use alloy_primitives::{Address, U256};
+#![allow(clippy::len_zero, clippy::expect_fun_call)]
+
+use alloy_primitives::U256;
 use routing_engine::utils::address_from_u64;
 use routing_engine::{
-    graph::{PoolEdge, PoolGraph, TokenNode},
-    routing::{find_best_multi_hop_route, find_top_routes, optimize_split_route, Router},
+    graph::{GraphFixture, PoolGraph},
+    routing::{find_top_routes, optimize_split_route, Router},
 };
 use std::sync::Arc;
 
 /// Create a complex graph for testing multi-hop and split routing
+///
+/// Five tokens A..E with several competing paths from A to E; see
+/// `fixtures/multi-path.toml`.
 fn create_complex_graph() -> Arc<PoolGraph> {
     let graph = Arc::new(PoolGraph::new());
-
-    // Create 5 tokens: A, B, C, D, E
-    let tokens: Vec<Address> = (1..=5).map(|i| address_from_u64(i)).collect();
-
-    let nodes: Vec<TokenNode> = tokens
-        .iter()
-        .enumerate()
-        .map(|(i, addr)| TokenNode::new(*addr, format!("TOKEN{}", (b'A' + i as u8) as char), 18))
-        .collect();
-
-    // Create multiple paths from A to E:
-    // Path 1: A -> B -> E (2 hops, medium liquidity)
-    // Path 2: A -> C -> E (2 hops, high liquidity, higher fee)
-    // Path 3: A -> D -> E (2 hops, low liquidity)
-    // Path 4: A -> B -> C -> E (3 hops, high liquidity)
-    // Path 5: A -> E (1 hop, low liquidity, high fee)
-
-    // Path 1: A -> B
-    let pool_ab = PoolEdge::new(
-        [1u8; 32],
-        tokens[0],
-        tokens[1],
-        3000, // 0.3%
-        60,
-        2_000_000_000_000_000_000_000,
-        U256::from(1u128 << 96),
-        0,
-    );
-
-    // Path 1: B -> E
-    let pool_be = PoolEdge::new(
-        [2u8; 32],
-        tokens[1],
-        tokens[4],
-        3000,
-        60,
-        2_000_000_000_000_000_000_000,
-        U256::from(1u128 << 96),
-        0,
-    );
-
-    // Path 2: A -> C
-    let pool_ac = PoolEdge::new(
-        [3u8; 32],
-        tokens[0],
-        tokens[2],
-        500, // 0.05% - lower fee
-        10,
-        5_000_000_000_000_000_000_000, // Higher liquidity
-        U256::from(1u128 << 96),
-        0,
-    );
-
-    // Path 2: C -> E
-    let pool_ce = PoolEdge::new(
-        [4u8; 32],
-        tokens[2],
-        tokens[4],
-        500,
-        10,
-        5_000_000_000_000_000_000_000,
-        U256::from(1u128 << 96),
-        0,
-    );
-
-    // Path 3: A -> D
-    let pool_ad = PoolEdge::new(
-        [5u8; 32],
-        tokens[0],
-        tokens[3],
-        3000,
-        60,
-        500_000_000_000_000_000_000, // Low liquidity
-        U256::from(1u128 << 96),
-        0,
-    );
-
-    // Path 3: D -> E
-    let pool_de = PoolEdge::new(
-        [6u8; 32],
-        tokens[3],
-        tokens[4],
-        3000,
-        60,
-        500_000_000_000_000_000_000,
-        U256::from(1u128 << 96),
-        0,
-    );
-
-    // Path 4: B -> C
-    let pool_bc = PoolEdge::new(
-        [7u8; 32],
-        tokens[1],
-        tokens[2],
-        3000,
-        60,
-        3_000_000_000_000_000_000_000,
-        U256::from(1u128 << 96),
-        0,
-    );
-
-    // Path 5: A -> E (direct, but expensive)
-    let pool_ae = PoolEdge::new(
-        [8u8; 32],
-        tokens[0],
-        tokens[4],
-        10000, // 1% - high fee
-        200,
-        800_000_000_000_000_000_000, // Low liquidity
-        U256::from(1u128 << 96),
-        0,
-    );
-
-    graph.upsert_pool(pool_ab, nodes[0].clone(), nodes[1].clone());
-    graph.upsert_pool(pool_be, nodes[1].clone(), nodes[4].clone());
-    graph.upsert_pool(pool_ac, nodes[0].clone(), nodes[2].clone());
-    graph.upsert_pool(pool_ce, nodes[2].clone(), nodes[4].clone());
-    graph.upsert_pool(pool_ad, nodes[0].clone(), nodes[3].clone());
-    graph.upsert_pool(pool_de, nodes[3].clone(), nodes[4].clone());
-    graph.upsert_pool(pool_bc, nodes[1].clone(), nodes[2].clone());
-    graph.upsert_pool(pool_ae, nodes[0].clone(), nodes[4].clone());
-
+    let fixture = GraphFixture::from_toml(include_str!("../fixtures/multi-path.toml"))
+        .expect("Fixture should parse");
+    graph.import_fixture(&fixture).expect("Fixture should import");
     graph
 }
 
@@ -251,8 +137,8 @@ fn test_split_routing_optimization() {
     }
 }
 
-#[tokio::test]
-async fn test_router_multi_hop_integration() {
+#[test]
+fn test_router_multi_hop_integration() {
     let graph = create_complex_graph();
     let router = Router::new(graph);
 
@@ -265,7 +151,6 @@ async fn test_router_multi_hop_integration() {
     for max_hops in [1, 2, 3, 4] {
         let route = router
             .find_route(token_a, token_e, amount, Some(max_hops))
-            .await
             .expect(&format!("Should find route with max_hops={}", max_hops));
 
         assert!(
@@ -283,8 +168,8 @@ async fn test_router_multi_hop_integration() {
     }
 }
 
-#[tokio::test]
-async fn test_router_split_routing_integration() {
+#[test]
+fn test_router_split_routing_integration() {
     let graph = create_complex_graph();
     let router = Router::new(graph);
 
@@ -296,7 +181,6 @@ async fn test_router_split_routing_integration() {
 
     let split = router
         .find_split_route(token_a, token_e, amount, Some(3), Some(3))
-        .await
         .expect("Should find split route");
 
     assert!(split.routes.len() >= 1);
@@ -312,8 +196,8 @@ async fn test_router_split_routing_integration() {
     println!("  Price impact: {:.4}%", split.combined_price_impact);
 }
 
-#[tokio::test]
-async fn test_router_split_quote() {
+#[test]
+fn test_router_split_quote() {
     let graph = create_complex_graph();
     let router = Router::new(graph);
 
@@ -324,7 +208,6 @@ async fn test_router_split_quote() {
 
     let quote = router
         .get_split_quote(token_a, token_e, amount, 0.5, Some(4), Some(3))
-        .await
         .expect("Should get split quote");
 
     assert!(!quote.amount_in.is_empty());
@@ -340,8 +223,8 @@ async fn test_router_split_quote() {
     println!("  Gas: {}", quote.gas_estimate);
 }
 
-#[tokio::test]
-async fn test_small_amount_single_route() {
+#[test]
+fn test_small_amount_single_route() {
     let graph = create_complex_graph();
     let router = Router::new(graph);
 
@@ -353,7 +236,6 @@ async fn test_small_amount_single_route() {
 
     let split = router
         .find_split_route(token_a, token_e, amount, Some(4), Some(3))
-        .await
         .expect("Should find route");
 
     // Should use single route for small amounts